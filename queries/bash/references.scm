@@ -2,6 +2,9 @@
 ;; Bash/Shell Environment Variable Reference Queries
 ;; ═════════════════════════════════════════════════════════════════════════
 
+;; Expansions match wherever they appear, including inside double-quoted
+;; strings ("$VAR") and heredoc bodies.
+
 ;; ───────────────────────────────────────────────────────────────────────────
 ;; $VAR (simple expansion)
 ;; ───────────────────────────────────────────────────────────────────────────
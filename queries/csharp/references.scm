@@ -4,12 +4,14 @@
 
 ;; ───────────────────────────────────────────────────────────────────────────
 ;; Environment.GetEnvironmentVariable("VAR")
+;; Environment.GetEnvironmentVariable("VAR", EnvironmentVariableTarget.User)
 ;; ───────────────────────────────────────────────────────────────────────────
 (invocation_expression
   function: (member_access_expression
     expression: (identifier) @_obj
     name: (identifier) @_method)
   arguments: (argument_list
+    .
     (argument
       (string_literal
         (string_literal_content) @env_var_name)))
@@ -26,9 +28,67 @@
       name: (identifier) @_obj)
     name: (identifier) @_method)
   arguments: (argument_list
+    .
     (argument
       (string_literal
         (string_literal_content) @env_var_name)))
   (#eq? @_ns "System")
   (#eq? @_obj "Environment")
   (#eq? @_method "GetEnvironmentVariable")) @env_access
+;; ───────────────────────────────────────────────────────────────────────────
+;; Configuration["VAR"] / configuration["VAR"] / _configuration["VAR"]
+;; ───────────────────────────────────────────────────────────────────────────
+(element_access_expression
+  expression: (identifier) @_obj
+  subscript: (bracketed_argument_list
+    .
+    (argument
+      (string_literal
+        (string_literal_content) @env_var_name)))
+  (#match? @_obj "^_?[cC]onfiguration$")) @env_access
+
+;; ───────────────────────────────────────────────────────────────────────────
+;; builder.Configuration["VAR"]
+;; ───────────────────────────────────────────────────────────────────────────
+(element_access_expression
+  expression: (member_access_expression
+    name: (identifier) @_obj)
+  subscript: (bracketed_argument_list
+    .
+    (argument
+      (string_literal
+        (string_literal_content) @env_var_name)))
+  (#eq? @_obj "Configuration")) @env_access
+
+;; ───────────────────────────────────────────────────────────────────────────
+;; configuration.GetValue<T>("VAR")
+;; ───────────────────────────────────────────────────────────────────────────
+(invocation_expression
+  function: (member_access_expression
+    expression: (identifier) @_obj
+    name: (generic_name
+      (identifier) @_method))
+  arguments: (argument_list
+    .
+    (argument
+      (string_literal
+        (string_literal_content) @env_var_name)))
+  (#match? @_obj "^_?[cC]onfiguration$")
+  (#eq? @_method "GetValue")) @env_access
+
+;; ───────────────────────────────────────────────────────────────────────────
+;; builder.Configuration.GetValue<T>("VAR")
+;; ───────────────────────────────────────────────────────────────────────────
+(invocation_expression
+  function: (member_access_expression
+    expression: (member_access_expression
+      name: (identifier) @_obj)
+    name: (generic_name
+      (identifier) @_method))
+  arguments: (argument_list
+    .
+    (argument
+      (string_literal
+        (string_literal_content) @env_var_name)))
+  (#eq? @_obj "Configuration")
+  (#eq? @_method "GetValue")) @env_access
@@ -0,0 +1,21 @@
+;; ═════════════════════════════════════════════════════════════════════════
+;; Dart Variable Assignment Queries (for chain tracking)
+;; ═════════════════════════════════════════════════════════════════════════
+
+;; ───────────────────────────────────────────────────────────────────────────
+;; final b = a; / var b = a;
+;; ───────────────────────────────────────────────────────────────────────────
+(initialized_variable_definition
+  (identifier) @assignment_target
+  .
+  (identifier) @assignment_source
+  .) @assignment
+
+;; ───────────────────────────────────────────────────────────────────────────
+;; const b = a; (top level)
+;; ───────────────────────────────────────────────────────────────────────────
+(static_final_declaration
+  (identifier) @assignment_target
+  .
+  (identifier) @assignment_source
+  .) @assignment
@@ -0,0 +1,149 @@
+;; ═════════════════════════════════════════════════════════════════════════
+;; Dart Environment Variable Binding Queries
+;; ═════════════════════════════════════════════════════════════════════════
+
+;; ───────────────────────────────────────────────────────────────────────────
+;; final x = String.fromEnvironment('VAR') (top level)
+;; ───────────────────────────────────────────────────────────────────────────
+(static_final_declaration
+  (identifier) @binding_name
+  .
+  (identifier) @_class
+  .
+  (selector
+    (unconditional_assignable_selector
+      (identifier) @_func))
+  .
+  (selector
+    (argument_part
+      (arguments
+        .
+        (argument
+          (string_literal
+            [
+              (string_literal_single_quotes
+                (template_chars_single_single) @bound_env_var)
+              (string_literal_double_quotes
+                (template_chars_double_single) @bound_env_var)
+            ])))))
+  (#any-of? @_class "String" "bool" "int")
+  (#eq? @_func "fromEnvironment")) @env_binding
+
+;; ───────────────────────────────────────────────────────────────────────────
+;; final x = const String.fromEnvironment('VAR') (top level)
+;; ───────────────────────────────────────────────────────────────────────────
+(static_final_declaration
+  (identifier) @binding_name
+  .
+  (const_object_expression
+    (type_identifier) @_class
+    (type_identifier) @_func
+    (arguments
+      .
+      (argument
+        (string_literal
+          [
+            (string_literal_single_quotes
+              (template_chars_single_single) @bound_env_var)
+            (string_literal_double_quotes
+              (template_chars_double_single) @bound_env_var)
+          ]))))
+  (#any-of? @_class "String" "bool" "int")
+  (#eq? @_func "fromEnvironment")) @env_binding
+
+;; ───────────────────────────────────────────────────────────────────────────
+;; final x = dotenv.env['VAR'] / Platform.environment['VAR'] (top level)
+;; ───────────────────────────────────────────────────────────────────────────
+(static_final_declaration
+  (identifier) @binding_name
+  .
+  (identifier) @_object
+  .
+  (selector
+    (unconditional_assignable_selector
+      (identifier) @_property))
+  .
+  (selector
+    (unconditional_assignable_selector
+      (string_literal
+        [
+          (string_literal_single_quotes
+            (template_chars_single_single) @bound_env_var)
+          (string_literal_double_quotes
+            (template_chars_double_single) @bound_env_var)
+        ])))
+  (#any-of? @_object "dotenv" "Platform")
+  (#any-of? @_property "env" "environment")) @env_binding
+
+;; ───────────────────────────────────────────────────────────────────────────
+;; final x = String.fromEnvironment('VAR') (local)
+;; ───────────────────────────────────────────────────────────────────────────
+(initialized_variable_definition
+  (identifier) @binding_name
+  .
+  (identifier) @_class
+  .
+  (selector
+    (unconditional_assignable_selector
+      (identifier) @_func))
+  .
+  (selector
+    (argument_part
+      (arguments
+        .
+        (argument
+          (string_literal
+            [
+              (string_literal_single_quotes
+                (template_chars_single_single) @bound_env_var)
+              (string_literal_double_quotes
+                (template_chars_double_single) @bound_env_var)
+            ])))))
+  (#any-of? @_class "String" "bool" "int")
+  (#eq? @_func "fromEnvironment")) @env_binding
+
+;; ───────────────────────────────────────────────────────────────────────────
+;; final x = const String.fromEnvironment('VAR') (local)
+;; ───────────────────────────────────────────────────────────────────────────
+(initialized_variable_definition
+  (identifier) @binding_name
+  .
+  (const_object_expression
+    (type_identifier) @_class
+    (type_identifier) @_func
+    (arguments
+      .
+      (argument
+        (string_literal
+          [
+            (string_literal_single_quotes
+              (template_chars_single_single) @bound_env_var)
+            (string_literal_double_quotes
+              (template_chars_double_single) @bound_env_var)
+          ]))))
+  (#any-of? @_class "String" "bool" "int")
+  (#eq? @_func "fromEnvironment")) @env_binding
+
+;; ───────────────────────────────────────────────────────────────────────────
+;; final x = dotenv.env['VAR'] / Platform.environment['VAR'] (local)
+;; ───────────────────────────────────────────────────────────────────────────
+(initialized_variable_definition
+  (identifier) @binding_name
+  .
+  (identifier) @_object
+  .
+  (selector
+    (unconditional_assignable_selector
+      (identifier) @_property))
+  .
+  (selector
+    (unconditional_assignable_selector
+      (string_literal
+        [
+          (string_literal_single_quotes
+            (template_chars_single_single) @bound_env_var)
+          (string_literal_double_quotes
+            (template_chars_double_single) @bound_env_var)
+        ])))
+  (#any-of? @_object "dotenv" "Platform")
+  (#any-of? @_property "env" "environment")) @env_binding
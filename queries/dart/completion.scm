@@ -0,0 +1,38 @@
+;; ═════════════════════════════════════════════════════════════════════════
+;; Dart Completion Context Queries
+;; ═════════════════════════════════════════════════════════════════════════
+
+;; ───────────────────────────────────────────────────────────────────────────
+;; String.fromEnvironment(' - trigger completion
+;; ───────────────────────────────────────────────────────────────────────────
+(_
+  (identifier) @object
+  .
+  (selector
+    (unconditional_assignable_selector
+      (identifier) @_func))
+  .
+  (selector
+    (argument_part
+      (arguments
+        .
+        (argument
+          (string_literal) @completion_target))))
+  (#any-of? @object "String" "bool" "int")
+  (#eq? @_func "fromEnvironment"))
+
+;; ───────────────────────────────────────────────────────────────────────────
+;; dotenv.env[' / Platform.environment[' - trigger completion
+;; ───────────────────────────────────────────────────────────────────────────
+(_
+  (identifier) @object
+  .
+  (selector
+    (unconditional_assignable_selector
+      (identifier) @_property))
+  .
+  (selector
+    (unconditional_assignable_selector
+      (string_literal) @completion_target))
+  (#any-of? @object "dotenv" "Platform")
+  (#any-of? @_property "env" "environment"))
@@ -0,0 +1,5 @@
+;; ═════════════════════════════════════════════════════════════════════════
+;; Dart Destructure Queries
+;; ═════════════════════════════════════════════════════════════════════════
+;; Dart record/object patterns are not tracked for env vars.
+;; This file is intentionally minimal.
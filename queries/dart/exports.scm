@@ -0,0 +1,13 @@
+;; ═════════════════════════════════════════════════════════════════════════
+;; Dart Export Queries
+;; ═════════════════════════════════════════════════════════════════════════
+;; Dart has no export keyword for declarations: every top-level name that
+;; does not start with an underscore is public.
+
+;; ───────────────────────────────────────────────────────────────────────────
+;; const apiUrl = ...; / final apiUrl = ...;
+;; ───────────────────────────────────────────────────────────────────────────
+(static_final_declaration
+  .
+  (identifier) @export_name
+  (#not-match? @export_name "^_")) @export_stmt
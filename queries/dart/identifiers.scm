@@ -0,0 +1,5 @@
+;; ═════════════════════════════════════════════════════════════════════════
+;; Dart Identifier Query
+;; ═════════════════════════════════════════════════════════════════════════
+
+(identifier) @identifier
@@ -0,0 +1,18 @@
+;; ═════════════════════════════════════════════════════════════════════════
+;; Dart Import Queries
+;; ═════════════════════════════════════════════════════════════════════════
+
+;; ───────────────────────────────────────────────────────────────────────────
+;; import 'package:foo/foo.dart' as foo;
+;; ───────────────────────────────────────────────────────────────────────────
+(import_specification
+  (configurable_uri
+    (uri
+      (string_literal
+        [
+          (string_literal_single_quotes
+            (template_chars_single_single) @import_path)
+          (string_literal_double_quotes
+            (template_chars_double_single) @import_path)
+        ])))
+  (identifier) @alias_name) @import_stmt
@@ -0,0 +1,10 @@
+;; ═════════════════════════════════════════════════════════════════════════
+;; Dart Reassignment Queries
+;; ═════════════════════════════════════════════════════════════════════════
+
+;; ───────────────────────────────────────────────────────────────────────────
+;; x = value;
+;; ───────────────────────────────────────────────────────────────────────────
+(assignment_expression
+  (assignable_expression
+    (identifier) @reassigned_name)) @reassignment
@@ -0,0 +1,91 @@
+;; ═════════════════════════════════════════════════════════════════════════
+;; Dart Environment Variable Reference Queries
+;; ═════════════════════════════════════════════════════════════════════════
+
+;; ───────────────────────────────────────────────────────────────────────────
+;; String.fromEnvironment('VAR') / bool.fromEnvironment / int.fromEnvironment
+;; ───────────────────────────────────────────────────────────────────────────
+(_
+  (identifier) @_class
+  .
+  (selector
+    (unconditional_assignable_selector
+      (identifier) @_func))
+  .
+  (selector
+    (argument_part
+      (arguments
+        .
+        (argument
+          (string_literal
+            [
+              (string_literal_single_quotes
+                (template_chars_single_single) @env_var_name)
+              (string_literal_double_quotes
+                (template_chars_double_single) @env_var_name)
+            ]))))) @env_access
+  (#any-of? @_class "String" "bool" "int")
+  (#eq? @_func "fromEnvironment"))
+
+;; ───────────────────────────────────────────────────────────────────────────
+;; const String.fromEnvironment('VAR')
+;; ───────────────────────────────────────────────────────────────────────────
+(const_object_expression
+  (type_identifier) @_class
+  (type_identifier) @_func
+  (arguments
+    .
+    (argument
+      (string_literal
+        [
+          (string_literal_single_quotes
+            (template_chars_single_single) @env_var_name)
+          (string_literal_double_quotes
+            (template_chars_double_single) @env_var_name)
+        ])))
+  (#any-of? @_class "String" "bool" "int")
+  (#eq? @_func "fromEnvironment")) @env_access
+
+;; ───────────────────────────────────────────────────────────────────────────
+;; dotenv.env['VAR'] (flutter_dotenv)
+
+;; ───────────────────────────────────────────────────────────────────────────
+(_
+  (identifier) @_object
+  .
+  (selector
+    (unconditional_assignable_selector
+      (identifier) @_property))
+  .
+  (selector
+    (unconditional_assignable_selector
+      (string_literal
+        [
+          (string_literal_single_quotes
+            (template_chars_single_single) @env_var_name)
+          (string_literal_double_quotes
+            (template_chars_double_single) @env_var_name)
+        ]))) @env_access
+  (#eq? @_object "dotenv")
+  (#eq? @_property "env"))
+;; ───────────────────────────────────────────────────────────────────────────
+;; Platform.environment['VAR'] (dart:io)
+;; ───────────────────────────────────────────────────────────────────────────
+(_
+  (identifier) @_object
+  .
+  (selector
+    (unconditional_assignable_selector
+      (identifier) @_property))
+  .
+  (selector
+    (unconditional_assignable_selector
+      (string_literal
+        [
+          (string_literal_single_quotes
+            (template_chars_single_single) @env_var_name)
+          (string_literal_double_quotes
+            (template_chars_double_single) @env_var_name)
+        ]))) @env_access
+  (#eq? @_object "Platform")
+  (#eq? @_property "environment"))
@@ -0,0 +1,31 @@
+;; ═════════════════════════════════════════════════════════════════════════
+;; Dart Scope Node Queries
+;; ═════════════════════════════════════════════════════════════════════════
+
+;; ───────────────────────────────────────────────────────────────────────────
+;; Functions and methods
+;; ───────────────────────────────────────────────────────────────────────────
+(function_body) @scope_node
+(function_expression) @scope_node
+
+;; ───────────────────────────────────────────────────────────────────────────
+;; Blocks
+;; ───────────────────────────────────────────────────────────────────────────
+(block) @scope_node
+
+;; ───────────────────────────────────────────────────────────────────────────
+;; Classes
+;; ───────────────────────────────────────────────────────────────────────────
+(class_body) @scope_node
+
+;; ───────────────────────────────────────────────────────────────────────────
+;; Loops
+;; ───────────────────────────────────────────────────────────────────────────
+(for_statement) @scope_node
+(while_statement) @scope_node
+
+;; ───────────────────────────────────────────────────────────────────────────
+;; Conditionals
+;; ───────────────────────────────────────────────────────────────────────────
+(if_statement) @scope_node
+(switch_statement) @scope_node
@@ -4,6 +4,22 @@
 
 ;; ───────────────────────────────────────────────────────────────────────────
 ;; System.get_env("VAR")
+;; System.get_env("VAR", "default")
+;; ───────────────────────────────────────────────────────────────────────────
+(call
+  target: (dot
+    left: (alias) @_obj
+    right: (identifier) @_func)
+  (arguments
+    .
+    (string
+      (quoted_content) @env_var_name)
+    .
+    (_)? @env_default_value)
+  (#eq? @_obj "System")
+  (#eq? @_func "get_env")) @env_access
+
+;; ───────────────────────────────────────────────────────────────────────────
 ;; System.fetch_env("VAR")
 ;; System.fetch_env!("VAR")
 ;; ───────────────────────────────────────────────────────────────────────────
@@ -12,7 +28,8 @@
     left: (alias) @_obj
     right: (identifier) @_func)
   (arguments
+    .
     (string
       (quoted_content) @env_var_name))
   (#eq? @_obj "System")
-  (#any-of? @_func "get_env" "fetch_env" "fetch_env!")) @env_access
+  (#any-of? @_func "fetch_env" "fetch_env!")) @env_access
@@ -81,3 +81,57 @@
     property: (property_identifier) @_property)
   (#eq? @_object "process")
   (#eq? @_property "env")) @env_object_binding
+
+;; ───────────────────────────────────────────────────────────────────
+;; import { VAR } from "$env/static/private" (SvelteKit)
+;; ───────────────────────────────────────────────────────────────────
+(import_statement
+  (import_clause
+    (named_imports
+      (import_specifier
+        name: (identifier) @binding_name @bound_env_var
+        !alias) @env_binding))
+  source: (string
+    (string_fragment) @_source)
+  (#match? @_source "^\\$env/static/(private|public)$"))
+
+;; ───────────────────────────────────────────────────────────────────
+;; import { VAR as myVar } from "$env/static/private" (SvelteKit)
+;; ───────────────────────────────────────────────────────────────────
+(import_statement
+  (import_clause
+    (named_imports
+      (import_specifier
+        name: (identifier) @bound_env_var
+        alias: (identifier) @binding_name) @env_binding))
+  source: (string
+    (string_fragment) @_source)
+  (#match? @_source "^\\$env/static/(private|public)$"))
+
+;; ───────────────────────────────────────────────────────────────────
+;; import { env } from "$env/dynamic/private" (SvelteKit object alias)
+;; ───────────────────────────────────────────────────────────────────
+(import_statement
+  (import_clause
+    (named_imports
+      (import_specifier
+        name: (identifier) @binding_name
+        !alias) @env_object_binding))
+  source: (string
+    (string_fragment) @_source)
+  (#eq? @binding_name "env")
+  (#match? @_source "^\\$env/dynamic/(private|public)$"))
+
+;; ───────────────────────────────────────────────────────────────────
+;; import { env as myEnv } from "$env/dynamic/private" (SvelteKit object alias)
+;; ───────────────────────────────────────────────────────────────────
+(import_statement
+  (import_clause
+    (named_imports
+      (import_specifier
+        name: (identifier) @_name
+        alias: (identifier) @binding_name) @env_object_binding))
+  source: (string
+    (string_fragment) @_source)
+  (#eq? @_name "env")
+  (#match? @_source "^\\$env/dynamic/(private|public)$"))
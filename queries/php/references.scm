@@ -33,6 +33,36 @@
         (string_content) @env_var_name)))
   (#eq? @_func "getenv")) @env_access
 
+;; ───────────────────────────────────────────────────────────────────────────
+;; $_ENV['VAR'] ?? 'default', $_SERVER['VAR'] ?? 'default'
+;; The plain patterns above match the same access; the extractor keeps this
+;; one for its default.
+;; ───────────────────────────────────────────────────────────────────────────
+(binary_expression
+  left: (subscript_expression
+    (variable_name) @_var
+    (string
+      (string_content) @env_var_name)) @env_access
+  operator: "??"
+  right: (_) @env_default_value
+  (#any-of? @_var "$_ENV" "$_SERVER"))
+
+;; ───────────────────────────────────────────────────────────────────────────
+;; getenv('VAR') ?: 'default'
+;; getenv returns false for unset vars, so only the short ternary applies.
+;; ───────────────────────────────────────────────────────────────────────────
+(conditional_expression
+  condition: (function_call_expression
+    function: (name) @_func
+    arguments: (arguments
+      .
+      (argument
+        (string
+          (string_content) @env_var_name)))) @env_access
+  !body
+  alternative: (_) @env_default_value
+  (#eq? @_func "getenv"))
+
 ;; ───────────────────────────────────────────────────────────────────────────
 ;; env('VAR') - Laravel helper
 ;; ───────────────────────────────────────────────────────────────────────────
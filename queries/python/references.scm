@@ -1,5 +1,5 @@
 ;; ───────────────────────────────────────────────────────────────────────────
-;; os.environ.get("VAR") / o.environ.get("VAR")
+;; os.environ.get("VAR") / o.environ.get("VAR", "default")
 ;; ───────────────────────────────────────────────────────────────────────────
 (call
   function: (attribute
@@ -10,7 +10,7 @@
   arguments: (argument_list
     (string
       (string_content) @env_var_name)
-    (_)?)
+    (_)? @env_default_value)
   (#eq? @_object "environ")
   (#any-of? @_method "get" "pop" "setdefault")) @env_access
 
@@ -35,7 +35,7 @@
   arguments: (argument_list
     (string
       (string_content) @env_var_name)
-    (_)?)
+    (_)? @env_default_value)
   (#eq? @_method "getenv")) @env_access
 
 ;; ───────────────────────────────────────────────────────────────────────────
@@ -59,7 +59,7 @@
   arguments: (argument_list
     (string
       (string_content) @env_var_name)
-    (_)?)
+    (_)? @env_default_value)
   (#any-of? @_method "get" "pop" "setdefault")
 ) @env_access
 
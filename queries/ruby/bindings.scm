@@ -15,20 +15,8 @@
 
 ;; ───────────────────────────────────────────────────────────────────────────
 ;; x = ENV.fetch('VAR')
-;; ───────────────────────────────────────────────────────────────────────────
-(assignment
-  left: (identifier) @binding_name
-  right: (call
-    receiver: (constant) @_obj
-    method: (identifier) @_method
-    arguments: (argument_list
-      (string
-        (string_content) @bound_env_var)))
-  (#eq? @_obj "ENV")
-  (#eq? @_method "fetch")) @env_binding
-
-;; ───────────────────────────────────────────────────────────────────────────
 ;; x = ENV.fetch('VAR', default)
+;; x = ENV.fetch('VAR') { default }
 ;; ───────────────────────────────────────────────────────────────────────────
 (assignment
   left: (identifier) @binding_name
@@ -36,9 +24,9 @@
     receiver: (constant) @_obj
     method: (identifier) @_method
     arguments: (argument_list
+      .
       (string
-        (string_content) @bound_env_var)
-      (_)))
+        (string_content) @bound_env_var)))
   (#eq? @_obj "ENV")
   (#eq? @_method "fetch")) @env_binding
 
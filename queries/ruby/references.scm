@@ -3,6 +3,7 @@
 ;; ═════════════════════════════════════════════════════════════════════════
 ;;
 ;; Primary patterns: ENV['VAR'], ENV.fetch('VAR')
+;; A fetch default, positional or from a block, is captured as @env_default_value.
 
 ;; ───────────────────────────────────────────────────────────────────────────
 ;; ENV['VAR']
@@ -15,26 +16,37 @@
 
 ;; ───────────────────────────────────────────────────────────────────────────
 ;; ENV.fetch('VAR')
+;; ENV.fetch('VAR', default)
 ;; ───────────────────────────────────────────────────────────────────────────
 (call
   receiver: (constant) @_obj
   method: (identifier) @_method
   arguments: (argument_list
+    .
     (string
-      (string_content) @env_var_name))
+      (string_content) @env_var_name)
+    .
+    (_)? @env_default_value)
+  !block
   (#eq? @_obj "ENV")
   (#eq? @_method "fetch")) @env_access
 
 ;; ───────────────────────────────────────────────────────────────────────────
-;; ENV.fetch('VAR', default)
+;; ENV.fetch('VAR') { default }
+;; ENV.fetch('VAR') do default end
 ;; ───────────────────────────────────────────────────────────────────────────
 (call
   receiver: (constant) @_obj
   method: (identifier) @_method
   arguments: (argument_list
+    .
     (string
       (string_content) @env_var_name)
-    (_))
+    .)
+  block: [
+    (block body: (_) @env_default_value)
+    (do_block body: (_) @env_default_value)
+  ]
   (#eq? @_obj "ENV")
   (#eq? @_method "fetch")) @env_access
 
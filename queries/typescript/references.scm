@@ -37,3 +37,46 @@
   property: (property_identifier) @env_var_name
   (#eq? @_meta "meta")
   (#eq? @_env "env")) @env_access
+
+;; ───────────────────────────────────────────────────────────────────────────
+;; Deno.env.get("VAR_NAME") (Deno)
+;; ───────────────────────────────────────────────────────────────────────────
+(call_expression
+  function: (member_expression
+    object: (member_expression
+      object: (identifier) @_object
+      property: (property_identifier) @_property)
+    property: (property_identifier) @_method)
+  arguments: (arguments
+    .
+    (string
+      (string_fragment) @env_var_name))
+  (#eq? @_object "Deno")
+  (#eq? @_property "env")
+  (#eq? @_method "get")) @env_access
+
+;; ───────────────────────────────────────────────────────────────────────────
+;; Whole-environment reads: Deno.env.toObject() and import.meta.env spread or
+;; passed as an argument (Bun/Vite). Any variable may be consumed.
+;; ───────────────────────────────────────────────────────────────────────────
+(call_expression
+  function: (member_expression
+    object: (member_expression
+      object: (identifier) @_object
+      property: (property_identifier) @_property)
+    property: (property_identifier) @_method)
+  (#eq? @_object "Deno")
+  (#eq? @_property "env")
+  (#eq? @_method "toObject")) @env_dynamic_access
+
+(spread_element
+  (member_expression
+    object: (meta_property)
+    property: (property_identifier) @_env)
+  (#eq? @_env "env")) @env_dynamic_access
+
+(arguments
+  (member_expression
+    object: (meta_property)
+    property: (property_identifier) @_env) @env_dynamic_access
+  (#eq? @_env "env"))
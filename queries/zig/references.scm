@@ -11,8 +11,28 @@
       (identifier) @_root
       (identifier) @_module)
     (identifier) @_func)
+  .
   (string
     (string_content) @env_var_name)
   (#eq? @_root "std")
   (#any-of? @_module "os" "posix")
   (#eq? @_func "getenv")) @env_access
+
+;; ───────────────────────────────────────────────────────────────────────────
+;; std.process.getEnvVarOwned(allocator, "VAR")
+;; The allocator comes first, so the name is the second argument.
+;; ───────────────────────────────────────────────────────────────────────────
+(call_expression
+  (field_expression
+    (field_expression
+      (identifier) @_root
+      (identifier) @_module)
+    (identifier) @_func)
+  .
+  (_)
+  .
+  (string
+    (string_content) @env_var_name)
+  (#eq? @_root "std")
+  (#eq? @_module "process")
+  (#eq? @_func "getEnvVarOwned")) @env_access
@@ -0,0 +1,111 @@
+//! Env var definitions emitted by Cargo build scripts.
+//!
+//! A `build.rs` that prints `cargo:rustc-env=KEY=value` (or the newer
+//! `cargo::rustc-env=KEY=value`) sets `KEY` for the crate's compilation, so
+//! `env!("KEY")` elsewhere in the crate resolves. Each emission becomes a
+//! definition seen only by the Rust files under the script's directory; the
+//! script itself is still analyzed as ordinary Rust. A value
+//! built from format arguments, like `GIT_HASH={}`, is only known when the
+//! script runs, so its definition is dynamic.
+
+use crate::analysis::line_index::LineIndex;
+use crate::types::{is_valid_env_var_name, EnvDefinition};
+use compact_str::CompactString;
+use std::path::Path;
+
+const BUILD_SCRIPT: &str = "build.rs";
+const DIRECTIVES: [&str; 2] = ["cargo::rustc-env=", "cargo:rustc-env="];
+
+/// Returns true if the path's file name is `build.rs`.
+pub fn is_build_script(path: &Path) -> bool {
+    path.file_name().and_then(|n| n.to_str()) == Some(BUILD_SCRIPT)
+}
+
+/// Extracts the variables a build script sets with `rustc-env`.
+pub fn extract_definitions(content: &str) -> Vec<EnvDefinition> {
+    let index = LineIndex::new(content);
+    let mut definitions = Vec::new();
+    let mut pos = 0;
+    while let Some((start, directive)) = next_directive(content, pos) {
+        let key_start = start + directive.len();
+        pos = key_start;
+
+        let rest = &content[key_start..];
+        let line_end = rest.find(['"', '\n']).unwrap_or(rest.len());
+        let Some(eq) = rest[..line_end].find('=') else {
+            continue;
+        };
+        let key = &rest[..eq];
+        if !is_valid_env_var_name(key) {
+            continue;
+        }
+
+        let value = rest[eq + 1..line_end].replace("{{", "\0").replace("}}", "}");
+        let dynamic = value.contains('{');
+        definitions.push(EnvDefinition {
+            name: CompactString::from(key),
+            value: if dynamic {
+                CompactString::default()
+            } else {
+                CompactString::from(value.replace('\0', "{"))
+            },
+            name_range: index.range(key_start, key_start + eq),
+            dynamic,
+        });
+    }
+    definitions
+}
+
+/// Finds the earliest directive at or after `from`.
+fn next_directive(content: &str, from: usize) -> Option<(usize, &'static str)> {
+    DIRECTIVES
+        .iter()
+        .filter_map(|directive| {
+            content[from..]
+                .find(directive)
+                .map(|offset| (from + offset, *directive))
+        })
+        .min_by_key(|(start, _)| *start)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tower_lsp::lsp_types::Position;
+
+    #[test]
+    fn test_rustc_env_definitions() {
+        let content = r#"fn main() {
+    println!("cargo:rustc-env=GIT_HASH={}", hash);
+    println!("cargo::rustc-env=BUILD_PROFILE=release");
+    println!("cargo:rerun-if-changed=build.rs");
+    println!("cargo:rustc-env=TEMPLATE={{name}}");
+}
+"#;
+        let defs = extract_definitions(content);
+        let names: Vec<_> = defs.iter().map(|d| d.name.as_str()).collect();
+        assert_eq!(names, vec!["GIT_HASH", "BUILD_PROFILE", "TEMPLATE"]);
+
+        assert!(defs[0].dynamic);
+        assert_eq!(defs[0].value, "");
+        assert_eq!(defs[0].name_range.start, Position::new(1, 30));
+        assert_eq!(defs[0].name_range.end, Position::new(1, 38));
+        assert!(!defs[1].dynamic);
+        assert_eq!(defs[1].value, "release");
+        assert!(!defs[2].dynamic);
+        assert_eq!(defs[2].value, "{name}");
+    }
+
+    #[test]
+    fn test_invalid_keys_skipped() {
+        let content = r#"println!("cargo:rustc-env={}={}", key, value);
+println!("cargo:rustc-env=NO_VALUE");"#;
+        assert!(extract_definitions(content).is_empty());
+    }
+
+    #[test]
+    fn test_is_build_script() {
+        assert!(is_build_script(Path::new("/app/build.rs")));
+        assert!(!is_build_script(Path::new("/app/src/main.rs")));
+    }
+}
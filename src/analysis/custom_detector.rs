@@ -0,0 +1,239 @@
+//! User-defined reference detectors for files no language handles.
+//!
+//! Each `[[detectors]]` entry in `ecolog.toml` maps file name globs to the
+//! patterns that read env vars in those files. Matching files are scanned as
+//! text, like `package.json` and deployment configs:
+//!
+//! - an object path such as `ctx.env` matches `ctx.env.NAME` and
+//!   `ctx.env["NAME"]`
+//! - a call such as `getEnv` matches `getEnv("NAME")` and `getEnv('NAME')`
+
+use crate::analysis::line_index::LineIndex;
+use crate::analysis::BindingGraph;
+use crate::types::{AccessType, EnvReference};
+use compact_str::CompactString;
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize, Serialize)]
+pub struct CustomDetector {
+    /// Glob patterns matched against file names, e.g. `quasar.conf.js`.
+    #[serde(default)]
+    pub files: Vec<String>,
+    /// Dotted object paths whose members are env vars, e.g. `ctx.env`.
+    #[serde(default)]
+    pub object_paths: Vec<String>,
+    /// Functions whose first string argument is an env var, e.g. `getEnv`.
+    #[serde(default)]
+    pub calls: Vec<String>,
+}
+
+impl CustomDetector {
+    /// Returns true if one of the file globs matches the path's file name.
+    pub fn matches(&self, path: &Path) -> bool {
+        let Some(name) = path.file_name().and_then(|n| n.to_str()) else {
+            return false;
+        };
+        self.files.iter().any(|pattern| {
+            glob::Pattern::new(pattern)
+                .map(|p| p.matches(name))
+                .unwrap_or(false)
+        })
+    }
+
+    /// Scans the whole content for object path and call references.
+    pub fn extract_references(&self, content: &str) -> Vec<EnvReference> {
+        let index = LineIndex::new(content);
+        let mut references = Vec::new();
+        for object_path in self.object_paths.iter().filter(|p| !p.is_empty()) {
+            for start in occurrences(content, object_path) {
+                if let Some(reference) = member_access(&index, start, start + object_path.len()) {
+                    references.push(reference);
+                }
+            }
+        }
+        for call in self.calls.iter().filter(|c| !c.is_empty()) {
+            for start in occurrences(content, call) {
+                if let Some(reference) = call_argument(&index, start, start + call.len()) {
+                    references.push(reference);
+                }
+            }
+        }
+        references.sort_by_key(|r| (r.full_range.start.line, r.full_range.start.character));
+        references
+    }
+
+    /// Builds a binding graph holding only direct references.
+    pub fn analyze(&self, content: &str) -> BindingGraph {
+        let mut graph = BindingGraph::new();
+        for reference in self.extract_references(content) {
+            graph.add_direct_reference(reference);
+        }
+        graph.rebuild_range_index();
+        graph
+    }
+}
+
+/// Returns the first detector that handles the file at `path`.
+pub fn detector_for<'a>(detectors: &'a [CustomDetector], path: &Path) -> Option<&'a CustomDetector> {
+    detectors.iter().find(|detector| detector.matches(path))
+}
+
+/// Byte offsets of `needle` that aren't the tail of a longer identifier.
+fn occurrences(content: &str, needle: &str) -> Vec<usize> {
+    content
+        .match_indices(needle)
+        .map(|(start, _)| start)
+        .filter(|&start| {
+            content[..start]
+                .bytes()
+                .next_back()
+                .is_none_or(|b| !is_ident_byte(b, false) && b != b'.')
+        })
+        .collect()
+}
+
+/// Parses `.NAME` or `["NAME"]` right after an object path.
+fn member_access(index: &LineIndex, start: usize, path_end: usize) -> Option<EnvReference> {
+    let bytes = index.content().as_bytes();
+    match bytes.get(path_end)? {
+        b'.' => {
+            let name_start = path_end + 1;
+            let name_end = ident_end(bytes, name_start);
+            (name_end > name_start).then(|| {
+                reference(index, start, name_end, (name_start, name_end), AccessType::Property)
+            })
+        }
+        b'[' => {
+            let (name_start, name_end) = quoted(bytes, skip_spaces(bytes, path_end + 1))?;
+            let close = skip_spaces(bytes, name_end + 1);
+            (bytes.get(close) == Some(&b']')).then(|| {
+                reference(index, start, close + 1, (name_start, name_end), AccessType::Subscript)
+            })
+        }
+        _ => None,
+    }
+}
+
+/// Parses `("NAME"` right after a function name.
+fn call_argument(index: &LineIndex, start: usize, name_end: usize) -> Option<EnvReference> {
+    let bytes = index.content().as_bytes();
+    let open = skip_spaces(bytes, name_end);
+    if bytes.get(open) != Some(&b'(') {
+        return None;
+    }
+    let (arg_start, arg_end) = quoted(bytes, skip_spaces(bytes, open + 1))?;
+    Some(reference(
+        index,
+        start,
+        arg_end + 1,
+        (arg_start, arg_end),
+        AccessType::FunctionCall,
+    ))
+}
+
+fn reference(
+    index: &LineIndex,
+    start: usize,
+    end: usize,
+    name: (usize, usize),
+    access_type: AccessType,
+) -> EnvReference {
+    EnvReference {
+        name: CompactString::from(&index.content()[name.0..name.1]),
+        full_range: index.range(start, end),
+        name_range: index.range(name.0, name.1),
+        access_type,
+        has_default: false,
+        default_value: None,
+    }
+}
+
+/// Returns the span inside a quoted env var name starting at `pos`.
+fn quoted(bytes: &[u8], pos: usize) -> Option<(usize, usize)> {
+    let quote = *bytes.get(pos)?;
+    if !matches!(quote, b'"' | b'\'' | b'`') {
+        return None;
+    }
+    let name_start = pos + 1;
+    let name_end = ident_end(bytes, name_start);
+    (name_end > name_start && bytes.get(name_end) == Some(&quote)).then_some((name_start, name_end))
+}
+
+fn ident_end(bytes: &[u8], start: usize) -> usize {
+    let mut end = start;
+    while end < bytes.len() && is_ident_byte(bytes[end], end == start) {
+        end += 1;
+    }
+    end
+}
+
+fn skip_spaces(bytes: &[u8], mut pos: usize) -> usize {
+    while pos < bytes.len() && bytes[pos].is_ascii_whitespace() {
+        pos += 1;
+    }
+    pos
+}
+
+fn is_ident_byte(b: u8, first: bool) -> bool {
+    b == b'_' || b == b'$' || b.is_ascii_alphabetic() || (!first && b.is_ascii_digit())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tower_lsp::lsp_types::Position;
+
+    fn detector() -> CustomDetector {
+        CustomDetector {
+            files: vec!["quasar.conf.js".into(), "*.build.cfg".into()],
+            object_paths: vec!["ctx.env".into()],
+            calls: vec!["getEnv".into()],
+        }
+    }
+
+    #[test]
+    fn test_matches_file_globs() {
+        let detector = detector();
+        assert!(detector.matches(Path::new("/app/quasar.conf.js")));
+        assert!(detector.matches(Path::new("/app/web.build.cfg")));
+        assert!(!detector.matches(Path::new("/app/vite.config.js")));
+    }
+
+    #[test]
+    fn test_object_path_member_and_subscript() {
+        let content = "const a = ctx.env.API_KEY;\nconst b = ctx.env[ 'DB_URL' ];\n";
+        let refs = detector().extract_references(content);
+        let names: Vec<_> = refs.iter().map(|r| r.name.as_str()).collect();
+        assert_eq!(names, vec!["API_KEY", "DB_URL"]);
+
+        assert_eq!(refs[0].access_type, AccessType::Property);
+        assert_eq!(refs[0].full_range.start, Position::new(0, 10));
+        assert_eq!(refs[0].name_range.start, Position::new(0, 18));
+        assert_eq!(refs[1].access_type, AccessType::Subscript);
+        assert_eq!(refs[1].name_range.start, Position::new(1, 20));
+    }
+
+    #[test]
+    fn test_call_pattern() {
+        let content = "port: getEnv(\"PORT\"), host: getEnv(HOST), x: forgetEnv('NOPE')";
+        let refs = detector().extract_references(content);
+        assert_eq!(refs.len(), 1);
+        assert_eq!(refs[0].name, "PORT");
+        assert_eq!(refs[0].access_type, AccessType::FunctionCall);
+    }
+
+    #[test]
+    fn test_longer_paths_not_matched() {
+        let content = "myctx.env.A; other.ctx.env.B; ctx.envs.C";
+        assert!(detector().extract_references(content).is_empty());
+    }
+
+    #[test]
+    fn test_detector_for_picks_first_match() {
+        let detectors = vec![CustomDetector::default(), detector()];
+        let found = detector_for(&detectors, Path::new("/app/quasar.conf.js"));
+        assert_eq!(found, Some(&detectors[1]));
+        assert!(detector_for(&detectors, Path::new("/app/main.rs")).is_none());
+    }
+}
@@ -0,0 +1,236 @@
+//! Env var definitions in deployment configs.
+//!
+//! `netlify.toml` defines variables in its `[build.environment]` table and
+//! `vercel.json` in its top-level `env` object. `netlify.toml` is parsed with
+//! the `toml` crate; `vercel.json` is scanned directly. Each entry becomes a
+//! definition, and `$VAR` / `${VAR}` occurrences inside the values become
+//! direct references.
+
+use crate::analysis::line_index::LineIndex;
+use crate::analysis::text_refs::{collect_references, section_string_members, Span};
+use crate::analysis::BindingGraph;
+use crate::types::{is_valid_env_var_name, EnvDefinition, EnvReference};
+use compact_str::CompactString;
+use serde::Deserialize;
+use std::collections::BTreeMap;
+use std::path::Path;
+use toml::Spanned;
+
+const NETLIFY_TOML: &str = "netlify.toml";
+const VERCEL_JSON: &str = "vercel.json";
+
+/// One entry: byte spans of the key and of the value as written, and the
+/// value's text.
+struct Entry {
+    key: Span,
+    value: Span,
+    text: CompactString,
+}
+
+/// Returns true if the path's file name is `netlify.toml` or `vercel.json`.
+pub fn is_deploy_config(path: &Path) -> bool {
+    matches!(
+        path.file_name().and_then(|n| n.to_str()),
+        Some(NETLIFY_TOML | VERCEL_JSON)
+    )
+}
+
+/// Extracts the variables a deployment config defines. Keys that aren't
+/// valid env var names are skipped.
+pub fn extract_definitions(path: &Path, content: &str) -> Vec<EnvDefinition> {
+    let index = LineIndex::new(content);
+    entries(path, content)
+        .into_iter()
+        .filter(|entry| is_valid_env_var_name(&content[entry.key.0..entry.key.1]))
+        .map(|entry| EnvDefinition {
+            name: CompactString::from(&content[entry.key.0..entry.key.1]),
+            value: entry.text,
+            name_range: index.range(entry.key.0, entry.key.1),
+            dynamic: false,
+        })
+        .collect()
+}
+
+/// Extracts `$VAR` and `${VAR}` references from definition values.
+pub fn extract_references(path: &Path, content: &str) -> Vec<EnvReference> {
+    let index = LineIndex::new(content);
+    let mut references = Vec::new();
+    for entry in entries(path, content) {
+        collect_references(&index, entry.value.0, entry.value.1, &mut references);
+    }
+    references
+}
+
+/// Builds a binding graph holding only the direct references found in values.
+pub fn analyze(path: &Path, content: &str) -> BindingGraph {
+    let mut graph = BindingGraph::new();
+    for reference in extract_references(path, content) {
+        graph.add_direct_reference(reference);
+    }
+    graph.rebuild_range_index();
+    graph
+}
+
+fn entries(path: &Path, content: &str) -> Vec<Entry> {
+    match path.file_name().and_then(|n| n.to_str()) {
+        Some(NETLIFY_TOML) => netlify_entries(content),
+        Some(VERCEL_JSON) => section_string_members(content, "env")
+            .into_iter()
+            .map(|(key, value)| Entry {
+                key,
+                value,
+                text: CompactString::from(&content[value.0..value.1]),
+            })
+            .collect(),
+        _ => Vec::new(),
+    }
+}
+
+#[derive(Deserialize)]
+struct NetlifyToml {
+    build: Option<NetlifyBuild>,
+}
+
+#[derive(Deserialize)]
+struct NetlifyBuild {
+    #[serde(default)]
+    environment: BTreeMap<Spanned<String>, Spanned<toml::Value>>,
+}
+
+/// Entries of the `[build.environment]` table, however the TOML spells it:
+/// a table header, an inline table, or dotted keys. String values keep their
+/// parsed text; other scalars are taken as written. An invalid file has no
+/// entries.
+fn netlify_entries(content: &str) -> Vec<Entry> {
+    let Ok(netlify) = toml::from_str::<NetlifyToml>(content) else {
+        return Vec::new();
+    };
+    let Some(build) = netlify.build else {
+        return Vec::new();
+    };
+
+    let mut entries: Vec<Entry> = build
+        .environment
+        .into_iter()
+        .filter_map(|(key, value)| {
+            let raw = value.span();
+            let text = match value.into_inner() {
+                toml::Value::String(text) => CompactString::from(text),
+                toml::Value::Table(_) | toml::Value::Array(_) => return None,
+                _ => CompactString::from(content.get(raw.clone())?),
+            };
+            Some(Entry {
+                key: unquoted(content, key.span()),
+                value: (raw.start, raw.end),
+                text,
+            })
+        })
+        .collect();
+    entries.sort_by_key(|entry| entry.key.0);
+    entries
+}
+
+/// Span of a TOML key without its quotes, if it has any.
+fn unquoted(content: &str, span: std::ops::Range<usize>) -> Span {
+    match content.as_bytes().get(span.start) {
+        Some(b'"' | b'\'') if span.len() >= 2 => (span.start + 1, span.end - 1),
+        _ => (span.start, span.end),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tower_lsp::lsp_types::Position;
+
+    const NETLIFY: &str = r#"[build]
+  command = "npm run build"
+
+[build.environment]
+  NODE_VERSION = "20"
+  API_URL = "https://${API_HOST}/v1"
+  # COMMENTED = "x"
+
+[context.production.environment]
+  ONLY_IN_PRODUCTION = "1"
+"#;
+
+    const VERCEL: &str = r#"{
+  "name": "app",
+  "env": {
+    "DATABASE_URL": "postgres://$DB_HOST/app"
+  }
+}"#;
+
+    #[test]
+    fn test_netlify_build_environment_definitions() {
+        let defs = extract_definitions(Path::new("/app/netlify.toml"), NETLIFY);
+        let names: Vec<_> = defs.iter().map(|d| d.name.as_str()).collect();
+        assert_eq!(names, vec!["NODE_VERSION", "API_URL"]);
+
+        let node = &defs[0];
+        assert_eq!(node.value, "20");
+        assert_eq!(node.name_range.start, Position::new(4, 2));
+        assert_eq!(node.name_range.end, Position::new(4, 14));
+    }
+
+    #[test]
+    fn test_netlify_references_in_values() {
+        let refs = extract_references(Path::new("/app/netlify.toml"), NETLIFY);
+        assert_eq!(refs.len(), 1);
+        assert_eq!(refs[0].name, "API_HOST");
+        assert_eq!(refs[0].name_range.start, Position::new(5, 23));
+    }
+
+    #[test]
+    fn test_netlify_inline_table_and_quoted_keys() {
+        let content = "[build]\nenvironment = { \"QUOTED_KEY\" = \"a\", PLAIN = 'b' }\n";
+        let defs = extract_definitions(Path::new("/app/netlify.toml"), content);
+        let names: Vec<_> = defs.iter().map(|d| (d.name.as_str(), d.value.as_str())).collect();
+        assert_eq!(names, vec![("QUOTED_KEY", "a"), ("PLAIN", "b")]);
+        assert_eq!(defs[0].name_range.start, Position::new(1, 17));
+        assert_eq!(defs[0].name_range.end, Position::new(1, 27));
+    }
+
+    #[test]
+    fn test_netlify_multiline_string_and_dotted_keys() {
+        let content = "build.environment.PORT = 8080\n\
+                       build.environment.MOTD = \"\"\"\nhello\n${USER_NAME}\"\"\"\n";
+        let path = Path::new("/app/netlify.toml");
+        let defs = extract_definitions(path, content);
+        let values: Vec<_> = defs.iter().map(|d| (d.name.as_str(), d.value.as_str())).collect();
+        assert_eq!(values, vec![("PORT", "8080"), ("MOTD", "hello\n${USER_NAME}")]);
+
+        let refs = extract_references(path, content);
+        assert_eq!(refs.len(), 1);
+        assert_eq!(refs[0].name, "USER_NAME");
+        assert_eq!(refs[0].name_range.start, Position::new(3, 2));
+    }
+
+    #[test]
+    fn test_netlify_invalid_toml_has_no_entries() {
+        let path = Path::new("/app/netlify.toml");
+        assert!(extract_definitions(path, "[build.environment]\nA = \"unterminated\n").is_empty());
+    }
+
+    #[test]
+    fn test_vercel_env_definitions_and_references() {
+        let path = Path::new("/app/vercel.json");
+        let defs = extract_definitions(path, VERCEL);
+        assert_eq!(defs.len(), 1);
+        assert_eq!(defs[0].name, "DATABASE_URL");
+        assert_eq!(defs[0].value, "postgres://$DB_HOST/app");
+
+        let refs = extract_references(path, VERCEL);
+        assert_eq!(refs.len(), 1);
+        assert_eq!(refs[0].name, "DB_HOST");
+    }
+
+    #[test]
+    fn test_is_deploy_config() {
+        assert!(is_deploy_config(Path::new("/app/netlify.toml")));
+        assert!(is_deploy_config(Path::new("/app/vercel.json")));
+        assert!(!is_deploy_config(Path::new("/app/application.properties")));
+        assert!(!is_deploy_config(Path::new("/app/package.json")));
+    }
+}
@@ -1,11 +1,16 @@
+use crate::analysis::graph::EnvVarLocation;
 use crate::analysis::resolver::BindingResolver;
-use crate::analysis::{AnalysisPipeline, BindingGraph, QueryEngine};
+use crate::analysis::{
+    custom_detector, deploy_config, package_json, properties, yaml_config, AnalysisPipeline,
+    BindingGraph, CustomDetector, QueryEngine,
+};
 use crate::languages::{LanguageRegistry, LanguageSupport};
 use crate::types::{
     BindingKind, DocumentState, EnvBinding, EnvBindingUsage, EnvReference, ImportContext,
 };
 use compact_str::CompactString;
 use dashmap::DashMap;
+use std::sync::atomic::{AtomicU64, Ordering};
 use std::sync::Arc;
 use tower_lsp::lsp_types::{Position, Range, TextDocumentContentChangeEvent, Url};
 use tree_sitter::Tree;
@@ -47,6 +52,9 @@ struct AnalysisResult {
 pub struct DocumentEntry {
     pub state: DocumentState,
     pub binding_graph: Arc<BindingGraph>,
+    /// Which open of the document this is. Clients restart versions on
+    /// reopen, so a version alone can't tell two opens apart.
+    pub generation: u64,
 }
 
 pub struct DocumentManager {
@@ -54,6 +62,10 @@ pub struct DocumentManager {
     documents: DashMap<Url, DocumentEntry>,
     query_engine: Arc<QueryEngine>,
     languages: Arc<LanguageRegistry>,
+    detectors: parking_lot::RwLock<Vec<CustomDetector>>,
+    scan_all_config_files: parking_lot::RwLock<bool>,
+    /// Bumped on every open and close.
+    generation: AtomicU64,
 }
 
 impl DocumentManager {
@@ -62,16 +74,45 @@ impl DocumentManager {
             documents: DashMap::new(),
             query_engine,
             languages,
+            detectors: parking_lot::RwLock::new(Vec::new()),
+            scan_all_config_files: parking_lot::RwLock::new(false),
+            generation: AtomicU64::new(0),
         }
     }
 
+    /// Replaces the configured custom detectors. Open documents keep their
+    /// graphs until their next change.
+    pub fn set_detectors(&self, detectors: Vec<CustomDetector>) {
+        *self.detectors.write() = detectors;
+    }
+
+    /// When set, every `.yml`, `.yaml` and `.json` document is scanned for
+    /// interpolation, not just Compose files.
+    pub fn set_scan_all_config_files(&self, scan_all: bool) {
+        *self.scan_all_config_files.write() = scan_all;
+    }
+
+    /// Scans the document with the first custom detector matching its path.
+    /// A matching detector takes precedence over language analysis.
+    pub fn analyze_with_detector(&self, uri: &Url, content: &str) -> Option<BindingGraph> {
+        let path = uri.to_file_path().ok()?;
+        let detectors = self.detectors.read();
+        custom_detector::detector_for(&detectors, &path).map(|detector| detector.analyze(content))
+    }
+
+    /// An extension override wins, then the client's language id, then the
+    /// URI's extension.
+    fn language_for(&self, uri: &Url, language_id: &str) -> Option<Arc<dyn LanguageSupport>> {
+        self.languages
+            .get_override_for_uri(uri)
+            .or_else(|| self.languages.get_by_language_id(language_id))
+            .or_else(|| self.languages.get_for_uri(uri))
+    }
+
     pub async fn open(&self, uri: Url, language_id: String, content: String, version: i32) {
 
         
-        let lang_opt = self
-            .languages
-            .get_by_language_id(&language_id)
-            .or_else(|| self.languages.get_for_uri(&uri));
+        let lang_opt = self.language_for(&uri, &language_id);
 
         let mut doc = DocumentState::new(
             uri.clone(),
@@ -80,7 +121,9 @@ impl DocumentManager {
             version,
         );
 
-        let binding_graph = if let Some(lang) = lang_opt {
+        let binding_graph = if let Some(graph) = self.analyze_non_code(&uri, &content) {
+            Arc::new(graph)
+        } else if let Some(lang) = lang_opt {
             let AnalysisResult {
                 tree,
                 import_context,
@@ -100,6 +143,7 @@ impl DocumentManager {
             DocumentEntry {
                 state: doc,
                 binding_graph,
+                generation: self.generation.fetch_add(1, Ordering::Relaxed) + 1,
             },
         );
     }
@@ -194,12 +238,15 @@ impl DocumentManager {
             }
         };
 
-        let lang_opt = self
-            .languages
-            .get_by_language_id(&language_id)
-            .or_else(|| self.languages.get_for_uri(uri));
+        let lang_opt = self.language_for(uri, &language_id);
 
-        if let Some(lang) = lang_opt {
+        if let Some(binding_graph) = self.analyze_non_code(uri, &content) {
+            if let Some(mut entry) = self.documents.get_mut(uri) {
+                if entry.state.version == version {
+                    entry.binding_graph = Arc::new(binding_graph);
+                }
+            }
+        } else if let Some(lang) = lang_opt {
             let AnalysisResult {
                 tree,
                 import_context,
@@ -224,12 +271,44 @@ impl DocumentManager {
         }
     }
 
+    /// Scans a document that is analyzed without a language: one matched by
+    /// a custom detector, `package.json`, deployment configs, `.properties`
+    /// files and YAML or JSON config files. Returns `None` for any other
+    /// document.
+    fn analyze_non_code(&self, uri: &Url, content: &str) -> Option<BindingGraph> {
+        if let Some(graph) = self.analyze_with_detector(uri, content) {
+            return Some(graph);
+        }
+        let path = uri.to_file_path().ok()?;
+        if package_json::is_package_json(&path) {
+            Some(package_json::analyze(content))
+        } else if deploy_config::is_deploy_config(&path) {
+            Some(deploy_config::analyze(&path, content))
+        } else if properties::is_properties_file(&path) {
+            Some(properties::analyze(content))
+        } else if yaml_config::is_yaml_config(&path, *self.scan_all_config_files.read()) {
+            Some(yaml_config::analyze(&path, content))
+        } else {
+            None
+        }
+    }
+
     
     
     pub fn close(&self, uri: &Url) {
+        self.generation.fetch_add(1, Ordering::Relaxed);
         self.documents.remove(uri);
     }
 
+    /// The open document's generation and version. Work scheduled for one
+    /// stamp is stale once the stamp changes, even if the document was
+    /// closed and reopened at the same version.
+    pub fn version_stamp(&self, uri: &Url) -> Option<(u64, i32)> {
+        self.documents
+            .get(uri)
+            .map(|entry| (entry.generation, entry.state.version))
+    }
+
     async fn analyze_content(
         &self,
         content: &str,
@@ -349,6 +428,25 @@ impl DocumentManager {
 
     
     
+    /// See [`BindingResolver::local_binding_ranges`].
+    pub fn get_local_binding_ranges(&self, uri: &Url, position: Position) -> Option<Vec<Range>> {
+        let entry = self.documents.get(uri)?;
+        let resolver = BindingResolver::new(&entry.binding_graph);
+        resolver.local_binding_ranges(position)
+    }
+
+    /// Whether `name` is a valid identifier in the document's language. See
+    /// [`LanguageSupport::is_valid_identifier`].
+    pub fn is_valid_identifier(&self, uri: &Url, name: &str) -> bool {
+        let Some(language_id) = self.documents.get(uri).map(|e| e.state.language_id.clone()) else {
+            return false;
+        };
+        self.language_for(uri, &language_id)
+            .is_some_and(|lang| lang.is_valid_identifier(name))
+    }
+
+    
+    
     pub fn get_binding_kind_for_usage(&self, uri: &Url, binding_name: &str) -> Option<BindingKind> {
         let entry = self.documents.get(uri)?;
         let resolver = BindingResolver::new(&entry.binding_graph);
@@ -374,7 +472,7 @@ impl DocumentManager {
         };
 
         
-        let lang = match self.languages.get_by_language_id(&language_id) {
+        let lang = match self.language_for(uri, &language_id) {
             Some(l) => l,
             None => return false,
         };
@@ -422,7 +520,7 @@ impl DocumentManager {
         };
 
         
-        let lang = self.languages.get_by_language_id(&language_id)?;
+        let lang = self.language_for(uri, &language_id)?;
 
         self.query_engine
             .check_completion_context(lang.as_ref(), &tree, content.as_bytes(), position)
@@ -438,6 +536,21 @@ impl DocumentManager {
         self.documents.get(uri).map(|entry| Arc::clone(&entry.binding_graph))
     }
 
+    /// Every location of the env var `name` in an open document, in source
+    /// order. See [`BindingGraph::get_env_var_locations`].
+    pub fn env_var_locations(&self, uri: &Url, name: &str) -> Vec<EnvVarLocation> {
+        let Some(entry) = self.documents.get(uri) else {
+            return Vec::new();
+        };
+        let mut locations = entry
+            .binding_graph
+            .get_env_var_locations(name)
+            .cloned()
+            .unwrap_or_default();
+        locations.sort_by_key(|loc| (loc.range.start.line, loc.range.start.character));
+        locations
+    }
+
     
     pub fn all_uris(&self) -> Vec<Url> {
         self.documents.iter().map(|entry| entry.key().clone()).collect()
@@ -448,6 +561,25 @@ impl DocumentManager {
         self.documents.len()
     }
 
+    /// Returns true for open documents that no registered language can analyze.
+    ///
+    /// Passive documents are kept for content tracking only: they have no tree
+    /// and an empty binding graph. `package.json`, deployment configs,
+    /// `.properties` files, YAML configs and files matched by a custom
+    /// detector are never passive since they are scanned for references.
+    pub fn is_passive(&self, uri: &Url) -> bool {
+        let Some(language_id) = self
+            .documents
+            .get(uri)
+            .map(|entry| entry.state.language_id.clone())
+        else {
+            return false;
+        };
+        // Scanning no content is cheap and tells whether any scanner claims
+        // the document
+        self.language_for(uri, &language_id).is_none() && self.analyze_non_code(uri, "").is_none()
+    }
+
     pub fn query_engine(&self) -> &Arc<QueryEngine> {
         &self.query_engine
     }
@@ -459,6 +591,12 @@ impl DocumentManager {
         Some(tree.root_node().has_error())
     }
 
+    /// Range of the first ERROR node in the document's tree.
+    pub fn first_parse_error(&self, uri: &Url) -> Option<Range> {
+        let entry = self.documents.get(uri)?;
+        first_error_node_range(entry.state.tree.as_ref()?)
+    }
+
     /// Get syntax error locations and messages from the parsed tree.
     /// Returns a list of (Range, Option<message>) for each error node.
     pub fn get_syntax_errors(&self, uri: &Url) -> Vec<(Range, Option<String>)> {
@@ -479,6 +617,22 @@ impl DocumentManager {
     }
 }
 
+/// Range of the first ERROR node in document order, if any.
+pub fn first_error_node_range(tree: &tree_sitter::Tree) -> Option<Range> {
+    fn find(node: tree_sitter::Node) -> Option<tree_sitter::Node> {
+        if node.is_error() {
+            return Some(node);
+        }
+        if !node.has_error() {
+            return None;
+        }
+        let mut cursor = node.walk();
+        let found = node.children(&mut cursor).find_map(find);
+        found
+    }
+    find(tree.root_node()).map(node_to_lsp_range)
+}
+
 /// Recursively collect ERROR and MISSING nodes from the tree.
 fn collect_error_nodes(node: tree_sitter::Node, source: &[u8], errors: &mut Vec<(Range, Option<String>)>) {
     if node.is_error() {
@@ -640,6 +794,24 @@ func main() {
         assert!(doc.tree.is_none());
     }
 
+    #[tokio::test]
+    async fn test_is_passive() {
+        let manager = create_test_manager();
+        let md = test_uri("README.md");
+        let js = test_uri("test.js");
+
+        manager
+            .open(md.clone(), "markdown".to_string(), "# Notes".to_string(), 1)
+            .await;
+        manager
+            .open(js.clone(), "javascript".to_string(), "const x = 1;".to_string(), 1)
+            .await;
+
+        assert!(manager.is_passive(&md));
+        assert!(!manager.is_passive(&js));
+        assert!(!manager.is_passive(&test_uri("closed.md")));
+    }
+
     #[tokio::test]
     async fn test_change_document() {
         let manager = create_test_manager();
@@ -1315,4 +1487,23 @@ const secret = process.env.SECRET;"#.to_string();
         let errors = manager.get_syntax_errors(&uri);
         assert!(errors.is_empty());
     }
+
+    #[tokio::test]
+    async fn test_version_stamp_changes_on_reopen() {
+        let manager = create_test_manager();
+        let uri = test_uri("test.js");
+        let content = "process.env.DB_URL;".to_string();
+
+        manager.open(uri.clone(), "javascript".to_string(), content.clone(), 2).await;
+        let first = manager.version_stamp(&uri).unwrap();
+        assert_eq!(first.1, 2);
+
+        manager.close(&uri);
+        assert!(manager.version_stamp(&uri).is_none());
+
+        manager.open(uri.clone(), "javascript".to_string(), content, 2).await;
+        let reopened = manager.version_stamp(&uri).unwrap();
+        assert_eq!(reopened.1, 2);
+        assert_ne!(first, reopened);
+    }
 }
@@ -0,0 +1,103 @@
+//! User-configured helper functions that read env vars.
+//!
+//! Each `[[custom_env_accessors]]` entry in `ecolog.toml` names a callee, like
+//! `env` or `config.get`, and which argument holds the variable name. Any
+//! call to that callee whose argument is a string literal becomes a direct
+//! reference, in every language whose grammar gives calls `function` and
+//! `arguments` fields.
+
+use crate::analysis::ts_to_lsp_range;
+use crate::languages::LanguageSupport;
+use crate::types::{AccessType, EnvReference};
+use compact_str::CompactString;
+use serde::{Deserialize, Serialize};
+use tower_lsp::lsp_types::{Position, Range};
+use tree_sitter::{Node, Tree};
+
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize, Serialize)]
+pub struct EnvAccessor {
+    /// Callee as written at the call site, e.g. `env` or `config.get`.
+    pub callee: String,
+    /// Zero-based index of the argument holding the variable name.
+    #[serde(default)]
+    pub arg_index: usize,
+}
+
+/// Finds calls to any of `accessors` with a string literal name argument.
+pub fn extract_references(
+    accessors: &[EnvAccessor],
+    language: &dyn LanguageSupport,
+    tree: &Tree,
+    source: &[u8],
+) -> Vec<EnvReference> {
+    let mut references = Vec::new();
+    if !accessors.is_empty() {
+        walk(tree.root_node(), accessors, language, source, &mut references);
+    }
+    references
+}
+
+fn walk(
+    node: Node,
+    accessors: &[EnvAccessor],
+    language: &dyn LanguageSupport,
+    source: &[u8],
+    references: &mut Vec<EnvReference>,
+) {
+    if language.comment_node_kinds().contains(&node.kind()) {
+        return;
+    }
+    if let Some(reference) = accessor_call(node, accessors, language, source) {
+        references.push(reference);
+    }
+    let mut cursor = node.walk();
+    for child in node.children(&mut cursor) {
+        walk(child, accessors, language, source, references);
+    }
+}
+
+fn accessor_call(
+    node: Node,
+    accessors: &[EnvAccessor],
+    language: &dyn LanguageSupport,
+    source: &[u8],
+) -> Option<EnvReference> {
+    let function = node.child_by_field_name("function")?;
+    let arguments = node.child_by_field_name("arguments")?;
+    let callee: String = function
+        .utf8_text(source)
+        .ok()?
+        .chars()
+        .filter(|c| !c.is_whitespace())
+        .collect();
+    let accessor = accessors.iter().find(|a| a.callee == callee)?;
+
+    let mut cursor = arguments.walk();
+    let argument = arguments
+        .named_children(&mut cursor)
+        .filter(|child| !language.comment_node_kinds().contains(&child.kind()))
+        .nth(accessor.arg_index)?;
+    let single_line = argument.start_position().row == argument.end_position().row;
+    if !argument.kind().contains("string") || !single_line {
+        return None;
+    }
+
+    let text = argument.utf8_text(source).ok()?;
+    let name = language.strip_quotes(text);
+    if name.is_empty() || !name.chars().all(|c| c.is_ascii_alphanumeric() || c == '_') {
+        return None;
+    }
+    let offset = name.as_ptr() as usize - text.as_ptr() as usize;
+    let start = argument.start_position();
+    let name_start = Position::new(start.row as u32, (start.column + offset) as u32);
+    let name_end = Position::new(name_start.line, name_start.character + name.len() as u32);
+
+    Some(EnvReference {
+        name: CompactString::from(name),
+        full_range: ts_to_lsp_range(node.range()),
+        name_range: Range::new(name_start, name_end),
+        access_type: AccessType::FunctionCall,
+        has_default: false,
+        default_value: None,
+    })
+}
@@ -0,0 +1,140 @@
+//! `# include: <path>` comments in env files.
+//!
+//! With `workspace.follow_env_includes` on, an env file containing
+//! `# include: shared.env` also provides every key of `shared.env`. Paths are
+//! relative to the including file. Includes are followed transitively, and a
+//! file reached twice is only read once, so include cycles end. Targets
+//! outside the workspace root are ignored.
+
+use crate::analysis::indexer::read_env_file;
+use crate::types::KorniEntryExt;
+use korni::ParseOptions;
+use rustc_hash::FxHashSet;
+use std::collections::VecDeque;
+use std::path::{Path, PathBuf};
+
+/// Returns the files `env_file` includes, in the order they're declared.
+pub fn include_paths(content: &str, env_file: &Path) -> Vec<PathBuf> {
+    let dir = env_file.parent().unwrap_or(Path::new(""));
+    content
+        .lines()
+        .filter_map(|line| {
+            let comment = line.trim_start().strip_prefix('#')?;
+            let target = comment.trim_start().strip_prefix("include:")?.trim();
+            (!target.is_empty()).then(|| dir.join(target))
+        })
+        .collect()
+}
+
+/// Reads the files reachable through includes from `roots`, nearest first.
+/// The roots themselves aren't returned, and neither are targets that
+/// resolve outside `workspace_root`.
+pub async fn included_files(
+    roots: &[PathBuf],
+    workspace_root: &Path,
+    max_bytes: u64,
+) -> Vec<(PathBuf, String)> {
+    let workspace_root = normalize(workspace_root).await;
+    let mut visited = FxHashSet::default();
+    for root in roots {
+        visited.insert(normalize(root).await);
+    }
+    let mut queue: VecDeque<(PathBuf, bool)> =
+        roots.iter().map(|root| (root.clone(), true)).collect();
+    let mut files = Vec::new();
+
+    while let Some((path, is_root)) = queue.pop_front() {
+        let Some(content) = read_env_file(&path, max_bytes).await else {
+            continue;
+        };
+        for include in include_paths(&content, &path) {
+            let target = normalize(&include).await;
+            if !target.starts_with(&workspace_root) {
+                tracing::debug!("Ignoring include of {:?} outside the workspace", include);
+                continue;
+            }
+            if visited.insert(target) {
+                queue.push_back((include, false));
+            }
+        }
+        if !is_root {
+            files.push((path, content));
+        }
+    }
+    files
+}
+
+/// Finds the first of `files` defining `key`, returning its path and value.
+pub fn find_included_key(files: &[(PathBuf, String)], key: &str) -> Option<(PathBuf, String)> {
+    files.iter().find_map(|(path, content)| {
+        korni::parse_with_options(content, ParseOptions::full())
+            .into_iter()
+            .filter_map(|entry| entry.into_valid_pair())
+            .find(|kv| kv.key.as_ref() == key)
+            .map(|kv| (path.clone(), kv.value.to_string()))
+    })
+}
+
+async fn normalize(path: &Path) -> PathBuf {
+    tokio::fs::canonicalize(path)
+        .await
+        .unwrap_or_else(|_| path.to_path_buf())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_include_paths_relative_to_file() {
+        let content = "# include: shared.env\n#include:../base.env\nKEY=1\n# not an include\n";
+        let paths = include_paths(content, Path::new("/app/config/a.env"));
+        assert_eq!(
+            paths,
+            vec![
+                PathBuf::from("/app/config/shared.env"),
+                PathBuf::from("/app/config/../base.env"),
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_included_files_follow_chains_and_stop_at_cycles() {
+        let dir = tempfile::tempdir().unwrap();
+        let write = |name: &str, content: &str| {
+            std::fs::write(dir.path().join(name), content).unwrap();
+            dir.path().join(name)
+        };
+        let a = write("a.env", "# include: b.env\nA=1\n");
+        write("b.env", "# include: c.env\nB=2\n");
+        write("c.env", "# include: a.env\nC=3\n");
+
+        let files = included_files(&[a], dir.path(), u64::MAX).await;
+        let names: Vec<_> = files
+            .iter()
+            .map(|(path, _)| path.file_name().unwrap().to_str().unwrap())
+            .collect();
+        assert_eq!(names, vec!["b.env", "c.env"]);
+
+        let (path, value) = find_included_key(&files, "C").unwrap();
+        assert!(path.ends_with("c.env"));
+        assert_eq!(value, "3");
+        assert!(find_included_key(&files, "A").is_none());
+    }
+
+    #[tokio::test]
+    async fn test_included_files_stay_inside_workspace() {
+        let outside = tempfile::tempdir().unwrap();
+        std::fs::write(outside.path().join("secret.env"), "SECRET=1\n").unwrap();
+        let workspace = tempfile::tempdir().unwrap();
+        let env = workspace.path().join(".env");
+        let include = format!(
+            "# include: {}\n# include: ../{}/secret.env\n",
+            outside.path().join("secret.env").display(),
+            outside.path().file_name().unwrap().to_str().unwrap()
+        );
+        std::fs::write(&env, include).unwrap();
+
+        assert!(included_files(&[env], workspace.path(), u64::MAX).await.is_empty());
+    }
+}
@@ -0,0 +1,35 @@
+//! Extension point for custom env file formats.
+//!
+//! Downstream crates implement [`EnvSourcePlugin`] for formats abundantis
+//! can't parse and register it with the [`WorkspaceIndexer`]. Matching files
+//! are discovered and indexed like env files, and their definitions resolve
+//! through the same fallback path as deployment configs.
+//!
+//! [`WorkspaceIndexer`]: crate::analysis::WorkspaceIndexer
+
+use crate::types::EnvDefinition;
+use std::path::Path;
+
+/// A parser for a custom env file format.
+pub trait EnvSourcePlugin: Send + Sync {
+    /// Name used in logs.
+    fn name(&self) -> &str;
+
+    /// Glob patterns matched against file names, e.g. `*.myenv`.
+    fn file_patterns(&self) -> &[&str];
+
+    /// Parses a file's content into the variables it defines.
+    fn parse(&self, content: &str) -> Vec<EnvDefinition>;
+
+    /// Returns true if this plugin handles the file at `path`.
+    fn matches(&self, path: &Path) -> bool {
+        let Some(name) = path.file_name().and_then(|n| n.to_str()) else {
+            return false;
+        };
+        self.file_patterns().iter().any(|pattern| {
+            glob::Pattern::new(pattern)
+                .map(|p| p.matches(name))
+                .unwrap_or(false)
+        })
+    }
+}
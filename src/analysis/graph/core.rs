@@ -11,7 +11,7 @@ use crate::analysis::range_utils::range_size;
 use crate::types::{Scope, ScopeId, ScopeKind, Symbol, SymbolId, SymbolOrigin};
 use compact_str::CompactString;
 use parking_lot::RwLock;
-use rustc_hash::FxHashMap;
+use rustc_hash::{FxHashMap, FxHashSet};
 use tower_lsp::lsp_types::Range;
 
 impl BindingGraph {
@@ -40,6 +40,9 @@ impl BindingGraph {
             scope_cache: RwLock::new(FxHashMap::default()),
             next_symbol_id: 0,
             next_scope_id: 1,
+            dynamic_env_access: false,
+            shadowed_env_keys: FxHashMap::default(),
+            local_declarations: FxHashSet::default(),
         };
 
         // Add root scope
@@ -213,7 +216,8 @@ impl BindingGraph {
     /// Look up a symbol by name in the given scope or its ancestors.
     ///
     /// Returns the most recent valid symbol with the given name that is
-    /// visible from the specified scope.
+    /// visible from the specified scope. A local declaration of the name
+    /// in a nearer scope hides symbols further out.
     pub fn lookup_symbol(&self, name: &str, scope: ScopeId) -> Option<&Symbol> {
         let mut current_scope = Some(scope);
 
@@ -230,6 +234,10 @@ impl BindingGraph {
                 }
             }
 
+            if self.local_declarations.contains(&key) {
+                return None;
+            }
+
             current_scope = self.get_scope(scope_id).and_then(|s| s.parent);
         }
 
@@ -252,6 +260,12 @@ impl BindingGraph {
             .flatten()
     }
 
+    /// Record that `name` is declared in `scope` by something that isn't a
+    /// symbol, like a plain object or a function parameter.
+    pub fn add_local_declaration(&mut self, name: CompactString, scope: ScopeId) {
+        self.local_declarations.insert((name, scope));
+    }
+
     /// Add a scope to the graph.
     ///
     /// The scope is assigned a new ID and added to the pending scope entries.
@@ -59,6 +59,9 @@ impl BindingGraph {
     /// Uncached O(log n) scope lookup by position using the interval tree.
     ///
     /// Returns the most specific (smallest) scope containing the position.
+    /// Scopes with equal ranges, like a function and its body block, are
+    /// tied by taking the higher `ScopeId`. Scopes are added outside-in, so
+    /// that is the inner one, independent of interval tree iteration order.
     fn scope_at_position_uncached(&self, position: Position) -> ScopeId {
         let point = position_to_point(position);
         let mut best_scope = ScopeId::root();
@@ -68,7 +71,9 @@ impl BindingGraph {
         if let Some(tree) = &self.scope_range_tree {
             for entry in tree.query_point(point) {
                 let (scope_id, size) = entry.value;
-                if size < best_size {
+                if size < best_size
+                    || (size == best_size && scope_id.index() > best_scope.index())
+                {
                     best_size = size;
                     best_scope = scope_id;
                 }
@@ -104,6 +109,18 @@ impl BindingGraph {
         self.direct_references.push(reference);
     }
 
+    /// Marks the file as reading the whole environment, so any variable may
+    /// be consumed without a direct reference.
+    pub fn set_dynamic_env_access(&mut self) {
+        self.dynamic_env_access = true;
+    }
+
+    /// Whether the file reads the whole environment.
+    #[inline]
+    pub fn has_dynamic_env_access(&self) -> bool {
+        self.dynamic_env_access
+    }
+
     /// Get all direct references.
     #[inline]
     pub fn direct_references(&self) -> &[EnvReference] {
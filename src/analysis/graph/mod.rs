@@ -29,7 +29,7 @@ use crate::types::{EnvReference, ResolvedEnv, Scope, ScopeId, Symbol, SymbolId,
 use compact_str::CompactString;
 use intervaltree::IntervalTree;
 use parking_lot::RwLock;
-use rustc_hash::FxHashMap;
+use rustc_hash::{FxHashMap, FxHashSet};
 use smallvec::SmallVec;
 use tower_lsp::lsp_types::Range;
 
@@ -104,6 +104,17 @@ pub struct BindingGraph {
 
     /// Next scope ID to assign
     pub(crate) next_scope_id: u32,
+
+    /// Whether the file reads the whole environment, e.g. `Deno.env.toObject()`
+    pub(crate) dynamic_env_access: bool,
+
+    /// Keys an env object binding sets itself, like `PORT` in
+    /// `const cfg = { ...process.env, PORT: 3000 }`
+    pub(crate) shadowed_env_keys: FxHashMap<SymbolId, Vec<CompactString>>,
+
+    /// Names declared in a scope without a symbol, like `env` in
+    /// `const env = {}`, which hide symbols of the same name in outer scopes
+    pub(crate) local_declarations: FxHashSet<(CompactString, ScopeId)>,
 }
 
 impl Clone for BindingGraph {
@@ -129,6 +140,9 @@ impl Clone for BindingGraph {
             scope_cache: RwLock::new(FxHashMap::default()),
             next_symbol_id: self.next_symbol_id,
             next_scope_id: self.next_scope_id,
+            dynamic_env_access: self.dynamic_env_access,
+            shadowed_env_keys: self.shadowed_env_keys.clone(),
+            local_declarations: self.local_declarations.clone(),
         }
     }
 }
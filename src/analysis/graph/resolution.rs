@@ -9,6 +9,7 @@ use super::BindingGraph;
 use crate::constants::MAX_CHAIN_DEPTH;
 use crate::types::{ResolvedEnv, SymbolId, SymbolOrigin};
 use compact_str::CompactString;
+use tower_lsp::lsp_types::Range;
 
 impl BindingGraph {
     /// Resolve a symbol to its env var or env object.
@@ -58,6 +59,7 @@ impl BindingGraph {
 
             SymbolOrigin::DestructuredProperty { source, key } => {
                 match self.resolve_to_env_with_depth(*source, max_depth, current_depth + 1)? {
+                    ResolvedEnv::Object(_) if self.is_env_key_shadowed(*source, key) => None,
                     ResolvedEnv::Object(_) => Some(ResolvedEnv::Variable(key.clone())),
                     ResolvedEnv::Variable(_) => None,
                 }
@@ -70,6 +72,34 @@ impl BindingGraph {
         }
     }
 
+    /// Marks `keys` as set by the env object binding itself rather than read
+    /// from the environment.
+    pub fn set_shadowed_env_keys(&mut self, symbol_id: SymbolId, keys: Vec<CompactString>) {
+        if !keys.is_empty() {
+            self.shadowed_env_keys.insert(symbol_id, keys);
+        }
+    }
+
+    /// Whether `key` on the env object `symbol_id` resolves to (following
+    /// `const b = a` links) is a literal key shadowing the env var.
+    pub fn is_env_key_shadowed(&self, symbol_id: SymbolId, key: &str) -> bool {
+        let mut current = symbol_id;
+        for _ in 0..MAX_CHAIN_DEPTH {
+            if self
+                .shadowed_env_keys
+                .get(&current)
+                .is_some_and(|keys| keys.iter().any(|k| k == key))
+            {
+                return true;
+            }
+            match self.get_symbol(current).map(|symbol| &symbol.origin) {
+                Some(SymbolOrigin::Symbol { target }) => current = *target,
+                _ => return false,
+            }
+        }
+        false
+    }
+
     /// Check if a symbol resolves to an environment object (e.g., `process.env`).
     pub fn resolves_to_env_object(&self, symbol_id: SymbolId) -> bool {
         matches!(self.resolve_to_env(symbol_id), Some(ResolvedEnv::Object(_)))
@@ -84,4 +114,30 @@ impl BindingGraph {
             ResolvedEnv::Object(_) => None,
         }
     }
+
+    /// Get the declaration range of the symbol at the root of a chain.
+    ///
+    /// Follows `const b = a` and destructuring links back to the binding that
+    /// reads the env var or env object directly (e.g. `const env = process.env`).
+    pub fn origin_declaration_range(&self, symbol_id: SymbolId) -> Option<Range> {
+        let mut symbol = self.get_symbol(symbol_id)?;
+
+        for _ in 0..MAX_CHAIN_DEPTH {
+            match &symbol.origin {
+                SymbolOrigin::EnvVar { .. } | SymbolOrigin::EnvObject { .. } => {
+                    return Some(symbol.declaration_range);
+                }
+                SymbolOrigin::Symbol { target: next }
+                | SymbolOrigin::DestructuredProperty { source: next, .. } => {
+                    symbol = self.get_symbol(*next)?;
+                }
+                SymbolOrigin::Unknown
+                | SymbolOrigin::Unresolvable
+                | SymbolOrigin::UnresolvedSymbol { .. }
+                | SymbolOrigin::UnresolvedDestructure { .. } => return None,
+            }
+        }
+
+        None
+    }
 }
@@ -555,6 +555,72 @@ fn test_resolves_to_env_object() {
     assert!(!graph.resolves_to_env_object(var_id));
 }
 
+#[test]
+fn test_origin_declaration_range_follows_chain() {
+    let mut graph = BindingGraph::new();
+
+    let env_id = graph.add_symbol(Symbol {
+        id: SymbolId::new(1).unwrap(),
+        name: "env".into(),
+        declaration_range: make_range(0, 6, 0, 23),
+        name_range: make_range(0, 6, 0, 9),
+        scope: ScopeId::root(),
+        origin: SymbolOrigin::EnvObject {
+            canonical_name: "process.env".into(),
+        },
+        kind: SymbolKind::EnvObject,
+        is_valid: true,
+        destructured_key_range: None,
+    });
+
+    let db_id = graph.add_symbol(Symbol {
+        id: SymbolId::new(2).unwrap(),
+        name: "DB_URL".into(),
+        declaration_range: make_range(1, 6, 1, 20),
+        name_range: make_range(1, 8, 1, 14),
+        scope: ScopeId::root(),
+        origin: SymbolOrigin::DestructuredProperty {
+            source: env_id,
+            key: "DB_URL".into(),
+        },
+        kind: SymbolKind::Value,
+        is_valid: true,
+        destructured_key_range: None,
+    });
+
+    let alias_id = graph.add_symbol(Symbol {
+        id: SymbolId::new(3).unwrap(),
+        name: "url".into(),
+        declaration_range: make_range(2, 6, 2, 18),
+        name_range: make_range(2, 6, 2, 9),
+        scope: ScopeId::root(),
+        origin: SymbolOrigin::Symbol { target: db_id },
+        kind: SymbolKind::Variable,
+        is_valid: true,
+        destructured_key_range: None,
+    });
+
+    let unknown_id = graph.add_symbol(Symbol {
+        id: SymbolId::new(4).unwrap(),
+        name: "other".into(),
+        declaration_range: make_range(3, 6, 3, 20),
+        name_range: make_range(3, 6, 3, 11),
+        scope: ScopeId::root(),
+        origin: SymbolOrigin::UnresolvedSymbol {
+            source_name: "config".into(),
+        },
+        kind: SymbolKind::Variable,
+        is_valid: true,
+        destructured_key_range: None,
+    });
+
+    let env_declaration = Some(make_range(0, 6, 0, 23));
+    assert_eq!(graph.origin_declaration_range(env_id), env_declaration);
+    assert_eq!(graph.origin_declaration_range(db_id), env_declaration);
+    assert_eq!(graph.origin_declaration_range(alias_id), env_declaration);
+    assert_eq!(graph.origin_declaration_range(unknown_id), None);
+}
+
 #[test]
 fn test_get_env_var_name() {
     let mut graph = BindingGraph::new();
@@ -1109,6 +1175,30 @@ fn test_scope_at_position_nested_scopes() {
     assert_eq!(scope, ScopeId::root());
 }
 
+#[test]
+fn test_scope_at_position_equal_ranges_prefers_inner() {
+    let mut graph = BindingGraph::new();
+    graph.set_root_range(make_range(0, 0, 20, 0));
+
+    let function_scope = graph.add_scope(Scope {
+        id: ScopeId::root(),
+        parent: Some(ScopeId::root()),
+        range: make_range(2, 0, 8, 1),
+        kind: ScopeKind::Function,
+    });
+    let block_scope = graph.add_scope(Scope {
+        id: ScopeId::root(),
+        parent: Some(function_scope),
+        range: make_range(2, 0, 8, 1),
+        kind: ScopeKind::Block,
+    });
+
+    for _ in 0..5 {
+        graph.rebuild_range_index();
+        assert_eq!(graph.scope_at_position(Position::new(4, 2)), block_scope);
+    }
+}
+
 #[test]
 fn test_scope_at_position_empty_tree() {
     let graph = BindingGraph::new();
@@ -1629,7 +1719,7 @@ fn test_remove_in_range_entire_document() {
 
     for i in 0..5 {
         graph.add_symbol(Symbol {
-            id: SymbolId::new(i as u32 + 1).unwrap(),
+            id: SymbolId::new(i + 1).unwrap(),
             name: format!("sym{}", i).into(),
             declaration_range: make_range(i * 10, 0, i * 10, 20),
             name_range: make_range(i * 10, 6, i * 10, 10),
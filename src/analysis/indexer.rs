@@ -3,14 +3,19 @@
 
 
 
-use crate::analysis::workspace_index::{FileIndexEntry, WorkspaceIndex};
-use crate::analysis::{AnalysisPipeline, BindingGraph, BindingResolver, QueryEngine};
+use crate::analysis::workspace_glob::is_env_file_path;
+use crate::analysis::workspace_index::{FileIndexEntry, IndexStats, WorkspaceIndex};
+use crate::analysis::{
+    build_script, custom_detector, deploy_config, env_include, package_json, properties, yaml_config, AnalysisPipeline, CustomDetector, EnvSourcePlugin, BindingGraph, BindingResolver, QueryEngine,
+};
+use crate::cancellation::CancellationToken;
 use crate::languages::LanguageRegistry;
 use crate::types::{
     ExportResolution, FileExportEntry, ImportContext, KorniEntryExt, SymbolId, SymbolOrigin,
 };
 use anyhow::Result;
 use compact_str::CompactString;
+use futures::StreamExt;
 use korni::ParseOptions;
 use rustc_hash::FxHashSet;
 use std::path::{Path, PathBuf};
@@ -26,6 +31,81 @@ use tracing::{debug, info, warn};
 
 
 
+/// Default for `workspace.max_env_file_bytes`.
+pub const DEFAULT_MAX_ENV_FILE_BYTES: u64 = 1024 * 1024;
+
+/// Default for `workspace.case_insensitive_env_files`: on for macOS and
+/// Windows, whose filesystems are usually case-insensitive.
+pub const DEFAULT_CASE_INSENSITIVE_ENV_FILES: bool =
+    cfg!(any(target_os = "macos", target_os = "windows"));
+
+/// Reads a file as UTF-8, replacing invalid sequences with U+FFFD rather
+/// than failing, so a stray Latin-1 byte doesn't hide the rest of the file.
+pub async fn read_to_string_lossy(path: &Path) -> std::io::Result<String> {
+    let bytes = tokio::fs::read(path).await?;
+    Ok(from_utf8_lossy(path, bytes))
+}
+
+fn from_utf8_lossy(path: &Path, bytes: Vec<u8>) -> String {
+    match String::from_utf8(bytes) {
+        Ok(content) => content,
+        Err(err) => {
+            warn!(
+                "{:?} is not valid UTF-8 at byte {}, reading it lossily",
+                path,
+                err.utf8_error().valid_up_to()
+            );
+            String::from_utf8_lossy(err.as_bytes()).into_owned()
+        }
+    }
+}
+
+/// Offsets into the lossily decoded text of each U+FFFD that stands for an
+/// invalid sequence in `bytes`. Taken from the bytes themselves, so a file
+/// that genuinely contains U+FFFD isn't reported.
+pub fn invalid_utf8_offsets(bytes: &[u8]) -> Vec<usize> {
+    let mut offsets = Vec::new();
+    let mut offset = 0;
+    for chunk in bytes.utf8_chunks() {
+        offset += chunk.valid().len();
+        if !chunk.invalid().is_empty() {
+            offsets.push(offset);
+            offset += char::REPLACEMENT_CHARACTER.len_utf8();
+        }
+    }
+    offsets
+}
+
+/// Reads an env file, skipping it with a warning if it's larger than
+/// `max_bytes` or contains null bytes. A glob that matches a huge or binary
+/// file would otherwise stall the parser. Invalid UTF-8 is replaced, see
+/// [`read_to_string_lossy`].
+pub async fn read_env_file(path: &Path, max_bytes: u64) -> Option<String> {
+    let len = tokio::fs::metadata(path).await.ok()?.len();
+    if len > max_bytes {
+        warn!(
+            "Skipping env file {:?}: {} bytes exceeds max_env_file_bytes ({})",
+            path, len, max_bytes
+        );
+        return None;
+    }
+    let bytes = tokio::fs::read(path).await.ok()?;
+    if bytes.contains(&0) {
+        warn!("Skipping env file {:?}: looks binary (contains null bytes)", path);
+        return None;
+    }
+    Some(from_utf8_lossy(path, bytes))
+}
+
+/// How many files are read and indexed at once.
+fn index_parallelism() -> usize {
+    (num_cpus::get() / 2).clamp(1, 4)
+}
+
+fn canonical(path: &Path) -> PathBuf {
+    std::fs::canonicalize(path).unwrap_or_else(|_| path.to_path_buf())
+}
+
 pub struct WorkspaceIndexer {
     
     workspace_index: Arc<WorkspaceIndex>,
@@ -38,8 +118,37 @@ pub struct WorkspaceIndexer {
 
     
     workspace_root: PathBuf,
+
+    
+    max_depth: parking_lot::RwLock<Option<usize>>,
+
+    
+    max_env_file_bytes: parking_lot::RwLock<u64>,
+
+    follow_env_includes: parking_lot::RwLock<bool>,
+
+    case_insensitive_env_files: parking_lot::RwLock<bool>,
+
+    scan_all_config_files: parking_lot::RwLock<bool>,
+
+    
+    plugins: Arc<parking_lot::RwLock<Vec<Arc<dyn EnvSourcePlugin>>>>,
+
+    
+    detectors: Arc<parking_lot::RwLock<Vec<CustomDetector>>>,
+
+    /// Files found by the last walk of the workspace and the `env_files`
+    /// patterns it used, reused by lazy scans instead of walking again.
+    discovered_files: Arc<parking_lot::RwLock<Option<DiscoveredFiles>>>,
+
+    /// Names [`Self::index_files_mentioning`] already read every file for.
+    /// Files created or changed afterwards are indexed by the file watcher,
+    /// so a name never needs a second scan.
+    scanned_names: Arc<parking_lot::Mutex<FxHashSet<CompactString>>>,
 }
 
+type DiscoveredFiles = (Vec<CompactString>, Arc<[PathBuf]>);
+
 impl WorkspaceIndexer {
     
     pub fn new(
@@ -53,9 +162,76 @@ impl WorkspaceIndexer {
             query_engine,
             languages,
             workspace_root,
+            max_depth: parking_lot::RwLock::new(None),
+            max_env_file_bytes: parking_lot::RwLock::new(DEFAULT_MAX_ENV_FILE_BYTES),
+            follow_env_includes: parking_lot::RwLock::new(false),
+            case_insensitive_env_files: parking_lot::RwLock::new(
+                DEFAULT_CASE_INSENSITIVE_ENV_FILES,
+            ),
+            scan_all_config_files: parking_lot::RwLock::new(false),
+            plugins: Arc::new(parking_lot::RwLock::new(Vec::new())),
+            detectors: Arc::new(parking_lot::RwLock::new(Vec::new())),
+            discovered_files: Arc::new(parking_lot::RwLock::new(None)),
+            scanned_names: Arc::new(parking_lot::Mutex::new(FxHashSet::default())),
         }
     }
 
+    /// Limits how deep `discover_files` walks below the workspace root.
+    /// `None` walks the whole tree.
+    pub fn set_max_depth(&self, max_depth: Option<usize>) {
+        *self.max_depth.write() = max_depth;
+        *self.discovered_files.write() = None;
+        self.scanned_names.lock().clear();
+    }
+
+    /// Env files larger than `max_bytes` are skipped instead of parsed.
+    pub fn set_max_env_file_bytes(&self, max_bytes: u64) {
+        *self.max_env_file_bytes.write() = max_bytes;
+    }
+
+    /// When set, an env file's entry also lists the keys of the files it
+    /// pulls in with `# include:` comments.
+    pub fn set_follow_env_includes(&self, follow: bool) {
+        *self.follow_env_includes.write() = follow;
+    }
+
+    /// When set, `env_files` patterns match ignoring case.
+    pub fn set_case_insensitive_env_files(&self, case_insensitive: bool) {
+        *self.case_insensitive_env_files.write() = case_insensitive;
+    }
+
+    /// When set, every `.yml`, `.yaml` and `.json` file is indexed for
+    /// interpolation, not just Compose files.
+    pub fn set_scan_all_config_files(&self, scan_all: bool) {
+        *self.scan_all_config_files.write() = scan_all;
+        *self.discovered_files.write() = None;
+        self.scanned_names.lock().clear();
+    }
+
+    /// Registers a parser for a custom env file format. Takes effect on the
+    /// next index of matching files.
+    pub fn register_plugin(&self, plugin: Arc<dyn EnvSourcePlugin>) {
+        self.plugins.write().push(plugin);
+    }
+
+    /// Replaces the configured custom detectors. Takes effect on the next
+    /// index of matching files.
+    pub fn set_detectors(&self, detectors: Vec<CustomDetector>) {
+        *self.detectors.write() = detectors;
+    }
+
+    fn detector_for(&self, path: &Path) -> Option<CustomDetector> {
+        custom_detector::detector_for(&self.detectors.read(), path).cloned()
+    }
+
+    fn plugin_for(&self, path: &Path) -> Option<Arc<dyn EnvSourcePlugin>> {
+        self.plugins
+            .read()
+            .iter()
+            .find(|plugin| plugin.matches(path))
+            .cloned()
+    }
+
     
     
     
@@ -64,13 +240,69 @@ impl WorkspaceIndexer {
     
     
     
-    pub async fn index_workspace(&self, env_files: &[CompactString]) -> Result<()> {
+    pub async fn index_workspace(&self, env_files: &[CompactString]) -> Result<IndexStats> {
+        self.index_workspace_with_cancellation(env_files, &CancellationToken::new())
+            .await
+    }
+
+    /// Indexes the workspace, stopping early once `cancel` is triggered.
+    ///
+    /// The file walk and the spawn loop check the token between files;
+    /// already running file tasks are aborted. Files indexed before the
+    /// cancel stay in the index.
+    ///
+    /// A file that fails to index (e.g. deleted mid-walk) is skipped and
+    /// counted in `IndexStats::failed_files`; only an unreadable workspace
+    /// root fails the whole pass.
+    pub async fn index_workspace_with_cancellation(
+        &self,
+        env_files: &[CompactString],
+        cancel: &CancellationToken,
+    ) -> Result<IndexStats> {
+        self.index_discovered(env_files, cancel, |_| true).await
+    }
+
+    /// Indexes only the env files, including plugin formats, for
+    /// `workspace.lazy_index`. Code files are indexed as they're opened, or
+    /// by [`Self::index_files_mentioning`] when references need them.
+    pub async fn index_env_files_with_cancellation(
+        &self,
+        env_files: &[CompactString],
+        cancel: &CancellationToken,
+    ) -> Result<IndexStats> {
+        let is_env_file =
+            |path: &Path| self.plugin_for(path).is_some() || self.is_env_file(path, env_files);
+        self.index_discovered(env_files, cancel, is_env_file).await
+    }
+
+    /// Indexes the discovered files that pass `filter`.
+    async fn index_discovered(
+        &self,
+        env_files: &[CompactString],
+        cancel: &CancellationToken,
+        filter: impl Fn(&Path) -> bool,
+    ) -> Result<IndexStats> {
         info!("Starting workspace indexing at {:?}", self.workspace_root);
 
+        let _ = tokio::fs::read_dir(&self.workspace_root).await.map_err(|e| {
+            anyhow::anyhow!("Cannot read workspace root {:?}: {}", self.workspace_root, e)
+        })?;
+
         self.workspace_index.set_indexing(true);
 
 
-        let files = self.discover_files(env_files).await;
+        let mut files = self.discover_files(env_files, cancel).await;
+        if !cancel.is_cancelled() {
+            let discovered = (env_files.to_vec(), files.as_slice().into());
+            *self.discovered_files.write() = Some(discovered);
+            self.scanned_names.lock().clear();
+        }
+        files.retain(|path| filter(path));
+        if cancel.is_cancelled() {
+            info!("Workspace indexing cancelled during discovery");
+            self.workspace_index.set_indexing(false);
+            return Ok(self.workspace_index.stats());
+        }
         let file_count = files.len();
         info!("Discovered {} files to index", file_count);
 
@@ -78,25 +310,103 @@ impl WorkspaceIndexer {
 
         if file_count == 0 {
             self.workspace_index.set_indexing(false);
-            return Ok(());
+            return Ok(self.workspace_index.stats());
         }
 
-        
-        
-        
-        let parallelism = (num_cpus::get() / 2).clamp(1, 4);
-        let semaphore = Arc::new(Semaphore::new(parallelism));
+        self.index_files(files, env_files, cancel).await;
+        self.workspace_index.set_indexing(false);
+        Ok(self.workspace_index.stats())
+    }
+
+    /// Indexes the files that aren't indexed yet and whose text contains
+    /// `name`, for `workspace.lazy_index`, so references and rename also see
+    /// files that were never opened. Every unindexed file is read in
+    /// parallel, but only those that mention `name` are parsed, and each name
+    /// is scanned for once.
+    pub async fn index_files_mentioning(
+        &self,
+        name: &str,
+        env_files: &[CompactString],
+        cancel: &CancellationToken,
+    ) {
+        if self.scanned_names.lock().contains(name) {
+            return;
+        }
+        let unindexed: Vec<PathBuf> = self
+            .discovered_files(env_files, cancel)
+            .await
+            .iter()
+            .filter(|path| {
+                Url::from_file_path(path)
+                    .is_ok_and(|uri| !self.workspace_index.is_file_indexed(&uri))
+            })
+            .cloned()
+            .collect();
+        let mentioning: Vec<PathBuf> = futures::stream::iter(unindexed)
+            .take_while(|_| std::future::ready(!cancel.is_cancelled()))
+            .map(|path| async move {
+                let content = tokio::fs::read(&path).await.ok()?;
+                String::from_utf8_lossy(&content).contains(name).then_some(path)
+            })
+            .buffer_unordered(index_parallelism())
+            .filter_map(std::future::ready)
+            .collect()
+            .await;
+        if cancel.is_cancelled() {
+            return;
+        }
+        if !mentioning.is_empty() {
+            self.index_files(mentioning, env_files, cancel).await;
+        }
+        if !cancel.is_cancelled() {
+            self.scanned_names.lock().insert(name.into());
+        }
+    }
+
+    /// The files the last walk found, walking again only if `env_files`
+    /// changed since.
+    async fn discovered_files(
+        &self,
+        env_files: &[CompactString],
+        cancel: &CancellationToken,
+    ) -> Arc<[PathBuf]> {
+        if let Some((patterns, files)) = self.discovered_files.read().as_ref() {
+            if patterns.as_slice() == env_files {
+                return Arc::clone(files);
+            }
+        }
+        let files: Arc<[PathBuf]> = self.discover_files(env_files, cancel).await.into();
+        if !cancel.is_cancelled() {
+            *self.discovered_files.write() = Some((env_files.to_vec(), Arc::clone(&files)));
+        }
+        files
+    }
+
+    /// Indexes `files` in parallel, skipping and counting files that fail.
+    async fn index_files(
+        &self,
+        files: Vec<PathBuf>,
+        env_files: &[CompactString],
+        cancel: &CancellationToken,
+    ) {
+        let file_count = files.len();
+        let semaphore = Arc::new(Semaphore::new(index_parallelism()));
         let mut handles = Vec::with_capacity(file_count);
 
         for (i, file_path) in files.into_iter().enumerate() {
-            let permit = semaphore.clone().acquire_owned().await?;
+            if cancel.is_cancelled() {
+                break;
+            }
+            let Ok(permit) = semaphore.clone().acquire_owned().await else {
+                break;
+            };
             let indexer = self.clone_for_task();
             let env_files_clone = env_files.to_vec();
 
             handles.push(tokio::spawn(async move {
                 let result = indexer.index_file(&file_path, &env_files_clone).await;
                 drop(permit);
-                result
+                result.map_err(|e| (file_path, e))
             }));
 
             
@@ -110,19 +420,25 @@ impl WorkspaceIndexer {
         let mut error_count = 0;
 
         for (i, handle) in handles.into_iter().enumerate() {
+            if cancel.is_cancelled() {
+                handle.abort();
+            }
             match handle.await {
                 Ok(Ok(())) => {
                     success_count += 1;
                     self.workspace_index.increment_indexed();
                 }
-                Ok(Err(e)) => {
-                    debug!("Failed to index file: {}", e);
+                Ok(Err((path, e))) => {
+                    warn!("Skipping {:?}, failed to index: {}", path, e);
                     error_count += 1;
+                    self.workspace_index.increment_failed();
                     self.workspace_index.increment_indexed();
                 }
+                Err(e) if e.is_cancelled() => {}
                 Err(e) => {
                     warn!("Task panicked: {}", e);
                     error_count += 1;
+                    self.workspace_index.increment_failed();
                 }
             }
 
@@ -132,45 +448,47 @@ impl WorkspaceIndexer {
             }
         }
 
-        self.workspace_index.set_indexing(false);
+        if cancel.is_cancelled() {
+            info!(
+                "Workspace indexing cancelled after {} of {} files",
+                success_count + error_count,
+                file_count
+            );
+            return;
+        }
 
         info!(
             "Workspace indexing complete: {} succeeded, {} failed",
             success_count, error_count
         );
-
-        Ok(())
     }
 
     
-    async fn discover_files(&self, env_files: &[CompactString]) -> Vec<PathBuf> {
+    async fn discover_files(
+        &self,
+        env_files: &[CompactString],
+        cancel: &CancellationToken,
+    ) -> Vec<PathBuf> {
         let mut files = Vec::new();
 
 
-        let extensions: Vec<&str> = self
-            .languages
-            .all_languages()
-            .iter()
-            .flat_map(|l| l.extensions())
-            .copied()
-            .collect();
-
-
-        let env_patterns: Vec<glob::Pattern> = env_files
-            .iter()
-            .filter_map(|p| glob::Pattern::new(p).ok())
-            .collect();
-
         
+        // Symlinks are never followed, so symlink loops can't recurse forever
         let walker = ignore::WalkBuilder::new(&self.workspace_root)
             .hidden(false) 
             .git_ignore(true) 
             .git_global(true) 
             .git_exclude(true) 
             .require_git(false) 
+            .follow_links(false)
+            .max_depth(*self.max_depth.read())
             .build();
+        let scan_all_config_files = *self.scan_all_config_files.read();
 
         for entry in walker.flatten() {
+            if cancel.is_cancelled() {
+                break;
+            }
             let path = entry.path();
             if !path.is_file() {
                 continue;
@@ -178,17 +496,27 @@ impl WorkspaceIndexer {
 
             
             if let Some(ext) = path.extension().and_then(|e| e.to_str()) {
-                if extensions.contains(&ext) {
+                if self.languages.get_by_extension(ext).is_some() {
                     files.push(path.to_path_buf());
                     continue;
                 }
             }
 
             
-            if let Some(name) = path.file_name().and_then(|n| n.to_str()) {
-                if env_patterns.iter().any(|p| p.matches(name)) {
-                    files.push(path.to_path_buf());
-                }
+            if package_json::is_package_json(path)
+                || deploy_config::is_deploy_config(path)
+                || properties::is_properties_file(path)
+                || yaml_config::is_yaml_config(path, scan_all_config_files)
+                || self.plugin_for(path).is_some()
+                || self.detector_for(path).is_some()
+            {
+                files.push(path.to_path_buf());
+                continue;
+            }
+
+            
+            if self.is_env_file(path, env_files) {
+                files.push(path.to_path_buf());
             }
         }
 
@@ -204,17 +532,91 @@ impl WorkspaceIndexer {
         let uri = Url::from_file_path(path)
             .map_err(|_| anyhow::anyhow!("Invalid file path: {:?}", path))?;
 
-        let content = tokio::fs::read_to_string(path).await?;
+        let plugin = self.plugin_for(path);
+        let is_env_file = plugin.is_some() || self.is_env_file(path, env_files);
+
+        let content = if is_env_file {
+            let max_bytes = *self.max_env_file_bytes.read();
+            match read_env_file(path, max_bytes).await {
+                Some(content) => content,
+                None => {
+                    // A file that grew past the limit or turned binary keeps
+                    // no keys from an earlier index
+                    self.workspace_index.remove_file(&uri);
+                    return Ok(());
+                }
+            }
+        } else {
+            read_to_string_lossy(path).await?
+        };
         let mtime = tokio::fs::metadata(path).await?.modified()?;
 
-        let is_env_file = self.is_env_file(path, env_files);
-
-        let (env_vars, exports) = if is_env_file {
-            (self.extract_env_vars_from_env_file(&content), None)
+        let (env_vars, exports) = if let Some(plugin) = plugin {
+            let definitions = plugin.parse(&content);
+            debug!("Plugin {} parsed {} definitions", plugin.name(), definitions.len());
+            let vars = definitions.iter().map(|def| def.name.clone()).collect();
+            self.workspace_index.update_external_definitions(&uri, definitions);
+            (vars, None)
+        } else if is_env_file {
+            let mut vars = self.extract_env_vars_from_env_file(&content);
+            // Included files are recorded as dependencies, so a change to
+            // one re-indexes the files that include it
+            let mut includes = Vec::new();
+            let mut included_files = Vec::new();
+            if *self.follow_env_includes.read() {
+                let max_bytes = *self.max_env_file_bytes.read();
+                let roots = [path.to_path_buf()];
+                included_files =
+                    env_include::included_files(&roots, &self.workspace_root, max_bytes).await;
+                for (included_path, included) in &included_files {
+                    vars.extend(self.extract_env_vars_from_env_file(included));
+                    includes.extend(Url::from_file_path(canonical(included_path)).ok());
+                }
+            }
+            self.workspace_index.update_dependency_graph(&uri, includes);
+            self.workspace_index.set_env_includes(&uri, included_files);
+            (vars, None)
+        } else if let Some(detector) = self.detector_for(path) {
+            (self.collect_env_vars(&detector.analyze(&content)), None)
+        } else if package_json::is_package_json(path) {
+            let graph = package_json::analyze(&content);
+            (self.collect_env_vars(&graph), None)
+        } else if deploy_config::is_deploy_config(path) {
+            let definitions = deploy_config::extract_definitions(path, &content);
+            let mut vars = self.collect_env_vars(&deploy_config::analyze(path, &content));
+            vars.extend(definitions.iter().map(|def| def.name.clone()));
+            self.workspace_index.update_external_definitions(&uri, definitions);
+            (vars, None)
+        } else if properties::is_properties_file(path) {
+            let definitions = properties::extract_definitions(&content);
+            let mut vars = self.collect_env_vars(&properties::analyze(&content));
+            vars.extend(definitions.iter().map(|def| def.name.clone()));
+            self.workspace_index.update_external_definitions(&uri, definitions);
+            (vars, None)
+        } else if yaml_config::is_yaml_config(path, *self.scan_all_config_files.read()) {
+            let max_bytes = *self.max_env_file_bytes.read();
+            let root = &self.workspace_root;
+            let definitions =
+                yaml_config::extract_definitions(path, &content, root, max_bytes).await;
+            // A change to a loaded env file re-indexes the Compose file
+            let env_files = yaml_config::env_file_paths(path, &content, root)
+                .into_iter()
+                .filter_map(|(env_file, _)| Url::from_file_path(canonical(&env_file)).ok())
+                .collect();
+            self.workspace_index.update_dependency_graph(&uri, env_files);
+            let mut vars = self.collect_env_vars(&yaml_config::analyze(path, &content));
+            vars.extend(definitions.iter().map(|def| def.name.clone()));
+            self.workspace_index.update_local_definitions(&uri, definitions);
+            (vars, None)
         } else {
-            let (vars, exports) = self
+            let (mut vars, exports) = self
                 .extract_env_vars_and_exports_from_code_file(&uri, &content)
                 .await?;
+            if build_script::is_build_script(path) {
+                let definitions = build_script::extract_definitions(&content);
+                vars.extend(definitions.iter().map(|def| def.name.clone()));
+                self.workspace_index.update_crate_definitions(&uri, definitions);
+            }
             (vars, Some(exports))
         };
 
@@ -249,16 +651,12 @@ impl WorkspaceIndexer {
 
     
     fn is_env_file(&self, path: &Path, env_files: &[CompactString]) -> bool {
-        let name = match path.file_name().and_then(|n| n.to_str()) {
-            Some(n) => n,
-            None => return false,
-        };
-
-        env_files.iter().any(|pattern| {
-            glob::Pattern::new(pattern)
-                .map(|p| p.matches(name))
-                .unwrap_or(false)
-        })
+        is_env_file_path(
+            env_files,
+            path,
+            &self.workspace_root,
+            *self.case_insensitive_env_files.read(),
+        )
     }
 
     
@@ -305,6 +703,8 @@ impl WorkspaceIndexer {
 
         
         let env_vars = self.collect_env_vars(&binding_graph);
+        self.workspace_index
+            .set_dynamic_env_access(uri, binding_graph.has_dynamic_env_access());
 
         
         let mut exports = self
@@ -487,14 +887,69 @@ impl WorkspaceIndexer {
 
         self.workspace_index.invalidate_resolution_cache(uri);
 
-        if let Ok(path) = uri.to_file_path() {
-            if let Err(e) = self.index_file(&path, env_files).await {
-                debug!("Failed to re-index {:?}: {}", uri, e);
+        let Ok(path) = uri.to_file_path() else {
+            return;
+        };
+        if let Err(e) = self.index_file(&path, env_files).await {
+            debug!("Failed to re-index {:?}: {}", uri, e);
+        }
+
+        // Files listing this one's keys: env files including it through
+        // `# include:` and Compose files loading it with `env_file:`
+        let mut includers = self.workspace_index.get_dependents(uri);
+        if let Ok(canonical_uri) = Url::from_file_path(canonical(&path)) {
+            if &canonical_uri != uri {
+                includers.extend(self.workspace_index.get_dependents(&canonical_uri));
+            }
+        }
+        for includer in includers {
+            if let Ok(includer_path) = includer.to_file_path() {
+                if let Err(e) = self.index_file(&includer_path, env_files).await {
+                    debug!("Failed to re-index includer {:?}: {}", includer, e);
+                }
             }
         }
     }
 
     
+    /// Moves a renamed file's index entry instead of dropping and rebuilding
+    /// it. Falls back to a delete and re-index if `old` wasn't indexed.
+    pub async fn on_file_renamed(&self, old: &Url, new: &Url, env_files: &[CompactString]) {
+        debug!("Moving {:?} to {:?} in index", old, new);
+        if !self.workspace_index.rename_file(old, new) {
+            self.on_file_deleted(old);
+        }
+        // The new name may change the language or env file status.
+        self.on_file_changed(new, env_files).await;
+    }
+
+    /// Pairs deletions with creations that look like the same file renamed:
+    /// a rename keeps the modification time, so a created file whose mtime
+    /// matches what a deleted file was indexed with is taken to be it.
+    pub async fn pair_renames(&self, deleted: &[Url], created: &[Url]) -> Vec<(Url, Url)> {
+        let mut created_mtimes = Vec::new();
+        for uri in created {
+            let Ok(path) = uri.to_file_path() else {
+                continue;
+            };
+            if let Ok(mtime) = tokio::fs::metadata(&path).await.and_then(|m| m.modified()) {
+                created_mtimes.push((uri, mtime));
+            }
+        }
+
+        let mut pairs = Vec::new();
+        for old in deleted {
+            let Some(indexed) = self.workspace_index.indexed_mtime(old) else {
+                continue;
+            };
+            if let Some(i) = created_mtimes.iter().position(|(_, mtime)| *mtime == indexed) {
+                let (new, _) = created_mtimes.swap_remove(i);
+                pairs.push((old.clone(), new.clone()));
+            }
+        }
+        pairs
+    }
+
     pub fn on_file_deleted(&self, uri: &Url) {
         debug!("Removing {:?} from index", uri);
 
@@ -529,6 +984,17 @@ impl WorkspaceIndexer {
             query_engine: Arc::clone(&self.query_engine),
             languages: Arc::clone(&self.languages),
             workspace_root: self.workspace_root.clone(),
+            max_depth: parking_lot::RwLock::new(*self.max_depth.read()),
+            max_env_file_bytes: parking_lot::RwLock::new(*self.max_env_file_bytes.read()),
+            follow_env_includes: parking_lot::RwLock::new(*self.follow_env_includes.read()),
+            case_insensitive_env_files: parking_lot::RwLock::new(
+                *self.case_insensitive_env_files.read(),
+            ),
+            scan_all_config_files: parking_lot::RwLock::new(*self.scan_all_config_files.read()),
+            plugins: Arc::clone(&self.plugins),
+            detectors: Arc::clone(&self.detectors),
+            discovered_files: Arc::clone(&self.discovered_files),
+            scanned_names: Arc::clone(&self.scanned_names),
         }
     }
 
@@ -557,6 +1023,18 @@ mod tests {
     use std::io::Write;
     use tempfile::TempDir;
 
+    #[test]
+    fn test_invalid_utf8_offsets_index_lossy_text() {
+        // Each invalid sequence becomes a 3-byte U+FFFD, the genuine one on B is skipped
+        let bytes = b"A=\xe9\xe9\nB=\xef\xbf\xbd\nC=\xff";
+        let content = String::from_utf8_lossy(bytes);
+        let offsets = invalid_utf8_offsets(bytes);
+        assert_eq!(offsets, vec![2, 5, 17]);
+        for offset in offsets {
+            assert!(content[offset..].starts_with(char::REPLACEMENT_CHARACTER));
+        }
+    }
+
     async fn setup_test_indexer(temp_dir: &Path) -> WorkspaceIndexer {
         let mut registry = LanguageRegistry::new();
         registry.register(Arc::new(JavaScript));
@@ -720,4 +1198,258 @@ mod tests {
         assert!(indexer.index().files_for_env_var("IGNORED").is_empty());
         assert!(indexer.index().files_for_env_var("ALSO_IGNORED").is_empty());
     }
+
+    #[tokio::test]
+    async fn test_index_package_json_scripts() {
+        let temp_dir = TempDir::new().unwrap();
+        create_file(
+            temp_dir.path(),
+            "package.json",
+            r#"{ "name": "app", "scripts": { "start": "PORT=$PORT node ." } }"#,
+        );
+
+        let indexer = setup_test_indexer(temp_dir.path()).await;
+        indexer.index_workspace(&default_env_files()).await.unwrap();
+
+        let files = indexer.index().files_for_env_var("PORT");
+        assert_eq!(files.len(), 1);
+        assert!(files[0].path().ends_with("package.json"));
+    }
+
+    #[tokio::test]
+    async fn test_index_netlify_build_environment() {
+        let temp_dir = TempDir::new().unwrap();
+        create_file(
+            temp_dir.path(),
+            "netlify.toml",
+            "[build]\n  command = \"npm run build\"\n\n[build.environment]\n  NODE_VERSION = \"20\"\n",
+        );
+
+        let indexer = setup_test_indexer(temp_dir.path()).await;
+        indexer.index_workspace(&default_env_files()).await.unwrap();
+
+        let files = indexer.index().files_for_env_var("NODE_VERSION");
+        assert_eq!(files.len(), 1);
+        assert!(files[0].path().ends_with("netlify.toml"));
+
+        let (_, definition) = indexer.index().external_definition("NODE_VERSION", None).unwrap();
+        assert_eq!(definition.value, "20");
+    }
+
+    #[tokio::test]
+    async fn test_skips_oversized_and_binary_env_files() {
+        let temp_dir = TempDir::new().unwrap();
+        create_file(temp_dir.path(), ".env", "SMALL=1\n");
+        create_file(temp_dir.path(), ".env.local", "BIG=1\n# padding padding padding\n");
+        create_file(temp_dir.path(), ".env.production", "BINARY=1\n\0\0\0");
+
+        let indexer = setup_test_indexer(temp_dir.path()).await;
+        indexer.set_max_env_file_bytes(16);
+        indexer.index_workspace(&default_env_files()).await.unwrap();
+
+        assert_eq!(indexer.index().files_for_env_var("SMALL").len(), 1);
+        assert!(indexer.index().files_for_env_var("BIG").is_empty());
+        assert!(indexer.index().files_for_env_var("BINARY").is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_reindex_drops_env_file_that_becomes_oversized() {
+        let temp_dir = TempDir::new().unwrap();
+        create_file(temp_dir.path(), ".env", "KEEP=1\n");
+        create_file(temp_dir.path(), ".env.local", "GROWN=1\n");
+
+        let indexer = setup_test_indexer(temp_dir.path()).await;
+        indexer.set_max_env_file_bytes(16);
+        let env_files = default_env_files();
+        indexer.index_workspace(&env_files).await.unwrap();
+        assert_eq!(indexer.index().files_for_env_var("GROWN").len(), 1);
+
+        create_file(temp_dir.path(), ".env.local", "GROWN=1\n# padding padding padding\n");
+        let uri = Url::from_file_path(temp_dir.path().join(".env.local")).unwrap();
+        indexer.on_file_changed(&uri, &env_files).await;
+
+        assert!(indexer.index().files_for_env_var("GROWN").is_empty());
+        assert!(!indexer.index().is_file_indexed(&uri));
+        assert_eq!(indexer.index().files_for_env_var("KEEP").len(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_env_file_lists_included_keys() {
+        let temp_dir = TempDir::new().unwrap();
+        create_file(temp_dir.path(), ".env", "# include: shared/common.env\nOWN=1\n");
+        create_file(temp_dir.path(), "shared/common.env", "SHARED=1\n");
+
+        let indexer = setup_test_indexer(temp_dir.path()).await;
+        indexer.index_workspace(&default_env_files()).await.unwrap();
+        assert!(indexer.index().files_for_env_var("SHARED").is_empty());
+
+        indexer.set_follow_env_includes(true);
+        indexer.index_workspace(&default_env_files()).await.unwrap();
+        let files = indexer.index().files_for_env_var("SHARED");
+        assert_eq!(files.len(), 1);
+        assert!(files[0].path().ends_with(".env"));
+    }
+
+    #[tokio::test]
+    async fn test_included_file_change_reindexes_includer() {
+        let temp_dir = TempDir::new().unwrap();
+        create_file(temp_dir.path(), ".env", "# include: shared/common.env\nOWN=1\n");
+        create_file(temp_dir.path(), "shared/common.env", "SHARED=1\n");
+
+        let indexer = setup_test_indexer(temp_dir.path()).await;
+        indexer.set_follow_env_includes(true);
+        let env_files = default_env_files();
+        indexer.index_workspace(&env_files).await.unwrap();
+        assert_eq!(indexer.index().files_for_env_var("SHARED").len(), 1);
+
+        create_file(temp_dir.path(), "shared/common.env", "ADDED=1\n");
+        let uri = Url::from_file_path(temp_dir.path().join("shared/common.env")).unwrap();
+        indexer.on_file_changed(&uri, &env_files).await;
+
+        assert!(indexer.index().files_for_env_var("SHARED").is_empty());
+        let files = indexer.index().files_for_env_var("ADDED");
+        assert_eq!(files.len(), 1);
+        assert!(files[0].path().ends_with(".env"));
+    }
+
+    #[tokio::test]
+    async fn test_excluded_env_files_are_not_indexed() {
+        let temp_dir = TempDir::new().unwrap();
+        create_file(temp_dir.path(), ".env.local", "LOCAL_ONLY=1\n");
+        create_file(temp_dir.path(), ".env.example", "EXAMPLE_ONLY=\n");
+
+        let indexer = setup_test_indexer(temp_dir.path()).await;
+        let env_files = vec![CompactString::new(".env*"), CompactString::new("!.env.example")];
+        indexer.index_workspace(&env_files).await.unwrap();
+
+        assert_eq!(indexer.index().files_for_env_var("LOCAL_ONLY").len(), 1);
+        assert!(indexer.index().files_for_env_var("EXAMPLE_ONLY").is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_discovers_env_files_by_directory_pattern() {
+        let temp_dir = TempDir::new().unwrap();
+        create_file(temp_dir.path(), "config/dev.env", "DEV_ONLY=1\n");
+        create_file(temp_dir.path(), "other/dev.env", "ELSEWHERE=1\n");
+
+        let indexer = setup_test_indexer(temp_dir.path()).await;
+        let env_files = vec![CompactString::new(".env"), CompactString::new("config/*.env")];
+        indexer.index_workspace(&env_files).await.unwrap();
+
+        let files = indexer.index().files_for_env_var("DEV_ONLY");
+        assert_eq!(files.len(), 1);
+        assert!(indexer.index().is_env_file(&files[0]));
+        assert!(indexer.index().files_for_env_var("ELSEWHERE").is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_respects_max_depth() {
+        let temp_dir = TempDir::new().unwrap();
+
+        create_file(temp_dir.path(), "top.js", "const x = process.env.TOP;");
+        create_file(temp_dir.path(), "a/mid.js", "const x = process.env.MID;");
+        create_file(temp_dir.path(), "a/b/deep.js", "const x = process.env.DEEP;");
+
+        let indexer = setup_test_indexer(temp_dir.path()).await;
+        indexer.set_max_depth(Some(2));
+        indexer.index_workspace(&default_env_files()).await.unwrap();
+
+        assert!(!indexer.index().files_for_env_var("TOP").is_empty());
+        assert!(!indexer.index().files_for_env_var("MID").is_empty());
+        assert!(indexer.index().files_for_env_var("DEEP").is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_index_workspace_precancelled() {
+        let temp_dir = TempDir::new().unwrap();
+        create_file(temp_dir.path(), "a.js", "const x = process.env.API_KEY;");
+
+        let indexer = setup_test_indexer(temp_dir.path()).await;
+        let cancel = CancellationToken::new();
+        cancel.cancel();
+        indexer
+            .index_workspace_with_cancellation(&default_env_files(), &cancel)
+            .await
+            .unwrap();
+
+        assert_eq!(indexer.index().stats().total_files, 0);
+        assert!(!indexer.index().is_indexing());
+    }
+
+    #[tokio::test]
+    async fn test_index_workspace_cancelled_midway() {
+        let temp_dir = TempDir::new().unwrap();
+        let body = "const x = process.env.API_KEY;\n".repeat(200);
+        for i in 0..2000 {
+            create_file(temp_dir.path(), &format!("src/f{}.js", i), &body);
+        }
+
+        let indexer = setup_test_indexer(temp_dir.path()).await;
+        let cancel = CancellationToken::new();
+        let task = {
+            let indexer = indexer.clone_for_task();
+            let cancel = cancel.clone();
+            tokio::spawn(async move {
+                indexer
+                    .index_workspace_with_cancellation(&default_env_files(), &cancel)
+                    .await
+            })
+        };
+
+        tokio::time::sleep(std::time::Duration::from_millis(20)).await;
+        cancel.cancel();
+
+        let result = tokio::time::timeout(std::time::Duration::from_secs(5), task)
+            .await
+            .expect("indexing should stop promptly after cancel")
+            .expect("indexing task should not panic");
+        assert!(result.is_ok());
+        assert!(indexer.index().stats().total_files < 2000);
+        assert!(!indexer.index().is_indexing());
+    }
+
+    #[tokio::test]
+    async fn test_index_workspace_skips_file_deleted_mid_walk() {
+        let temp_dir = TempDir::new().unwrap();
+        create_file(temp_dir.path(), "a.js", "const x = process.env.API_KEY;");
+        create_file(temp_dir.path(), "b.py", "import os\nos.environ['DB_URL']");
+        create_file(temp_dir.path(), "gone.js", "const y = process.env.GONE;");
+
+        let indexer = setup_test_indexer(temp_dir.path()).await;
+        let cancel = CancellationToken::new();
+        let files = indexer.discover_files(&default_env_files(), &cancel).await;
+        assert_eq!(files.len(), 3);
+        fs::remove_file(temp_dir.path().join("gone.js")).unwrap();
+
+        indexer.index().set_indexing(true);
+        indexer.index_files(files, &default_env_files(), &cancel).await;
+        indexer.index().set_indexing(false);
+
+        let stats = indexer.index().stats();
+        assert_eq!(stats.total_files, 2);
+        assert_eq!(stats.failed_files, 1);
+        assert_eq!(indexer.index().files_for_env_var("API_KEY").len(), 1);
+        assert_eq!(indexer.index().files_for_env_var("DB_URL").len(), 1);
+        assert!(indexer.index().files_for_env_var("GONE").is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_index_workspace_reports_failures_in_stats() {
+        let temp_dir = TempDir::new().unwrap();
+        create_file(temp_dir.path(), "a.js", "const x = process.env.API_KEY;");
+
+        let indexer = setup_test_indexer(temp_dir.path()).await;
+        let stats = indexer.index_workspace(&default_env_files()).await.unwrap();
+        assert_eq!(stats.total_files, 1);
+        assert_eq!(stats.failed_files, 0);
+    }
+
+    #[tokio::test]
+    async fn test_index_workspace_fails_on_missing_root() {
+        let temp_dir = TempDir::new().unwrap();
+        let indexer = setup_test_indexer(&temp_dir.path().join("missing")).await;
+
+        assert!(indexer.index_workspace(&default_env_files()).await.is_err());
+        assert!(!indexer.index().is_indexing());
+    }
 }
@@ -0,0 +1,78 @@
+//! Byte offset to LSP position conversion for files scanned without a
+//! tree-sitter grammar, like `package.json` scripts or deploy configs.
+//!
+//! Line starts are computed once per file, so each lookup is a binary search
+//! plus a scan of the part of the line before the offset. Columns are UTF-16
+//! code units, as LSP positions expect.
+
+use tower_lsp::lsp_types::{Position, Range};
+
+pub struct LineIndex<'a> {
+    content: &'a str,
+    line_starts: Vec<usize>,
+}
+
+impl<'a> LineIndex<'a> {
+    pub fn new(content: &'a str) -> Self {
+        let line_starts = std::iter::once(0)
+            .chain(content.match_indices('\n').map(|(i, _)| i + 1))
+            .collect();
+        Self {
+            content,
+            line_starts,
+        }
+    }
+
+    /// The content the index was built from.
+    pub fn content(&self) -> &'a str {
+        self.content
+    }
+
+    /// Position of byte `offset`, which must lie on a char boundary.
+    /// Offsets past the end clamp to the end.
+    pub fn position(&self, offset: usize) -> Position {
+        let offset = offset.min(self.content.len());
+        let line = self.line_starts.partition_point(|&start| start <= offset) - 1;
+        let line_start = self.line_starts[line];
+        let character: usize = self.content[line_start..offset]
+            .chars()
+            .map(char::len_utf16)
+            .sum();
+        Position::new(line as u32, character as u32)
+    }
+
+    pub fn range(&self, start: usize, end: usize) -> Range {
+        Range::new(self.position(start), self.position(end))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_positions_across_lines() {
+        let index = LineIndex::new("ab\ncd\n\nef");
+        assert_eq!(index.position(0), Position::new(0, 0));
+        assert_eq!(index.position(2), Position::new(0, 2));
+        assert_eq!(index.position(3), Position::new(1, 0));
+        assert_eq!(index.position(7), Position::new(3, 0));
+        assert_eq!(index.position(9), Position::new(3, 2));
+    }
+
+    #[test]
+    fn test_columns_are_utf16_units() {
+        // "é" is 2 bytes and 1 unit, "😀" is 4 bytes and 2 units
+        let content = "é😀=X";
+        let index = LineIndex::new(content);
+        let x = content.find('X').unwrap();
+        assert_eq!(index.position(x), Position::new(0, 4));
+        assert_eq!(index.range(x, x + 1), Range::new(Position::new(0, 4), Position::new(0, 5)));
+    }
+
+    #[test]
+    fn test_offsets_past_the_end_clamp() {
+        let index = LineIndex::new("A=1\nLAST=x");
+        assert_eq!(index.position(100), Position::new(1, 6));
+    }
+}
@@ -1,22 +1,38 @@
+pub mod build_script;
 pub mod cross_module_resolver;
+pub mod custom_detector;
+pub mod deploy_config;
 pub mod document;
+pub mod env_include;
+pub mod env_accessor;
+pub mod env_plugin;
 pub mod graph;
 pub mod indexer;
+pub mod line_index;
 pub mod module_resolver;
+pub mod package_json;
 pub mod pipeline;
+pub mod properties;
 pub mod query;
 pub mod range_utils;
 pub mod resolver;
+pub mod text_refs;
+pub mod workspace_glob;
 pub mod workspace_index;
+pub mod yaml_config;
 
 pub use graph::BindingGraph;
 pub use cross_module_resolver::{CrossModuleResolution, CrossModuleResolver};
+pub use custom_detector::CustomDetector;
 pub use document::{DocumentEntry, DocumentManager};
+pub use env_accessor::EnvAccessor;
+pub use env_plugin::EnvSourcePlugin;
 pub use indexer::WorkspaceIndexer;
 pub use module_resolver::ModuleResolver;
 pub use pipeline::{ts_to_lsp_range, AnalysisPipeline};
 pub use query::QueryEngine;
 pub use resolver::BindingResolver;
 pub use workspace_index::{
-    EnvVarLocation, FileIndexEntry, IndexStats, IndexStateSnapshot, LocationKind, WorkspaceIndex,
+    EnvVarLocation, FileIndexEntry, IndexStats, IndexStateSnapshot, LocationKind, ModuleCacheStats,
+    WorkspaceIndex,
 };
@@ -89,7 +89,8 @@ impl ModuleResolver {
     }
 }
 
-fn normalize_path(path: &Path) -> PathBuf {
+/// Resolves `.` and `..` components without touching the filesystem.
+pub(crate) fn normalize_path(path: &Path) -> PathBuf {
     let mut components = Vec::new();
 
     for component in path.components() {
@@ -0,0 +1,91 @@
+//! Env var detection in `package.json` scripts.
+//!
+//! There is no JSON grammar registered, so `package.json` is scanned directly:
+//! only string values of the top-level `scripts` object are considered, and
+//! `$VAR` / `${VAR}` occurrences inside them become direct references.
+
+use crate::analysis::line_index::LineIndex;
+use crate::analysis::text_refs::{collect_references, section_string_members};
+use crate::analysis::BindingGraph;
+use crate::types::EnvReference;
+use std::path::Path;
+
+/// Returns true if the path's file name is `package.json`.
+pub fn is_package_json(path: &Path) -> bool {
+    path.file_name().and_then(|n| n.to_str()) == Some("package.json")
+}
+
+/// Builds a binding graph holding only the direct references found in scripts.
+pub fn analyze(content: &str) -> BindingGraph {
+    let mut graph = BindingGraph::new();
+    for reference in extract_script_references(content) {
+        graph.add_direct_reference(reference);
+    }
+    graph.rebuild_range_index();
+    graph
+}
+
+/// Extracts `$VAR` and `${VAR}` references from the `scripts` object.
+pub fn extract_script_references(content: &str) -> Vec<EnvReference> {
+    let index = LineIndex::new(content);
+    let mut references = Vec::new();
+    for (_, (start, end)) in section_string_members(content, "scripts") {
+        collect_references(&index, start, end, &mut references);
+    }
+    references
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tower_lsp::lsp_types::Position;
+
+    const PACKAGE_JSON: &str = r#"{
+  "name": "app",
+  "config": { "port": "$NOT_A_SCRIPT" },
+  "scripts": {
+    "start": "PORT=$PORT node .",
+    "build": "vite build --base ${BASE_URL}",
+    "lint": "eslint ."
+  }
+}"#;
+
+    #[test]
+    fn test_detects_port_in_script() {
+        let refs = extract_script_references(PACKAGE_JSON);
+        let port = refs.iter().find(|r| r.name == "PORT").expect("PORT reference");
+
+        assert_eq!(port.name_range.start, Position::new(4, 20));
+        assert_eq!(port.name_range.end, Position::new(4, 24));
+        assert_eq!(port.full_range.start, Position::new(4, 19));
+    }
+
+    #[test]
+    fn test_braced_references() {
+        let refs = extract_script_references(PACKAGE_JSON);
+        let base = refs.iter().find(|r| r.name == "BASE_URL").expect("BASE_URL reference");
+
+        assert_eq!(base.full_range.start, Position::new(5, 32));
+        assert_eq!(base.full_range.end, Position::new(5, 43));
+    }
+
+    #[test]
+    fn test_ignores_values_outside_scripts() {
+        let refs = extract_script_references(PACKAGE_JSON);
+        let names: Vec<_> = refs.iter().map(|r| r.name.as_str()).collect();
+
+        assert_eq!(names, vec!["PORT", "BASE_URL"]);
+    }
+
+    #[test]
+    fn test_malformed_json_does_not_panic() {
+        assert!(extract_script_references(r#"{ "scripts": { "start": "$PORT"#).is_empty());
+        assert!(extract_script_references("").is_empty());
+    }
+
+    #[test]
+    fn test_is_package_json() {
+        assert!(is_package_json(Path::new("/app/package.json")));
+        assert!(!is_package_json(Path::new("/app/package.jsonc")));
+    }
+}
@@ -1,10 +1,13 @@
+use crate::analysis::env_accessor;
 use crate::analysis::graph::BindingGraph;
 use crate::analysis::query::QueryEngine;
 use crate::languages::LanguageSupport;
 use crate::types::{
-    ImportContext, Scope, ScopeId, Symbol, SymbolId, SymbolKind, SymbolOrigin, SymbolUsage,
+    EnvReference, ImportContext, Scope, ScopeId, Symbol, SymbolId, SymbolKind, SymbolOrigin,
+    SymbolUsage,
 };
 use compact_str::CompactString;
+use rustc_hash::FxHashMap;
 use tower_lsp::lsp_types::{Position, Range};
 use tree_sitter::Tree;
 
@@ -93,6 +96,10 @@ impl AnalysisPipeline {
         parent_scope: ScopeId,
         candidates: &mut Vec<PropertyAccessCandidate>,
     ) {
+        if language.comment_node_kinds().contains(&node.kind()) {
+            return;
+        }
+
         let current_scope = if language.is_scope_node(node) && !language.is_root_node(node) {
             let scope_kind = language.node_to_scope_kind(node.kind());
             let scope = Scope {
@@ -106,6 +113,10 @@ impl AnalysisPipeline {
             parent_scope
         };
 
+        for name in language.declared_names(node, source) {
+            graph.add_local_declaration(name, current_scope);
+        }
+
         if node.kind() == "member_expression" {
             if let Some(candidate) = Self::extract_member_expression_candidate(node, source) {
                 candidates.push(candidate);
@@ -204,7 +215,9 @@ impl AnalysisPipeline {
             let scope = graph.scope_at_position(candidate.object_position);
 
             if let Some(symbol) = graph.lookup_symbol(&candidate.object_name, scope) {
-                if graph.resolves_to_env_object(symbol.id) {
+                if graph.resolves_to_env_object(symbol.id)
+                    && !graph.is_env_key_shadowed(symbol.id, &candidate.property_name)
+                {
                     let usage = SymbolUsage {
                         symbol_id: symbol.id,
                         range: candidate.usage_range,
@@ -230,9 +243,52 @@ impl AnalysisPipeline {
             .extract_references(language, tree, source, import_context)
             .await;
 
+        // A pattern for an access with a default, like PHP's
+        // `$_ENV['KEY'] ?? 'fallback'`, also matches the plain access
+        // pattern; keep one reference per name, preferring the default.
+        let mut unique: Vec<EnvReference> = Vec::with_capacity(references.len());
+        // Keyed by name range; `Range` isn't `Hash`
+        let range_key = |range: Range| {
+            (range.start.line, range.start.character, range.end.line, range.end.character)
+        };
+        let mut index_by_range: FxHashMap<(u32, u32, u32, u32), usize> = FxHashMap::default();
         for reference in references {
+            match index_by_range.get(&range_key(reference.name_range)) {
+                Some(&index) => {
+                    let existing = &mut unique[index];
+                    if reference.has_default && !existing.has_default {
+                        *existing = reference;
+                    }
+                }
+                None => {
+                    index_by_range.insert(range_key(reference.name_range), unique.len());
+                    unique.push(reference);
+                }
+            }
+        }
+
+        // Configured accessors are matched on the syntax tree, after the
+        // language queries so calls they already cover aren't added twice.
+        let accessors = query_engine.env_accessors();
+        for reference in env_accessor::extract_references(&accessors, language, tree, source) {
+            if let std::collections::hash_map::Entry::Vacant(slot) =
+                index_by_range.entry(range_key(reference.name_range))
+            {
+                slot.insert(unique.len());
+                unique.push(reference);
+            }
+        }
+
+        for reference in unique {
             graph.add_direct_reference(reference);
         }
+
+        if query_engine
+            .has_dynamic_env_access(language, tree, source)
+            .await
+        {
+            graph.set_dynamic_env_access();
+        }
     }
 
     async fn extract_bindings(
@@ -293,6 +349,41 @@ impl AnalysisPipeline {
             graph.add_symbol(symbol);
         }
 
+        for binding in language.env_object_call_bindings(tree, source) {
+            let scope = graph.scope_at_position(binding.binding_range.start);
+            graph.add_symbol(Symbol {
+                id: SymbolId::new(1).unwrap(),
+                name: binding.binding_name,
+                declaration_range: binding.declaration_range,
+                name_range: binding.binding_range,
+                scope,
+                origin: SymbolOrigin::EnvObject {
+                    canonical_name: binding.env_var_name,
+                },
+                kind: SymbolKind::EnvObject,
+                is_valid: true,
+                destructured_key_range: None,
+            });
+        }
+
+        for (binding, shadowed_keys) in language.env_object_spread_bindings(tree, source) {
+            let scope = graph.scope_at_position(binding.binding_range.start);
+            let symbol_id = graph.add_symbol(Symbol {
+                id: SymbolId::new(1).unwrap(),
+                name: binding.binding_name,
+                declaration_range: binding.declaration_range,
+                name_range: binding.binding_range,
+                scope,
+                origin: SymbolOrigin::EnvObject {
+                    canonical_name: binding.env_var_name,
+                },
+                kind: SymbolKind::EnvObject,
+                is_valid: true,
+                destructured_key_range: None,
+            });
+            graph.set_shadowed_env_keys(symbol_id, shadowed_keys);
+        }
+
         let assignments = query_engine
             .extract_assignments(language, tree, source)
             .await;
@@ -479,6 +570,8 @@ mod tests {
     use super::*;
     use crate::analysis::QueryEngine;
     use crate::languages::javascript::JavaScript;
+    use crate::languages::python::Python;
+    use crate::languages::ruby::Ruby;
     use crate::languages::typescript::TypeScript;
     use crate::languages::LanguageSupport;
     use crate::types::{ResolvedEnv, ScopeKind};
@@ -578,6 +671,81 @@ const secret = process.env.SECRET;"#;
         assert!(matches!(resolved, Some(ResolvedEnv::Object(_))));
     }
 
+    #[tokio::test]
+    async fn test_analyze_env_object_through_function_return() {
+        let query_engine = QueryEngine::new();
+        let js = JavaScript;
+        let code = "function getEnv() {\n  // the whole environment\n  return process.env;\n}\nconst getOther = () => (process.env);\nfunction notEnv() { log(); return process.env; }\nconst e = getEnv();\nconst o = getOther();\nconst n = notEnv();\nconst v = e.API_KEY;";
+        let tree = parse_with_lang(&js, code);
+        let import_ctx = ImportContext::new();
+
+        let graph =
+            AnalysisPipeline::analyze(&query_engine, &js, &tree, code.as_bytes(), &import_ctx)
+                .await;
+
+        let resolves_to_object = |name: &str| {
+            graph
+                .symbols()
+                .iter()
+                .find(|s| s.name == name)
+                .is_some_and(|s| matches!(graph.resolve_to_env(s.id), Some(ResolvedEnv::Object(_))))
+        };
+        assert!(resolves_to_object("e"));
+        assert!(resolves_to_object("o"));
+        assert!(!resolves_to_object("n"));
+    }
+
+    #[tokio::test]
+    async fn test_analyze_env_object_spread() {
+        let query_engine = QueryEngine::new();
+        let js = JavaScript;
+        let code = "const cfg = { ...process.env, PORT: 3000, 'HOST': 'x' };\n\
+                    const { PORT, DB_URL } = cfg;\nconst a = cfg.HOST;\nconst b = cfg.API_KEY;";
+        let tree = parse_with_lang(&js, code);
+        let import_ctx = ImportContext::new();
+
+        let graph =
+            AnalysisPipeline::analyze(&query_engine, &js, &tree, code.as_bytes(), &import_ctx)
+                .await;
+
+        let resolved = |name: &str| {
+            let symbol = graph.symbols().iter().find(|s| s.name == name)?;
+            graph.resolve_to_env(symbol.id)
+        };
+        assert!(matches!(resolved("cfg"), Some(ResolvedEnv::Object(_))));
+        assert!(resolved("PORT").is_none());
+        assert!(matches!(resolved("DB_URL"), Some(ResolvedEnv::Variable(n)) if n == "DB_URL"));
+
+        let accessed: Vec<_> = graph
+            .usages()
+            .iter()
+            .filter_map(|u| u.property_access.as_deref())
+            .collect();
+        assert_eq!(accessed, vec!["API_KEY"]);
+    }
+
+    #[tokio::test]
+    async fn test_analyze_local_env_shadows_env_object() {
+        let query_engine = QueryEngine::new();
+        let js = JavaScript;
+        let code = "const env = process.env;\nconst a = env.API_KEY;\n\
+                    function f() {\n  const env = { NOT_REAL: 1 };\n  return env.NOT_REAL;\n}\n\
+                    function g(env) {\n  return env.ALSO_NOT_REAL;\n}";
+        let tree = parse_with_lang(&js, code);
+        let import_ctx = ImportContext::new();
+
+        let graph =
+            AnalysisPipeline::analyze(&query_engine, &js, &tree, code.as_bytes(), &import_ctx)
+                .await;
+
+        let accessed: Vec<_> = graph
+            .usages()
+            .iter()
+            .filter_map(|u| u.property_access.as_deref())
+            .collect();
+        assert_eq!(accessed, vec!["API_KEY"]);
+    }
+
     #[tokio::test]
     async fn test_analyze_destructuring() {
         let query_engine = QueryEngine::new();
@@ -919,6 +1087,61 @@ const real = process.env.REAL_VAR;"#;
         assert_eq!(graph.direct_references()[0].name, "REAL_VAR");
     }
 
+    #[tokio::test]
+    async fn test_analyze_comment_property_access_ignored() {
+        let query_engine = QueryEngine::new();
+        let js = JavaScript;
+        let code = r#"const env = process.env;
+// TODO use env.DATABASE_URL
+/* env.BLOCK_URL */
+const port = env.PORT;"#;
+        let tree = parse_with_lang(&js, code);
+        let import_ctx = ImportContext::new();
+
+        let graph =
+            AnalysisPipeline::analyze(&query_engine, &js, &tree, code.as_bytes(), &import_ctx)
+                .await;
+
+        let accessed: Vec<_> = graph
+            .usages()
+            .iter()
+            .filter_map(|u| u.property_access.as_deref())
+            .collect();
+        assert_eq!(accessed, vec!["PORT"]);
+    }
+
+    #[tokio::test]
+    async fn test_analyze_python_comment_ignored() {
+        let query_engine = QueryEngine::new();
+        let python = Python;
+        let code = "import os\n# os.environ['DATABASE_URL']\nreal = os.environ['REAL_VAR']";
+        let tree = parse_with_lang(&python, code);
+        let import_ctx = ImportContext::new();
+
+        let graph =
+            AnalysisPipeline::analyze(&query_engine, &python, &tree, code.as_bytes(), &import_ctx)
+                .await;
+
+        assert_eq!(graph.direct_references().len(), 1);
+        assert_eq!(graph.direct_references()[0].name, "REAL_VAR");
+    }
+
+    #[tokio::test]
+    async fn test_analyze_ruby_comment_ignored() {
+        let query_engine = QueryEngine::new();
+        let ruby = Ruby;
+        let code = "# ENV['DATABASE_URL']\nreal = ENV['REAL_VAR']";
+        let tree = parse_with_lang(&ruby, code);
+        let import_ctx = ImportContext::new();
+
+        let graph =
+            AnalysisPipeline::analyze(&query_engine, &ruby, &tree, code.as_bytes(), &import_ctx)
+                .await;
+
+        assert_eq!(graph.direct_references().len(), 1);
+        assert_eq!(graph.direct_references()[0].name, "REAL_VAR");
+    }
+
     #[tokio::test]
     async fn test_analyze_template_literal_env_access() {
         let query_engine = QueryEngine::new();
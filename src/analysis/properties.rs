@@ -0,0 +1,147 @@
+//! Env var definitions in Java-style `*.properties` files, like Spring's
+//! `application.properties`.
+//!
+//! Lines are scanned directly as `key=value`, `key: value` or `key value`.
+//! Keys that are valid env var names become definitions; dotted keys like
+//! `spring.datasource.url` are app settings rather than env vars. `$VAR` /
+//! `${VAR}` occurrences in any value become direct references.
+
+use crate::analysis::line_index::LineIndex;
+use crate::analysis::text_refs::{collect_references, Span};
+use crate::analysis::BindingGraph;
+use crate::types::{is_valid_env_var_name, EnvDefinition, EnvReference};
+use compact_str::CompactString;
+use std::path::Path;
+
+const PROPERTIES_EXTENSION: &str = "properties";
+
+/// One `key=value` line: byte spans of the key and of the value.
+struct Entry {
+    key: Span,
+    value: Span,
+}
+
+/// Returns true if the path has a `.properties` extension.
+pub fn is_properties_file(path: &Path) -> bool {
+    path.extension().and_then(|e| e.to_str()) == Some(PROPERTIES_EXTENSION)
+}
+
+/// Extracts the variables a properties file defines.
+pub fn extract_definitions(content: &str) -> Vec<EnvDefinition> {
+    let index = LineIndex::new(content);
+    entries(content)
+        .into_iter()
+        .filter(|entry| is_valid_env_var_name(&content[entry.key.0..entry.key.1]))
+        .map(|entry| EnvDefinition {
+            name: CompactString::from(&content[entry.key.0..entry.key.1]),
+            value: CompactString::from(&content[entry.value.0..entry.value.1]),
+            name_range: index.range(entry.key.0, entry.key.1),
+            dynamic: false,
+        })
+        .collect()
+}
+
+/// Extracts `$VAR` and `${VAR}` references from values.
+pub fn extract_references(content: &str) -> Vec<EnvReference> {
+    let index = LineIndex::new(content);
+    let mut references = Vec::new();
+    for entry in entries(content) {
+        collect_references(&index, entry.value.0, entry.value.1, &mut references);
+    }
+    references
+}
+
+/// Builds a binding graph holding only the direct references found in values.
+pub fn analyze(content: &str) -> BindingGraph {
+    let mut graph = BindingGraph::new();
+    for reference in extract_references(content) {
+        graph.add_direct_reference(reference);
+    }
+    graph.rebuild_range_index();
+    graph
+}
+
+/// Scans `key=value`, `key: value` and `key value` lines. Only the first
+/// line of a `\`-continued value is kept.
+fn entries(content: &str) -> Vec<Entry> {
+    let mut entries = Vec::new();
+    let mut line_start = 0;
+    let mut continued = false;
+
+    for line in content.split_inclusive('\n') {
+        let offset = line_start;
+        line_start += line.len();
+
+        let line = line.trim_end_matches(['\n', '\r']);
+        let was_continued = continued;
+        continued = line.bytes().rev().take_while(|&b| b == b'\\').count() % 2 == 1;
+        if was_continued {
+            continue;
+        }
+
+        let trimmed = line.trim_start();
+        if trimmed.is_empty() || trimmed.starts_with(['#', '!']) {
+            // Comments never continue onto the next line
+            continued = false;
+            continue;
+        }
+
+        let key_start = line.len() - trimmed.len();
+        let key_len = trimmed
+            .find(|c: char| c == '=' || c == ':' || c.is_whitespace())
+            .unwrap_or(trimmed.len());
+        let rest = &trimmed[key_len..];
+        let after_ws = rest.trim_start();
+        let after_sep = after_ws
+            .strip_prefix(['=', ':'])
+            .map(str::trim_start)
+            .unwrap_or(after_ws);
+        let value_start = key_start + key_len + (rest.len() - after_sep.len());
+        let value_end = line.trim_end_matches('\\').trim_end().len().max(value_start);
+
+        entries.push(Entry {
+            key: (offset + key_start, offset + key_start + key_len),
+            value: (offset + value_start, offset + value_end),
+        });
+    }
+
+    entries
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tower_lsp::lsp_types::Position;
+
+    #[test]
+    fn test_properties_definitions_and_references() {
+        let content = "# Database\nDB_HOST=localhost\n! legacy comment\nAPI_KEY : abc123\nspring.datasource.url=jdbc:postgresql://${DB_HOST}/app\nMULTI=first \\\n  second\nTIMEOUT 30\n";
+
+        let defs = extract_definitions(content);
+        let pairs: Vec<_> = defs
+            .iter()
+            .map(|d| (d.name.as_str(), d.value.as_str()))
+            .collect();
+        assert_eq!(
+            pairs,
+            vec![
+                ("DB_HOST", "localhost"),
+                ("API_KEY", "abc123"),
+                ("MULTI", "first"),
+                ("TIMEOUT", "30"),
+            ]
+        );
+        assert_eq!(defs[1].name_range.start, Position::new(3, 0));
+
+        let refs = extract_references(content);
+        assert_eq!(refs.len(), 1);
+        assert_eq!(refs[0].name, "DB_HOST");
+        assert_eq!(refs[0].name_range.start, Position::new(4, 42));
+    }
+
+    #[test]
+    fn test_is_properties_file() {
+        assert!(is_properties_file(Path::new("/app/application.properties")));
+        assert!(!is_properties_file(Path::new("/app/application.yml")));
+    }
+}
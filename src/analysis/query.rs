@@ -1,3 +1,4 @@
+use crate::analysis::EnvAccessor;
 use crate::analysis::ts_to_lsp_range;
 use crate::languages::LanguageSupport;
 use crate::types::{
@@ -13,14 +14,14 @@ use tree_sitter::{Node, Parser, Query, QueryCursor, QueryMatch, Tree};
 const MAX_PARSERS_PER_LANGUAGE: usize = 8;
 
 /// Checks if a node is inside a comment by walking up the tree.
-/// Returns true if any ancestor node is a comment type.
+/// Returns true if the node itself or any ancestor node is a comment type.
 fn is_in_comment(node: &Node, comment_kinds: &[&str]) -> bool {
-    let mut current = node.parent();
-    while let Some(parent) = current {
-        if comment_kinds.contains(&parent.kind()) {
+    let mut current = Some(*node);
+    while let Some(n) = current {
+        if comment_kinds.contains(&n.kind()) {
             return true;
         }
-        current = parent.parent();
+        current = n.parent();
     }
     false
 }
@@ -72,6 +73,8 @@ pub struct QueryEngine {
     parser_pool: Arc<Mutex<ParserPool>>,
 
     cursor_pool: Arc<Mutex<Vec<QueryCursor>>>,
+
+    env_accessors: parking_lot::RwLock<Vec<EnvAccessor>>,
 }
 
 impl Default for QueryEngine {
@@ -85,9 +88,20 @@ impl QueryEngine {
         Self {
             parser_pool: Arc::new(Mutex::new(ParserPool::new())),
             cursor_pool: Arc::new(Mutex::new(Vec::new())),
+            env_accessors: parking_lot::RwLock::new(Vec::new()),
         }
     }
 
+    /// Replaces the configured env accessor calls. Takes effect on the next
+    /// analysis of each file.
+    pub fn set_env_accessors(&self, accessors: Vec<EnvAccessor>) {
+        *self.env_accessors.write() = accessors;
+    }
+
+    pub fn env_accessors(&self) -> Vec<EnvAccessor> {
+        self.env_accessors.read().clone()
+    }
+
     pub async fn parse(
         &self,
         language: &dyn LanguageSupport,
@@ -269,6 +283,32 @@ impl QueryEngine {
         .await
     }
 
+    /// Returns true if the reference query matches an `@env_dynamic_access`,
+    /// an expression that reads the whole environment such as
+    /// `Deno.env.toObject()`.
+    pub async fn has_dynamic_env_access(
+        &self,
+        language: &dyn LanguageSupport,
+        tree: &Tree,
+        source: &[u8],
+    ) -> bool {
+        let query = language.reference_query();
+        let Some(idx_dynamic) = query.capture_index_for_name("env_dynamic_access") else {
+            return false;
+        };
+
+        let comment_kinds = language.comment_node_kinds();
+        let matches = self
+            .execute_query_skip_comments(query, tree, source, comment_kinds, |m, _| {
+                m.captures
+                    .iter()
+                    .any(|capture| capture.index == idx_dynamic)
+                    .then_some(())
+            })
+            .await;
+        !matches.is_empty()
+    }
+
     pub async fn check_completion_context(
         &self,
         language: &dyn LanguageSupport,
@@ -555,8 +595,10 @@ impl QueryEngine {
 
         let idx_reassigned_name = query.capture_index_for_name("reassigned_name");
 
+        let comment_kinds = language.comment_node_kinds();
+
         let reassignments = self
-            .execute_query(query, tree, source, |m, src| {
+            .execute_query_skip_comments(query, tree, source, comment_kinds, |m, src| {
                 for capture in m.captures {
                     if Some(capture.index) == idx_reassigned_name {
                         return capture
@@ -586,7 +628,9 @@ impl QueryEngine {
 
         let idx_reassigned_name = query.capture_index_for_name("reassigned_name");
 
-        self.execute_query(query, tree, source, |m, src| {
+        let comment_kinds = language.comment_node_kinds();
+
+        self.execute_query_skip_comments(query, tree, source, comment_kinds, |m, src| {
             for capture in m.captures {
                 if Some(capture.index) == idx_reassigned_name {
                     let name = capture.node.utf8_text(src).ok()?;
@@ -614,7 +658,9 @@ impl QueryEngine {
 
         let idx_identifier = query.capture_index_for_name("identifier");
 
-        self.execute_query(query, tree, source, |m, src| {
+        let comment_kinds = language.comment_node_kinds();
+
+        self.execute_query_skip_comments(query, tree, source, comment_kinds, |m, src| {
             for capture in m.captures {
                 if Some(capture.index) == idx_identifier {
                     if let Some(name) = language.extract_identifier(capture.node, src) {
@@ -641,7 +687,9 @@ impl QueryEngine {
         let idx_target = query.capture_index_for_name("assignment_target");
         let idx_source = query.capture_index_for_name("assignment_source");
 
-        self.execute_query(query, tree, source, |m, src| {
+        let comment_kinds = language.comment_node_kinds();
+
+        self.execute_query_skip_comments(query, tree, source, comment_kinds, |m, src| {
             let mut target_name = None;
             let mut target_range = None;
             let mut source_name = None;
@@ -688,7 +736,9 @@ impl QueryEngine {
         let idx_key = query.capture_index_for_name("destructure_key");
         let idx_source = query.capture_index_for_name("destructure_source");
 
-        self.execute_query(query, tree, source, |m, src| {
+        let comment_kinds = language.comment_node_kinds();
+
+        self.execute_query_skip_comments(query, tree, source, comment_kinds, |m, src| {
             let mut target_name = None;
             let mut target_range = None;
             let mut key_name = None;
@@ -822,12 +872,14 @@ impl QueryEngine {
             let exported_name = match raw.export_name {
                 Some(name) => name,
                 None => {
-                    if raw.is_default && raw.declaration_range.is_some() {
+                    if let (true, Some(declaration_range)) =
+                        (raw.is_default, raw.declaration_range)
+                    {
                         let export = ModuleExport {
                             exported_name: CompactString::from("default"),
                             local_name: None,
                             resolution: ExportResolution::Unknown,
-                            declaration_range: raw.declaration_range.unwrap(),
+                            declaration_range,
                             is_default: true,
                         };
                         entry.default_export = Some(export);
@@ -934,7 +986,7 @@ mod tests {
 
         let parser = pool.acquire(&js);
         assert!(
-            pool.parsers.get(js.id()).is_none() || pool.parsers.get(js.id()).unwrap().is_empty()
+            !pool.parsers.contains_key(js.id()) || pool.parsers.get(js.id()).unwrap().is_empty()
         );
 
         pool.release(js.id(), parser);
@@ -486,6 +486,51 @@ impl<'a> BindingResolver<'a> {
         Some(binding.to_binding_usage())
     }
 
+    /// Ranges of an env binding's local name and every usage of that symbol,
+    /// name first, when `position` is on the name or on a usage. A shorthand
+    /// destructure like `{ DB_URL }` names the key too, so it isn't a local
+    /// name.
+    pub fn local_binding_ranges(&self, position: Position) -> Option<Vec<Range>> {
+        let at_usage = self.graph.usage_at_position(position).and_then(|usage| {
+            let symbol = self.graph.get_symbol(usage.symbol_id)?;
+            let range = Self::usage_name_range(usage, &symbol.name);
+            BindingGraph::contains_position(range, position).then_some(symbol)
+        });
+        let symbol = match at_usage {
+            Some(symbol) => symbol,
+            None => self
+                .graph
+                .symbol_at_position(position)
+                .filter(|symbol| BindingGraph::contains_position(symbol.name_range, position))?,
+        };
+        let shorthand = symbol
+            .destructured_key_range
+            .is_some_and(|key| BindingGraph::contains_position(key, symbol.name_range.start));
+        if shorthand {
+            return None;
+        }
+        self.graph.resolve_to_env(symbol.id)?;
+
+        let usages = self
+            .graph
+            .usages()
+            .iter()
+            .filter(|usage| usage.symbol_id == symbol.id)
+            .map(|usage| Self::usage_name_range(usage, &symbol.name));
+        Some(std::iter::once(symbol.name_range).chain(usages).collect())
+    }
+
+    /// The part of a usage naming the symbol: all of `dbUrl`, or just `env`
+    /// in a property access like `env.DB_URL`.
+    fn usage_name_range(usage: &SymbolUsage, name: &str) -> Range {
+        if usage.property_access.is_none() {
+            return usage.range;
+        }
+        let start = usage.range.start;
+        let len = name.encode_utf16().count() as u32;
+        Range::new(start, Position::new(start.line, start.character + len))
+    }
+
     pub fn get_binding_kind(&self, name: &str) -> Option<BindingKind> {
         for symbol in self.graph.symbols() {
             if symbol.name == name && symbol.is_valid {
@@ -0,0 +1,282 @@
+//! Text scanning shared by the config file analyzers that have no grammar
+//! registered: shell-style env var references and a minimal JSON walker.
+
+use crate::analysis::line_index::LineIndex;
+use crate::types::{AccessType, EnvReference};
+use compact_str::CompactString;
+
+/// Byte offsets `(start, end)` into the source.
+pub(crate) type Span = (usize, usize);
+
+/// Returns the key and value spans of string members in a top-level object.
+///
+/// Spans are byte offsets between the quotes; non-string values are skipped.
+pub(crate) fn section_string_members(content: &str, section: &str) -> Vec<(Span, Span)> {
+    let mut scanner = Scanner::new(content);
+    let mut members = Vec::new();
+    scanner.scan_section(section.as_bytes(), &mut members);
+    members
+}
+
+/// Finds shell-style variable references in `content[start..end]`: `$VAR`,
+/// `${VAR}` and the `${VAR:-default}`, `${VAR-default}`, `${VAR:?error}` and
+/// `${VAR:+alt}` forms. `$$` is an escaped dollar, and references nested in
+/// a default, as in `${A:-${B}}`, are found too.
+pub(crate) fn collect_references(
+    index: &LineIndex,
+    start: usize,
+    end: usize,
+    out: &mut Vec<EnvReference>,
+) {
+    let content = index.content();
+    let bytes = content.as_bytes();
+    let mut i = start;
+
+    while i < end {
+        if bytes[i] != b'$' {
+            i += 1;
+            continue;
+        }
+        if i + 1 < end && bytes[i + 1] == b'$' {
+            i += 2;
+            continue;
+        }
+
+        let braced = i + 1 < end && bytes[i + 1] == b'{';
+        let name_start = if braced { i + 2 } else { i + 1 };
+        let mut name_end = name_start;
+        while name_end < end && is_name_byte(bytes[name_end], name_end == name_start) {
+            name_end += 1;
+        }
+        if name_end == name_start {
+            i += 1;
+            continue;
+        }
+
+        let (full_end, has_default, default_value) = if braced {
+            let Some(close) = closing_brace(bytes, name_end, end) else {
+                i += 1;
+                continue;
+            };
+            let modifier = &content[name_end..close];
+            let operand = modifier.trim_start_matches(':');
+            let (has_default, default_value) = match operand.as_bytes().first() {
+                Some(b'-') => (true, Some(CompactString::from(&operand[1..]))),
+                Some(b'+') => (true, None),
+                _ => (false, None),
+            };
+            (close + 1, has_default, default_value)
+        } else {
+            (name_end, false, None)
+        };
+
+        out.push(EnvReference {
+            name: CompactString::from(&content[name_start..name_end]),
+            full_range: index.range(i, full_end),
+            name_range: index.range(name_start, name_end),
+            access_type: AccessType::Variable,
+            has_default,
+            default_value,
+        });
+        // Continue inside the braces so nested defaults are scanned
+        i = name_end;
+    }
+}
+
+/// Finds the `}` closing a `${` whose name ends at `from`, skipping nested
+/// `${...}` in the modifier.
+fn closing_brace(bytes: &[u8], from: usize, end: usize) -> Option<usize> {
+    let mut depth = 0;
+    let mut i = from;
+    while i < end {
+        match bytes[i] {
+            b'{' if i > 0 && bytes[i - 1] == b'$' => depth += 1,
+            b'}' if depth == 0 => return Some(i),
+            b'}' => depth -= 1,
+            _ => {}
+        }
+        i += 1;
+    }
+    None
+}
+
+fn is_name_byte(b: u8, first: bool) -> bool {
+    b == b'_' || b.is_ascii_alphabetic() || (!first && b.is_ascii_digit())
+}
+
+/// Minimal JSON walker that records the byte spans of a section's string values.
+struct Scanner<'a> {
+    src: &'a [u8],
+    pos: usize,
+}
+
+impl<'a> Scanner<'a> {
+    fn new(content: &'a str) -> Self {
+        Self {
+            src: content.as_bytes(),
+            pos: 0,
+        }
+    }
+
+    fn peek(&self) -> Option<u8> {
+        self.src.get(self.pos).copied()
+    }
+
+    fn skip_whitespace(&mut self) {
+        while matches!(self.peek(), Some(b' ' | b'\t' | b'\n' | b'\r')) {
+            self.pos += 1;
+        }
+    }
+
+    fn eat(&mut self, b: u8) -> bool {
+        self.skip_whitespace();
+        if self.peek() == Some(b) {
+            self.pos += 1;
+            true
+        } else {
+            false
+        }
+    }
+
+    /// Parses a string literal, returning the span between the quotes.
+    fn string(&mut self) -> Option<Span> {
+        self.skip_whitespace();
+        if self.peek() != Some(b'"') {
+            return None;
+        }
+        self.pos += 1;
+        let start = self.pos;
+        while let Some(b) = self.peek() {
+            match b {
+                b'\\' => self.pos += 2,
+                b'"' => {
+                    let end = self.pos;
+                    self.pos += 1;
+                    return Some((start, end));
+                }
+                _ => self.pos += 1,
+            }
+        }
+        None
+    }
+
+    /// Skips over any JSON value, returning false on malformed input.
+    fn skip_value(&mut self) -> bool {
+        self.skip_whitespace();
+        match self.peek() {
+            Some(b'"') => self.string().is_some(),
+            Some(b'{') => self.members(|scanner, _| scanner.skip_value()),
+            Some(b'[') => {
+                self.pos += 1;
+                if self.eat(b']') {
+                    return true;
+                }
+                loop {
+                    if !self.skip_value() {
+                        return false;
+                    }
+                    if self.eat(b',') {
+                        continue;
+                    }
+                    return self.eat(b']');
+                }
+            }
+            Some(_) => {
+                let start = self.pos;
+                while let Some(b) = self.peek() {
+                    if matches!(b, b',' | b'}' | b']' | b' ' | b'\t' | b'\n' | b'\r') {
+                        break;
+                    }
+                    self.pos += 1;
+                }
+                self.pos > start
+            }
+            None => false,
+        }
+    }
+
+    /// Walks an object's members, handing each value to `on_value` with its key span.
+    fn members<F>(&mut self, mut on_value: F) -> bool
+    where
+        F: FnMut(&mut Self, Span) -> bool,
+    {
+        if !self.eat(b'{') {
+            return false;
+        }
+        if self.eat(b'}') {
+            return true;
+        }
+        loop {
+            let Some(key_span) = self.string() else {
+                return false;
+            };
+            if !self.eat(b':') {
+                return false;
+            }
+            if !on_value(self, key_span) {
+                return false;
+            }
+            if self.eat(b',') {
+                continue;
+            }
+            return self.eat(b'}');
+        }
+    }
+
+    fn scan_section(&mut self, section: &[u8], out: &mut Vec<(Span, Span)>) {
+        self.members(|scanner, key| {
+            scanner.skip_whitespace();
+            if &scanner.src[key.0..key.1] != section || scanner.peek() != Some(b'{') {
+                return scanner.skip_value();
+            }
+            scanner.members(|scanner, key_span| {
+                scanner.skip_whitespace();
+                if scanner.peek() == Some(b'"') {
+                    match scanner.string() {
+                        Some(span) => {
+                            out.push((key_span, span));
+                            true
+                        }
+                        None => false,
+                    }
+                } else {
+                    scanner.skip_value()
+                }
+            })
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn references(content: &str) -> Vec<EnvReference> {
+        let index = LineIndex::new(content);
+        let mut refs = Vec::new();
+        collect_references(&index, 0, content.len(), &mut refs);
+        refs
+    }
+
+    #[test]
+    fn test_defaults_and_escaped_dollars() {
+        let refs = references("echo $$ && PORT=${PORT:-${FALLBACK}} node .");
+        let names: Vec<_> = refs.iter().map(|r| r.name.as_str()).collect();
+
+        assert_eq!(names, vec!["PORT", "FALLBACK"]);
+        assert!(refs[0].has_default);
+        assert_eq!(refs[0].default_value.as_deref(), Some("${FALLBACK}"));
+        assert!(!refs[1].has_default);
+    }
+
+    #[test]
+    fn test_section_string_members() {
+        let content = r#"{ "env": { "A": "1", "B": 2, "C": "$C" }, "other": { "D": "4" } }"#;
+        let keys: Vec<_> = section_string_members(content, "env")
+            .into_iter()
+            .map(|((start, end), _)| &content[start..end])
+            .collect();
+
+        assert_eq!(keys, vec!["A", "C"]);
+    }
+}
@@ -0,0 +1,110 @@
+//! Matching files against the workspace globs in the config, like
+//! `workspace.env_files`.
+
+use std::path::Path;
+
+/// Checks `path` against `workspace.env_files` patterns. With
+/// `case_insensitive`, `.ENV` matches a `.env` pattern.
+///
+/// Patterns starting with `!` exclude: a path is an env file if it matches
+/// any other pattern and none of the excluding ones.
+pub fn is_env_file_path(
+    patterns: &[impl AsRef<str>],
+    path: &Path,
+    root: &Path,
+    case_insensitive: bool,
+) -> bool {
+    let (excludes, includes): (Vec<&str>, Vec<&str>) = patterns
+        .iter()
+        .map(AsRef::as_ref)
+        .partition(|pattern| pattern.starts_with('!'));
+    let excludes: Vec<&str> = excludes.iter().map(|pattern| &pattern[1..]).collect();
+    matches_glob(&includes, path, root, !case_insensitive)
+        && !matches_glob(&excludes, path, root, !case_insensitive)
+}
+
+/// Checks `path` against workspace file globs.
+///
+/// A pattern containing `/`, like `config/*.env`, matches the path relative
+/// to `root`. A bare pattern like `.env` matches the file name in any
+/// directory.
+pub fn matches_workspace_glob(patterns: &[impl AsRef<str>], path: &Path, root: &Path) -> bool {
+    matches_glob(patterns, path, root, true)
+}
+
+fn matches_glob(
+    patterns: &[impl AsRef<str>],
+    path: &Path,
+    root: &Path,
+    case_sensitive: bool,
+) -> bool {
+    let Some(file_name) = path.file_name().and_then(|n| n.to_str()) else {
+        return false;
+    };
+    let relative = path.strip_prefix(root).unwrap_or(path);
+    let relative = relative
+        .components()
+        .map(|c| c.as_os_str().to_string_lossy())
+        .collect::<Vec<_>>()
+        .join("/");
+    let options = glob::MatchOptions {
+        case_sensitive,
+        require_literal_separator: true,
+        ..Default::default()
+    };
+
+    patterns.iter().any(|pattern| {
+        let pattern = pattern.as_ref();
+        let Ok(glob) = glob::Pattern::new(pattern.trim_start_matches("./")) else {
+            return false;
+        };
+        if pattern.contains('/') {
+            glob.matches_with(&relative, options)
+        } else {
+            glob.matches_with(file_name, options)
+        }
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_is_env_file_path_directory_patterns() {
+        let root = Path::new("/app");
+        let patterns = [".env", "config/*.env"];
+        let matches = |patterns: &[&str], path: &str| {
+            is_env_file_path(patterns, Path::new(path), root, false)
+        };
+        assert!(matches(&patterns, "/app/config/dev.env"));
+        assert!(matches(&patterns, "/app/pkg/.env"));
+        assert!(!matches(&patterns, "/app/other/dev.env"));
+        assert!(!matches(&patterns, "/app/config/nested/dev.env"));
+        assert!(matches(&["config/**/*.env"], "/app/config/nested/dev.env"));
+    }
+
+    #[test]
+    fn test_is_env_file_path_case_insensitive() {
+        let root = Path::new("/app");
+        let patterns = [".env", "config/*.env"];
+        assert!(!is_env_file_path(&patterns, Path::new("/app/.ENV"), root, false));
+        assert!(is_env_file_path(&patterns, Path::new("/app/.ENV"), root, true));
+        assert!(is_env_file_path(&patterns, Path::new("/app/Config/Dev.ENV"), root, true));
+        assert!(!is_env_file_path(&patterns, Path::new("/app/.envrc"), root, true));
+    }
+
+    #[test]
+    fn test_is_env_file_path_exclusions() {
+        let root = Path::new("/app");
+        for patterns in [[".env*", "!.env.example"], ["!.env.example", ".env*"]] {
+            let matches = |path: &str| is_env_file_path(&patterns, Path::new(path), root, false);
+            assert!(matches("/app/.env"));
+            assert!(matches("/app/.env.local"));
+            assert!(matches("/app/pkg/.env.local"));
+            assert!(!matches("/app/.env.example"));
+            assert!(!matches("/app/pkg/.env.example"));
+        }
+        assert!(!is_env_file_path(&["!.env.example"], Path::new("/app/.env"), root, false));
+    }
+}
@@ -4,21 +4,22 @@
 
 
 
-use crate::types::FileExportEntry;
+use crate::types::{EnvDefinition, FileExportEntry};
 use compact_str::CompactString;
 use dashmap::{DashMap, DashSet};
 use parking_lot::RwLock;
 use quick_cache::sync::Cache;
 use rustc_hash::FxHashSet;
 use std::hash::{Hash, Hasher};
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
 use std::time::SystemTime;
 use tower_lsp::lsp_types::{Range, Url};
 
-/// Maximum number of module resolution cache entries.
-/// This bounds memory growth from import resolution.
-const MAX_MODULE_RESOLUTION_CACHE_SIZE: usize = 2000;
+/// Default for `workspace.module_cache_max`, the maximum number of module
+/// resolution cache entries. This bounds memory growth from import resolution.
+pub const DEFAULT_MODULE_CACHE_MAX: usize = 512;
 
 /// Key type for module resolution cache that implements proper hashing.
 #[derive(Clone, Debug, PartialEq, Eq)]
@@ -85,6 +86,8 @@ pub struct IndexState {
     pub total_files: usize,
     
     pub indexed_files: AtomicUsize,
+    /// Files that failed to index during the current or last workspace pass.
+    pub failed_files: AtomicUsize,
     
     pub indexing_in_progress: bool,
     
@@ -102,6 +105,14 @@ impl IndexState {
         self.indexed_files.load(Ordering::Relaxed)
     }
 
+    pub fn increment_failed(&self) {
+        self.failed_files.fetch_add(1, Ordering::Relaxed);
+    }
+
+    pub fn failed_count(&self) -> usize {
+        self.failed_files.load(Ordering::Relaxed)
+    }
+
     
     pub fn progress_percent(&self) -> u8 {
         if self.total_files == 0 {
@@ -150,7 +161,7 @@ pub struct WorkspaceIndex {
 
     /// LRU cache for module resolution with bounded size.
     /// Key: (importer_url, specifier), Value: resolved URL or None for failed lookups.
-    /// Bounded to MAX_MODULE_RESOLUTION_CACHE_SIZE entries to prevent unbounded memory growth.
+    /// Bounded to `workspace.module_cache_max` entries to prevent unbounded memory growth.
     module_resolution_cache: Cache<ModuleResolutionKey, Option<Url>>,
 
     /// Files that this file imports from (dependencies)
@@ -163,6 +174,24 @@ pub struct WorkspaceIndex {
 
     /// Files that need re-analysis after a dependency change
     dirty_files: DashSet<Url>,
+
+    /// Variables defined outside env files (deployment configs, source plugins)
+    external_definitions: DashMap<Url, Vec<EnvDefinition>>,
+
+    /// Variables defined only for the file that declares them, like the keys
+    /// a Compose file loads with `env_file:`
+    local_definitions: DashMap<Url, Vec<EnvDefinition>>,
+
+    /// Variables a `build.rs` sets for the Rust files of its crate
+    crate_definitions: DashMap<Url, Vec<EnvDefinition>>,
+
+    /// Files that read the whole environment, e.g. `Deno.env.toObject()`
+    dynamic_access_files: DashSet<Url>,
+
+    /// Files each env file pulls in with `# include:`, nearest first, with
+    /// their content. Rebuilt when the env file is re-indexed, which a change
+    /// to an included file triggers through the dependency graph.
+    env_includes: DashMap<Url, Arc<[(PathBuf, String)]>>,
 }
 
 impl WorkspaceIndex {
@@ -174,10 +203,15 @@ impl WorkspaceIndex {
             state: RwLock::new(IndexState::default()),
             export_index: DashMap::new(),
             env_export_to_files: DashMap::new(),
-            module_resolution_cache: Cache::new(MAX_MODULE_RESOLUTION_CACHE_SIZE),
+            module_resolution_cache: Cache::new(DEFAULT_MODULE_CACHE_MAX),
             file_dependencies: DashMap::new(),
             file_dependents: DashMap::new(),
             dirty_files: DashSet::new(),
+            external_definitions: DashMap::new(),
+            local_definitions: DashMap::new(),
+            crate_definitions: DashMap::new(),
+            dynamic_access_files: DashSet::new(),
+            env_includes: DashMap::new(),
         }
     }
 
@@ -197,6 +231,14 @@ impl WorkspaceIndex {
     }
 
     
+    pub fn is_env_file(&self, uri: &Url) -> bool {
+        self.file_entries
+            .get(uri)
+            .map(|e| e.is_env_file)
+            .unwrap_or(false)
+    }
+
+    
     pub fn is_file_indexed(&self, uri: &Url) -> bool {
         self.file_entries.contains_key(uri)
     }
@@ -207,6 +249,11 @@ impl WorkspaceIndex {
     }
 
     
+    /// Modification time the file had when it was last indexed.
+    pub fn indexed_mtime(&self, uri: &Url) -> Option<SystemTime> {
+        self.file_entries.get(uri).map(|e| e.mtime)
+    }
+
     pub fn is_file_stale(&self, uri: &Url, current_mtime: SystemTime) -> bool {
         self.file_entries
             .get(uri)
@@ -232,10 +279,155 @@ impl WorkspaceIndex {
                 .iter()
                 .filter(|e| e.is_env_file)
                 .count(),
+            failed_files: self.state.read().failed_count(),
         }
     }
 
     
+    pub fn update_external_definitions(&self, uri: &Url, definitions: Vec<EnvDefinition>) {
+        if definitions.is_empty() {
+            self.external_definitions.remove(uri);
+        } else {
+            self.external_definitions.insert(uri.clone(), definitions);
+        }
+    }
+
+    /// Sets the variables only `uri` itself sees, replacing earlier ones.
+    pub fn update_local_definitions(&self, uri: &Url, definitions: Vec<EnvDefinition>) {
+        if definitions.is_empty() {
+            self.local_definitions.remove(uri);
+        } else {
+            self.local_definitions.insert(uri.clone(), definitions);
+        }
+    }
+
+    /// Sets the variables the build script at `uri` sets for its crate,
+    /// replacing earlier ones.
+    pub fn update_crate_definitions(&self, uri: &Url, definitions: Vec<EnvDefinition>) {
+        if definitions.is_empty() {
+            self.crate_definitions.remove(uri);
+        } else {
+            self.crate_definitions.insert(uri.clone(), definitions);
+        }
+    }
+
+    /// Records whether a file reads the whole environment.
+    pub fn set_dynamic_env_access(&self, uri: &Url, dynamic: bool) {
+        if dynamic {
+            self.dynamic_access_files.insert(uri.clone());
+        } else {
+            self.dynamic_access_files.remove(uri);
+        }
+    }
+
+    /// Records the files an env file includes, as returned by
+    /// [`env_include::included_files`](crate::analysis::env_include::included_files).
+    pub fn set_env_includes(&self, uri: &Url, files: Vec<(PathBuf, String)>) {
+        if files.is_empty() {
+            self.env_includes.remove(uri);
+        } else {
+            self.env_includes.insert(uri.clone(), files.into());
+        }
+    }
+
+    /// The files an indexed env file includes, `None` if it isn't indexed
+    /// or includes nothing.
+    pub fn env_includes(&self, uri: &Url) -> Option<Arc<[(PathBuf, String)]>> {
+        self.env_includes.get(uri).map(|files| Arc::clone(&files))
+    }
+
+    /// Returns true if any indexed file reads the whole environment, in which
+    /// case every defined variable may be in use.
+    pub fn has_dynamic_env_access(&self) -> bool {
+        !self.dynamic_access_files.is_empty()
+    }
+
+    /// Finds a definition of `name` outside env files. Definitions local to
+    /// `from` come first; those local to other files aren't visible. A Rust
+    /// file then sees what the nearest build script above it sets.
+    ///
+    /// When several files define `name`, the one in the nearest directory
+    /// above `from` wins, then the first by URI, so the pick doesn't depend
+    /// on map iteration order.
+    pub fn external_definition(
+        &self,
+        name: &str,
+        from: Option<&Url>,
+    ) -> Option<(Url, EnvDefinition)> {
+        let local = from.and_then(|uri| {
+            let definitions = self.local_definitions.get(uri)?;
+            let definition = definitions.iter().find(|def| def.name == name)?;
+            Some((uri.clone(), definition.clone()))
+        });
+        let from_path = from.and_then(|uri| uri.to_file_path().ok());
+        let crate_level = || {
+            let build_script = self.build_script_for(from_path.as_deref()?)?;
+            let definitions = self.crate_definitions.get(&build_script)?;
+            let definition = definitions.iter().find(|def| def.name == name)?;
+            Some((build_script, definition.clone()))
+        };
+        local.or_else(crate_level).or_else(|| {
+            self.external_definitions
+                .iter()
+                .filter_map(|entry| {
+                    let definition = entry.value().iter().find(|def| def.name == name)?;
+                    Some((entry.key().clone(), definition.clone()))
+                })
+                .min_by(|(a, _), (b, _)| {
+                    let distance = |uri| {
+                        ancestor_distance(from_path.as_deref(), uri).unwrap_or(usize::MAX)
+                    };
+                    distance(a).cmp(&distance(b)).then_with(|| a.cmp(b))
+                })
+        })
+    }
+
+    /// The build script whose `rustc-env` variables the Rust file at `path`
+    /// sees: the one in the nearest directory above it.
+    fn build_script_for(&self, path: &Path) -> Option<Url> {
+        if path.extension().and_then(|e| e.to_str()) != Some("rs") {
+            return None;
+        }
+        self.crate_definitions
+            .iter()
+            .filter_map(|entry| {
+                let distance = ancestor_distance(Some(path), entry.key())?;
+                Some((distance, entry.key().clone()))
+            })
+            .min()
+            .map(|(_, uri)| uri)
+    }
+
+    /// Names with a definition outside env files that `from` can see.
+    pub fn external_definition_names(&self, from: Option<&Url>) -> Vec<CompactString> {
+        let mut names: Vec<_> = self
+            .external_definitions
+            .iter()
+            .flat_map(|entry| entry.value().iter().map(|def| def.name.clone()).collect::<Vec<_>>())
+            .collect();
+        if let Some(local) = from.and_then(|uri| self.local_definitions.get(uri)) {
+            names.extend(local.iter().map(|def| def.name.clone()));
+        }
+        let from_path = from.and_then(|uri| uri.to_file_path().ok());
+        let build_script = from_path.and_then(|path| self.build_script_for(&path));
+        if let Some(crate_level) = build_script.and_then(|uri| self.crate_definitions.get(&uri)) {
+            names.extend(crate_level.iter().map(|def| def.name.clone()));
+        }
+        names.sort();
+        names.dedup();
+        names
+    }
+
+    
+    pub fn indexed_code_files(&self) -> Vec<Url> {
+        self.file_entries
+            .iter()
+            .filter(|e| !e.is_env_file)
+            .map(|e| e.key().clone())
+            .collect()
+    }
+
+    
     
     
 
@@ -349,12 +541,92 @@ impl WorkspaceIndex {
 
         self.remove_exports(uri);
 
+        self.external_definitions.remove(uri);
+        self.local_definitions.remove(uri);
+        self.crate_definitions.remove(uri);
+
+        self.dynamic_access_files.remove(uri);
+
+        self.env_includes.remove(uri);
 
         self.invalidate_resolution_cache(uri);
 
         self.remove_from_dependency_graph(uri);
     }
 
+    /// Moves everything indexed for `old` to `new` after the file was renamed
+    /// on disk. Each map gets the `new` entry before the `old` one is
+    /// dropped, so lookups never see the file missing in between.
+    ///
+    /// Returns false, changing nothing, if `old` isn't indexed.
+    pub fn rename_file(&self, old: &Url, new: &Url) -> bool {
+        let Some(moved) = self.file_entries.get(old).map(|entry| FileIndexEntry {
+            mtime: entry.mtime,
+            env_vars: entry.env_vars.clone(),
+            is_env_file: entry.is_env_file,
+            path: new.to_file_path().unwrap_or_else(|_| entry.path.clone()),
+        }) else {
+            return false;
+        };
+        self.update_file(new, moved);
+        if let Some((_, entry)) = self.file_entries.remove(old) {
+            for env_var in &entry.env_vars {
+                if let Some(mut files) = self.env_to_files.get_mut(env_var) {
+                    files.remove(old);
+                }
+            }
+        }
+
+        if let Some(exports) = self.export_index.get(old).map(|e| e.clone()) {
+            self.update_exports(new, exports);
+            self.remove_exports(old);
+        }
+        if let Some(definitions) = self.external_definitions.get(old).map(|d| d.clone()) {
+            self.external_definitions.insert(new.clone(), definitions);
+            self.external_definitions.remove(old);
+        }
+        if let Some((_, definitions)) = self.local_definitions.remove(old) {
+            self.local_definitions.insert(new.clone(), definitions);
+        }
+        if let Some((_, definitions)) = self.crate_definitions.remove(old) {
+            self.crate_definitions.insert(new.clone(), definitions);
+        }
+        if self.dynamic_access_files.contains(old) {
+            self.dynamic_access_files.insert(new.clone());
+            self.dynamic_access_files.remove(old);
+        }
+        if self.dirty_files.contains(old) {
+            self.dirty_files.insert(new.clone());
+            self.dirty_files.remove(old);
+        }
+        self.env_includes.remove(old);
+
+        // A specifier that named the old file may not resolve to the new one,
+        // and the moved file's own imports may resolve differently from its
+        // new directory, so both are resolved again on the next lookup.
+        self.module_resolution_cache.retain(|key, resolved| {
+            &key.importer != old && resolved.as_ref() != Some(old)
+        });
+
+        let rename = |url: &Url| if url == old { new.clone() } else { url.clone() };
+        for (edges, reverse) in [
+            (&self.file_dependencies, &self.file_dependents),
+            (&self.file_dependents, &self.file_dependencies),
+        ] {
+            if let Some(targets) = edges.get(old).map(|t| t.clone()) {
+                for target in &targets {
+                    if let Some(mut sources) = reverse.get_mut(target) {
+                        sources.iter_mut().for_each(|u| *u = rename(u));
+                    }
+                }
+                edges.insert(new.clone(), targets);
+                edges.remove(old);
+            }
+        }
+
+        true
+    }
+
     
     pub fn clear(&self) {
         self.env_to_files.clear();
@@ -365,6 +637,11 @@ impl WorkspaceIndex {
         self.file_dependencies.clear();
         self.file_dependents.clear();
         self.dirty_files.clear();
+        self.external_definitions.clear();
+        self.local_definitions.clear();
+        self.crate_definitions.clear();
+        self.dynamic_access_files.clear();
+        self.env_includes.clear();
     }
 
     
@@ -469,6 +746,22 @@ impl WorkspaceIndex {
         self.module_resolution_cache.len()
     }
 
+    /// Changes the module resolution cache capacity, evicting entries if it
+    /// shrinks.
+    pub fn set_module_cache_max(&self, max_entries: usize) {
+        self.module_resolution_cache.set_capacity(max_entries.max(1) as u64);
+    }
+
+    /// Capacity, size and lookup counts of the module resolution cache.
+    pub fn module_cache_stats(&self) -> ModuleCacheStats {
+        ModuleCacheStats {
+            len: self.module_resolution_cache.len(),
+            capacity: self.module_resolution_cache.capacity() as usize,
+            hits: self.module_resolution_cache.hits(),
+            misses: self.module_resolution_cache.misses(),
+        }
+    }
+
     // =========================================================================
     // Dependency Graph Methods
     // =========================================================================
@@ -587,6 +880,7 @@ impl WorkspaceIndex {
         state.indexing_in_progress = in_progress;
         if in_progress {
             state.indexed_files.store(0, Ordering::Relaxed);
+            state.failed_files.store(0, Ordering::Relaxed);
         } else {
             state.last_full_index = Some(SystemTime::now());
         }
@@ -602,6 +896,11 @@ impl WorkspaceIndex {
         self.state.read().increment_indexed();
     }
 
+    /// Records a file skipped because it could not be read or analyzed.
+    pub fn increment_failed(&self) {
+        self.state.read().increment_failed();
+    }
+
     
     pub fn is_indexing(&self) -> bool {
         self.state.read().indexing_in_progress
@@ -636,6 +935,28 @@ pub struct IndexStats {
     pub total_files: usize,
     pub total_env_vars: usize,
     pub env_files: usize,
+    /// Files skipped by the last workspace index pass because of errors.
+    pub failed_files: usize,
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct ModuleCacheStats {
+    pub len: usize,
+    pub capacity: usize,
+    pub hits: u64,
+    pub misses: u64,
+}
+
+impl ModuleCacheStats {
+    /// Fraction of lookups answered from the cache, `0.0` before any lookup.
+    pub fn hit_rate(&self) -> f64 {
+        let lookups = self.hits + self.misses;
+        if lookups == 0 {
+            0.0
+        } else {
+            self.hits as f64 / lookups as f64
+        }
+    }
 }
 
 
@@ -651,6 +972,14 @@ pub struct IndexStateSnapshot {
 
 
 
+/// How many directories above `from` the file at `uri` sits, `None` unless
+/// its directory contains `from`.
+fn ancestor_distance(from: Option<&Path>, uri: &Url) -> Option<usize> {
+    let path = uri.to_file_path().ok()?;
+    let dir = path.parent()?;
+    from?.ancestors().skip(1).position(|ancestor| ancestor == dir)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -697,6 +1026,33 @@ mod tests {
         assert!(!index.files_for_env_var("NEW_VAR").is_empty());
     }
 
+    #[test]
+    fn test_rename_file_moves_entries() {
+        let index = WorkspaceIndex::new();
+        let old = url("/config.js");
+        let new = url("/config.ts");
+        let importer = url("/app.js");
+
+        index.update_file(&old, make_entry(&["API_KEY"], false));
+        index.update_exports(&old, FileExportEntry::default());
+        index.cache_module_resolution(&importer, "./config", Some(old.clone()));
+        index.update_dependency_graph(&importer, vec![old.clone()]);
+
+        assert!(index.rename_file(&old, &new));
+
+        assert_eq!(index.files_for_env_var("API_KEY"), vec![new.clone()]);
+        assert!(!index.is_file_indexed(&old));
+        assert!(index.is_file_indexed(&new));
+        assert!(index.get_exports(&new).is_some());
+        assert!(index.get_exports(&old).is_none());
+        assert_eq!(index.cached_module_resolution(&importer, "./config"), None);
+        assert_eq!(index.get_dependencies(&importer), vec![new.clone()]);
+        assert_eq!(index.get_dependents(&new), vec![importer]);
+        assert!(index.get_dependents(&old).is_empty());
+
+        assert!(!index.rename_file(&old, &new));
+    }
+
     #[test]
     fn test_remove_file_cleans_up() {
         let index = WorkspaceIndex::new();
@@ -953,6 +1309,29 @@ mod tests {
         assert_eq!(cached_none, Some(None));
     }
 
+    #[test]
+    fn test_module_resolution_cache_is_bounded() {
+        let index = WorkspaceIndex::new();
+        index.set_module_cache_max(64);
+        let importer = url("/app.js");
+
+        for i in 0..1000 {
+            let specifier = format!("./module{}", i);
+            index.cache_module_resolution(&importer, &specifier, None);
+        }
+        let stats = index.module_cache_stats();
+        assert_eq!(stats.capacity, 64);
+        assert!(stats.len <= 64, "{} entries cached", stats.len);
+        assert!(index.cached_module_resolution(&importer, "./module999").is_some());
+
+        let stats = index.module_cache_stats();
+        assert_eq!(stats.hits, 1);
+        assert!(stats.hit_rate() > 0.0);
+
+        index.set_module_cache_max(8);
+        assert!(index.module_cache_len() <= 8);
+    }
+
     #[test]
     fn test_invalidate_resolution_cache() {
         let index = WorkspaceIndex::new();
@@ -1592,4 +1971,23 @@ mod tests {
         let dirty = index.get_dirty_files();
         assert_eq!(dirty.iter().filter(|u| *u == &app).count(), 1);
     }
+
+    #[test]
+    fn test_external_definition_prefers_nearest_then_uri() {
+        let index = WorkspaceIndex::new();
+        let definition = |value: &str| EnvDefinition {
+            name: CompactString::from("PORT"),
+            value: CompactString::from(value),
+            name_range: Range::default(),
+            dynamic: false,
+        };
+        index.update_external_definitions(&url("/repo/web/netlify.toml"), vec![definition("web")]);
+        index.update_external_definitions(&url("/repo/b.properties"), vec![definition("b")]);
+        index.update_external_definitions(&url("/repo/a.properties"), vec![definition("a")]);
+
+        let pick = |from: Option<&Url>| index.external_definition("PORT", from).unwrap().1.value;
+        assert_eq!(pick(Some(&url("/repo/web/src/app.js"))), "web");
+        assert_eq!(pick(Some(&url("/repo/api/main.js"))), "a");
+        assert_eq!(pick(None), "a");
+    }
 }
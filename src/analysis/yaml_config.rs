@@ -0,0 +1,366 @@
+//! Env var references in YAML and JSON config files, such as
+//! `docker-compose.yml`.
+//!
+//! There is no YAML grammar registered, so these files are scanned directly
+//! for their interpolation: `$VAR`, `${VAR}` and the `${VAR:-default}`,
+//! `${VAR-default}`, `${VAR:?error}` and `${VAR:+alt}` forms. `$$` is an
+//! escaped dollar, and comments are skipped. JSON files only count `${VAR}`
+//! forms inside strings, since keys like `$schema` aren't env vars.
+//!
+//! Compose files are always scanned. Other YAML, like CI workflows or
+//! Kubernetes manifests, often uses `$VAR` for things that aren't env vars,
+//! so other `.yml`, `.yaml` and `.json` files are only scanned with
+//! `workspace.scan_all_config_files`.
+//!
+//! A service's `env_file:` entries name the env files Compose loads into its
+//! container. Every key in those files becomes a definition local to the
+//! Compose file, located at the `env_file:` entry that loads it. Only env
+//! files inside the workspace are loaded.
+
+use crate::analysis::indexer::read_env_file;
+use crate::analysis::line_index::LineIndex;
+use crate::analysis::module_resolver::normalize_path;
+use crate::analysis::text_refs::collect_references;
+use crate::analysis::BindingGraph;
+use crate::types::{EnvDefinition, EnvReference, KorniEntryExt};
+use compact_str::CompactString;
+use korni::ParseOptions;
+use std::path::{Path, PathBuf};
+use tower_lsp::lsp_types::Range;
+
+/// Compose file stems, also matched with a suffix as in `compose.prod.yaml`.
+const COMPOSE_STEMS: &[&str] = &["compose", "docker-compose", "docker-stack"];
+
+/// Returns true for the config files scanned for interpolation: Compose
+/// files, and with `scan_all` every `.yml`, `.yaml` and `.json` file.
+pub fn is_yaml_config(path: &Path, scan_all: bool) -> bool {
+    is_compose_file(path)
+        || (scan_all
+            && path
+                .extension()
+                .and_then(|ext| ext.to_str())
+                .is_some_and(|ext| matches!(ext, "yml" | "yaml" | "json")))
+}
+
+/// Returns true for Compose files: `compose.yaml`, `docker-compose.yml`,
+/// overrides like `docker-compose.override.yml`, and `docker-stack.yml`.
+pub fn is_compose_file(path: &Path) -> bool {
+    let Some(name) = path.file_name().and_then(|n| n.to_str()) else {
+        return false;
+    };
+    let Some(stem) = name.strip_suffix(".yml").or_else(|| name.strip_suffix(".yaml")) else {
+        return false;
+    };
+    COMPOSE_STEMS.iter().any(|compose| {
+        stem.strip_prefix(compose)
+            .is_some_and(|rest| rest.is_empty() || rest.starts_with('.'))
+    })
+}
+
+/// Builds a binding graph holding only the direct references in the file.
+pub fn analyze(path: &Path, content: &str) -> BindingGraph {
+    let references = if path.extension().is_some_and(|ext| ext == "json") {
+        extract_json_references(content)
+    } else {
+        extract_references(content)
+    };
+    let mut graph = BindingGraph::new();
+    for reference in references {
+        graph.add_direct_reference(reference);
+    }
+    graph.rebuild_range_index();
+    graph
+}
+
+/// Extracts the interpolated variables outside comments.
+pub fn extract_references(content: &str) -> Vec<EnvReference> {
+    let index = LineIndex::new(content);
+    let mut references = Vec::new();
+    let mut line_start = 0;
+    for line in content.split_inclusive('\n') {
+        let end = line_start + comment_start(line).unwrap_or(line.len());
+        collect_references(&index, line_start, end, &mut references);
+        line_start += line.len();
+    }
+    references
+}
+
+/// Extracts the `${VAR}` forms inside the strings of a JSON file.
+pub fn extract_json_references(content: &str) -> Vec<EnvReference> {
+    let index = LineIndex::new(content);
+    let bytes = content.as_bytes();
+    let mut references = Vec::new();
+    let mut i = 0;
+    while i < bytes.len() {
+        if bytes[i] != b'"' {
+            i += 1;
+            continue;
+        }
+        let start = i + 1;
+        let mut end = start;
+        while end < bytes.len() && bytes[end] != b'"' {
+            end += if bytes[end] == b'\\' { 2 } else { 1 };
+        }
+        let end = end.min(bytes.len());
+        let mut found = Vec::new();
+        collect_references(&index, start, end, &mut found);
+        // `$VAR` without braces is left out, `${VAR}` starts two columns
+        // before its name
+        references.extend(found.into_iter().filter(|reference| {
+            reference.name_range.start.character == reference.full_range.start.character + 2
+        }));
+        i = end + 1;
+    }
+    references
+}
+
+/// The files loaded by `env_file:` entries, resolved against the Compose
+/// file's directory, with the range of each path as written. Takes a single
+/// path, a block or flow list of paths, and the long `- path: ./file.env`
+/// item form. Absolute paths and paths leading out of `root` are skipped.
+/// Files other than Compose files load none.
+pub fn env_file_paths(path: &Path, content: &str, root: &Path) -> Vec<(PathBuf, Range)> {
+    if !is_compose_file(path) {
+        return Vec::new();
+    }
+    let dir = path.parent().unwrap_or(Path::new(""));
+    let root = normalize_path(root);
+    let index = LineIndex::new(content);
+    let mut entries = Vec::new();
+    // Indent of an `env_file:` key whose list items follow on the next lines
+    let mut list_indent = None;
+    let mut line_start = 0;
+    for line in content.split_inclusive('\n') {
+        let code = line[..comment_start(line).unwrap_or(line.len())].trim_end();
+        let trimmed = code.trim_start();
+        let indent = code.len() - trimmed.len();
+        let mut push = |value: &str| {
+            let value = value.trim();
+            let value = strip_quotes(value);
+            let inside_root = !Path::new(value).is_absolute()
+                && normalize_path(&dir.join(value)).starts_with(&root);
+            if !value.is_empty() && inside_root {
+                let start = line_start + (value.as_ptr() as usize - line.as_ptr() as usize);
+                entries.push((dir.join(value), index.range(start, start + value.len())));
+            }
+        };
+
+        if let Some(key_indent) = list_indent.filter(|_| !trimmed.is_empty()) {
+            let in_list = indent > key_indent || (indent == key_indent && trimmed.starts_with('-'));
+            if in_list {
+                let item = trimmed.strip_prefix('-').map(str::trim_start).unwrap_or(trimmed);
+                match item.strip_prefix("path:") {
+                    Some(value) => push(value),
+                    None if trimmed.starts_with('-') && !item.contains(": ") => push(item),
+                    None => {}
+                }
+                line_start += line.len();
+                continue;
+            }
+            list_indent = None;
+        }
+
+        if let Some(value) = trimmed.strip_prefix("env_file:").map(str::trim) {
+            match value.strip_prefix('[').and_then(|list| list.strip_suffix(']')) {
+                Some(list) => list.split(',').for_each(&mut push),
+                None if value.is_empty() => list_indent = Some(indent),
+                None => push(value),
+            }
+        }
+        line_start += line.len();
+    }
+    entries
+}
+
+/// The variables a Compose file's `env_file:` entries load. Each definition
+/// points at the entry that loads it. Files that can't be read are skipped.
+pub async fn extract_definitions(
+    path: &Path,
+    content: &str,
+    root: &Path,
+    max_bytes: u64,
+) -> Vec<EnvDefinition> {
+    let mut definitions = Vec::new();
+    for (env_file, range) in env_file_paths(path, content, root) {
+        let Some(env_content) = read_env_file(&env_file, max_bytes).await else {
+            continue;
+        };
+        definitions.extend(
+            korni::parse_with_options(&env_content, ParseOptions::full())
+                .into_iter()
+                .filter_map(|entry| entry.into_valid_pair())
+                .map(|kv| EnvDefinition {
+                    name: CompactString::from(kv.key.as_ref()),
+                    value: CompactString::from(kv.value.as_ref()),
+                    name_range: range,
+                    dynamic: false,
+                }),
+        );
+    }
+    definitions
+}
+
+fn strip_quotes(value: &str) -> &str {
+    for quote in ['"', '\''] {
+        if let Some(inner) = value.strip_prefix(quote).and_then(|v| v.strip_suffix(quote)) {
+            return inner;
+        }
+    }
+    value
+}
+
+/// Byte offset of a `#` comment in `line`, ignoring `#` inside quoted
+/// scalars or glued to a word like `image: app#1`. A quote only opens a
+/// scalar where one starts, so the apostrophe in `echo don't` doesn't.
+fn comment_start(line: &str) -> Option<usize> {
+    let bytes = line.as_bytes();
+    let mut quote = None;
+    let mut i = 0;
+    while i < bytes.len() {
+        let b = bytes[i];
+        match quote {
+            // `''` inside single quotes and `\"` inside double quotes are escapes
+            Some(b'\'') if b == b'\'' && bytes.get(i + 1) == Some(&b'\'') => i += 1,
+            Some(b'"') if b == b'\\' => i += 1,
+            Some(q) if b == q => quote = None,
+            Some(_) => {}
+            None if matches!(b, b'"' | b'\'') && starts_scalar(&line[..i]) => quote = Some(b),
+            None if b == b'#' && (i == 0 || bytes[i - 1].is_ascii_whitespace()) => {
+                return Some(i)
+            }
+            None => {}
+        }
+        i += 1;
+    }
+    None
+}
+
+/// Whether a scalar can start right after `before`: at the start of the
+/// line, after a `key: ` or `- ` indicator, or inside a flow collection.
+fn starts_scalar(before: &str) -> bool {
+    let trimmed = before.trim_end();
+    let spaced = trimmed.len() < before.len();
+    match trimmed.as_bytes().last() {
+        None => true,
+        Some(b':' | b'-') => spaced,
+        Some(b'[' | b'{' | b',') => true,
+        Some(_) => false,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tower_lsp::lsp_types::Position;
+
+    #[test]
+    fn test_compose_interpolation_forms() {
+        let content = "services:\n  db:\n    image: \"postgres:${PG_VERSION:-16}\"\n    \
+                       environment:\n      PASSWORD: $POSTGRES_PASSWORD\n      \
+                       URL: ${DB_URL:?required}\n      PRICE: $$5\n";
+        let refs = extract_references(content);
+        let names: Vec<_> = refs.iter().map(|r| r.name.as_str()).collect();
+        assert_eq!(names, vec!["PG_VERSION", "POSTGRES_PASSWORD", "DB_URL"]);
+
+        assert!(refs[0].has_default);
+        assert_eq!(refs[0].default_value.as_deref(), Some("16"));
+        assert_eq!(refs[0].name_range.start, Position::new(2, 23));
+        assert!(!refs[1].has_default);
+        assert!(!refs[2].has_default);
+    }
+
+    #[test]
+    fn test_nested_defaults_and_comments() {
+        let content = "# uses $IN_COMMENT\nurl: ${A:-${B}} # $TRAILING\ntag: 'app #$QUOTED'\n";
+        let names: Vec<_> = extract_references(content)
+            .into_iter()
+            .map(|r| r.name.to_string())
+            .collect();
+        assert_eq!(names, vec!["A", "B", "QUOTED"]);
+    }
+
+    #[test]
+    fn test_apostrophes_in_plain_scalars() {
+        let content = "command: echo don't # $NOT_A_REF\n\
+                       - 'it''s #$QUOTED' # $ALSO_NOT\n\
+                       env: [\"a # $IN_FLOW\", b] # $TRAILING\n";
+        let names: Vec<_> = extract_references(content)
+            .into_iter()
+            .map(|r| r.name.to_string())
+            .collect();
+        assert_eq!(names, vec!["QUOTED", "IN_FLOW"]);
+    }
+
+    #[test]
+    fn test_env_file_paths_forms() {
+        let content = "services:\n  api:\n    env_file: ./api.env # secrets\n  web:\n    \
+                       env_file:\n      - web.env\n      - path: \"./shared.env\"\n        \
+                       required: false\n    environment:\n      A: $A\n  \
+                       worker:\n    env_file: [one.env, 'two.env']\n";
+        let entries = env_file_paths(Path::new("/app/compose.yaml"), content, Path::new("/app"));
+        let paths: Vec<_> = entries.iter().map(|(path, _)| path.clone()).collect();
+        let expected = ["api.env", "web.env", "shared.env", "one.env", "two.env"];
+        let expected: Vec<_> = expected.iter().map(|name| Path::new("/app").join(name)).collect();
+        assert_eq!(paths, expected);
+        assert_eq!(
+            entries[2].1,
+            Range::new(Position::new(6, 15), Position::new(6, 27))
+        );
+    }
+
+    #[test]
+    fn test_env_file_paths_stay_inside_root() {
+        let content = "services:\n  api:\n    env_file:\n      - /etc/secrets.env\n      \
+                       - ../../outside.env\n      - ../shared.env\n      - ./local.env\n";
+        let entries =
+            env_file_paths(Path::new("/repo/app/compose.yaml"), content, Path::new("/repo"));
+        let paths: Vec<_> = entries.iter().map(|(path, _)| path.clone()).collect();
+        assert_eq!(
+            paths,
+            vec![
+                PathBuf::from("/repo/app/../shared.env"),
+                PathBuf::from("/repo/app/./local.env"),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_is_yaml_config() {
+        let is_compose = |path: &str| is_yaml_config(Path::new(path), false);
+        assert!(is_compose("/app/docker-compose.yml"));
+        assert!(is_compose("/app/compose.yaml"));
+        assert!(is_compose("/app/docker-compose.override.yml"));
+        assert!(is_compose("/app/docker-stack.yaml"));
+        assert!(!is_compose("/app/config.yaml"));
+        assert!(!is_compose("/app/.github/workflows/ci.yml"));
+        assert!(!is_compose("/app/composer.yml"));
+        assert!(!is_compose("/app/netlify.toml"));
+
+        let is_config = |path: &str| is_yaml_config(Path::new(path), true);
+        assert!(is_config("/app/docker-compose.yml"));
+        assert!(is_config("/app/config.yaml"));
+        assert!(is_config("/app/.github/workflows/ci.yml"));
+        assert!(is_config("/app/appsettings.json"));
+        assert!(!is_config("/app/netlify.toml"));
+    }
+
+    #[test]
+    fn test_json_references() {
+        let content = "{\n  \"$schema\": \"./schema.json\",\n  \
+                       \"db\": \"${DB_URL:-sqlite://}\",\n  \"price\": \"$5 or $PRICE\",\n  \
+                       \"nested\": \"\\\"${NESTED}\\\"\"\n}\n";
+        let refs = extract_json_references(content);
+        let names: Vec<_> = refs.iter().map(|r| r.name.as_str()).collect();
+        assert_eq!(names, vec!["DB_URL", "NESTED"]);
+        assert_eq!(refs[0].name_range.start, Position::new(2, 11));
+        assert!(refs[0].has_default);
+    }
+
+    #[test]
+    fn test_env_file_only_loaded_by_compose_files() {
+        let content = "env_file: ./api.env\n";
+        let paths = |path: &str| env_file_paths(Path::new(path), content, Path::new("/app"));
+        assert_eq!(paths("/app/compose.yaml").len(), 1);
+        assert!(paths("/app/config.yaml").is_empty());
+    }
+}
@@ -0,0 +1,108 @@
+//! Cooperative cancellation shared by the analysis and server layers.
+
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use tokio::sync::broadcast;
+
+/// Token for cooperative cancellation of background tasks.
+///
+/// Tasks should periodically check `is_cancelled()` and exit early if true.
+#[derive(Clone)]
+pub struct CancellationToken {
+    sender: Arc<broadcast::Sender<()>>,
+    cancelled: Arc<AtomicBool>,
+}
+
+impl CancellationToken {
+    /// Create a new cancellation token.
+    pub fn new() -> Self {
+        let (sender, _) = broadcast::channel(1);
+        Self {
+            sender: Arc::new(sender),
+            cancelled: Arc::new(AtomicBool::new(false)),
+        }
+    }
+
+    /// Check if cancellation has been requested.
+    pub fn is_cancelled(&self) -> bool {
+        self.cancelled.load(Ordering::Acquire)
+    }
+
+    /// Cancel all tasks associated with this token.
+    pub fn cancel(&self) {
+        self.cancelled.store(true, Ordering::Release);
+        // Ignore error if no receivers
+        let _ = self.sender.send(());
+    }
+
+    /// Subscribe to cancellation notifications.
+    ///
+    /// Returns a receiver that will receive a message when cancellation is requested.
+    pub fn subscribe(&self) -> broadcast::Receiver<()> {
+        self.sender.subscribe()
+    }
+
+    /// Wait for cancellation to be signaled.
+    pub async fn cancelled(&self) {
+        let mut receiver = self.subscribe();
+        // Subscribed before checking, so a cancel in between is still received
+        if self.is_cancelled() {
+            return;
+        }
+        let _ = receiver.recv().await;
+    }
+}
+
+impl Default for CancellationToken {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    #[tokio::test]
+    async fn test_cancellation_token() {
+        let token = CancellationToken::new();
+        let token2 = token.clone();
+
+        let completed = Arc::new(AtomicBool::new(false));
+        let completed2 = completed.clone();
+
+        tokio::spawn(async move {
+            token2.cancelled().await;
+            completed2.store(true, Ordering::SeqCst);
+        });
+
+        // Give the task time to start
+        tokio::time::sleep(Duration::from_millis(10)).await;
+
+        assert!(!completed.load(Ordering::SeqCst));
+
+        token.cancel();
+
+        // Give the task time to respond
+        tokio::time::sleep(Duration::from_millis(10)).await;
+
+        assert!(completed.load(Ordering::SeqCst));
+    }
+
+    #[tokio::test]
+    async fn test_is_cancelled_with_subscribers() {
+        let token = CancellationToken::new();
+        let _receiver = token.subscribe();
+        assert!(!token.is_cancelled());
+
+        token.cancel();
+
+        assert!(token.is_cancelled());
+        assert!(token.clone().is_cancelled());
+        // Already cancelled: returns immediately instead of waiting
+        tokio::time::timeout(Duration::from_millis(100), token.cancelled())
+            .await
+            .expect("cancelled() should resolve after cancel");
+    }
+}
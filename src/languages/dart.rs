@@ -0,0 +1,281 @@
+use crate::languages::LanguageSupport;
+use std::sync::OnceLock;
+use tree_sitter::{Language, Query};
+use tracing::error;
+
+pub struct Dart;
+
+static REFERENCE_QUERY: OnceLock<Query> = OnceLock::new();
+static BINDING_QUERY: OnceLock<Query> = OnceLock::new();
+static IMPORT_QUERY: OnceLock<Query> = OnceLock::new();
+static COMPLETION_QUERY: OnceLock<Query> = OnceLock::new();
+static REASSIGNMENT_QUERY: OnceLock<Query> = OnceLock::new();
+static IDENTIFIER_QUERY: OnceLock<Query> = OnceLock::new();
+static EXPORT_QUERY: OnceLock<Query> = OnceLock::new();
+static ASSIGNMENT_QUERY: OnceLock<Query> = OnceLock::new();
+static DESTRUCTURE_QUERY: OnceLock<Query> = OnceLock::new();
+static SCOPE_QUERY: OnceLock<Query> = OnceLock::new();
+
+fn compile_query(grammar: &Language, source: &str, query_name: &str) -> Query {
+    match Query::new(grammar, source) {
+        Ok(query) => query,
+        Err(e) => {
+            error!(
+                language = "dart",
+                query = query_name,
+                error = %e,
+                "Failed to compile query, using empty fallback"
+            );
+            Query::new(grammar, "").unwrap_or_else(|_| {
+                panic!(
+                    "Failed to create empty fallback query for Dart {}",
+                    query_name
+                )
+            })
+        }
+    }
+}
+
+impl LanguageSupport for Dart {
+    fn id(&self) -> &'static str {
+        "dart"
+    }
+
+    fn extensions(&self) -> &'static [&'static str] {
+        &["dart"]
+    }
+
+    fn language_ids(&self) -> &'static [&'static str] {
+        &["dart"]
+    }
+
+    fn grammar(&self) -> Language {
+        tree_sitter_dart::LANGUAGE.into()
+    }
+
+    fn reference_query(&self) -> &Query {
+        REFERENCE_QUERY.get_or_init(|| {
+            compile_query(
+                &self.grammar(),
+                include_str!("../../queries/dart/references.scm"),
+                "references",
+            )
+        })
+    }
+
+    fn binding_query(&self) -> Option<&Query> {
+        Some(BINDING_QUERY.get_or_init(|| {
+            compile_query(
+                &self.grammar(),
+                include_str!("../../queries/dart/bindings.scm"),
+                "bindings",
+            )
+        }))
+    }
+
+    fn import_query(&self) -> Option<&Query> {
+        Some(IMPORT_QUERY.get_or_init(|| {
+            compile_query(
+                &self.grammar(),
+                include_str!("../../queries/dart/imports.scm"),
+                "imports",
+            )
+        }))
+    }
+
+    fn completion_query(&self) -> Option<&Query> {
+        Some(COMPLETION_QUERY.get_or_init(|| {
+            compile_query(
+                &self.grammar(),
+                include_str!("../../queries/dart/completion.scm"),
+                "completion",
+            )
+        }))
+    }
+
+    fn reassignment_query(&self) -> Option<&Query> {
+        Some(REASSIGNMENT_QUERY.get_or_init(|| {
+            compile_query(
+                &self.grammar(),
+                include_str!("../../queries/dart/reassignments.scm"),
+                "reassignments",
+            )
+        }))
+    }
+
+    fn identifier_query(&self) -> Option<&Query> {
+        Some(IDENTIFIER_QUERY.get_or_init(|| {
+            compile_query(
+                &self.grammar(),
+                include_str!("../../queries/dart/identifiers.scm"),
+                "identifiers",
+            )
+        }))
+    }
+
+    fn export_query(&self) -> Option<&Query> {
+        Some(EXPORT_QUERY.get_or_init(|| {
+            compile_query(
+                &self.grammar(),
+                include_str!("../../queries/dart/exports.scm"),
+                "exports",
+            )
+        }))
+    }
+
+    fn assignment_query(&self) -> Option<&Query> {
+        Some(ASSIGNMENT_QUERY.get_or_init(|| {
+            compile_query(
+                &self.grammar(),
+                include_str!("../../queries/dart/assignments.scm"),
+                "assignments",
+            )
+        }))
+    }
+
+    fn destructure_query(&self) -> Option<&Query> {
+        Some(DESTRUCTURE_QUERY.get_or_init(|| {
+            compile_query(
+                &self.grammar(),
+                include_str!("../../queries/dart/destructures.scm"),
+                "destructures",
+            )
+        }))
+    }
+
+    fn scope_query(&self) -> Option<&Query> {
+        Some(SCOPE_QUERY.get_or_init(|| {
+            compile_query(
+                &self.grammar(),
+                include_str!("../../queries/dart/scopes.scm"),
+                "scopes",
+            )
+        }))
+    }
+
+    fn completion_trigger_characters(&self) -> &'static [&'static str] {
+        &["('", "(\"", "['", "[\""]
+    }
+
+    fn is_standard_env_object(&self, name: &str) -> bool {
+        matches!(name, "String" | "bool" | "int" | "dotenv" | "Platform")
+    }
+
+    fn comment_node_kinds(&self) -> &'static [&'static str] {
+        &["comment", "block_comment", "documentation_block_comment"]
+    }
+
+    fn is_scope_node(&self, node: tree_sitter::Node) -> bool {
+        matches!(
+            node.kind(),
+            "function_body"
+                | "function_expression"
+                | "block"
+                | "class_body"
+                | "for_statement"
+                | "while_statement"
+                | "if_statement"
+                | "switch_statement"
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn get_dart() -> Dart {
+        Dart
+    }
+
+    #[test]
+    fn test_id() {
+        assert_eq!(get_dart().id(), "dart");
+    }
+
+    #[test]
+    fn test_extensions() {
+        let exts = get_dart().extensions();
+        assert!(exts.contains(&"dart"));
+    }
+
+    #[test]
+    fn test_language_ids() {
+        let ids = get_dart().language_ids();
+        assert!(ids.contains(&"dart"));
+    }
+
+    #[test]
+    fn test_is_standard_env_object() {
+        let dart = get_dart();
+        assert!(dart.is_standard_env_object("String"));
+        assert!(dart.is_standard_env_object("dotenv"));
+        assert!(!dart.is_standard_env_object("process"));
+    }
+
+    #[test]
+    fn test_grammar_compiles() {
+        let dart = get_dart();
+        let _grammar = dart.grammar();
+    }
+
+    #[test]
+    fn test_reference_query_compiles() {
+        let dart = get_dart();
+        let _query = dart.reference_query();
+    }
+
+    #[test]
+    fn test_binding_query_compiles() {
+        let dart = get_dart();
+        assert!(dart.binding_query().is_some());
+    }
+
+    #[test]
+    fn test_import_query_compiles() {
+        let dart = get_dart();
+        assert!(dart.import_query().is_some());
+    }
+
+    #[test]
+    fn test_completion_query_compiles() {
+        let dart = get_dart();
+        assert!(dart.completion_query().is_some());
+    }
+
+    #[test]
+    fn test_reassignment_query_compiles() {
+        let dart = get_dart();
+        assert!(dart.reassignment_query().is_some());
+    }
+
+    #[test]
+    fn test_identifier_query_compiles() {
+        let dart = get_dart();
+        assert!(dart.identifier_query().is_some());
+    }
+
+    #[test]
+    fn test_export_query_compiles() {
+        let dart = get_dart();
+        assert!(dart.export_query().is_some());
+    }
+
+    #[test]
+    fn test_assignment_query_compiles() {
+        let dart = get_dart();
+        assert!(dart.assignment_query().is_some());
+    }
+
+    #[test]
+    fn test_scope_query_compiles() {
+        let dart = get_dart();
+        assert!(dart.scope_query().is_some());
+    }
+
+    #[test]
+    fn test_destructure_query_compiles() {
+        let dart = get_dart();
+        assert!(dart.destructure_query().is_some());
+    }
+}
@@ -376,11 +376,11 @@ mod tests {
             loop {
                 let node = cursor.node();
                 if node.kind() == "identifier" {
-                    if let Some(kind) = go.is_env_source_node(node, code.as_bytes()) {
-                        if let EnvSourceKind::Object { canonical_name } = kind {
-                            if canonical_name == "os" {
-                                return true;
-                            }
+                    if let Some(EnvSourceKind::Object { canonical_name }) =
+                        go.is_env_source_node(node, code.as_bytes())
+                    {
+                        if canonical_name == "os" {
+                            return true;
                         }
                     }
                 }
@@ -1,5 +1,5 @@
 use crate::languages::LanguageSupport;
-use crate::types::EnvSourceKind;
+use crate::types::{BindingKind, EnvBinding, EnvSourceKind};
 use compact_str::CompactString;
 use std::sync::OnceLock;
 use tree_sitter::{Language, Node, Query};
@@ -269,6 +269,239 @@ impl LanguageSupport for JavaScript {
 
         Some((object_name.into(), property_name.into()))
     }
+
+    fn env_object_call_bindings(
+        &self,
+        tree: &tree_sitter::Tree,
+        source: &[u8],
+    ) -> Vec<EnvBinding> {
+        env_object_call_bindings(tree, source, |node, src| self.is_env_source_node(node, src))
+    }
+
+    fn env_object_spread_bindings(
+        &self,
+        tree: &tree_sitter::Tree,
+        source: &[u8],
+    ) -> Vec<(EnvBinding, Vec<CompactString>)> {
+        env_object_spread_bindings(tree, source, |node, src| self.is_env_source_node(node, src))
+    }
+
+    fn declared_names(&self, node: Node, source: &[u8]) -> Vec<CompactString> {
+        declared_names(node, source)
+    }
+
+    fn is_valid_identifier(&self, name: &str) -> bool {
+        crate::languages::is_identifier(name, &['$'])
+    }
+}
+
+/// Shared by the JavaScript and TypeScript grammars, which agree on the node
+/// kinds involved. First collects functions whose body is a single
+/// `return <env object>` (or an arrow whose body is the object), then the
+/// `const x = fn()` declarations that call them.
+pub(crate) fn env_object_call_bindings(
+    tree: &tree_sitter::Tree,
+    source: &[u8],
+    is_env_source: impl Fn(Node, &[u8]) -> Option<EnvSourceKind>,
+) -> Vec<EnvBinding> {
+    let mut nodes = Vec::new();
+    let mut cursor = tree.walk();
+    collect_nodes(&mut cursor, &mut nodes);
+
+    let returned_env_object = |function: Node| -> Option<CompactString> {
+        let body = function.child_by_field_name("body")?;
+        let mut expression = if body.kind() == "statement_block" {
+            let mut walker = body.walk();
+            let statements: Vec<Node> = body
+                .named_children(&mut walker)
+                .filter(|n| n.kind() != "comment")
+                .collect();
+            match statements.as_slice() {
+                [statement] if statement.kind() == "return_statement" => statement.named_child(0)?,
+                _ => return None,
+            }
+        } else {
+            body
+        };
+        while expression.kind() == "parenthesized_expression" {
+            expression = expression.named_child(0)?;
+        }
+        match is_env_source(expression, source)? {
+            EnvSourceKind::Object { canonical_name } => Some(canonical_name),
+            EnvSourceKind::Variable { .. } => None,
+        }
+    };
+
+    let mut env_functions: Vec<(&str, CompactString)> = Vec::new();
+    for node in &nodes {
+        let (name, function) = match node.kind() {
+            "function_declaration" => (node.child_by_field_name("name"), Some(*node)),
+            "variable_declarator" => (
+                node.child_by_field_name("name"),
+                node.child_by_field_name("value").filter(|value| {
+                    matches!(value.kind(), "arrow_function" | "function_expression" | "function")
+                }),
+            ),
+            _ => continue,
+        };
+        let (Some(name), Some(function)) = (name, function) else {
+            continue;
+        };
+        if let (Ok(name), Some(canonical_name)) =
+            (name.utf8_text(source), returned_env_object(function))
+        {
+            env_functions.push((name, canonical_name));
+        }
+    }
+    if env_functions.is_empty() {
+        return Vec::new();
+    }
+
+    nodes
+        .iter()
+        .filter(|node| node.kind() == "variable_declarator")
+        .filter_map(|declarator| {
+            let name = declarator.child_by_field_name("name")?;
+            let call = declarator.child_by_field_name("value")?;
+            if name.kind() != "identifier" || call.kind() != "call_expression" {
+                return None;
+            }
+            let callee = call.child_by_field_name("function")?;
+            if callee.kind() != "identifier" {
+                return None;
+            }
+            let callee = callee.utf8_text(source).ok()?;
+            let (_, canonical_name) = env_functions.iter().find(|(f, _)| *f == callee)?;
+            let declaration_range = crate::analysis::pipeline::ts_to_lsp_range(declarator.range());
+            Some(EnvBinding {
+                binding_name: name.utf8_text(source).ok()?.into(),
+                env_var_name: canonical_name.clone(),
+                binding_range: crate::analysis::pipeline::ts_to_lsp_range(name.range()),
+                declaration_range,
+                scope_range: declaration_range,
+                is_valid: true,
+                kind: BindingKind::Object,
+                destructured_key_range: None,
+            })
+        })
+        .collect()
+}
+
+/// Shared by the JavaScript and TypeScript grammars. Finds
+/// `const x = { ...process.env, KEY: value }` declarations; the keys set
+/// after the last env spread are returned alongside each binding.
+pub(crate) fn env_object_spread_bindings(
+    tree: &tree_sitter::Tree,
+    source: &[u8],
+    is_env_source: impl Fn(Node, &[u8]) -> Option<EnvSourceKind>,
+) -> Vec<(EnvBinding, Vec<CompactString>)> {
+    let mut nodes = Vec::new();
+    let mut cursor = tree.walk();
+    collect_nodes(&mut cursor, &mut nodes);
+
+    nodes
+        .iter()
+        .filter(|node| node.kind() == "variable_declarator")
+        .filter_map(|declarator| {
+            let name = declarator.child_by_field_name("name")?;
+            let object = declarator.child_by_field_name("value")?;
+            if name.kind() != "identifier" || object.kind() != "object" {
+                return None;
+            }
+
+            let mut canonical_name = None;
+            let mut shadowed_keys = Vec::new();
+            let mut walker = object.walk();
+            for member in object.named_children(&mut walker) {
+                let key = match member.kind() {
+                    "spread_element" => {
+                        let spread = member.named_child(0)?;
+                        if let Some(EnvSourceKind::Object { canonical_name: name }) =
+                            is_env_source(spread, source)
+                        {
+                            canonical_name = Some(name);
+                            shadowed_keys.clear();
+                        }
+                        continue;
+                    }
+                    "pair" | "method_definition" => {
+                        let key = member.child_by_field_name(if member.kind() == "pair" {
+                            "key"
+                        } else {
+                            "name"
+                        })?;
+                        match key.kind() {
+                            "string" => key.named_child(0).unwrap_or(key),
+                            _ => key,
+                        }
+                    }
+                    "shorthand_property_identifier" => member,
+                    _ => continue,
+                };
+                if canonical_name.is_some() {
+                    shadowed_keys.push(CompactString::from(key.utf8_text(source).ok()?));
+                }
+            }
+
+            let declaration_range = crate::analysis::pipeline::ts_to_lsp_range(declarator.range());
+            let binding = EnvBinding {
+                binding_name: name.utf8_text(source).ok()?.into(),
+                env_var_name: canonical_name?,
+                binding_range: crate::analysis::pipeline::ts_to_lsp_range(name.range()),
+                declaration_range,
+                scope_range: declaration_range,
+                is_valid: true,
+                kind: BindingKind::Object,
+                destructured_key_range: None,
+            };
+            Some((binding, shadowed_keys))
+        })
+        .collect()
+}
+
+/// Shared by the JavaScript and TypeScript grammars. Covers variable
+/// declarators, function and arrow parameters (with or without a default or
+/// TypeScript annotation), and `catch` parameters.
+pub(crate) fn declared_names(node: Node, source: &[u8]) -> Vec<CompactString> {
+    let identifier = |node: Option<Node>| {
+        let node = node?;
+        let node = match node.kind() {
+            "assignment_pattern" => node.child_by_field_name("left")?,
+            "required_parameter" | "optional_parameter" => node.child_by_field_name("pattern")?,
+            _ => node,
+        };
+        if node.kind() != "identifier" {
+            return None;
+        }
+        node.utf8_text(source).ok().map(CompactString::from)
+    };
+
+    match node.kind() {
+        "variable_declarator" => identifier(node.child_by_field_name("name")).into_iter().collect(),
+        "arrow_function" | "catch_clause" => {
+            identifier(node.child_by_field_name("parameter")).into_iter().collect()
+        }
+        "formal_parameters" => {
+            let mut cursor = node.walk();
+            node.named_children(&mut cursor)
+                .filter_map(|param| identifier(Some(param)))
+                .collect()
+        }
+        _ => Vec::new(),
+    }
+}
+
+fn collect_nodes<'a>(cursor: &mut tree_sitter::TreeCursor<'a>, nodes: &mut Vec<Node<'a>>) {
+    nodes.push(cursor.node());
+    if cursor.goto_first_child() {
+        loop {
+            collect_nodes(cursor, nodes);
+            if !cursor.goto_next_sibling() {
+                break;
+            }
+        }
+        cursor.goto_parent();
+    }
 }
 
 #[cfg(test)]
@@ -412,11 +645,11 @@ mod tests {
             loop {
                 let node = cursor.node();
                 if node.kind() == "member_expression" {
-                    if let Some(kind) = js.is_env_source_node(node, code.as_bytes()) {
-                        if let EnvSourceKind::Object { canonical_name } = kind {
-                            if canonical_name == "process.env" {
-                                return true;
-                            }
+                    if let Some(EnvSourceKind::Object { canonical_name }) =
+                        js.is_env_source_node(node, code.as_bytes())
+                    {
+                        if canonical_name == "process.env" {
+                            return true;
                         }
                     }
                 }
@@ -1,4 +1,4 @@
-use crate::types::{EnvSourceKind, ScopeKind};
+use crate::types::{EnvBinding, EnvSourceKind, ScopeKind};
 use compact_str::CompactString;
 use tree_sitter::{Language, Node, Query};
 
@@ -6,6 +6,7 @@ pub mod bash;
 pub mod c;
 pub mod cpp;
 pub mod csharp;
+pub mod dart;
 pub mod elixir;
 pub mod go;
 pub mod java;
@@ -94,6 +95,45 @@ pub trait LanguageSupport: Send + Sync {
         None
     }
 
+    /// Bindings initialized by calling a function that only returns an env
+    /// object, like `e` in `const e = getEnv()` after
+    /// `function getEnv() { return process.env; }`. `env_var_name` holds the
+    /// object's canonical name. This is a single-return heuristic, not
+    /// dataflow.
+    fn env_object_call_bindings(
+        &self,
+        _tree: &tree_sitter::Tree,
+        _source: &[u8],
+    ) -> Vec<EnvBinding> {
+        Vec::new()
+    }
+
+    /// Bindings initialized with an object literal that spreads an env
+    /// object, like `cfg` in `const cfg = { ...process.env, PORT: 3000 }`,
+    /// paired with the literal keys after the last env spread. Those keys
+    /// shadow the env vars of the same name.
+    fn env_object_spread_bindings(
+        &self,
+        _tree: &tree_sitter::Tree,
+        _source: &[u8],
+    ) -> Vec<(EnvBinding, Vec<CompactString>)> {
+        Vec::new()
+    }
+
+    /// Plain identifiers `node` declares in its enclosing scope, like `env`
+    /// in `const env = {}` or `function f(env) {}`. A local declaration that
+    /// isn't an env binding hides an env object of the same name declared
+    /// in an outer scope.
+    fn declared_names(&self, _node: Node, _source: &[u8]) -> Vec<CompactString> {
+        Vec::new()
+    }
+
+    /// Whether `name` can replace a local binding's name, like `dbUrl` in
+    /// `const dbUrl = process.env.DB_URL`, when renaming it.
+    fn is_valid_identifier(&self, name: &str) -> bool {
+        is_identifier(name, &[])
+    }
+
     fn strip_quotes<'a>(&self, text: &'a str) -> &'a str {
         text.trim_matches(|c| c == '"' || c == '\'')
     }
@@ -178,6 +218,12 @@ pub trait LanguageSupport: Send + Sync {
     }
 }
 
+/// Letters, digits, `_` and the `extra` chars, not starting with a digit.
+pub(crate) fn is_identifier(name: &str, extra: &[char]) -> bool {
+    let valid = |c: char| c.is_alphanumeric() || c == '_' || extra.contains(&c);
+    name.chars().next().is_some_and(|c| !c.is_numeric()) && name.chars().all(valid)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -425,6 +471,10 @@ mod tests {
         // Bash
         let bash_lang = bash::Bash;
         validate_all_queries(&bash_lang);
+
+        // Dart
+        let dart_lang = dart::Dart;
+        validate_all_queries(&dart_lang);
     }
 
     #[test]
@@ -539,6 +589,22 @@ mod tests {
         assert!(zig_lang.export_query().is_some(), "Zig should have export_query");
     }
 
+    #[test]
+    fn test_dart_queries_compile() {
+        let dart_lang = dart::Dart;
+        validate_all_queries(&dart_lang);
+
+        assert!(dart_lang.binding_query().is_some(), "Dart should have binding_query");
+        assert!(dart_lang.completion_query().is_some(), "Dart should have completion_query");
+        assert!(dart_lang.reassignment_query().is_some(), "Dart should have reassignment_query");
+        assert!(dart_lang.import_query().is_some(), "Dart should have import_query");
+        assert!(dart_lang.identifier_query().is_some(), "Dart should have identifier_query");
+        assert!(dart_lang.assignment_query().is_some(), "Dart should have assignment_query");
+        assert!(dart_lang.destructure_query().is_some(), "Dart should have destructure_query");
+        assert!(dart_lang.scope_query().is_some(), "Dart should have scope_query");
+        assert!(dart_lang.export_query().is_some(), "Dart should have export_query");
+    }
+
     #[test]
     fn test_bash_queries_compile() {
         let bash_lang = bash::Bash;
@@ -55,6 +55,12 @@ impl LanguageSupport for Php {
         Some("$_ENV")
     }
 
+    /// Variable names keep their `$`, as in `$db`.
+    fn is_valid_identifier(&self, name: &str) -> bool {
+        name.strip_prefix('$')
+            .is_some_and(|rest| crate::languages::is_identifier(rest, &[]))
+    }
+
     fn is_scope_node(&self, node: Node) -> bool {
         matches!(
             node.kind(),
@@ -335,11 +341,11 @@ mod tests {
             loop {
                 let node = cursor.node();
                 if node.kind() == "variable_name" {
-                    if let Some(kind) = php.is_env_source_node(node, code.as_bytes()) {
-                        if let EnvSourceKind::Object { canonical_name } = kind {
-                            if canonical_name == "$_ENV" {
-                                return true;
-                            }
+                    if let Some(EnvSourceKind::Object { canonical_name }) =
+                        php.is_env_source_node(node, code.as_bytes())
+                    {
+                        if canonical_name == "$_ENV" {
+                            return true;
                         }
                     }
                 }
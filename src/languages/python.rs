@@ -387,11 +387,11 @@ mod tests {
             loop {
                 let node = cursor.node();
                 if node.kind() == "attribute" {
-                    if let Some(kind) = py.is_env_source_node(node, code.as_bytes()) {
-                        if let EnvSourceKind::Object { canonical_name } = kind {
-                            if canonical_name == "os.environ" {
-                                return true;
-                            }
+                    if let Some(EnvSourceKind::Object { canonical_name }) =
+                        py.is_env_source_node(node, code.as_bytes())
+                    {
+                        if canonical_name == "os.environ" {
+                            return true;
                         }
                     }
                 }
@@ -1,4 +1,5 @@
 use crate::languages::LanguageSupport;
+use parking_lot::RwLock;
 use std::collections::HashMap;
 use std::sync::Arc;
 use tower_lsp::lsp_types::Url;
@@ -9,6 +10,10 @@ pub struct LanguageRegistry {
     by_extension: HashMap<&'static str, Arc<dyn LanguageSupport>>,
 
     by_language_id: HashMap<&'static str, Arc<dyn LanguageSupport>>,
+
+    /// Configured extension to language id mappings, checked before
+    /// `by_extension`. Replaced when `ecolog.toml` changes.
+    extension_overrides: RwLock<HashMap<String, String>>,
 }
 
 impl Default for LanguageRegistry {
@@ -23,6 +28,7 @@ impl LanguageRegistry {
             by_id: HashMap::new(),
             by_extension: HashMap::new(),
             by_language_id: HashMap::new(),
+            extension_overrides: RwLock::new(HashMap::new()),
         }
     }
 
@@ -39,8 +45,41 @@ impl LanguageRegistry {
         }
     }
 
+    /// Replaces the extension overrides. Keys may be written with or
+    /// without the leading dot; ids naming no registered language are
+    /// ignored at lookup.
+    pub fn set_extension_overrides(&self, overrides: HashMap<String, String>) {
+        *self.extension_overrides.write() = overrides
+            .into_iter()
+            .map(|(ext, id)| (ext.trim_start_matches('.').to_string(), id))
+            .collect();
+    }
+
     pub fn get_by_extension(&self, ext: &str) -> Option<Arc<dyn LanguageSupport>> {
-        self.by_extension.get(ext).cloned()
+        self.get_override(ext)
+            .or_else(|| self.by_extension.get(ext).cloned())
+    }
+
+    /// The language an extension override maps `ext` to, if any.
+    fn get_override(&self, ext: &str) -> Option<Arc<dyn LanguageSupport>> {
+        self.extension_overrides
+            .read()
+            .get(ext)
+            .and_then(|id| {
+                self.by_id
+                    .get(id.as_str())
+                    .or_else(|| self.by_language_id.get(id.as_str()))
+            })
+            .cloned()
+    }
+
+    /// The language an extension override maps the URI's extension to.
+    /// Overrides outrank the client's language id, which is often a guess
+    /// for a custom extension.
+    pub fn get_override_for_uri(&self, uri: &Url) -> Option<Arc<dyn LanguageSupport>> {
+        let path = uri.to_file_path().ok()?;
+        let ext = path.extension()?.to_str()?;
+        self.get_override(ext)
     }
 
     pub fn get_by_language_id(&self, id: &str) -> Option<Arc<dyn LanguageSupport>> {
@@ -171,6 +210,31 @@ mod tests {
         assert!(lang.is_none());
     }
 
+    #[test]
+    fn test_extension_overrides() {
+        let mut registry = LanguageRegistry::new();
+        registry.register(Arc::new(JavaScript));
+        registry.register(Arc::new(TypeScript));
+
+        let uri = Url::parse("file:///path/to/file.mycustom").unwrap();
+        assert!(registry.get_for_uri(&uri).is_none());
+
+        registry.set_extension_overrides(HashMap::from([
+            (".mycustom".to_string(), "javascript".to_string()),
+            ("js".to_string(), "typescript".to_string()),
+            ("other".to_string(), "cobol".to_string()),
+        ]));
+        assert_eq!(registry.get_for_uri(&uri).unwrap().id(), "javascript");
+        assert_eq!(registry.get_override_for_uri(&uri).unwrap().id(), "javascript");
+        assert_eq!(registry.get_by_extension("js").unwrap().id(), "typescript");
+        assert!(registry.get_by_extension("other").is_none());
+
+        registry.set_extension_overrides(HashMap::new());
+        assert!(registry.get_for_uri(&uri).is_none());
+        assert!(registry.get_override_for_uri(&uri).is_none());
+        assert_eq!(registry.get_by_extension("js").unwrap().id(), "javascript");
+    }
+
     #[test]
     fn test_get_for_uri_invalid_uri() {
         let registry = LanguageRegistry::new();
@@ -336,11 +336,11 @@ mod tests {
             loop {
                 let node = cursor.node();
                 if node.kind() == "constant" {
-                    if let Some(kind) = ruby.is_env_source_node(node, code.as_bytes()) {
-                        if let EnvSourceKind::Object { canonical_name } = kind {
-                            if canonical_name == "ENV" {
-                                return true;
-                            }
+                    if let Some(EnvSourceKind::Object { canonical_name }) =
+                        ruby.is_env_source_node(node, code.as_bytes())
+                    {
+                        if canonical_name == "ENV" {
+                            return true;
                         }
                     }
                 }
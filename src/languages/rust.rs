@@ -371,11 +371,11 @@ mod tests {
             loop {
                 let node = cursor.node();
                 if node.kind() == "identifier" || node.kind() == "scoped_identifier" {
-                    if let Some(kind) = rs.is_env_source_node(node, code.as_bytes()) {
-                        if let EnvSourceKind::Object { canonical_name } = kind {
-                            if canonical_name == "std::env" {
-                                return true;
-                            }
+                    if let Some(EnvSourceKind::Object { canonical_name }) =
+                        rs.is_env_source_node(node, code.as_bytes())
+                    {
+                        if canonical_name == "std::env" {
+                            return true;
                         }
                     }
                 }
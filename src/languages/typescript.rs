@@ -1,5 +1,5 @@
 use crate::languages::LanguageSupport;
-use crate::types::EnvSourceKind;
+use crate::types::{EnvBinding, EnvSourceKind};
 use compact_str::CompactString;
 use std::sync::OnceLock;
 use tree_sitter::{Language, Node, Query};
@@ -245,6 +245,38 @@ macro_rules! impl_typescript_language {
                 typescript_is_env_source_node(node, source)
             }
 
+            fn env_object_call_bindings(
+                &self,
+                tree: &tree_sitter::Tree,
+                source: &[u8],
+            ) -> Vec<EnvBinding> {
+                crate::languages::javascript::env_object_call_bindings(
+                    tree,
+                    source,
+                    typescript_is_env_source_node,
+                )
+            }
+
+            fn env_object_spread_bindings(
+                &self,
+                tree: &tree_sitter::Tree,
+                source: &[u8],
+            ) -> Vec<(EnvBinding, Vec<CompactString>)> {
+                crate::languages::javascript::env_object_spread_bindings(
+                    tree,
+                    source,
+                    typescript_is_env_source_node,
+                )
+            }
+
+            fn declared_names(&self, node: Node, source: &[u8]) -> Vec<CompactString> {
+                crate::languages::javascript::declared_names(node, source)
+            }
+
+            fn is_valid_identifier(&self, name: &str) -> bool {
+                crate::languages::is_identifier(name, &['$'])
+            }
+
             fn extract_destructure_key(&self, node: Node, source: &[u8]) -> Option<CompactString> {
                 typescript_extract_destructure_key(node, source)
             }
@@ -520,11 +552,11 @@ mod tests {
             loop {
                 let node = cursor.node();
                 if node.kind() == "member_expression" {
-                    if let Some(kind) = ts.is_env_source_node(node, code.as_bytes()) {
-                        if let EnvSourceKind::Object { canonical_name } = kind {
-                            if canonical_name == "process.env" {
-                                return true;
-                            }
+                    if let Some(EnvSourceKind::Object { canonical_name }) =
+                        ts.is_env_source_node(node, code.as_bytes())
+                    {
+                        if canonical_name == "process.env" {
+                            return true;
                         }
                     }
                 }
@@ -764,11 +796,11 @@ mod tests {
             loop {
                 let node = cursor.node();
                 if node.kind() == "member_expression" {
-                    if let Some(kind) = tsx.is_env_source_node(node, code.as_bytes()) {
-                        if let EnvSourceKind::Object { canonical_name } = kind {
-                            if canonical_name == "process.env" {
-                                return true;
-                            }
+                    if let Some(EnvSourceKind::Object { canonical_name }) =
+                        tsx.is_env_source_node(node, code.as_bytes())
+                    {
+                        if canonical_name == "process.env" {
+                            return true;
                         }
                     }
                 }
@@ -1,4 +1,5 @@
 pub mod analysis;
+pub mod cancellation;
 pub mod constants;
 pub mod error_ext;
 pub mod languages;
@@ -1,10 +1,27 @@
+use ecolog_lsp::server::transport::{self, CliCommand};
 use ecolog_lsp::server::LspServer;
 use std::sync::Arc;
-use tower_lsp::{LspService, Server};
+use tower_lsp::LspService;
 use tracing_subscriber::EnvFilter;
 
 #[tokio::main]
 async fn main() {
+    let transport = match transport::parse_args(std::env::args().skip(1)) {
+        Ok(CliCommand::Serve(transport)) => transport,
+        Ok(CliCommand::Help) => {
+            print!("{}", transport::USAGE);
+            return;
+        }
+        Ok(CliCommand::Version) => {
+            println!("ecolog-lsp {}", env!("CARGO_PKG_VERSION"));
+            return;
+        }
+        Err(e) => {
+            eprintln!("error: {}\n\n{}", e, transport::USAGE);
+            std::process::exit(2);
+        }
+    };
+
     tracing_subscriber::fmt()
         .with_writer(std::io::stderr)
         .with_env_filter(
@@ -16,21 +33,23 @@ async fn main() {
 
     let config_manager = ecolog_lsp::server::config::ConfigManager::new();
 
-    let config_future = config_manager.load_from_workspace(&initial_root);
-    let core_future = {
-        let root = initial_root.clone();
-        async move { abundantis::Abundantis::builder().root(&root).build().await }
-    };
-
-    let (config_result, core_result) = tokio::join!(config_future, core_future);
+    let config = config_manager
+        .load_from_workspace(&initial_root)
+        .await
+        .expect("Failed to load configuration");
+    let abundantis_config = config.to_abundantis_config();
 
-    let config = config_result.expect("Failed to load configuration");
-    let core = core_result.expect("Failed to initialize Ecolog core");
+    // The core discovers file sources from the configured env_files patterns.
+    let core = abundantis::Abundantis::builder()
+        .root(&initial_root)
+        .env_files(abundantis_config.workspace.env_files.to_vec())
+        .build()
+        .await
+        .expect("Failed to initialize Ecolog core");
 
     // External providers are now managed via JSON-RPC.
     // Use `ecolog.provider.spawn` command to start a provider.
 
-    let abundantis_config = config.to_abundantis_config();
     core.resolution
         .update_resolution_config(abundantis_config.resolution);
     core.resolution
@@ -38,10 +57,10 @@ async fn main() {
 
     let config_arc = Arc::new(config_manager);
 
-    let stdin = tokio::io::stdin();
-    let stdout = tokio::io::stdout();
-
     let (service, socket) =
         LspService::new(|client| LspServer::new_with_config(client, core, config_arc));
-    Server::new(stdin, stdout, socket).serve(service).await;
+    if let Err(e) = transport::serve(transport, service, socket).await {
+        tracing::error!("Transport error: {}", e);
+        std::process::exit(1);
+    }
 }
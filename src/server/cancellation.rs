@@ -3,57 +3,27 @@
 //! This module provides utilities for managing background tasks with proper
 //! cancellation and shutdown semantics.
 
-use std::sync::Arc;
-use tokio::sync::broadcast;
 use tokio::task::JoinHandle;
 use tracing::{debug, info, warn};
 
-/// Token for cooperative cancellation of background tasks.
-///
-/// Tasks should periodically check `is_cancelled()` and exit early if true.
-#[derive(Clone)]
-pub struct CancellationToken {
-    sender: Arc<broadcast::Sender<()>>,
-}
-
-impl CancellationToken {
-    /// Create a new cancellation token.
-    pub fn new() -> Self {
-        let (sender, _) = broadcast::channel(1);
-        Self {
-            sender: Arc::new(sender),
-        }
-    }
+pub use crate::cancellation::CancellationToken;
 
-    /// Check if cancellation has been requested.
-    pub fn is_cancelled(&self) -> bool {
-        // If there are no receivers, the channel is effectively cancelled
-        self.sender.receiver_count() == 0 && !self.sender.is_empty()
-    }
-
-    /// Cancel all tasks associated with this token.
-    pub fn cancel(&self) {
-        // Ignore error if no receivers
-        let _ = self.sender.send(());
-    }
-
-    /// Subscribe to cancellation notifications.
-    ///
-    /// Returns a receiver that will receive a message when cancellation is requested.
-    pub fn subscribe(&self) -> broadcast::Receiver<()> {
-        self.sender.subscribe()
-    }
+/// Cancels its token when dropped.
+///
+/// tower-lsp drops a request's future when the client sends
+/// `$/cancelRequest`. Holding this guard in that future turns the drop into
+/// a cancellation that work spawned for the request can observe.
+pub struct CancelOnDrop(CancellationToken);
 
-    /// Wait for cancellation to be signaled.
-    pub async fn cancelled(&self) {
-        let mut receiver = self.subscribe();
-        let _ = receiver.recv().await;
+impl CancelOnDrop {
+    pub fn new(token: CancellationToken) -> Self {
+        Self(token)
     }
 }
 
-impl Default for CancellationToken {
-    fn default() -> Self {
-        Self::new()
+impl Drop for CancelOnDrop {
+    fn drop(&mut self) {
+        self.0.cancel();
     }
 }
 
@@ -158,32 +128,16 @@ impl Default for BackgroundTaskManager {
 mod tests {
     use super::*;
     use std::sync::atomic::{AtomicBool, Ordering};
+    use std::sync::Arc;
     use std::time::Duration;
 
-    #[tokio::test]
-    async fn test_cancellation_token() {
+    #[test]
+    fn test_cancel_on_drop() {
         let token = CancellationToken::new();
-        let token2 = token.clone();
-
-        let completed = Arc::new(AtomicBool::new(false));
-        let completed2 = completed.clone();
-
-        tokio::spawn(async move {
-            token2.cancelled().await;
-            completed2.store(true, Ordering::SeqCst);
-        });
-
-        // Give the task time to start
-        tokio::time::sleep(Duration::from_millis(10)).await;
-
-        assert!(!completed.load(Ordering::SeqCst));
-
-        token.cancel();
-
-        // Give the task time to respond
-        tokio::time::sleep(Duration::from_millis(10)).await;
-
-        assert!(completed.load(Ordering::SeqCst));
+        let guard = CancelOnDrop::new(token.clone());
+        assert!(!token.is_cancelled());
+        drop(guard);
+        assert!(token.is_cancelled());
     }
 
     #[tokio::test]
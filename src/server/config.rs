@@ -1,7 +1,7 @@
 use serde::{Deserialize, Serialize};
 use std::fs;
 use std::path::Path;
-use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
 use std::sync::Arc;
 use tokio::sync::RwLock;
 
@@ -57,7 +57,7 @@ pub struct EcologConfig {
     #[serde(default)]
     pub inlay_hints: InlayHintConfig,
     #[serde(default)]
-    pub workspace: abundantis::config::WorkspaceConfig,
+    pub workspace: WorkspaceSettings,
     #[serde(default)]
     pub resolution: abundantis::config::ResolutionConfig,
     #[serde(default)]
@@ -68,6 +68,408 @@ pub struct EcologConfig {
     pub sources: abundantis::config::SourcesConfig,
     #[serde(default)]
     pub providers: ProvidersConfig,
+    #[serde(default)]
+    pub init_settings_precedence: InitSettingsPrecedence,
+    #[serde(default)]
+    pub definition_target: DefinitionTarget,
+    #[serde(default)]
+    pub rename: RenameConfig,
+    #[serde(default)]
+    pub references: ReferencesConfig,
+    #[serde(default)]
+    pub diagnostics: DiagnosticsConfig,
+    #[serde(default)]
+    pub detectors: Vec<crate::analysis::CustomDetector>,
+    #[serde(default)]
+    pub custom_env_accessors: Vec<crate::analysis::EnvAccessor>,
+    /// Name globs of the only env vars analyzed; empty means all of them.
+    #[serde(default)]
+    pub env_var_allowlist: Vec<String>,
+    /// Name globs of env vars never analyzed, e.g. `PATH`. Wins over the
+    /// allowlist.
+    #[serde(default)]
+    pub env_var_denylist: Vec<String>,
+    /// Maps file extensions to the language that analyzes them, e.g.
+    /// `mycustom = "javascript"`.
+    #[serde(default)]
+    pub extension_overrides: std::collections::HashMap<String, String>,
+    #[serde(default)]
+    pub masking: MaskingConfig,
+    /// Named sets of active env files, switched with `ecolog.file.setProfile`.
+    #[serde(default)]
+    pub profiles: std::collections::HashMap<String, ProfileConfig>,
+    #[serde(default)]
+    pub logging: LoggingConfig,
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct LoggingConfig {
+    /// Seconds between heartbeat log lines; 0 disables the heartbeat.
+    #[serde(default = "default_heartbeat_secs")]
+    pub heartbeat_secs: u64,
+    /// Level heartbeat lines are logged at.
+    #[serde(default)]
+    pub heartbeat_level: HeartbeatLevel,
+}
+
+fn default_heartbeat_secs() -> u64 {
+    crate::constants::HEARTBEAT_INTERVAL_SECS
+}
+
+impl Default for LoggingConfig {
+    fn default() -> Self {
+        Self {
+            heartbeat_secs: default_heartbeat_secs(),
+            heartbeat_level: HeartbeatLevel::default(),
+        }
+    }
+}
+
+impl LoggingConfig {
+    /// The heartbeat period, or `None` when the heartbeat is disabled.
+    pub fn heartbeat_interval(&self) -> Option<std::time::Duration> {
+        (self.heartbeat_secs > 0).then(|| std::time::Duration::from_secs(self.heartbeat_secs))
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum HeartbeatLevel {
+    Trace,
+    Debug,
+    #[default]
+    Info,
+}
+
+/// A named set of env file patterns, e.g. `[profiles.prod] files = [".env.production"]`.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize, Serialize)]
+pub struct ProfileConfig {
+    #[serde(default)]
+    pub files: Vec<String>,
+}
+
+#[derive(Debug, Clone, Default, Deserialize, Serialize)]
+pub struct RenameConfig {
+    #[serde(default)]
+    pub concurrency: RenameConcurrency,
+    /// Rename references in every monorepo package instead of only the
+    /// package containing the file the rename starts from.
+    #[serde(default)]
+    pub across_packages: bool,
+}
+
+#[derive(Debug, Clone, Default, Deserialize, Serialize)]
+pub struct ReferencesConfig {
+    /// Also list uses of local bindings, like `a` in `console.log(a)` after
+    /// `const { API_KEY: a } = process.env`, to show where a value flows.
+    /// Rename never touches them.
+    #[serde(default)]
+    pub include_binding_usages: bool,
+}
+
+/// How a rename behaves while another rename is still being computed.
+///
+/// Renames are serialized per workspace so each one sees a consistent index.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RenameConcurrency {
+    /// Wait for the in-flight rename to finish, then run.
+    #[default]
+    Queue,
+    /// Return no edits instead of waiting.
+    Reject,
+}
+
+/// Hides env var values wherever the server displays them.
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct MaskingConfig {
+    /// Master switch; nothing is masked while this is off.
+    #[serde(default)]
+    pub enabled: bool,
+    #[serde(default = "true_bool")]
+    pub mask_in_hover: bool,
+    #[serde(default = "true_bool")]
+    pub mask_in_completion: bool,
+    #[serde(default)]
+    pub mode: MaskMode,
+    /// Workspace globs of files whose displayed values are never masked,
+    /// e.g. `tests/**` for local fixtures.
+    #[serde(default)]
+    pub mask_exclude_globs: Vec<String>,
+    /// Key globs, e.g. `*_SECRET` or `*PASSWORD*`, whose values are masked
+    /// everywhere, even where the settings above would show them.
+    #[serde(default)]
+    pub force_mask_key_patterns: Vec<String>,
+    /// Key globs of known-public values, e.g. `PUBLIC_*`, that are never
+    /// masked. These win over `force_mask_key_patterns`.
+    #[serde(default)]
+    pub never_mask_key_patterns: Vec<String>,
+}
+
+impl Default for MaskingConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            mask_in_hover: true,
+            mask_in_completion: true,
+            mode: MaskMode::default(),
+            mask_exclude_globs: Vec::new(),
+            force_mask_key_patterns: Vec::new(),
+            never_mask_key_patterns: Vec::new(),
+        }
+    }
+}
+
+impl MaskingConfig {
+    /// Returns true if values shown on `target` get masked.
+    pub fn applies_to(&self, target: MaskTarget) -> bool {
+        self.enabled
+            && match target {
+                MaskTarget::Hover => self.mask_in_hover,
+                MaskTarget::Completion => self.mask_in_completion,
+                MaskTarget::InlayHint | MaskTarget::CodeLens | MaskTarget::Command => true,
+            }
+    }
+
+    /// Returns true if `key`'s value gets masked on `target`, taking the key
+    /// patterns into account.
+    pub fn masks_key(&self, key: &str, target: MaskTarget) -> bool {
+        let matches = |patterns: &[String]| {
+            patterns
+                .iter()
+                .any(|pattern| glob::Pattern::new(pattern).is_ok_and(|p| p.matches(key)))
+        };
+        if matches(&self.never_mask_key_patterns) {
+            return false;
+        }
+        matches(&self.force_mask_key_patterns) || self.applies_to(target)
+    }
+}
+
+/// How much of a masked value stays visible.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum MaskMode {
+    /// Every character is replaced.
+    #[default]
+    Full,
+    /// The first and last few characters stay visible.
+    Partial,
+}
+
+/// Places that display env var values.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MaskTarget {
+    Hover,
+    Completion,
+    InlayHint,
+    CodeLens,
+    Command,
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct DiagnosticsConfig {
+    /// Only env files and deployment configs count as defining a variable,
+    /// so a var set only in the local shell is still flagged (CI parity).
+    #[serde(default)]
+    pub file_sources_only: bool,
+    /// Add an info diagnostic at the first parse error in a code file, since
+    /// env analysis of a broken tree may be incomplete.
+    #[serde(default)]
+    pub warn_on_parse_error: bool,
+    /// Env var name prefixes required in files matching a glob, e.g.
+    /// `"src/client/**" = "VITE_"` for frameworks that only expose prefixed
+    /// vars to client code.
+    #[serde(default)]
+    pub required_prefix_by_glob: std::collections::HashMap<String, String>,
+    /// Which kinds of env var use get diagnostics, e.g. only direct
+    /// references to keep refactors of destructured bindings quiet.
+    #[serde(default = "default_diagnose_kinds")]
+    pub diagnose_kinds: Vec<DiagnoseKind>,
+    /// Documentation env files like `.env.example`. An undefined var one of
+    /// them declares is reported as declared but not set.
+    #[serde(default = "default_example_files")]
+    pub example_files: Vec<String>,
+    /// Codes never reported, e.g. `["EDF001"]` for teams whose loader
+    /// accepts `KEY = value`.
+    #[serde(default)]
+    pub disabled_diagnostics: Vec<String>,
+}
+
+impl DiagnosticsConfig {
+    /// Entries of `disabled_diagnostics` that aren't codes the server reports.
+    pub fn unknown_disabled_diagnostics(&self) -> Vec<&str> {
+        self.disabled_diagnostics
+            .iter()
+            .map(String::as_str)
+            .filter(|code| !crate::types::DIAGNOSTIC_CODES.contains(code))
+            .collect()
+    }
+}
+
+fn default_example_files() -> Vec<String> {
+    vec![".env.example".to_string()]
+}
+
+impl Default for DiagnosticsConfig {
+    fn default() -> Self {
+        Self {
+            file_sources_only: false,
+            warn_on_parse_error: false,
+            required_prefix_by_glob: Default::default(),
+            diagnose_kinds: default_diagnose_kinds(),
+            example_files: default_example_files(),
+            disabled_diagnostics: Vec::new(),
+        }
+    }
+}
+
+/// A kind of env var use that diagnostics can be enabled for.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum DiagnoseKind {
+    /// `process.env.API_KEY`
+    DirectReference,
+    /// `const { API_KEY } = process.env`
+    BindingDeclaration,
+    /// `env.API_KEY` where `env` is `process.env`
+    PropertyAccess,
+}
+
+fn default_diagnose_kinds() -> Vec<DiagnoseKind> {
+    vec![
+        DiagnoseKind::DirectReference,
+        DiagnoseKind::BindingDeclaration,
+        DiagnoseKind::PropertyAccess,
+    ]
+}
+
+/// Which source wins when init settings and `ecolog.toml` set the same field.
+///
+/// Sources are merged field by field on top of the defaults, so a field set
+/// by only one source always applies; precedence only decides conflicts.
+/// The flag itself is read from `ecolog.toml` first, then init settings.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum InitSettingsPrecedence {
+    /// `ecolog.toml` overrides init settings.
+    #[default]
+    File,
+    /// Init settings override `ecolog.toml`.
+    InitSettings,
+}
+
+/// Where go-to-definition jumps for a binding, its usages or an imported
+/// env var.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum DefinitionTarget {
+    /// The variable's line in the env file that defines it.
+    #[default]
+    EnvFile,
+    /// The code that declares the binding or export. Direct references like
+    /// `process.env.X` declare nothing, so they still go to the env file.
+    Declaration,
+}
+
+/// Workspace settings: abundantis' workspace config plus indexer-only options.
+///
+/// Derefs to [`abundantis::config::WorkspaceConfig`] so existing accesses like
+/// `config.workspace.env_files` keep working.
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct WorkspaceSettings {
+    #[serde(flatten)]
+    pub base: abundantis::config::WorkspaceConfig,
+    /// Maximum directory depth the workspace indexer descends into.
+    /// `None` means unlimited.
+    #[serde(default)]
+    pub max_index_depth: Option<usize>,
+    /// Env files larger than this are skipped with a warning instead of
+    /// parsed, guarding against globs that match the wrong file.
+    #[serde(default = "default_max_env_file_bytes")]
+    pub max_env_file_bytes: u64,
+    /// How long a variable lookup may take before it's abandoned. Timed out
+    /// lookups aren't reported as undefined.
+    #[serde(default = "default_resolution_timeout_ms")]
+    pub resolution_timeout_ms: u64,
+    /// Documents with at most this many lines are analyzed as soon as they
+    /// change instead of after the debounce. `0` debounces every document.
+    #[serde(default = "default_inline_analysis_max_lines")]
+    pub inline_analysis_max_lines: usize,
+    /// Treats `# include: <path>` comments in env files as importing the
+    /// included file's keys. Off by default.
+    #[serde(default)]
+    pub follow_env_includes: bool,
+    /// Scans every `.yml`, `.yaml` and `.json` file for `${VAR}`
+    /// interpolation, not just Compose files. Off by default, since other
+    /// YAML often uses `$VAR` for things that aren't env vars.
+    #[serde(default)]
+    pub scan_all_config_files: bool,
+    /// Matches `env_files` patterns ignoring case, so `.ENV` counts as a
+    /// `.env` file. Defaults to on for macOS and Windows, whose filesystems
+    /// are usually case-insensitive.
+    #[serde(default = "default_case_insensitive_env_files")]
+    pub case_insensitive_env_files: bool,
+    /// Maximum number of cached module resolutions. The coldest entries are
+    /// evicted past it.
+    #[serde(default = "default_module_cache_max")]
+    pub module_cache_max: usize,
+    /// Indexes only env files at startup. Code files are indexed as they're
+    /// opened, or when references and rename search for a variable they
+    /// mention; workspace symbols only see the files indexed so far.
+    #[serde(default)]
+    pub lazy_index: bool,
+}
+
+fn default_max_env_file_bytes() -> u64 {
+    crate::analysis::indexer::DEFAULT_MAX_ENV_FILE_BYTES
+}
+
+fn default_resolution_timeout_ms() -> u64 {
+    crate::server::util::DEFAULT_RESOLUTION_TIMEOUT_MS
+}
+
+fn default_inline_analysis_max_lines() -> usize {
+    200
+}
+
+fn default_module_cache_max() -> usize {
+    crate::analysis::workspace_index::DEFAULT_MODULE_CACHE_MAX
+}
+
+fn default_case_insensitive_env_files() -> bool {
+    crate::analysis::indexer::DEFAULT_CASE_INSENSITIVE_ENV_FILES
+}
+
+impl Default for WorkspaceSettings {
+    fn default() -> Self {
+        Self {
+            base: Default::default(),
+            max_index_depth: None,
+            max_env_file_bytes: default_max_env_file_bytes(),
+            resolution_timeout_ms: default_resolution_timeout_ms(),
+            inline_analysis_max_lines: default_inline_analysis_max_lines(),
+            follow_env_includes: false,
+            scan_all_config_files: false,
+            case_insensitive_env_files: default_case_insensitive_env_files(),
+            module_cache_max: default_module_cache_max(),
+            lazy_index: false,
+        }
+    }
+}
+
+impl std::ops::Deref for WorkspaceSettings {
+    type Target = abundantis::config::WorkspaceConfig;
+
+    fn deref(&self) -> &Self::Target {
+        &self.base
+    }
+}
+
+impl std::ops::DerefMut for WorkspaceSettings {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.base
+    }
 }
 
 #[derive(Debug, Clone, Deserialize, Serialize)]
@@ -82,6 +484,60 @@ pub struct FeatureConfig {
     pub definition: bool,
     #[serde(default)]
     pub inlay_hints: bool,
+    /// Lenses showing each reference's value above its line. Off by default
+    /// since lenses are noisy.
+    #[serde(default)]
+    pub code_lens: bool,
+    /// Tags env var references with the `envVariable` semantic token type,
+    /// so themes can color them. Off by default since the tokens override
+    /// the language's own highlighting.
+    #[serde(default)]
+    pub semantic_tokens: bool,
+    /// Hovers also list the other sources defining a variable, with their
+    /// values, below the winning one.
+    #[serde(default)]
+    pub hover_show_overrides: bool,
+    #[serde(default)]
+    pub symbol_kind: EnvSymbolKind,
+    /// Completion lists longer than this are cut to the best matches and
+    /// marked incomplete, so the editor asks again as the user types.
+    #[serde(default = "default_max_completion_items")]
+    pub max_completion_items: usize,
+}
+
+fn default_max_completion_items() -> usize {
+    200
+}
+
+/// Symbol kind reported for env vars in symbol results.
+///
+/// Clients pick icons by kind, so some render env vars better as variables or
+/// properties than as constants.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum EnvSymbolKind {
+    #[default]
+    Constant,
+    Variable,
+    Property,
+    Field,
+    Key,
+    String,
+    EnumMember,
+}
+
+impl From<EnvSymbolKind> for tower_lsp::lsp_types::SymbolKind {
+    fn from(kind: EnvSymbolKind) -> Self {
+        match kind {
+            EnvSymbolKind::Constant => Self::CONSTANT,
+            EnvSymbolKind::Variable => Self::VARIABLE,
+            EnvSymbolKind::Property => Self::PROPERTY,
+            EnvSymbolKind::Field => Self::FIELD,
+            EnvSymbolKind::Key => Self::KEY,
+            EnvSymbolKind::String => Self::STRING,
+            EnvSymbolKind::EnumMember => Self::ENUM_MEMBER,
+        }
+    }
 }
 
 #[derive(Debug, Clone, Deserialize, Serialize)]
@@ -100,6 +556,11 @@ impl Default for FeatureConfig {
             diagnostics: true,
             definition: true,
             inlay_hints: false,
+            code_lens: false,
+            semantic_tokens: false,
+            hover_show_overrides: false,
+            symbol_kind: EnvSymbolKind::default(),
+            max_completion_items: default_max_completion_items(),
         }
     }
 }
@@ -123,6 +584,20 @@ pub struct InlayHintConfig {
 
     #[serde(default)]
     pub max_hints_per_line: usize,
+
+    #[serde(default)]
+    pub multiline_position: MultilineHintPosition,
+}
+
+/// Where the hint goes when a direct reference spans several lines.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum MultilineHintPosition {
+    /// After the whole reference, e.g. the closing `]` of a subscript.
+    #[default]
+    ReferenceEnd,
+    /// Right after the variable name.
+    NameEnd,
 }
 
 fn default_max_hint_length() -> usize {
@@ -138,6 +613,7 @@ impl Default for InlayHintConfig {
             property_accesses: true,
             max_value_length: default_max_hint_length(),
             max_hints_per_line: 0,
+            multiline_position: MultilineHintPosition::default(),
         }
     }
 }
@@ -153,15 +629,117 @@ impl Default for StrictConfig {
 
 
 impl EcologConfig {
+    /// Whether `name` passes `env_var_allowlist` and `env_var_denylist`.
+    pub fn tracks_env_var(&self, name: &str) -> bool {
+        let matches = |patterns: &[String]| {
+            patterns.iter().any(|pattern| {
+                glob::Pattern::new(pattern)
+                    .map(|p| p.matches(name))
+                    .unwrap_or(false)
+            })
+        };
+        (self.env_var_allowlist.is_empty() || matches(&self.env_var_allowlist))
+            && !matches(&self.env_var_denylist)
+    }
+
+    /// The abundantis side of the config. `workspace.env_files` keeps its `!`
+    /// patterns; abundantis' globs never match them, and
+    /// [`prepare_file_sources`](crate::server::util::prepare_file_sources)
+    /// drops the file sources they exclude.
     pub fn to_abundantis_config(&self) -> abundantis::config::AbundantisConfig {
         abundantis::config::AbundantisConfig {
-            workspace: self.workspace.clone(),
+            workspace: self.workspace.base.clone(),
             resolution: self.resolution.clone(),
             interpolation: self.interpolation.clone(),
             cache: self.cache.clone(),
             sources: self.sources.clone(),
         }
     }
+
+    /// Parses `ecolog.toml` content on top of the defaults, the same way
+    /// [`ConfigManager::load_from_workspace`] reads the file but without
+    /// touching the filesystem. Unknown keys are logged and ignored.
+    pub fn from_toml_str(content: &str) -> Result<Self, String> {
+        let (config, unknown) = Self::parse_toml(content)?;
+        for key in unknown {
+            tracing::warn!("Ignoring unknown config key `{}`", key);
+        }
+        Ok(config)
+    }
+
+    /// Like [`from_toml_str`](Self::from_toml_str), but rejects unknown keys
+    /// so typos fail validation instead of being silently ignored.
+    pub fn from_toml_str_strict(content: &str) -> Result<Self, String> {
+        let (config, unknown) = Self::parse_toml(content)?;
+        if !unknown.is_empty() {
+            return Err(format!("Unknown config keys: {}", unknown.join(", ")));
+        }
+        Ok(config)
+    }
+
+    /// Serializes the config as `ecolog.toml` content.
+    pub fn to_toml_string(&self) -> String {
+        toml::to_string_pretty(self).expect("config is representable as TOML")
+    }
+
+    fn parse_toml(content: &str) -> Result<(Self, Vec<String>), String> {
+        let value: toml::Value =
+            toml::from_str(content).map_err(|e| format!("Failed to parse config: {}", e))?;
+        let config = Self::from_overlay(&toml_to_json(&value))?;
+        let unknown = unknown_keys(&value, &config);
+        Ok((config, unknown))
+    }
+
+    /// Merges `overlay` over the defaults and deserializes the result.
+    fn from_overlay(overlay: &serde_json::Value) -> Result<Self, String> {
+        let mut config_json = serde_json::to_value(Self::default())
+            .map_err(|e| format!("Failed to serialize defaults: {}", e))?;
+        merge_json(&mut config_json, overlay);
+        let mut config: Self = serde_json::from_value(config_json)
+            .map_err(|e| format!("Failed to deserialize config: {}", e))?;
+        ConfigManager::apply_source_defaults(&mut config);
+        Ok(config)
+    }
+}
+
+/// Dotted paths of keys in `value` that don't survive deserializing into
+/// `config`, i.e. keys no config field reads.
+fn unknown_keys(value: &toml::Value, config: &EcologConfig) -> Vec<String> {
+    let Ok(known) = toml::Value::try_from(config) else {
+        return Vec::new();
+    };
+    let mut unknown = Vec::new();
+    collect_unknown_keys(value, &known, "", &mut unknown);
+    unknown
+}
+
+fn collect_unknown_keys(
+    value: &toml::Value,
+    known: &toml::Value,
+    path: &str,
+    out: &mut Vec<String>,
+) {
+    match (value, known) {
+        (toml::Value::Table(table), toml::Value::Table(known_table)) => {
+            for (key, child) in table {
+                let child_path = if path.is_empty() {
+                    key.clone()
+                } else {
+                    format!("{}.{}", path, key)
+                };
+                match known_table.get(key) {
+                    Some(known_child) => collect_unknown_keys(child, known_child, &child_path, out),
+                    None => out.push(child_path),
+                }
+            }
+        }
+        (toml::Value::Array(items), toml::Value::Array(known_items)) => {
+            for (i, (item, known_item)) in items.iter().zip(known_items).enumerate() {
+                collect_unknown_keys(item, known_item, &format!("{}[{}]", path, i), out);
+            }
+        }
+        _ => {}
+    }
 }
 
 /// Cached feature flags for lock-free access in hot paths.
@@ -173,6 +751,8 @@ pub struct CachedFeatureFlags {
     pub diagnostics: AtomicBool,
     pub definition: AtomicBool,
     pub inlay_hints: AtomicBool,
+    pub code_lens: AtomicBool,
+    pub semantic_tokens: AtomicBool,
 }
 
 impl CachedFeatureFlags {
@@ -183,6 +763,8 @@ impl CachedFeatureFlags {
             diagnostics: AtomicBool::new(true),
             definition: AtomicBool::new(true),
             inlay_hints: AtomicBool::new(false),
+            code_lens: AtomicBool::new(false),
+            semantic_tokens: AtomicBool::new(false),
         }
     }
 
@@ -192,6 +774,8 @@ impl CachedFeatureFlags {
         self.diagnostics.store(features.diagnostics, Ordering::Relaxed);
         self.definition.store(features.definition, Ordering::Relaxed);
         self.inlay_hints.store(features.inlay_hints, Ordering::Relaxed);
+        self.code_lens.store(features.code_lens, Ordering::Relaxed);
+        self.semantic_tokens.store(features.semantic_tokens, Ordering::Relaxed);
     }
 }
 
@@ -201,6 +785,10 @@ pub struct ConfigManager {
     /// Cached feature flags for lock-free access.
     /// Updated whenever config is loaded or updated.
     pub cached_features: CachedFeatureFlags,
+    /// `workspace.resolution_timeout_ms`, cached for the same reason.
+    resolution_timeout_ms: AtomicU64,
+    /// `workspace.case_insensitive_env_files`, cached for the same reason.
+    case_insensitive_env_files: AtomicBool,
 }
 
 impl Default for ConfigManager {
@@ -215,6 +803,8 @@ impl ConfigManager {
             config: Arc::new(RwLock::new(EcologConfig::default())),
             init_settings: Arc::new(RwLock::new(None)),
             cached_features: CachedFeatureFlags::new(),
+            resolution_timeout_ms: AtomicU64::new(default_resolution_timeout_ms()),
+            case_insensitive_env_files: AtomicBool::new(default_case_insensitive_env_files()),
         }
     }
 
@@ -248,6 +838,30 @@ impl ConfigManager {
         self.cached_features.inlay_hints.load(Ordering::Relaxed)
     }
 
+    /// Check if code lens feature is enabled (lock-free).
+    #[inline]
+    pub fn is_code_lens_enabled(&self) -> bool {
+        self.cached_features.code_lens.load(Ordering::Relaxed)
+    }
+
+    /// Check if semantic tokens feature is enabled (lock-free).
+    #[inline]
+    pub fn is_semantic_tokens_enabled(&self) -> bool {
+        self.cached_features.semantic_tokens.load(Ordering::Relaxed)
+    }
+
+    /// How long a variable lookup may take (lock-free).
+    #[inline]
+    pub fn resolution_timeout(&self) -> std::time::Duration {
+        std::time::Duration::from_millis(self.resolution_timeout_ms.load(Ordering::Relaxed))
+    }
+
+    /// Whether `env_files` patterns match ignoring case (lock-free).
+    #[inline]
+    pub fn case_insensitive_env_files(&self) -> bool {
+        self.case_insensitive_env_files.load(Ordering::Relaxed)
+    }
+
     pub fn get_config(&self) -> Arc<RwLock<EcologConfig>> {
         self.config.clone()
     }
@@ -261,23 +875,32 @@ impl ConfigManager {
         let mut config_json = serde_json::to_value(EcologConfig::default())
             .map_err(|e| format!("Failed to serialize defaults: {}", e))?;
 
-        {
-            let init_settings = self.init_settings.read().await;
-            if let Some(settings) = init_settings.as_ref() {
-                merge_json(&mut config_json, settings);
-            }
-        }
+        let init_json = self.init_settings.read().await.clone();
 
         let config_path = root.join("ecolog.toml");
-        if config_path.exists() {
+        let file_json = if config_path.exists() {
             let toml_content = fs::read_to_string(&config_path)
                 .map_err(|e| format!("Failed to read config: {}", e))?;
 
             let toml_value: toml::Value = toml::from_str(&toml_content)
                 .map_err(|e| format!("Failed to parse config: {}", e))?;
-            let toml_json = toml_to_json(&toml_value);
+            if let Ok(file_config) = EcologConfig::from_overlay(&toml_to_json(&toml_value)) {
+                for key in unknown_keys(&toml_value, &file_config) {
+                    tracing::warn!("Ignoring unknown key `{}` in ecolog.toml", key);
+                }
+            }
+            Some(toml_to_json(&toml_value))
+        } else {
+            None
+        };
 
-            merge_json(&mut config_json, &toml_json);
+        let precedence = Self::init_settings_precedence(file_json.as_ref(), init_json.as_ref());
+        let (lower, higher) = match precedence {
+            InitSettingsPrecedence::File => (init_json.as_ref(), file_json.as_ref()),
+            InitSettingsPrecedence::InitSettings => (file_json.as_ref(), init_json.as_ref()),
+        };
+        for overlay in [lower, higher].into_iter().flatten() {
+            merge_json(&mut config_json, overlay);
         }
 
         let mut config: EcologConfig = serde_json::from_value(config_json)
@@ -285,8 +908,19 @@ impl ConfigManager {
 
         Self::apply_source_defaults(&mut config);
 
+        for code in config.diagnostics.unknown_disabled_diagnostics() {
+            tracing::warn!(
+                "Unknown diagnostic code `{}` in diagnostics.disabled_diagnostics",
+                code
+            );
+        }
+
         // Update cached feature flags for lock-free access
         self.cached_features.update_from(&config.features);
+        self.resolution_timeout_ms
+            .store(config.workspace.resolution_timeout_ms, Ordering::Relaxed);
+        self.case_insensitive_env_files
+            .store(config.workspace.case_insensitive_env_files, Ordering::Relaxed);
 
         let mut lock = self.config.write().await;
         *lock = config.clone();
@@ -294,6 +928,19 @@ impl ConfigManager {
         Ok(config)
     }
 
+    /// Resolves `init_settings_precedence`, preferring the file's value.
+    fn init_settings_precedence(
+        file_json: Option<&serde_json::Value>,
+        init_json: Option<&serde_json::Value>,
+    ) -> InitSettingsPrecedence {
+        [file_json, init_json]
+            .into_iter()
+            .flatten()
+            .filter_map(|json| json.get("init_settings_precedence"))
+            .find_map(|value| serde_json::from_value(value.clone()).ok())
+            .unwrap_or_default()
+    }
+
     fn apply_source_defaults(config: &mut EcologConfig) {
         use abundantis::config::{ResolutionConfig, SourcePrecedence};
 
@@ -308,6 +955,10 @@ impl ConfigManager {
     pub async fn update(&self, new_config: EcologConfig) {
         // Update cached feature flags for lock-free access
         self.cached_features.update_from(&new_config.features);
+        self.resolution_timeout_ms
+            .store(new_config.workspace.resolution_timeout_ms, Ordering::Relaxed);
+        self.case_insensitive_env_files
+            .store(new_config.workspace.case_insensitive_env_files, Ordering::Relaxed);
 
         let mut lock = self.config.write().await;
         *lock = new_config;
@@ -493,6 +1144,19 @@ completion = false
         assert!(result.unwrap_err().contains("Failed to parse config"));
     }
 
+    #[test]
+    fn test_unknown_disabled_diagnostics() {
+        let config = DiagnosticsConfig {
+            disabled_diagnostics: vec![
+                "EDF001".to_string(),
+                "EDF777".to_string(),
+                "undefined-env-var".to_string(),
+            ],
+            ..DiagnosticsConfig::default()
+        };
+        assert_eq!(config.unknown_disabled_diagnostics(), vec!["EDF777"]);
+    }
+
     #[tokio::test]
     async fn test_config_manager_update() {
         let manager = ConfigManager::new();
@@ -594,4 +1258,347 @@ diagnostics = true
             Some(std::path::PathBuf::from("/custom/workspace/root"))
         );
     }
+
+    #[tokio::test]
+    async fn test_config_manager_init_settings_precedence() {
+        let manager = ConfigManager::new();
+        let temp_dir = TempDir::new().unwrap();
+        let config_path = temp_dir.path().join("ecolog.toml");
+
+        manager
+            .set_init_settings(Some(serde_json::json!({
+                "features": { "hover": false }
+            })))
+            .await;
+
+        // Default: the file wins
+        std::fs::write(&config_path, "[features]\nhover = true\n").unwrap();
+        let config = manager.load_from_workspace(temp_dir.path()).await.unwrap();
+        assert_eq!(config.init_settings_precedence, InitSettingsPrecedence::File);
+        assert!(config.features.hover);
+
+        // Flipped in the file: init settings win
+        std::fs::write(
+            &config_path,
+            "init_settings_precedence = \"init_settings\"\n[features]\nhover = true\n",
+        )
+        .unwrap();
+        let config = manager.load_from_workspace(temp_dir.path()).await.unwrap();
+        assert_eq!(
+            config.init_settings_precedence,
+            InitSettingsPrecedence::InitSettings
+        );
+        assert!(!config.features.hover);
+
+        // Flipped from init settings when the file doesn't say
+        manager
+            .set_init_settings(Some(serde_json::json!({
+                "init_settings_precedence": "init_settings",
+                "features": { "hover": false }
+            })))
+            .await;
+        std::fs::write(&config_path, "[features]\nhover = true\n").unwrap();
+        let config = manager.load_from_workspace(temp_dir.path()).await.unwrap();
+        assert!(!config.features.hover);
+    }
+
+    #[tokio::test]
+    async fn test_config_manager_max_index_depth() {
+        let manager = ConfigManager::new();
+        let temp_dir = TempDir::new().unwrap();
+
+        let config = manager.load_from_workspace(temp_dir.path()).await.unwrap();
+        assert_eq!(config.workspace.max_index_depth, None);
+
+        let config_content = r#"
+[workspace]
+max_index_depth = 3
+env_files = [".env"]
+"#;
+
+        let config_path = temp_dir.path().join("ecolog.toml");
+        let mut file = std::fs::File::create(&config_path).unwrap();
+        file.write_all(config_content.as_bytes()).unwrap();
+
+        let config = manager.load_from_workspace(temp_dir.path()).await.unwrap();
+        assert_eq!(config.workspace.max_index_depth, Some(3));
+        assert_eq!(config.workspace.env_files, vec![".env"]);
+    }
+
+    #[tokio::test]
+    async fn test_config_manager_rename_concurrency() {
+        let manager = ConfigManager::new();
+        let temp_dir = TempDir::new().unwrap();
+
+        let config = manager.load_from_workspace(temp_dir.path()).await.unwrap();
+        assert_eq!(config.rename.concurrency, RenameConcurrency::Queue);
+
+        let config_path = temp_dir.path().join("ecolog.toml");
+        let mut file = std::fs::File::create(&config_path).unwrap();
+        file.write_all(b"[rename]\nconcurrency = \"reject\"\n").unwrap();
+
+        let config = manager.load_from_workspace(temp_dir.path()).await.unwrap();
+        assert_eq!(config.rename.concurrency, RenameConcurrency::Reject);
+    }
+
+    #[tokio::test]
+    async fn test_config_manager_detectors() {
+        let manager = ConfigManager::new();
+        let temp_dir = TempDir::new().unwrap();
+
+        let config = manager.load_from_workspace(temp_dir.path()).await.unwrap();
+        assert!(config.detectors.is_empty());
+
+        let config_content = r#"
+[[detectors]]
+files = ["quasar.conf.js"]
+object_paths = ["ctx.env"]
+
+[[detectors]]
+files = ["*.build.cfg"]
+calls = ["getEnv"]
+"#;
+
+        let config_path = temp_dir.path().join("ecolog.toml");
+        let mut file = std::fs::File::create(&config_path).unwrap();
+        file.write_all(config_content.as_bytes()).unwrap();
+
+        let config = manager.load_from_workspace(temp_dir.path()).await.unwrap();
+        assert_eq!(config.detectors.len(), 2);
+        assert_eq!(config.detectors[0].object_paths, vec!["ctx.env"]);
+        assert!(config.detectors[0].calls.is_empty());
+        assert_eq!(config.detectors[1].calls, vec!["getEnv"]);
+    }
+
+    const FULL_CONFIG: &str = r#"
+init_settings_precedence = "init_settings"
+definition_target = "declaration"
+env_var_allowlist = ["APP_*"]
+env_var_denylist = ["PATH"]
+
+[features]
+hover = false
+completion = false
+diagnostics = false
+definition = false
+inlay_hints = true
+code_lens = true
+semantic_tokens = true
+hover_show_overrides = true
+symbol_kind = "variable"
+max_completion_items = 50
+
+[strict]
+hover = false
+completion = false
+
+[inlay_hints]
+direct_references = false
+binding_declarations = false
+binding_usages = true
+property_accesses = false
+max_value_length = 20
+max_hints_per_line = 2
+multiline_position = "name_end"
+
+[workspace]
+root = "/srv/app"
+provider = "pnpm"
+roots = ["packages/*"]
+cascading = true
+env_files = [".env", ".env.test"]
+ignores = ["**/vendor/**"]
+max_index_depth = 4
+max_env_file_bytes = 4096
+resolution_timeout_ms = 250
+inline_analysis_max_lines = 50
+follow_env_includes = true
+scan_all_config_files = true
+case_insensitive_env_files = true
+module_cache_max = 64
+lazy_index = true
+
+[resolution]
+precedence = ["file", "remote"]
+type_check = false
+
+[resolution.files]
+mode = "override"
+order = [".env.local", ".env"]
+
+[interpolation]
+enabled = false
+max_depth = 3
+
+[interpolation.features]
+defaults = false
+alternates = false
+recursion = false
+commands = true
+
+[cache]
+enabled = false
+hot_cache_size = 10
+ttl = "1m"
+
+[sources.defaults]
+shell = false
+file = false
+remote = true
+
+[sources.remote.vault]
+enabled = true
+address = "https://vault.local"
+
+[sources.providers]
+path = "/opt/abundantis/providers"
+
+[sources.providers.aws]
+enabled = true
+spawn = "eager"
+
+[providers]
+path = "/opt/ecolog/providers"
+
+[providers.doppler]
+enabled = true
+binary = "/usr/bin/doppler-provider"
+
+[rename]
+concurrency = "reject"
+across_packages = true
+
+[references]
+include_binding_usages = true
+
+[diagnostics]
+file_sources_only = true
+warn_on_parse_error = true
+diagnose_kinds = ["direct_reference"]
+example_files = [".env.sample"]
+disabled_diagnostics = ["EDF001"]
+
+[diagnostics.required_prefix_by_glob]
+"src/client/**" = "VITE_"
+
+[masking]
+enabled = true
+mask_in_hover = false
+mask_in_completion = false
+mode = "partial"
+mask_exclude_globs = ["tests/**"]
+force_mask_key_patterns = ["*_SECRET"]
+never_mask_key_patterns = ["PUBLIC_*"]
+
+[profiles.prod]
+files = [".env.production"]
+
+[[detectors]]
+files = ["quasar.conf.js"]
+object_paths = ["ctx.env"]
+calls = ["getEnv"]
+
+[[custom_env_accessors]]
+callee = "config.get"
+arg_index = 1
+
+[extension_overrides]
+mycustom = "javascript"
+
+[logging]
+heartbeat_secs = 0
+heartbeat_level = "debug"
+"#;
+
+    fn as_json(config: &EcologConfig) -> serde_json::Value {
+        serde_json::to_value(config).unwrap()
+    }
+
+    /// Asserts every leaf of `defaults` has a different value in `actual`,
+    /// so a field missing from `FULL_CONFIG` fails the round-trip test.
+    fn assert_no_default_leaves(
+        defaults: &serde_json::Value,
+        actual: &serde_json::Value,
+        path: &str,
+    ) {
+        match defaults {
+            serde_json::Value::Object(fields) if !fields.is_empty() => {
+                for (key, default) in fields {
+                    let field = actual.get(key).unwrap_or(&serde_json::Value::Null);
+                    assert_no_default_leaves(default, field, &format!("{}.{}", path, key));
+                }
+            }
+            _ => assert_ne!(defaults, actual, "FULL_CONFIG leaves {} at its default", path),
+        }
+    }
+
+    #[test]
+    fn test_from_toml_str_round_trip() {
+        let config = EcologConfig::from_toml_str_strict(FULL_CONFIG).unwrap();
+        assert_no_default_leaves(&as_json(&EcologConfig::default()), &as_json(&config), "");
+        assert!(!config.features.hover);
+        assert_eq!(config.features.symbol_kind, EnvSymbolKind::Variable);
+        assert_eq!(config.workspace.max_index_depth, Some(4));
+        assert_eq!(config.cache.ttl, std::time::Duration::from_secs(60));
+        assert_eq!(config.rename.concurrency, RenameConcurrency::Reject);
+        assert!(config.rename.across_packages);
+        assert!(!config.masking.applies_to(MaskTarget::Hover));
+        assert!(!config.masking.applies_to(MaskTarget::Completion));
+        assert!(config.masking.applies_to(MaskTarget::InlayHint));
+        assert_eq!(config.masking.mode, MaskMode::Partial);
+        assert!(config.diagnostics.file_sources_only);
+        assert_eq!(config.detectors[0].calls, vec!["getEnv"]);
+        assert_eq!(config.profiles["prod"].files, vec![".env.production"]);
+        assert!(config.providers.providers["doppler"].enabled);
+        assert_eq!(
+            config.init_settings_precedence,
+            InitSettingsPrecedence::InitSettings
+        );
+        assert_eq!(config.definition_target, DefinitionTarget::Declaration);
+        assert!(config.workspace.scan_all_config_files);
+        assert!(!config.resolution.type_check);
+        assert!(!config.sources.defaults.shell);
+        assert!(config.references.include_binding_usages);
+        assert_eq!(config.custom_env_accessors[0].arg_index, 1);
+        assert_eq!(config.env_var_allowlist, vec!["APP_*"]);
+        assert_eq!(config.env_var_denylist, vec!["PATH"]);
+        assert_eq!(config.extension_overrides["mycustom"], "javascript");
+        assert_eq!(config.logging.heartbeat_interval(), None);
+
+        let serialized = config.to_toml_string();
+        let reparsed = EcologConfig::from_toml_str_strict(&serialized).unwrap();
+        assert_eq!(as_json(&reparsed), as_json(&config));
+        assert_eq!(reparsed.to_toml_string(), serialized);
+    }
+
+    #[test]
+    fn test_default_config_round_trip() {
+        let config = EcologConfig::default();
+        let reparsed = EcologConfig::from_toml_str_strict(&config.to_toml_string()).unwrap();
+        assert_eq!(as_json(&reparsed), as_json(&config));
+    }
+
+    #[test]
+    fn test_from_toml_str_unknown_keys() {
+        let content =
+            "[features]\nhovr = false\n\n[[detectors]]\nfile = [\"x.js\"]\n\n[typo]\nx = 1\n";
+
+        let config = EcologConfig::from_toml_str(content).unwrap();
+        assert!(config.features.hover);
+
+        let err = EcologConfig::from_toml_str_strict(content).unwrap_err();
+        assert!(err.contains("features.hovr"), "{}", err);
+        assert!(err.contains("detectors[0].file"), "{}", err);
+        assert!(err.contains("typo"), "{}", err);
+    }
+
+    #[test]
+    fn test_from_toml_str_invalid() {
+        let err = EcologConfig::from_toml_str("invalid toml content {{{").unwrap_err();
+        assert!(err.contains("Failed to parse config"));
+
+        let err =
+            EcologConfig::from_toml_str("[rename]\nconcurrency = \"sometimes\"\n").unwrap_err();
+        assert!(err.contains("Failed to deserialize config"));
+    }
 }
@@ -3,6 +3,23 @@ use crate::server::state::ServerState;
 use compact_str::CompactString;
 use tower_lsp::lsp_types::{Position, Range, Url};
 
+/// Pluggable env source consulted after abundantis finds nothing.
+///
+/// Lets embedders inject sources abundantis doesn't know about, e.g. a
+/// provider that decrypts a `.env.vault` at runtime.
+pub trait EnvSourceProvider: Send + Sync {
+    /// Label shown as the value's source in hover and completion.
+    fn name(&self) -> &str;
+
+    /// Resolves a single variable.
+    fn resolve(&self, key: &str) -> Option<String>;
+
+    /// Keys offered in completion. Defaults to none.
+    fn keys(&self) -> Vec<CompactString> {
+        Vec::new()
+    }
+}
+
 #[derive(Debug, Clone)]
 pub enum EnvVarSource {
     DirectReference,
@@ -0,0 +1,272 @@
+use crate::analysis::indexer::read_env_file;
+use crate::analysis::line_index::LineIndex;
+use crate::analysis::pipeline::ts_to_lsp_range;
+use crate::server::handlers::util::{dotenv_value, KorniEntryExt};
+use crate::server::state::ServerState;
+use crate::server::util::{get_workspace_root, uri_to_path};
+use std::collections::{HashMap, HashSet};
+use tower_lsp::lsp_types::{
+    CodeAction, CodeActionKind, CodeActionOrCommand, CodeActionParams, CodeActionResponse,
+    CreateFile, CreateFileOptions, DocumentChangeOperation, DocumentChanges, OneOf,
+    OptionalVersionedTextDocumentIdentifier, Position, Range, ResourceOp, TextDocumentEdit,
+    TextEdit, Url, WorkspaceEdit,
+};
+use tree_sitter::{Node, Point};
+
+/// Name used when nothing around the literal suggests one.
+const FALLBACK_NAME: &str = "NEW_ENV_VAR";
+
+/// Offers "Extract to environment variable" on a string literal that looks
+/// like config, such as a URL or an API key.
+///
+/// The action replaces the literal with the language's env accessor and
+/// appends the value to the file's first active env file, in one edit.
+pub async fn handle_code_action(
+    params: CodeActionParams,
+    state: &ServerState,
+) -> Option<CodeActionResponse> {
+    let uri = &params.text_document.uri;
+    let position = params.range.start;
+
+    if state
+        .document_manager
+        .get_env_reference_cloned(uri, position)
+        .is_some()
+    {
+        return None;
+    }
+
+    let language = state.languages.get_for_uri(uri)?;
+    let env_object = language.default_env_object_name()?;
+    let (content, tree) = {
+        let doc = state.document_manager.get(uri)?;
+        (doc.content.clone(), doc.tree.clone()?)
+    };
+
+    let point = Point::new(position.line as usize, position.character as usize);
+    let node = tree.root_node().descendant_for_point_range(point, point)?;
+    let literal = string_literal_at(node, content.as_bytes())?;
+    let value = literal_value(literal, content.as_bytes())?;
+    if !looks_like_config(value) {
+        return None;
+    }
+
+    let name = suggested_name(literal, content.as_bytes());
+    let (name, env_edit) = append_to_env_file(state, uri, &name, value).await?;
+    let code_edit = TextEdit {
+        range: ts_to_lsp_range(literal.range()),
+        new_text: env_accessor(env_object, &name),
+    };
+
+    let edit = match env_edit {
+        EnvFileEdit::Existing(env_uri, edit) => WorkspaceEdit {
+            changes: Some(HashMap::from([
+                (uri.clone(), vec![code_edit]),
+                (env_uri, vec![edit]),
+            ])),
+            ..Default::default()
+        },
+        EnvFileEdit::Create(env_uri, edit) => {
+            let text_edit = |uri: Url, edit: TextEdit| {
+                DocumentChangeOperation::Edit(TextDocumentEdit {
+                    text_document: OptionalVersionedTextDocumentIdentifier { uri, version: None },
+                    edits: vec![OneOf::Left(edit)],
+                })
+            };
+            WorkspaceEdit {
+                document_changes: Some(DocumentChanges::Operations(vec![
+                    DocumentChangeOperation::Op(ResourceOp::Create(CreateFile {
+                        uri: env_uri.clone(),
+                        options: Some(CreateFileOptions {
+                            overwrite: Some(false),
+                            ignore_if_exists: Some(true),
+                        }),
+                        annotation_id: None,
+                    })),
+                    text_edit(env_uri, edit),
+                    text_edit(uri.clone(), code_edit),
+                ])),
+                ..Default::default()
+            }
+        }
+    };
+
+    Some(vec![CodeActionOrCommand::CodeAction(CodeAction {
+        title: format!("Extract to environment variable {}", name),
+        kind: Some(CodeActionKind::REFACTOR_EXTRACT),
+        edit: Some(edit),
+        ..Default::default()
+    })])
+}
+
+enum EnvFileEdit {
+    Existing(Url, TextEdit),
+    Create(Url, TextEdit),
+}
+
+/// Appends `NAME=value` to the first active env file of the document, or to
+/// a new `.env` at the workspace root when none is active. Returns the name
+/// actually used, suffixed when the env file already defines `name`.
+///
+/// An open env file is read from its buffer. Otherwise it's read like the
+/// indexer reads it, and no action is offered if that fails, since appending
+/// to content we couldn't read could clobber it.
+async fn append_to_env_file(
+    state: &ServerState,
+    uri: &Url,
+    name: &str,
+    value: &str,
+) -> Option<(String, EnvFileEdit)> {
+    let file_path = uri_to_path(uri);
+
+    if let Some(env_path) = state.core.active_env_files(&file_path).into_iter().next() {
+        let env_uri = Url::from_file_path(&env_path).ok()?;
+        let open_content = state.document_manager.get(&env_uri).map(|doc| doc.content.clone());
+        let content = match open_content {
+            Some(content) => content.to_string(),
+            None => {
+                let max_bytes = {
+                    let config = state.config.get_config();
+                    let config = config.read().await;
+                    config.workspace.max_env_file_bytes
+                };
+                read_env_file(&env_path, max_bytes).await?
+            }
+        };
+        let name = unused_name(name, &defined_keys(&content));
+        let end = LineIndex::new(&content).position(content.len());
+        let separator = if content.is_empty() || content.ends_with('\n') { "" } else { "\n" };
+        let edit = TextEdit {
+            range: Range::new(end, end),
+            new_text: format!("{}{}={}\n", separator, name, dotenv_value(value)),
+        };
+        return Some((name, EnvFileEdit::Existing(env_uri, edit)));
+    }
+
+    let root = get_workspace_root(&state.core.workspace).await;
+    let start = Position::new(0, 0);
+    let edit = TextEdit {
+        range: Range::new(start, start),
+        new_text: format!("{}={}\n", name, dotenv_value(value)),
+    };
+    Some((
+        name.to_string(),
+        EnvFileEdit::Create(Url::from_file_path(root.join(".env")).ok()?, edit),
+    ))
+}
+
+fn defined_keys(content: &str) -> HashSet<String> {
+    korni::parse_with_options(content, korni::ParseOptions::full())
+        .into_iter()
+        .filter_map(|entry| entry.into_valid_pair())
+        .map(|kv| kv.key.to_string())
+        .collect()
+}
+
+/// `name`, or `name_2`, `name_3`, ... if it's already defined.
+fn unused_name(name: &str, defined: &HashSet<String>) -> String {
+    if !defined.contains(name) {
+        return name.to_string();
+    }
+    (2..)
+        .map(|n| format!("{}_{}", name, n))
+        .find(|candidate| !defined.contains(candidate))
+        .expect("some suffix is unused")
+}
+
+/// The innermost quoted string node containing `node`.
+fn string_literal_at<'t>(node: Node<'t>, source: &[u8]) -> Option<Node<'t>> {
+    let mut current = Some(node);
+    while let Some(node) = current {
+        let text = node.utf8_text(source).unwrap_or_default();
+        let quoted = text.len() >= 2
+            && text
+                .chars()
+                .next()
+                .is_some_and(|q| matches!(q, '"' | '\'' | '`') && text.ends_with(q));
+        if node.kind().contains("string") && quoted {
+            return Some(node);
+        }
+        current = node.parent();
+    }
+    None
+}
+
+/// The literal's text between its quotes, unless it interpolates.
+fn literal_value<'a>(literal: Node, source: &'a [u8]) -> Option<&'a str> {
+    let text = literal.utf8_text(source).ok()?;
+    let inner = text.get(1..text.len().checked_sub(1)?)?;
+    (!inner.contains("${") && !inner.contains('\\')).then_some(inner)
+}
+
+/// URLs and long, space-free tokens mixing letters and digits, like keys.
+fn looks_like_config(value: &str) -> bool {
+    if value.contains("://") {
+        return true;
+    }
+    value.len() >= 16
+        && !value.contains(char::is_whitespace)
+        && value.chars().any(|c| c.is_ascii_alphabetic())
+        && value.chars().any(|c| c.is_ascii_digit())
+}
+
+/// Derives a name from the identifier the literal is assigned to, e.g.
+/// `const dbUrl = "..."` or `{ apiKey: "..." }`.
+fn suggested_name(literal: Node, source: &[u8]) -> String {
+    let mut current = literal.parent();
+    while let Some(node) = current {
+        for field in ["name", "key", "left"] {
+            let Some(target) = node.child_by_field_name(field) else {
+                continue;
+            };
+            if target.id() == literal.id() {
+                continue;
+            }
+            let text = target.utf8_text(source).unwrap_or_default();
+            let text = text.trim_matches(|c| matches!(c, '"' | '\'' | '`' | '$' | ':'));
+            if let Some(name) = screaming_snake_case(text) {
+                return name;
+            }
+        }
+        if node.kind().ends_with("statement") || node.kind().ends_with("declaration") {
+            break;
+        }
+        current = node.parent();
+    }
+    FALLBACK_NAME.to_string()
+}
+
+/// `dbUrl`, `db_url` and `db-url` all become `DB_URL`.
+fn screaming_snake_case(identifier: &str) -> Option<String> {
+    if identifier.is_empty()
+        || !identifier
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '-')
+    {
+        return None;
+    }
+    let mut name = String::new();
+    let mut previous_lower = false;
+    for c in identifier.chars() {
+        if c == '-' || c == '_' {
+            name.push('_');
+            previous_lower = false;
+            continue;
+        }
+        if c.is_ascii_uppercase() && previous_lower {
+            name.push('_');
+        }
+        previous_lower = c.is_ascii_lowercase() || c.is_ascii_digit();
+        name.push(c.to_ascii_uppercase());
+    }
+    name.starts_with(|c: char| c.is_ascii_alphabetic()).then_some(name)
+}
+
+/// How code reads `name` through the language's env object.
+fn env_accessor(env_object: &str, name: &str) -> String {
+    match env_object {
+        "process.env" => format!("{}.{}", env_object, name),
+        "os.getenv" => format!("{}(\"{}\")", env_object, name),
+        _ => format!("{}[\"{}\"]", env_object, name),
+    }
+}
@@ -0,0 +1,136 @@
+use crate::analysis::graph::EnvVarLocationKind;
+use crate::analysis::BindingResolver;
+use crate::server::config::MaskTarget;
+use crate::server::handlers::util::{mask_value, masking_for_file, resolve_env_var_value};
+use crate::server::util::is_env_var_tracked;
+use crate::server::state::ServerState;
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use std::time::Instant;
+use tower_lsp::lsp_types::{CodeLens, CodeLensParams, Command, Url};
+
+/// Command a resolved lens runs to show the unmasked value.
+const REVEAL_COMMAND: &str = "ecolog.variable.reveal";
+
+/// Longest value shown in a lens title before it is truncated.
+const MAX_TITLE_VALUE_CHARS: usize = 40;
+
+/// What `codeLens/resolve` needs to compute a lens's title.
+#[derive(Serialize, Deserialize)]
+struct LensData {
+    uri: Url,
+    name: String,
+}
+
+/// Returns one unresolved lens per direct env reference in the document.
+///
+/// Values are resolved lazily in [`handle_code_lens_resolve`], so lenses the
+/// client never shows cost nothing.
+pub async fn handle_code_lens(params: CodeLensParams, state: &ServerState) -> Option<Vec<CodeLens>> {
+    let uri = &params.text_document.uri;
+    let start = Instant::now();
+    tracing::debug!("[HANDLE_CODE_LENS_ENTER] uri={}", uri);
+
+    if !state.config.is_code_lens_enabled() {
+        tracing::debug!("[HANDLE_CODE_LENS_EXIT] feature disabled");
+        return None;
+    }
+
+    let graph = state.document_manager.get_binding_graph(uri)?;
+    let resolver = BindingResolver::new(&graph);
+
+    let mut lenses = Vec::new();
+    for var in resolver.all_env_vars() {
+        if !is_env_var_tracked(state, &var).await {
+            continue;
+        }
+        let Some(locations) = graph.get_env_var_locations(&var) else {
+            continue;
+        };
+        for loc in locations {
+            if loc.kind != EnvVarLocationKind::DirectReference {
+                continue;
+            }
+            lenses.push(CodeLens {
+                range: loc.range,
+                command: None,
+                data: serde_json::to_value(LensData {
+                    uri: uri.clone(),
+                    name: var.to_string(),
+                })
+                .ok(),
+            });
+        }
+    }
+    lenses.sort_by_key(|lens| (lens.range.start.line, lens.range.start.character));
+
+    tracing::debug!(
+        "[HANDLE_CODE_LENS_EXIT] count={} elapsed_ms={}",
+        lenses.len(),
+        start.elapsed().as_millis()
+    );
+    Some(lenses)
+}
+
+/// Fills in a lens's title with the variable's masked value and source.
+///
+/// Lenses without data are returned unchanged.
+pub async fn handle_code_lens_resolve(mut lens: CodeLens, state: &ServerState) -> CodeLens {
+    let Some(data) = lens
+        .data
+        .take()
+        .and_then(|data| serde_json::from_value::<LensData>(data).ok())
+    else {
+        return lens;
+    };
+    tracing::debug!("[HANDLE_CODE_LENS_RESOLVE] name={}", data.name);
+
+    let Ok(file_path) = data.uri.to_file_path() else {
+        return lens;
+    };
+    let title = match resolve_env_var_value(&data.name, &file_path, state).await {
+        Some(resolved) => {
+            let masking = masking_for_file(state, &file_path).await;
+            let value = mask_value(&data.name, &resolved.value, &masking, MaskTarget::CodeLens);
+            format!("= {} ({})", format_value(&value), resolved.source)
+        }
+        None => format!("{} is not defined", data.name),
+    };
+    lens.command = Some(Command {
+        title,
+        command: REVEAL_COMMAND.to_string(),
+        arguments: Some(vec![json!(data.name), json!(data.uri)]),
+    });
+    lens
+}
+
+/// Shows the first line of a value, truncated to fit a lens title.
+fn format_value(value: &str) -> String {
+    if value.is_empty() {
+        return "(empty)".to_string();
+    }
+    let first_line = value.lines().next().unwrap_or("");
+    if first_line.len() < value.len() || first_line.chars().count() > MAX_TITLE_VALUE_CHARS {
+        let truncated: String = first_line.chars().take(MAX_TITLE_VALUE_CHARS).collect();
+        format!("{}...", truncated)
+    } else {
+        first_line.to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_format_value() {
+        assert_eq!(format_value(""), "(empty)");
+        assert_eq!(format_value("postgres://localhost"), "postgres://localhost");
+        assert_eq!(format_value("line one\nline two"), "line one...");
+        let long = "é".repeat(MAX_TITLE_VALUE_CHARS + 5);
+        assert_eq!(
+            format_value(&long),
+            format!("{}...", "é".repeat(MAX_TITLE_VALUE_CHARS))
+        );
+    }
+}
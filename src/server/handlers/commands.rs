@@ -1,9 +1,15 @@
-use crate::server::handlers::util::{format_source, resolve_env_var_value};
+use crate::server::config::{MaskTarget, ProfileConfig};
+use crate::server::handlers::util::{
+    dotenv_value, format_source, format_uri_source, is_valid_env_var_name, mask_value,
+    masking_for_file, resolve_env_var_value, KorniEntryExt,
+};
 use crate::server::state::ServerState;
 use abundantis::source::AsyncEnvSource;
+use serde::Deserialize;
 use serde_json::json;
+use std::collections::{BTreeMap, HashMap, HashSet};
 use std::time::Instant;
-use tower_lsp::lsp_types::ExecuteCommandParams;
+use tower_lsp::lsp_types::{ExecuteCommandParams, Url};
 
 pub async fn handle_execute_command(
     params: ExecuteCommandParams,
@@ -48,10 +54,10 @@ async fn handle_execute_command_inner(
                 .collect();
 
             if patterns.is_empty() {
-                state.core.clear_active_files();
+                state.env.clear_active_files();
                 Some(json!({ "success": true, "message": "Cleared active file filter" }))
             } else {
-                state.core.set_active_files(&patterns);
+                state.env.set_active_files(&patterns);
                 Some(json!({ "success": true, "patterns": patterns }))
             }
         }
@@ -65,14 +71,21 @@ async fn handle_execute_command_inner(
             let root = crate::server::util::get_workspace_root(&state.core.workspace).await;
 
             let resolve_path = file_path.as_ref().unwrap_or(&root);
-            let vars = crate::server::util::safe_all_for_file(&state.core, resolve_path).await;
+            let vars = crate::server::util::safe_all_for_file(
+                &state.core,
+                resolve_path,
+                state.config.resolution_timeout(),
+            )
+            .await;
+            let masking = state.config.get_config().read().await.masking.clone();
 
             let var_list: Vec<serde_json::Value> = vars
                 .iter()
                 .map(|v| {
+                    let value = mask_value(&v.key, &v.resolved_value, &masking, MaskTarget::Command);
                     json!({
                         "name": v.key,
-                        "value": v.resolved_value,
+                        "value": value,
                         "source": format_source(&v.source, &root)
                     })
                 })
@@ -81,43 +94,12 @@ async fn handle_execute_command_inner(
             Some(json!({ "variables": var_list, "count": var_list.len() }))
         }
         "ecolog.generateEnvExample" => {
-            let root = crate::server::util::get_workspace_root(&state.core.workspace).await;
-
-            // Collect from both sources
-            let mut env_vars: std::collections::HashSet<String> = std::collections::HashSet::new();
-
-            // Source 1: Variables defined in .env files
-            let defined_vars = crate::server::util::safe_all_for_file(&state.core, &root).await;
-            for var in defined_vars {
-                env_vars.insert(var.key.to_string());
-            }
-
-            // Source 2: Variables referenced in code files
-            for var in state.workspace_index.all_env_vars() {
-                env_vars.insert(var.to_string());
-            }
-
-            // Sort for consistent output
-            let mut env_vars: Vec<String> = env_vars.into_iter().collect();
-            env_vars.sort();
-
-            if env_vars.is_empty() {
-                return Some(json!({
-                    "content": "# No environment variables found in workspace\n",
-                    "count": 0
-                }));
-            }
-
-            let content = env_vars
-                .iter()
-                .map(|var| format!("{}=", var))
-                .collect::<Vec<_>>()
-                .join("\n");
-
-            Some(json!({
-                "content": format!("{}\n", content),
-                "count": env_vars.len()
-            }))
+            let options = params
+                .arguments
+                .first()
+                .and_then(|arg| serde_json::from_value(arg.clone()).ok())
+                .unwrap_or_default();
+            handle_generate_env_example(state, options).await
         }
         "ecolog.file.list" => {
             {
@@ -182,21 +164,51 @@ async fn handle_execute_command_inner(
                 state.core.active_env_files(&root)
             };
 
-            let env_files: Vec<String> = env_file_paths
-                .iter()
-                .filter_map(|path| {
-                    if let Ok(relative) = path.strip_prefix(&root) {
-                        Some(relative.to_string_lossy().to_string())
-                    } else {
-                        path.file_name()
-                            .and_then(|n| n.to_str())
-                            .map(|s| s.to_string())
-                    }
-                })
-                .collect();
+            let env_files = relative_env_files(&env_file_paths, &root);
 
             Some(json!({ "files": env_files, "count": env_files.len() }))
         }
+        "ecolog.file.setProfile" => {
+            let Some(name) = params.arguments.first().and_then(|arg| arg.as_str()) else {
+                return Some(json!({ "error": "Profile name required" }));
+            };
+
+            let patterns = {
+                let config = state.config.get_config();
+                let config = config.read().await;
+                if !config
+                    .resolution
+                    .precedence
+                    .contains(&abundantis::config::SourcePrecedence::File)
+                {
+                    return Some(json!({ "error": "File source is not enabled in configuration" }));
+                }
+                profile_patterns(&json!(name), &config.profiles)
+            };
+            let patterns = match patterns {
+                Ok(patterns) => patterns,
+                Err(error) => return Some(json!({ "error": error })),
+            };
+
+            // A single replace, so resolution never sees an empty filter
+            state.env.set_active_files(&patterns);
+
+            let root = crate::server::util::get_workspace_root(&state.core.workspace).await;
+            let env_files = relative_env_files(&state.core.active_env_files(&root), &root);
+            Some(json!({ "success": true, "profile": name, "files": env_files }))
+        }
+        "ecolog.profile.diff" => handle_profile_diff(state, &params.arguments).await,
+        "ecolog.export" => handle_export(state, &params.arguments).await,
+        "ecolog.file.resolutionOrder" => {
+            let file = params.arguments.first().and_then(|arg| arg.as_str());
+            let name = params.arguments.get(1).and_then(|arg| arg.as_str());
+            handle_resolution_order(state, file, name).await
+        }
+        "ecolog.file.envVarLocations" => {
+            let file = params.arguments.first().and_then(|arg| arg.as_str());
+            let name = params.arguments.get(1).and_then(|arg| arg.as_str());
+            handle_env_var_locations(state, file, name)
+        }
         "ecolog.variable.get" => {
             let var_name = params
                 .arguments
@@ -211,9 +223,10 @@ async fn handle_execute_command_inner(
             let root = crate::server::util::get_workspace_root(&state.core.workspace).await;
 
             if let Some(resolved) = resolve_env_var_value(&name, &root, state).await {
+                let masking = state.config.get_config().read().await.masking.clone();
                 Some(json!({
                     "name": name,
-                    "value": resolved.value,
+                    "value": mask_value(&name, &resolved.value, &masking, MaskTarget::Command),
                     "source": resolved.source,
                     "description": resolved.description
                 }))
@@ -221,6 +234,59 @@ async fn handle_execute_command_inner(
                 Some(json!({ "error": format!("Variable '{}' not found", name) }))
             }
         }
+        "ecolog.variable.getForFile" => {
+            // Resolves as if from the given file, so package and active-file
+            // scoping apply without opening it.
+            let (Some(name), Some(file)) = (
+                params.arguments.first().and_then(|arg| arg.as_str()),
+                params.arguments.get(1).and_then(|arg| arg.as_str()),
+            ) else {
+                return Some(json!({ "error": "Variable name and file path required" }));
+            };
+            let root = crate::server::util::get_workspace_root(&state.core.workspace).await;
+            let file_path = root.join(file);
+
+            if let Some(resolved) = resolve_env_var_value(name, &file_path, state).await {
+                let masking = masking_for_file(state, &file_path).await;
+                Some(json!({
+                    "name": name,
+                    "file": file,
+                    "value": mask_value(name, &resolved.value, &masking, MaskTarget::Command),
+                    "source": resolved.source,
+                    "description": resolved.description
+                }))
+            } else {
+                Some(json!({
+                    "error": format!("Variable '{}' not found for {}", name, file)
+                }))
+            }
+        }
+        "ecolog.variable.reveal" => {
+            // Explicitly requested by the user (e.g. from a code lens), so
+            // the value is returned unmasked.
+            let Some(name) = params.arguments.first().and_then(|arg| arg.as_str()) else {
+                return Some(json!({ "error": "Variable name required" }));
+            };
+            let file_path = match params
+                .arguments
+                .get(1)
+                .and_then(|arg| arg.as_str())
+                .and_then(|uri| Url::parse(uri).ok())
+                .and_then(|uri| uri.to_file_path().ok())
+            {
+                Some(path) => path,
+                None => crate::server::util::get_workspace_root(&state.core.workspace).await,
+            };
+
+            match resolve_env_var_value(name, &file_path, state).await {
+                Some(resolved) => Some(json!({
+                    "name": name,
+                    "value": resolved.value,
+                    "source": resolved.source
+                })),
+                None => Some(json!({ "error": format!("Variable '{}' not found", name) })),
+            }
+        }
         "ecolog.workspace.list" => {
             let workspace = std::sync::Arc::clone(&state.core.workspace);
             let workspace_info = tokio::task::spawn_blocking(move || {
@@ -248,7 +314,8 @@ async fn handle_execute_command_inner(
             let root = crate::server::util::get_workspace_root(&state.core.workspace).await;
 
             // Get all resolved variables to count by source type
-            let all_vars = crate::server::util::safe_all_for_file(&state.core, &root).await;
+            let all_vars =
+                crate::server::util::safe_all_for_file(&state.core, &root, state.config.resolution_timeout()).await;
 
             // Count variables by source type
             let mut shell_count = 0usize;
@@ -345,6 +412,7 @@ async fn handle_execute_command_inner(
 
             crate::server::util::safe_refresh(
                 &state.core,
+                &state.config,
                 abundantis::RefreshOptions::preserve_all(),
             )
             .await;
@@ -419,6 +487,7 @@ async fn handle_execute_command_inner(
 
             crate::server::util::safe_refresh(
                 &state.core,
+                &state.config,
                 abundantis::RefreshOptions::preserve_all(),
             )
             .await;
@@ -573,10 +642,695 @@ async fn handle_execute_command_inner(
                 .and_then(|arg| arg.as_str());
             handle_provider_shutdown(state, provider).await
         }
+        // Diagnostics commands
+        "ecolog.debug.stats" => handle_debug_stats(state),
+        "ecolog.ping" => handle_ping(state),
+        "ecolog.workspace.findUnused" => handle_find_unused(state),
+        "ecolog.workspace.findUndefined" => handle_find_undefined(state).await,
         _ => None,
     }
 }
 
+/// Options for `ecolog.generateEnvExample`.
+#[derive(Debug, Default, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+struct EnvExampleOptions {
+    /// Append a masked example of the current value, `KEY= # e.g. ***`.
+    with_values: bool,
+    /// Group keys under a comment header naming their defining file.
+    grouped: bool,
+}
+
+/// One `.env.example` line plus the comment lines above it.
+struct ExampleEntry {
+    key: String,
+    description: Option<String>,
+    example: Option<String>,
+}
+
+/// Header for keys that are referenced in code but not defined in a file.
+const CODE_ONLY_GROUP: &str = "Referenced in code";
+
+/// Builds a `.env.example` from the keys defined in env files and the keys
+/// referenced in code.
+///
+/// Shell variables are left out unless code references them: the shell
+/// environment is per machine (`PATH`, `HOME`, ...), not part of the project.
+/// A referenced shell-only key is listed under "Referenced in code" without
+/// an example value.
+async fn handle_generate_env_example(
+    state: &ServerState,
+    options: EnvExampleOptions,
+) -> Option<serde_json::Value> {
+    use abundantis::source::VariableSource;
+
+    let root = crate::server::util::get_workspace_root(&state.core.workspace).await;
+    // Example values always end up in a committed file, so they're masked
+    // even when masking is off for the editor.
+    let masking = {
+        let config = state.config.get_config();
+        let config = config.read().await;
+        crate::server::config::MaskingConfig {
+            enabled: true,
+            ..config.masking.clone()
+        }
+    };
+
+    let mut groups: BTreeMap<String, Vec<ExampleEntry>> = BTreeMap::new();
+    let mut env_files: HashMap<std::path::PathBuf, HashMap<String, EnvFileEntry>> =
+        HashMap::new();
+    let example_for = |key: &str, value: &str| {
+        (options.with_values && !value.is_empty())
+            .then(|| mask_value(key, value, &masking, MaskTarget::Command).replace('\n', "\\n"))
+    };
+
+    // Variables resolved from env files
+    let mut defined_vars =
+        crate::server::util::safe_all_for_file(&state.core, &root, state.config.resolution_timeout()).await;
+    defined_vars.retain(|var| matches!(var.source, VariableSource::File { .. }));
+    for var in &defined_vars {
+        let mut description = var
+            .description
+            .as_ref()
+            .filter(|d| !d.is_empty())
+            .map(|d| d.to_string());
+        if let (None, VariableSource::File { path, .. }) = (&description, &var.source) {
+            description = load_env_file_entries(&mut env_files, path)
+                .await
+                .get(var.key.as_str())
+                .and_then(|entry| entry.description.clone());
+        }
+        groups
+            .entry(format_source(&var.source, &root))
+            .or_default()
+            .push(ExampleEntry {
+                key: var.key.to_string(),
+                description,
+                example: example_for(&var.key, &var.resolved_value),
+            });
+    }
+
+    // Indexed variables: defined in env files the resolver didn't pick, or
+    // only referenced in code
+    let defined: HashSet<&str> = defined_vars.iter().map(|v| v.key.as_str()).collect();
+    for var in state.workspace_index.all_env_vars() {
+        if defined.contains(var.as_str()) {
+            continue;
+        }
+        let mut env_file_uris: Vec<_> = state
+            .workspace_index
+            .files_for_env_var(&var)
+            .into_iter()
+            .filter(|uri| state.workspace_index.is_env_file(uri))
+            .collect();
+        env_file_uris.sort();
+
+        let defining = match env_file_uris.first().and_then(|uri| uri.to_file_path().ok()) {
+            Some(path) => load_env_file_entries(&mut env_files, &path)
+                .await
+                .get(var.as_str())
+                .cloned()
+                .map(|entry| (format_uri_source(&env_file_uris[0], &root), entry)),
+            None => None,
+        };
+        let (group, entry) = match defining {
+            Some((group, entry)) => (
+                group,
+                ExampleEntry {
+                    key: var.to_string(),
+                    example: example_for(&var, &entry.value),
+                    description: entry.description,
+                },
+            ),
+            None => (
+                CODE_ONLY_GROUP.to_string(),
+                ExampleEntry {
+                    key: var.to_string(),
+                    description: None,
+                    example: None,
+                },
+            ),
+        };
+        groups.entry(group).or_default().push(entry);
+    }
+
+    let count: usize = groups.values().map(Vec::len).sum();
+    if count == 0 {
+        return Some(json!({
+            "content": "# No environment variables found in workspace\n",
+            "count": 0
+        }));
+    }
+
+    // Keys only seen in code go last
+    let code_only = groups.remove(CODE_ONLY_GROUP);
+    let mut ordered: Vec<(String, Vec<ExampleEntry>)> = groups.into_iter().collect();
+    ordered.extend(code_only.map(|entries| (CODE_ONLY_GROUP.to_string(), entries)));
+    if !options.grouped {
+        let all = ordered.into_iter().flat_map(|(_, entries)| entries).collect();
+        ordered = vec![(String::new(), all)];
+    }
+
+    let mut sections = Vec::new();
+    for (header, mut entries) in ordered {
+        entries.sort_by(|a, b| a.key.cmp(&b.key));
+        entries.dedup_by(|a, b| a.key == b.key);
+
+        let mut lines = Vec::new();
+        if options.grouped {
+            lines.push(format!("# {}", header));
+        }
+        for entry in entries {
+            if let Some(description) = &entry.description {
+                lines.extend(description.lines().map(|line| format!("# {}", line)));
+            }
+            match &entry.example {
+                Some(example) => lines.push(format!("{}= # e.g. {}", entry.key, example)),
+                None => lines.push(format!("{}=", entry.key)),
+            }
+        }
+        sections.push(lines.join("\n"));
+    }
+
+    Some(json!({
+        "content": format!("{}\n", sections.join("\n\n")),
+        "count": count
+    }))
+}
+
+/// A key's value and description as written in an env file.
+#[derive(Clone)]
+struct EnvFileEntry {
+    value: String,
+    description: Option<String>,
+}
+
+/// Resolves a command argument to active file patterns: a profile name from
+/// `[profiles]`, or an explicit array of patterns.
+fn profile_patterns(
+    argument: &serde_json::Value,
+    profiles: &HashMap<String, ProfileConfig>,
+) -> Result<Vec<String>, String> {
+    match argument {
+        serde_json::Value::String(name) => profiles
+            .get(name)
+            .map(|profile| profile.files.clone())
+            .ok_or_else(|| format!("Unknown profile: {}", name)),
+        serde_json::Value::Array(patterns) => Ok(patterns
+            .iter()
+            .filter_map(|p| p.as_str().map(|s| s.to_string()))
+            .collect()),
+        _ => Err("Expected a profile name or an array of patterns".to_string()),
+    }
+}
+
+/// Compares the file-sourced variables of two profiles: keys only in the
+/// first, keys only in the second, and keys whose values differ.
+///
+/// Each side is resolved against its own files; the active files filter
+/// isn't switched, so concurrent requests never see a profile's files.
+async fn handle_profile_diff(
+    state: &ServerState,
+    arguments: &[serde_json::Value],
+) -> Option<serde_json::Value> {
+    let (Some(first), Some(second)) = (arguments.first(), arguments.get(1)) else {
+        return Some(json!({ "error": "Two profiles required" }));
+    };
+
+    let (patterns, masking) = {
+        let config = state.config.get_config();
+        let config = config.read().await;
+        let patterns = profile_patterns(first, &config.profiles)
+            .and_then(|a| Ok((a, profile_patterns(second, &config.profiles)?)));
+        (patterns, config.masking.clone())
+    };
+    let (patterns_a, patterns_b) = match patterns {
+        Ok(patterns) => patterns,
+        Err(error) => return Some(json!({ "error": error })),
+    };
+
+    let root = crate::server::util::get_workspace_root(&state.core.workspace).await;
+    let values_a = file_values(state, &root, &patterns_a).await;
+    let values_b = file_values(state, &root, &patterns_b).await;
+
+    let mask = |name: &str, value: &str| mask_value(name, value, &masking, MaskTarget::Command);
+    let only = |this: &BTreeMap<String, String>, other: &BTreeMap<String, String>| {
+        this.iter()
+            .filter(|(name, _)| !other.contains_key(*name))
+            .map(|(name, value)| json!({ "name": name, "value": mask(name, value) }))
+            .collect::<Vec<_>>()
+    };
+    let differs: Vec<_> = values_a
+        .iter()
+        .filter_map(|(name, a)| {
+            let b = values_b.get(name).filter(|b| *b != a)?;
+            Some(json!({ "name": name, "a": mask(name, a), "b": mask(name, b) }))
+        })
+        .collect();
+
+    Some(json!({
+        "onlyInA": only(&values_a, &values_b),
+        "onlyInB": only(&values_b, &values_a),
+        "differs": differs,
+    }))
+}
+
+/// A serialization for `ecolog.export`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum ExportFormat {
+    Dotenv,
+    Json,
+    Shell,
+}
+
+impl ExportFormat {
+    fn parse(name: &str) -> Option<Self> {
+        match name {
+            "dotenv" => Some(ExportFormat::Dotenv),
+            "json" => Some(ExportFormat::Json),
+            "shell" => Some(ExportFormat::Shell),
+            _ => None,
+        }
+    }
+}
+
+/// Serializes every variable resolved for a file as `[format, file_uri?,
+/// masked?]` asks. The file defaults to the workspace root, and values are
+/// masked per the masking config unless `masked` is false.
+async fn handle_export(
+    state: &ServerState,
+    arguments: &[serde_json::Value],
+) -> Option<serde_json::Value> {
+    let Some(format) = arguments
+        .first()
+        .and_then(|arg| arg.as_str())
+        .and_then(ExportFormat::parse)
+    else {
+        return Some(json!({ "error": "Format must be dotenv, json or shell" }));
+    };
+    let file_path = match arguments
+        .get(1)
+        .and_then(|arg| arg.as_str())
+        .and_then(|uri| Url::parse(uri).ok())
+        .and_then(|uri| uri.to_file_path().ok())
+    {
+        Some(path) => path,
+        None => crate::server::util::get_workspace_root(&state.core.workspace).await,
+    };
+    let masked = arguments.get(2).and_then(|arg| arg.as_bool()).unwrap_or(true);
+
+    let masking = state.config.get_config().read().await.masking.clone();
+    let vars: BTreeMap<String, String> = crate::server::util::safe_all_for_file(
+        &state.core,
+        &file_path,
+        state.config.resolution_timeout(),
+    )
+    .await
+    .iter()
+    .map(|var| {
+        let value = if masked {
+            mask_value(&var.key, &var.resolved_value, &masking, MaskTarget::Command)
+        } else {
+            var.resolved_value.to_string()
+        };
+        (var.key.to_string(), value)
+    })
+    .collect();
+
+    Some(json!({
+        "content": export_vars(&vars, format),
+        "count": vars.len(),
+    }))
+}
+
+fn export_vars(vars: &BTreeMap<String, String>, format: ExportFormat) -> String {
+    match format {
+        ExportFormat::Json => serde_json::to_string_pretty(vars).unwrap_or_default(),
+        ExportFormat::Dotenv => vars
+            .iter()
+            .map(|(key, value)| format!("{}={}\n", key, dotenv_value(value)))
+            .collect(),
+        ExportFormat::Shell => vars
+            .iter()
+            .map(|(key, value)| format!("export {}={}\n", key, shell_quote(value)))
+            .collect(),
+    }
+}
+
+/// Single quotes a value for POSIX shells, where nothing inside single
+/// quotes is special; each `'` becomes `'\''`.
+fn shell_quote(value: &str) -> String {
+    format!("'{}'", value.replace('\'', "'\\''"))
+}
+
+/// Values resolved from the env files matching `patterns` for the workspace
+/// root.
+async fn file_values(
+    state: &ServerState,
+    root: &std::path::Path,
+    patterns: &[String],
+) -> BTreeMap<String, String> {
+    let timeout = state.config.resolution_timeout();
+    crate::server::util::safe_all_for_patterns(&state.core, root, patterns, timeout)
+        .await
+        .iter()
+        .filter(|var| matches!(var.source, abundantis::source::VariableSource::File { .. }))
+        .map(|var| (var.key.to_string(), var.resolved_value.to_string()))
+        .collect()
+}
+
+/// Formats env file paths relative to the workspace root, falling back to
+/// the file name for files outside it.
+fn relative_env_files(paths: &[std::path::PathBuf], root: &std::path::Path) -> Vec<String> {
+    paths
+        .iter()
+        .filter_map(|path| {
+            if let Ok(relative) = path.strip_prefix(root) {
+                Some(relative.to_string_lossy().to_string())
+            } else {
+                path.file_name()
+                    .and_then(|n| n.to_str())
+                    .map(|s| s.to_string())
+            }
+        })
+        .collect()
+}
+
+/// Parses an env file once per command run; unreadable files have no entries.
+async fn load_env_file_entries<'a>(
+    cache: &'a mut HashMap<std::path::PathBuf, HashMap<String, EnvFileEntry>>,
+    path: &std::path::Path,
+) -> &'a HashMap<String, EnvFileEntry> {
+    if !cache.contains_key(path) {
+        let entries = tokio::fs::read_to_string(path)
+            .await
+            .map(|content| env_file_entries(&content))
+            .unwrap_or_default();
+        cache.insert(path.to_path_buf(), entries);
+    }
+    &cache[path]
+}
+
+fn env_file_entries(content: &str) -> HashMap<String, EnvFileEntry> {
+    let mut descriptions = leading_comments(content);
+    korni::parse_with_options(content, korni::ParseOptions::full())
+        .into_iter()
+        .filter_map(|entry| entry.into_valid_pair())
+        .map(|kv| {
+            let key = kv.key.to_string();
+            let entry = EnvFileEntry {
+                value: kv.value.to_string(),
+                description: descriptions.remove(&key),
+            };
+            (key, entry)
+        })
+        .collect()
+}
+
+/// Maps each key in an env file to the comment block directly above it.
+///
+/// The block ends at a blank line; commented-out assignments such as
+/// `# OLD_KEY=value` aren't descriptions and are skipped.
+fn leading_comments(content: &str) -> HashMap<String, String> {
+    let mut comments = HashMap::new();
+    let mut block: Vec<&str> = Vec::new();
+    for line in content.lines() {
+        let trimmed = line.trim();
+        if let Some(comment) = trimmed.strip_prefix('#') {
+            let comment = comment.trim();
+            if !comment.is_empty() && !is_assignment(comment) {
+                block.push(comment);
+            }
+            continue;
+        }
+        if is_assignment(trimmed) && !block.is_empty() {
+            let key = trimmed
+                .trim_start_matches("export ")
+                .split('=')
+                .next()
+                .unwrap_or("")
+                .trim();
+            comments.insert(key.to_string(), block.join("\n"));
+        }
+        block.clear();
+    }
+    comments
+}
+
+fn is_assignment(line: &str) -> bool {
+    line.trim_start_matches("export ")
+        .split_once('=')
+        .is_some_and(|(key, _)| is_valid_env_var_name(key.trim()))
+}
+
+/// Returns an on-demand snapshot of the metrics the heartbeat logs.
+fn handle_debug_stats(state: &ServerState) -> Option<serde_json::Value> {
+    let index_stats = state.workspace_index.stats();
+    let module_cache = state.workspace_index.module_cache_stats();
+
+    let mut languages: std::collections::BTreeMap<String, usize> =
+        std::collections::BTreeMap::new();
+    for uri in state.workspace_index.indexed_code_files() {
+        if let Some(lang) = state.languages.get_for_uri(&uri) {
+            *languages.entry(lang.id().to_string()).or_default() += 1;
+        }
+    }
+
+    Some(json!({
+        "documentCount": state.document_manager.document_count(),
+        "index": {
+            "totalFiles": index_stats.total_files,
+            "totalEnvVars": index_stats.total_env_vars,
+            "envFiles": index_stats.env_files,
+            "failedFiles": index_stats.failed_files,
+        },
+        "moduleCache": {
+            "len": module_cache.len,
+            "capacity": module_cache.capacity,
+            "hitRate": module_cache.hit_rate(),
+        },
+        "pendingAnalysisTasks": state.pending_analysis_count(),
+        "uptimeSecs": state.started_at.elapsed().as_secs(),
+        "languages": languages,
+    }))
+}
+
+/// Cheap liveness probe for editor extensions. `ready` turns true once the
+/// initial workspace indexing has finished.
+fn handle_ping(state: &ServerState) -> Option<serde_json::Value> {
+    Some(json!({
+        "ok": true,
+        "version": env!("CARGO_PKG_VERSION"),
+        "ready": state
+            .initial_index_complete
+            .load(std::sync::atomic::Ordering::Acquire),
+    }))
+}
+
+/// Env keys defined in indexed env files that no other indexed file references.
+///
+/// Nothing is reported while some file reads the whole environment (e.g.
+/// `Deno.env.toObject()`), since any key may be consumed there.
+fn handle_find_unused(state: &ServerState) -> Option<serde_json::Value> {
+    let index = &state.workspace_index;
+    if index.has_dynamic_env_access() {
+        return Some(json!([]));
+    }
+    let mut names = index.all_env_vars();
+    names.sort();
+
+    let unused: Vec<_> = names
+        .into_iter()
+        .filter_map(|name| {
+            let (env_files, referencing): (Vec<_>, Vec<_>) = index
+                .files_for_env_var(&name)
+                .into_iter()
+                .partition(|uri| index.is_env_file(uri));
+            if env_files.is_empty() || !referencing.is_empty() {
+                return None;
+            }
+            let defined_in: Vec<String> = env_files.iter().map(|uri| uri.to_string()).collect();
+            Some(json!({ "name": name, "definedIn": defined_in }))
+        })
+        .collect();
+
+    Some(json!(unused))
+}
+
+/// Env vars referenced outside env files that no source resolves.
+async fn handle_find_undefined(state: &ServerState) -> Option<serde_json::Value> {
+    let index = &state.workspace_index;
+    let mut names = index.all_env_vars();
+    names.sort();
+
+    let mut undefined = Vec::new();
+    for name in names {
+        let (env_files, mut referencing): (Vec<_>, Vec<_>) = index
+            .files_for_env_var(&name)
+            .into_iter()
+            .partition(|uri| index.is_env_file(uri));
+        if !env_files.is_empty() || referencing.is_empty() {
+            continue;
+        }
+        referencing.sort();
+
+        let file_path = crate::server::util::uri_to_path(&referencing[0]);
+        if resolve_env_var_value(&name, &file_path, state).await.is_some() {
+            continue;
+        }
+
+        let files: Vec<String> = referencing.iter().map(|uri| uri.to_string()).collect();
+        undefined.push(json!({ "name": name, "files": files }));
+    }
+
+    Some(json!(undefined))
+}
+
+/// Mirrors abundantis' file ordering: files matching an earlier
+/// `resolution.files.order` entry are consulted first, unmatched files last.
+fn file_order_index(path: &std::path::Path, order: &[compact_str::CompactString]) -> usize {
+    let file_name = path.file_name().and_then(|n| n.to_str()).unwrap_or("");
+    let path_str = path.to_string_lossy();
+    order
+        .iter()
+        .position(|pattern| file_name == pattern.as_str() || path_str.ends_with(pattern.as_str()))
+        .map_or(order.len() + 1, |i| i + 1)
+}
+
+/// Lists where `name` is used in the open document `file`, with each
+/// location's kind and, for bindings, the local name.
+fn handle_env_var_locations(
+    state: &ServerState,
+    file: Option<&str>,
+    name: Option<&str>,
+) -> Option<serde_json::Value> {
+    use crate::analysis::graph::EnvVarLocationKind;
+
+    let (Some(file), Some(name)) = (file, name) else {
+        return Some(json!({ "error": "File URI and variable name required" }));
+    };
+    let Ok(uri) = Url::parse(file) else {
+        return Some(json!({ "error": format!("Invalid file URI: {}", file) }));
+    };
+    if state.document_manager.get(&uri).is_none() {
+        return Some(json!({ "error": format!("Document not open: {}", file) }));
+    }
+
+    let locations: Vec<_> = state
+        .document_manager
+        .env_var_locations(&uri, name)
+        .into_iter()
+        .map(|loc| {
+            let kind = match loc.kind {
+                EnvVarLocationKind::DirectReference => "directReference",
+                EnvVarLocationKind::BindingDeclaration => "bindingDeclaration",
+                EnvVarLocationKind::BindingUsage => "bindingUsage",
+                EnvVarLocationKind::PropertyAccess => "propertyAccess",
+            };
+            json!({
+                "range": loc.range,
+                "kind": kind,
+                "bindingName": loc.binding_name.as_deref(),
+            })
+        })
+        .collect();
+
+    Some(json!({ "name": name, "locations": locations }))
+}
+
+/// Lists the sources consulted when resolving variables for `file`, in the
+/// order abundantis applies them: later entries override earlier ones.
+///
+/// With a variable `name`, also reports which source currently supplies it.
+async fn handle_resolution_order(
+    state: &ServerState,
+    file: Option<&str>,
+    name: Option<&str>,
+) -> Option<serde_json::Value> {
+    use abundantis::config::SourcePrecedence;
+    use abundantis::source::VariableSource;
+
+    let Some(file) = file else {
+        return Some(json!({ "error": "File URI required" }));
+    };
+    let file_path = tower_lsp::lsp_types::Url::parse(file)
+        .ok()
+        .and_then(|uri| uri.to_file_path().ok())
+        .unwrap_or_else(|| std::path::PathBuf::from(file));
+
+    let root = crate::server::util::get_workspace_root(&state.core.workspace).await;
+    let (precedence, file_order) = {
+        let config = state.config.get_config();
+        let config = config.read().await;
+        (
+            config.resolution.precedence.clone(),
+            config.resolution.files.order.clone(),
+        )
+    };
+
+    let winner = match name {
+        Some(name) => crate::server::util::safe_get_for_file(
+            &state.core,
+            name,
+            &file_path,
+            state.config.resolution_timeout(),
+        )
+        .await
+            .map(|var| var.source.clone()),
+        None => None,
+    };
+
+    let relative = |path: &std::path::Path| {
+        path.strip_prefix(&root)
+            .unwrap_or(path)
+            .to_string_lossy()
+            .to_string()
+    };
+
+    // Non-file sources sort ahead of every file, so files override them
+    let mut sources = Vec::new();
+    for source in &precedence {
+        let entry = match source {
+            SourcePrecedence::Shell => json!({
+                "type": "Shell",
+                "wins": matches!(winner, Some(VariableSource::Shell)),
+            }),
+            SourcePrecedence::Remote => json!({
+                "type": "Remote",
+                "wins": matches!(winner, Some(VariableSource::Remote { .. })),
+            }),
+            SourcePrecedence::File => continue,
+        };
+        sources.push(entry);
+    }
+
+    if precedence.contains(&SourcePrecedence::File) {
+        let mut files = state.core.active_env_files(&file_path);
+        files.sort_by_key(|path| file_order_index(path, &file_order));
+        for path in files {
+            let wins = matches!(&winner, Some(VariableSource::File { path: p, .. }) if *p == path);
+            sources.push(json!({
+                "type": "File",
+                "path": relative(&path),
+                "wins": wins,
+            }));
+        }
+    }
+
+    let mut result = json!({
+        "file": relative(&file_path),
+        "sources": sources,
+    });
+    if let Some(name) = name {
+        result["variable"] = json!(name);
+        result["winner"] = match &winner {
+            Some(source) => json!(format_source(source, &root)),
+            None => serde_json::Value::Null,
+        };
+    }
+    Some(result)
+}
+
 // Remote source command handlers
 
 /// Gets an external provider adapter by provider ID.
@@ -616,7 +1370,7 @@ async fn handle_remote_list(state: &ServerState) -> Option<serde_json::Value> {
         sources.push(json!({
             "id": info.id.as_str(),
             "displayName": info.name.as_str(),
-            "shortName": info.short_name.as_ref().map(|s| s.as_str()).unwrap_or(info.id.as_str()),
+            "shortName": info.short_name.as_deref().unwrap_or(info.id.as_str()),
             "authStatus": format!("{:?}", auth_status),
             "isAuthenticated": auth_status.is_authenticated(),
             "scope": scope.selections,
@@ -658,11 +1412,11 @@ async fn handle_remote_auth_fields(
         json!({
             "name": f.name.as_str(),
             "label": f.label.as_str(),
-            "description": f.description.as_ref().map(|d| d.as_str()),
+            "description": f.description.as_deref(),
             "required": f.required,
             "secret": f.secret,
-            "envVar": f.env_var.as_ref().map(|e| e.as_str()),
-            "default": f.default.as_ref().map(|d| d.as_str()),
+            "envVar": f.env_var.as_deref(),
+            "default": f.default.as_deref(),
         })
     }).collect();
 
@@ -746,8 +1500,8 @@ async fn handle_remote_navigate(
                 json!({
                     "id": o.id.as_str(),
                     "displayName": o.display_name.as_str(),
-                    "description": o.description.as_ref().map(|d| d.as_str()),
-                    "icon": o.icon.as_ref().map(|i| i.as_str()),
+                    "description": o.description.as_deref(),
+                    "icon": o.icon.as_deref(),
                 })
             }).collect();
 
@@ -801,6 +1555,7 @@ async fn handle_remote_select(
             // Trigger refresh so other components see the new variables
             crate::server::util::safe_refresh(
                 &state.core,
+                &state.config,
                 abundantis::RefreshOptions::preserve_all(),
             )
             .await;
@@ -884,6 +1639,7 @@ async fn handle_remote_refresh(
         // Also trigger global refresh
         crate::server::util::safe_refresh(
             &state.core,
+            &state.config,
             abundantis::RefreshOptions::preserve_all(),
         )
         .await;
@@ -913,7 +1669,7 @@ async fn handle_provider_list(state: &ServerState) -> Option<serde_json::Value>
             "scope": info.scope.selections,
             "secretCount": info.secret_count,
             "loading": info.loading,
-            "lastError": info.last_error.as_ref().map(|e| e.as_str()),
+            "lastError": info.last_error.as_deref(),
         }));
     }
 
@@ -976,11 +1732,11 @@ async fn handle_provider_auth_fields(
                 json!({
                     "name": f.name.as_str(),
                     "label": f.label.as_str(),
-                    "description": f.description.as_ref().map(|d| d.as_str()),
+                    "description": f.description.as_deref(),
                     "required": f.required,
                     "secret": f.secret,
-                    "envVar": f.env_var.as_ref().map(|e| e.as_str()),
-                    "default": f.default.as_ref().map(|d| d.as_str()),
+                    "envVar": f.env_var.as_deref(),
+                    "default": f.default.as_deref(),
                 })
             }).collect();
 
@@ -1102,8 +1858,8 @@ async fn handle_provider_navigate(
                 json!({
                     "id": o.id.as_str(),
                     "displayName": o.display_name.as_str(),
-                    "description": o.description.as_ref().map(|d| d.as_str()),
-                    "icon": o.icon.as_ref().map(|i| i.as_str()),
+                    "description": o.description.as_deref(),
+                    "icon": o.icon.as_deref(),
                 })
             }).collect();
 
@@ -1158,6 +1914,7 @@ async fn handle_provider_select(
             // Trigger refresh so other components see the new variables
             crate::server::util::safe_refresh(
                 &state.core,
+                &state.config,
                 abundantis::RefreshOptions::preserve_all(),
             )
             .await;
@@ -1198,6 +1955,7 @@ async fn handle_provider_refresh(
 
                 crate::server::util::safe_refresh(
                     &state.core,
+                    &state.config,
                     abundantis::RefreshOptions::preserve_all(),
                 )
                 .await;
@@ -1247,6 +2005,7 @@ async fn handle_provider_refresh(
 
         crate::server::util::safe_refresh(
             &state.core,
+            &state.config,
             abundantis::RefreshOptions::preserve_all(),
         )
         .await;
@@ -1,10 +1,13 @@
 use crate::analysis::{CrossModuleResolution, CrossModuleResolver};
-use crate::server::handlers::util::format_source;
+use crate::server::config::{MaskTarget, MaskingConfig};
+use crate::server::handlers::util::{
+    format_source, format_uri_source, format_value_markdown, mask_value, masking_for_file,
+};
 use crate::server::state::ServerState;
 use std::time::Instant;
 use tower_lsp::lsp_types::{
-    CompletionItem, CompletionItemKind, CompletionParams, Documentation, MarkupContent, MarkupKind,
-    Position, Url,
+    CompletionItem, CompletionItemKind, CompletionList, CompletionParams, CompletionResponse,
+    CompletionTriggerKind, Documentation, MarkupContent, MarkupKind, Position, Url,
 };
 
 pub async fn handle_completion(
@@ -35,9 +38,25 @@ pub async fn handle_completion(
         config.strict.completion
     };
 
+    // A manual invoke (Ctrl-Space) skips the trigger character check, so
+    // completion can be forced mid-identifier inside an env access, or just
+    // after one while typing it
+    let invoked = params
+        .context
+        .as_ref()
+        .is_some_and(|context| context.trigger_kind == CompletionTriggerKind::INVOKED);
+
     if is_strict {
         let position = params.text_document_position.position;
-        if !state.document_manager.check_completion(uri, position).await && !check_imported_env_object_completion(uri, position, state).await {
+        let just_before = Position::new(position.line, position.character.saturating_sub(1));
+        let in_env_access = invoked
+            && [position, just_before].into_iter().any(|at| {
+                state
+                    .document_manager
+                    .get_env_reference_cloned(uri, at)
+                    .is_some()
+            });
+        if !in_env_access && !state.document_manager.check_completion(uri, position).await && !check_imported_env_object_completion(uri, position, state).await {
             return None;
         }
     }
@@ -45,9 +64,11 @@ pub async fn handle_completion(
     let file_path = uri.to_file_path().ok()?;
 
     let workspace_root = crate::server::util::get_workspace_root(&state.core.workspace).await;
+    let masking = masking_for_file(state, &file_path).await;
 
     let start = Instant::now();
-    let all_vars = crate::server::util::safe_all_for_file(&state.core, &file_path).await;
+    let all_vars =
+        crate::server::util::safe_all_for_file(&state.core, &file_path, state.config.resolution_timeout()).await;
     let elapsed = start.elapsed();
     if elapsed.as_millis() > 100 {
         tracing::warn!(
@@ -57,52 +78,69 @@ pub async fn handle_completion(
         );
     }
 
-    if !all_vars.is_empty() {
-        let count = all_vars.len();
-        let result = Some(
-            all_vars
-                .into_iter()
-                .map(|var| {
-                    let value = var.resolved_value.to_string();
-                    let source_str = format_source(&var.source, &workspace_root);
-
-                    let value_formatted = if value.is_empty() {
-                        "*(empty)*".to_string()
-                    } else if value.contains('\n') {
-                        format!("`{}`", value.replace('\n', "`\n`"))
-                    } else {
-                        format!("`{}`", value)
-                    };
-
-                    let mut doc = format!(
-                        "**Value**: {}\n\n**Source**: `{}`",
-                        value_formatted, source_str
-                    );
-                    if let Some(desc) = &var.description {
-                        if !desc.is_empty() {
-                            doc.push_str(&format!("\n\n*{}*", desc));
-                        }
-                    }
-
-                    CompletionItem {
-                        label: var.key.to_string(),
-                        kind: Some(CompletionItemKind::VARIABLE),
-                        detail: None,
-                        documentation: Some(Documentation::MarkupContent(MarkupContent {
-                            kind: MarkupKind::Markdown,
-                            value: doc,
-                        })),
-                        ..Default::default()
-                    }
-                })
-                .collect(),
-        );
+    let mut items: Vec<CompletionItem> = all_vars
+        .iter()
+        .map(|var| {
+            let source_str = format_source(&var.source, &workspace_root);
+            completion_item(
+                &var.key,
+                &var.resolved_value,
+                &source_str,
+                var.description.as_deref(),
+                &masking,
+            )
+        })
+        .collect();
+
+    // External definitions that abundantis doesn't already know about, and
+    // that have a value to show
+    let mut external_names = state.workspace_index.external_definition_names(Some(uri));
+    external_names.retain(|name| !all_vars.iter().any(|var| var.key == *name));
+    for name in &external_names {
+        let external = state.workspace_index.external_definition(name, Some(uri));
+        if let Some((uri, definition)) = external {
+            if definition.dynamic {
+                continue;
+            }
+            let source_str = format_uri_source(&uri, &workspace_root);
+            items.push(completion_item(
+                name,
+                &definition.value,
+                &source_str,
+                None,
+                &masking,
+            ));
+        }
+    }
+
+    // Fallback provider keys that neither source above covers
+    if let Some(provider) = &state.env_source_provider {
+        for key in provider.keys() {
+            if all_vars.iter().any(|var| var.key == key) || external_names.contains(&key) {
+                continue;
+            }
+            if let Some(value) = provider.resolve(&key) {
+                items.push(completion_item(&key, &value, provider.name(), None, &masking));
+            }
+        }
+    }
+
+    {
+        let config = state.config.get_config();
+        let config = config.read().await;
+        items.retain(|item| config.tracks_env_var(&item.label));
+    }
+
+    let prefix = typed_prefix(state, uri, position);
+    rank_items(&mut items, &prefix);
+
+    if !items.is_empty() {
         tracing::debug!(
             "[HANDLE_COMPLETION_EXIT] count={} elapsed_ms={}",
-            count,
+            items.len(),
             start.elapsed().as_millis()
         );
-        result
+        Some(items)
     } else {
         tracing::debug!(
             "[HANDLE_COMPLETION_EXIT] none elapsed_ms={}",
@@ -112,6 +150,113 @@ pub async fn handle_completion(
     }
 }
 
+/// Wraps ranked completion items for the client. Past `max_items`, only the
+/// best ranked are kept and the list is marked incomplete, so the client
+/// re-requests as the user types instead of filtering a stale list.
+pub fn completion_response(
+    mut items: Vec<CompletionItem>,
+    max_items: usize,
+) -> CompletionResponse {
+    if items.len() <= max_items {
+        return CompletionResponse::Array(items);
+    }
+    items.sort_by(|a, b| a.sort_text.cmp(&b.sort_text));
+    items.truncate(max_items);
+    CompletionResponse::List(CompletionList {
+        is_incomplete: true,
+        items,
+    })
+}
+
+/// The identifier characters typed just before the cursor, e.g. `DA` in
+/// `process.env.DA|`.
+fn typed_prefix(state: &ServerState, uri: &Url, position: Position) -> String {
+    let Some(doc) = state.document_manager.get(uri) else {
+        return String::new();
+    };
+    let Some(line) = doc.content.lines().nth(position.line as usize) else {
+        return String::new();
+    };
+    let before: Vec<char> = line.chars().take(position.character as usize).collect();
+    let start = before
+        .iter()
+        .rposition(|c| !c.is_alphanumeric() && *c != '_')
+        .map_or(0, |i| i + 1);
+    before[start..].iter().collect()
+}
+
+/// How well `label` matches what was typed; lower is better. Exact-case
+/// prefixes beat case-insensitive prefixes, which beat substrings, which
+/// beat in-order subsequences. `None` means no match at all.
+fn match_score(label: &str, prefix: &str) -> Option<u8> {
+    if label.starts_with(prefix) {
+        return Some(0);
+    }
+    let label = label.to_lowercase();
+    let prefix = prefix.to_lowercase();
+    if label.starts_with(&prefix) {
+        Some(1)
+    } else if label.contains(&prefix) {
+        Some(2)
+    } else {
+        let mut chars = label.chars();
+        prefix
+            .chars()
+            .all(|p| chars.any(|c| c == p))
+            .then_some(3)
+    }
+}
+
+/// Orders items by how well they match the typed prefix and preselects the
+/// best match. Nothing is preselected when nothing has been typed.
+fn rank_items(items: &mut [CompletionItem], prefix: &str) {
+    let mut best: Option<(String, usize)> = None;
+    for (index, item) in items.iter_mut().enumerate() {
+        let score = (!prefix.is_empty())
+            .then(|| match_score(&item.label, prefix))
+            .flatten();
+        let sort_text = format!("{}{}", score.unwrap_or(4), item.label);
+        if score.is_some() && best.as_ref().is_none_or(|(text, _)| sort_text < *text) {
+            best = Some((sort_text.clone(), index));
+        }
+        item.sort_text = Some(sort_text);
+    }
+    if let Some((_, index)) = best {
+        items[index].preselect = Some(true);
+    }
+}
+
+fn completion_item(
+    key: &str,
+    value: &str,
+    source: &str,
+    description: Option<&str>,
+    masking: &MaskingConfig,
+) -> CompletionItem {
+    let value = mask_value(key, value, masking, MaskTarget::Completion);
+    let mut doc = format!(
+        "**Value**: {}\n\n**Source**: `{}`",
+        format_value_markdown(&value),
+        source
+    );
+    if let Some(desc) = description {
+        if !desc.is_empty() {
+            doc.push_str(&format!("\n\n*{}*", desc));
+        }
+    }
+
+    CompletionItem {
+        label: key.to_string(),
+        kind: Some(CompletionItemKind::VARIABLE),
+        detail: None,
+        documentation: Some(Documentation::MarkupContent(MarkupContent {
+            kind: MarkupKind::Markdown,
+            value: doc,
+        })),
+        ..Default::default()
+    }
+}
+
 async fn check_imported_env_object_completion(
     uri: &Url,
     position: Position,
@@ -1,4 +1,5 @@
 use crate::analysis::{CrossModuleResolution, CrossModuleResolver};
+use crate::server::config::DefinitionTarget;
 use crate::server::handlers::util::get_identifier_at_position;
 use crate::server::state::ServerState;
 use abundantis::source::VariableSource;
@@ -27,26 +28,46 @@ pub async fn handle_definition(
         return None;
     }
 
-    let env_var_name = if let Some(reference) = state
+    let target = state.config.get_config().read().await.definition_target;
+
+    // The declaring code for bindings and usages; direct references have none
+    let (env_var_name, declaration) = if let Some(reference) = state
         .document_manager
         .get_env_reference_cloned(uri, position)
     {
-        reference.name
+        (reference.name, None)
     } else if let Some(binding) = state.document_manager.get_env_binding_cloned(uri, position) {
-        binding.env_var_name
+        (binding.env_var_name, Some(binding.binding_range))
     } else if let Some(usage) = state
         .document_manager
         .get_binding_usage_cloned(uri, position)
     {
-        usage.env_var_name
+        (usage.env_var_name, Some(usage.declaration_range))
     } else {
-        return handle_definition_cross_module(&params, state).await;
+        return handle_definition_cross_module(&params, state, target).await;
     };
 
+    if let (DefinitionTarget::Declaration, Some(range)) = (target, declaration) {
+        tracing::debug!(
+            "[HANDLE_DEFINITION_EXIT] declaration elapsed_ms={}",
+            start.elapsed().as_millis()
+        );
+        return Some(GotoDefinitionResponse::Scalar(Location {
+            uri: uri.clone(),
+            range,
+        }));
+    }
+
     let file_path = uri.to_file_path().ok()?;
 
     if let Some(variable) =
-        crate::server::util::safe_get_for_file(&state.core, &env_var_name, &file_path).await
+        crate::server::util::safe_get_for_file(
+            &state.core,
+            &env_var_name,
+            &file_path,
+            state.config.resolution_timeout(),
+        )
+        .await
     {
         match &variable.source {
             VariableSource::File { path, offset } => {
@@ -89,6 +110,7 @@ pub async fn handle_definition(
 async fn handle_definition_cross_module(
     params: &GotoDefinitionParams,
     state: &ServerState,
+    target: DefinitionTarget,
 ) -> Option<GotoDefinitionResponse> {
     let uri = &params.text_document_position_params.text_document.uri;
     let position = params.text_document_position_params.position;
@@ -112,7 +134,7 @@ async fn handle_definition_cross_module(
         state.languages.clone(),
     );
 
-    let is_default = original_name == identifier_name;
+    let is_default = original_name == module_path;
 
     match cross_resolver.resolve_import(uri, &module_path, &original_name, is_default) {
         CrossModuleResolution::EnvVar {
@@ -120,6 +142,13 @@ async fn handle_definition_cross_module(
             defining_file,
             declaration_range,
         } => {
+            if target == DefinitionTarget::Declaration {
+                return Some(GotoDefinitionResponse::Scalar(Location {
+                    uri: defining_file,
+                    range: declaration_range,
+                }));
+            }
+
             let file_path = uri.to_file_path().ok()?;
 
             let workspace = std::sync::Arc::clone(&state.core.workspace);
@@ -1,16 +1,250 @@
-use crate::server::handlers::util::get_line_col;
+use crate::analysis::indexer::{invalid_utf8_offsets, read_env_file};
+use crate::analysis::line_index::LineIndex;
+use crate::server::handlers::util::{get_line_col, is_valid_env_var_name, KorniEntryExt};
+use crate::server::config::DiagnoseKind;
 use crate::server::state::ServerState;
+use crate::server::util::ResolutionTimedOut;
+use abundantis::source::VariableSource;
+use compact_str::CompactString;
 use korni::{Error as KorniError, ParseOptions};
+use std::collections::HashMap;
+use std::path::Path;
 use std::time::Instant;
-use tower_lsp::lsp_types::{Diagnostic, DiagnosticSeverity, NumberOrString, Position, Range, Url};
+use tower_lsp::lsp_types::{
+    Diagnostic, DiagnosticRelatedInformation, DiagnosticSeverity, Location, NumberOrString,
+    Position, Range, Url,
+};
+
+/// Why `diagnostics.warn_on_parse_error` flags the first parse error.
+const PARSE_ERROR_NOTE: &str = "ecolog analysis may be incomplete due to a parse error";
+
+/// An env var name, where it is used, and the declaration its binding chain starts from.
+type EnvVarUse = (CompactString, Range, Option<Range>);
 
 /// Type alias for diagnostic analysis results: (direct references, env var symbols, property accesses)
 type DiagnosticAnalysisResult = (
     Vec<crate::types::EnvReference>,
-    Vec<(compact_str::CompactString, Range)>,
-    Vec<(compact_str::CompactString, Range)>,
+    Vec<EnvVarUse>,
+    Vec<EnvVarUse>,
 );
 
+/// Keys declared in `diagnostics.example_files` in the directory of
+/// `file_path` or a parent up to `root`, each mapped to its declaring file
+/// relative to `root`. The nearest file wins when several declare a key.
+async fn example_declarations(
+    state: &ServerState,
+    file_path: &Path,
+    root: &Path,
+) -> HashMap<CompactString, String> {
+    let (patterns, max_bytes) = {
+        let config = state.config.get_config();
+        let config = config.read().await;
+        (
+            config.diagnostics.example_files.clone(),
+            config.workspace.max_env_file_bytes,
+        )
+    };
+
+    let mut declarations = HashMap::new();
+    let dirs = file_path
+        .ancestors()
+        .skip(1)
+        .take_while(|dir| dir.starts_with(root));
+    for dir in dirs {
+        for pattern in &patterns {
+            let path = dir.join(pattern);
+            if !path.exists() {
+                continue;
+            }
+            let Some(content) = read_env_file(&path, max_bytes).await else {
+                continue;
+            };
+            let name = path.strip_prefix(root).unwrap_or(&path).display().to_string();
+            let entries = korni::parse_with_options(&content, ParseOptions::full());
+            for kv in entries.into_iter().filter_map(|e| e.into_valid_pair()) {
+                declarations
+                    .entry(CompactString::from(kv.key.as_ref()))
+                    .or_insert_with(|| name.clone());
+            }
+        }
+    }
+    declarations
+}
+
+/// Invalid UTF-8 in the env file on disk, at offsets into `content`, which
+/// korni only ever sees decoded. The bytes are checked only when `content`
+/// has a U+FFFD and is exactly their lossy decoding, so a buffer edited since
+/// or a file with a genuine U+FFFD reports nothing.
+async fn invalid_utf8_errors(path: &Path, content: &str, max_bytes: u64) -> Vec<KorniError> {
+    if !content.contains(char::REPLACEMENT_CHARACTER) {
+        return Vec::new();
+    }
+    match tokio::fs::metadata(path).await {
+        Ok(metadata) if metadata.len() <= max_bytes => {}
+        _ => return Vec::new(),
+    }
+    let Ok(bytes) = tokio::fs::read(path).await else {
+        return Vec::new();
+    };
+    if String::from_utf8_lossy(&bytes) != content {
+        return Vec::new();
+    }
+    invalid_utf8_offsets(&bytes)
+        .into_iter()
+        .map(|offset| KorniError::InvalidUtf8 {
+            offset,
+            reason: "the file isn't valid UTF-8 here".to_string(),
+        })
+        .collect()
+}
+
+/// Style warnings for values korni accepts but other dotenv parsers may
+/// read differently.
+fn lint_env_file(content: &str, entries: &[korni::Entry]) -> Vec<Diagnostic> {
+    let line_index = LineIndex::new(content);
+    let mut diagnostics = Vec::new();
+    for pair in entries.iter().filter_map(|entry| entry.as_pair()) {
+        if pair.is_comment {
+            continue;
+        }
+        let Some(span) = pair.value_span else {
+            continue;
+        };
+        match pair.quote {
+            korni::QuoteType::None => {
+                let rest = &content[span.end.offset..];
+                let line_end = rest.find('\n').unwrap_or(rest.len());
+                let trailing = rest[..line_end].trim_end_matches('\r');
+                if !trailing.is_empty() && trailing.trim().is_empty() {
+                    diagnostics.push(lint_warning(
+                        &line_index,
+                        (span.end.offset, span.end.offset + trailing.len()),
+                        "EDF031",
+                        format!("Trailing whitespace after the value of '{}'", pair.key),
+                    ));
+                }
+            }
+            _ => {
+                if swallows_assignments(&pair.value) {
+                    let open = pair.open_quote_pos.map_or(span.start.offset, |p| p.offset);
+                    let line_end = content[open..].find('\n').map_or(content.len(), |i| open + i);
+                    diagnostics.push(lint_warning(
+                        &line_index,
+                        (open, line_end),
+                        "EDF032",
+                        format!(
+                            "Quote opened for '{}' is not closed on its line and swallows the following assignments",
+                            pair.key
+                        ),
+                    ));
+                }
+            }
+        }
+    }
+
+    diagnostics
+}
+
+/// True if a quoted value continues over lines that look like assignments,
+/// which means its opening quote was never closed.
+fn swallows_assignments(value: &str) -> bool {
+    value.lines().skip(1).any(|line| {
+        let line = line.trim_start();
+        let line = line.strip_prefix("export ").unwrap_or(line);
+        line.split_once('=')
+            .is_some_and(|(key, _)| is_valid_env_var_name(key))
+    })
+}
+
+fn lint_warning(
+    line_index: &LineIndex,
+    span: (usize, usize),
+    code: &str,
+    message: String,
+) -> Diagnostic {
+    Diagnostic {
+        range: line_index.range(span.0, span.1),
+        severity: Some(DiagnosticSeverity::WARNING),
+        code: Some(NumberOrString::String(code.to_string())),
+        source: Some("ecolog-linter".to_string()),
+        message,
+        ..Default::default()
+    }
+}
+
+/// Whether the optional fallback env source supplies `name` to `from`.
+fn provided_by_fallback(state: &ServerState, name: &str, from: Option<&Url>) -> bool {
+    state.workspace_index.external_definition(name, from).is_some()
+        || state
+            .env_source_provider
+            .as_ref()
+            .is_some_and(|provider| provider.resolve(name).is_some())
+}
+
+/// Whether an active env file for `file_path` defines `name`.
+fn defined_in_env_files(state: &ServerState, name: &str, file_path: &Path) -> bool {
+    let active_files = state.core.active_env_files(file_path);
+    state
+        .core
+        .registry
+        .sources_for_paths(&active_files)
+        .iter()
+        .any(|source| {
+            source
+                .load()
+                .is_ok_and(|snapshot| snapshot.variables.iter().any(|v| v.key == name))
+        })
+}
+
+/// Whether `name` resolves for `file_path` from abundantis or a fallback source.
+///
+/// With `file_sources_only`, shell, remote and provider values don't count.
+/// Fails when abundantis times out and no fallback source has the name.
+async fn is_env_var_defined(
+    state: &ServerState,
+    name: &str,
+    file_path: &Path,
+    file_sources_only: bool,
+) -> Result<bool, ResolutionTimedOut> {
+    let timeout = state.config.resolution_timeout();
+    let from = Url::from_file_path(file_path).ok();
+    let external = || state.workspace_index.external_definition(name, from.as_ref()).is_some();
+    let resolved =
+        match crate::server::util::try_get_for_file(&state.core, name, file_path, timeout).await {
+            Ok(resolved) => resolved,
+            Err(timed_out) => {
+                let external = external();
+                let fallback =
+                    !file_sources_only && provided_by_fallback(state, name, from.as_ref());
+                return if external || fallback {
+                    Ok(true)
+                } else {
+                    Err(timed_out)
+                };
+            }
+        };
+
+    if resolved.is_none()
+        && crate::server::handlers::util::resolve_from_included_files(name, file_path, state)
+            .await
+            .is_some()
+    {
+        return Ok(true);
+    }
+
+    if !file_sources_only {
+        return Ok(resolved.is_some() || provided_by_fallback(state, name, from.as_ref()));
+    }
+
+    let in_env_files = match resolved {
+        Some(var) if matches!(var.source, VariableSource::File { .. }) => true,
+        // Another source may win over a file that also defines the key
+        Some(_) => defined_in_env_files(state, name, file_path),
+        None => false,
+    };
+    Ok(in_env_files || external())
+}
+
 pub async fn compute_diagnostics(uri: &Url, state: &ServerState) -> Vec<Diagnostic> {
     tracing::debug!("[COMPUTE_DIAGNOSTICS_ENTER] uri={}", uri);
     let start = Instant::now();
@@ -43,7 +277,7 @@ pub async fn compute_diagnostics(uri: &Url, state: &ServerState) -> Vec<Diagnost
                 .iter()
                 .filter_map(|s| {
                     if let crate::types::SymbolOrigin::EnvVar { name } = &s.origin {
-                        Some((name.clone(), s.name_range))
+                        Some((name.clone(), s.name_range, Some(s.declaration_range)))
                     } else {
                         None
                     }
@@ -62,7 +296,8 @@ pub async fn compute_diagnostics(uri: &Url, state: &ServerState) -> Vec<Diagnost
                         Some(crate::types::ResolvedEnv::Object(_))
                     ) {
                         let range = usage.property_access_range.unwrap_or(usage.range);
-                        Some((prop_name.clone(), range))
+                        let origin = graph.origin_declaration_range(symbol.id);
+                        Some((prop_name.clone(), range, origin))
                     } else {
                         None
                     }
@@ -79,75 +314,106 @@ pub async fn compute_diagnostics(uri: &Url, state: &ServerState) -> Vec<Diagnost
     } else {
         return vec![];
     };
-    let file_name = file_path
-        .file_name()
-        .map(|s| s.to_string_lossy().to_string())
-        .unwrap_or_default();
+    let workspace_root = crate::server::util::get_workspace_root(&state.core.workspace).await;
 
-    let is_env_file = {
+    let (
+        is_env_file,
+        file_sources_only,
+        warn_on_parse_error,
+        required_prefixes,
+        diagnose_kinds,
+        disabled_codes,
+        max_env_file_bytes,
+    ) = {
         let config = state.config.get_config();
         let config = config.read().await;
-        config.workspace.env_files.iter().any(|pattern| {
-            glob::Pattern::new(pattern)
-                .map(|p| p.matches(&file_name))
-                .unwrap_or(false)
-        })
+        let is_env_file = crate::server::util::is_env_file_path(
+            &config.workspace.env_files,
+            &file_path,
+            &workspace_root,
+            config.workspace.case_insensitive_env_files,
+        );
+        let required_prefixes: Vec<String> = config
+            .diagnostics
+            .required_prefix_by_glob
+            .iter()
+            .filter(|(glob, _)| {
+                crate::server::util::matches_workspace_glob(&[glob], &file_path, &workspace_root)
+            })
+            .map(|(_, prefix)| prefix.clone())
+            .collect();
+        (
+            is_env_file,
+            config.diagnostics.file_sources_only,
+            config.diagnostics.warn_on_parse_error,
+            required_prefixes,
+            config.diagnostics.diagnose_kinds.clone(),
+            config.diagnostics.disabled_diagnostics.clone(),
+            config.workspace.max_env_file_bytes,
+        )
     };
 
     if is_env_file {
         let entries = korni::parse_with_options(&content, ParseOptions::full());
-        for entry in entries {
-            if let korni::Entry::Error(err) = entry {
-                let (msg, code, severity) = match &err {
-                    KorniError::ForbiddenWhitespace { .. } => {
-                        ("Forbidden whitespace", "EDF001", DiagnosticSeverity::ERROR)
-                    }
-                    KorniError::DoubleEquals { .. } => (
-                        "Double equals sign detected",
-                        "EDF002",
-                        DiagnosticSeverity::ERROR,
-                    ),
-                    KorniError::Generic { message, .. } if message == "Empty key" => {
-                        ("Empty key", "EDF003", DiagnosticSeverity::ERROR)
-                    }
-                    KorniError::InvalidKey { .. } => (
-                        "Invalid character in key",
-                        "EDF004",
-                        DiagnosticSeverity::ERROR,
-                    ),
-                    KorniError::UnclosedQuote { .. } => {
-                        ("Unclosed quote", "EDF005", DiagnosticSeverity::ERROR)
-                    }
-                    KorniError::InvalidUtf8 { .. } => (
-                        "Invalid UTF-8 sequence",
-                        "EDF006",
-                        DiagnosticSeverity::WARNING,
-                    ),
-                    KorniError::Expected { .. } => {
-                        ("Syntax error", "EDF999", DiagnosticSeverity::ERROR)
-                    }
-                    _ => ("Syntax Error", "EDF999", DiagnosticSeverity::ERROR),
-                };
+        let utf8_errors = invalid_utf8_errors(&file_path, &content, max_env_file_bytes).await;
+        let errors = entries
+            .iter()
+            .filter_map(|entry| match entry {
+                korni::Entry::Error(err) => Some(err),
+                _ => None,
+            })
+            .chain(&utf8_errors);
+        for err in errors {
+            let (msg, code, severity) = match err {
+                KorniError::ForbiddenWhitespace { .. } => {
+                    ("Forbidden whitespace", "EDF001", DiagnosticSeverity::ERROR)
+                }
+                KorniError::DoubleEquals { .. } => (
+                    "Double equals sign detected",
+                    "EDF002",
+                    DiagnosticSeverity::ERROR,
+                ),
+                KorniError::Generic { message, .. } if message == "Empty key" => {
+                    ("Empty key", "EDF003", DiagnosticSeverity::ERROR)
+                }
+                KorniError::InvalidKey { .. } => (
+                    "Invalid character in key",
+                    "EDF004",
+                    DiagnosticSeverity::ERROR,
+                ),
+                KorniError::UnclosedQuote { .. } => {
+                    ("Unclosed quote", "EDF005", DiagnosticSeverity::ERROR)
+                }
+                KorniError::InvalidUtf8 { .. } => (
+                    "Invalid UTF-8 sequence",
+                    "EDF006",
+                    DiagnosticSeverity::WARNING,
+                ),
+                KorniError::Expected { .. } => {
+                    ("Syntax error", "EDF999", DiagnosticSeverity::ERROR)
+                }
+                _ => ("Syntax Error", "EDF999", DiagnosticSeverity::ERROR),
+            };
 
-                let offset = err.offset();
+            let offset = err.offset();
 
-                let (line, col) = get_line_col(&content, offset);
+            let (line, col) = get_line_col(&content, offset);
 
-                let range = Range {
-                    start: Position::new(line, col),
-                    end: Position::new(line, col + 1),
-                };
+            let range = Range {
+                start: Position::new(line, col),
+                end: Position::new(line, col + 1),
+            };
 
-                diagnostics.push(Diagnostic {
-                    range,
-                    severity: Some(severity),
-                    code: Some(NumberOrString::String(code.to_string())),
-                    source: Some("ecolog-linter".to_string()),
-                    message: format!("{}: {}", msg, err),
-                    ..Default::default()
-                });
-            }
+            diagnostics.push(Diagnostic {
+                range,
+                severity: Some(severity),
+                code: Some(NumberOrString::String(code.to_string())),
+                source: Some("ecolog-linter".to_string()),
+                message: format!("{}: {}", msg, err),
+                ..Default::default()
+            });
         }
+        diagnostics.extend(lint_env_file(&content, &entries));
     }
 
     if !is_env_file {
@@ -165,56 +431,210 @@ pub async fn compute_diagnostics(uri: &Url, state: &ServerState) -> Vec<Diagnost
             });
         }
 
-        for reference in references {
-            let resolved =
-                crate::server::util::safe_get_for_file(&state.core, &reference.name, &file_path)
-                    .await;
-
-            if resolved.is_none() {
+        if warn_on_parse_error {
+            if let Some(range) = state.document_manager.first_parse_error(uri) {
                 diagnostics.push(Diagnostic {
-                    range: reference.name_range,
-                    severity: Some(DiagnosticSeverity::WARNING),
-                    code: Some(NumberOrString::String("undefined-env-var".to_string())),
+                    range,
+                    severity: Some(DiagnosticSeverity::INFORMATION),
+                    code: Some(NumberOrString::String("parse-error".to_string())),
                     source: Some("ecolog".to_string()),
-                    message: format!("Environment variable '{}' is not defined.", reference.name),
+                    message: PARSE_ERROR_NOTE.to_string(),
                     ..Default::default()
                 });
             }
         }
 
-        for (env_name, range) in env_var_symbols {
-            let resolved =
-                crate::server::util::safe_get_for_file(&state.core, &env_name, &file_path).await;
+        // The file path is fixed within one call, so active-file filtering
+        // gives the same answer for a name every time; resolve each name once.
+        let mut defined: HashMap<CompactString, Result<bool, ResolutionTimedOut>> = HashMap::new();
+        let mut timed_out = false;
+        // Read on the first undefined var, since most files have none
+        let mut examples: Option<HashMap<CompactString, String>> = None;
+        // References with a code-level default, like Python's
+        // `os.environ.get("KEY", "fallback")`. Bindings declared from one of
+        // them inherit the default.
+        let defaulted: Vec<(CompactString, Range)> = references
+            .iter()
+            .filter(|reference| reference.has_default)
+            .map(|reference| (reference.name.clone(), reference.full_range))
+            .collect();
+        let has_default = |name: &CompactString, origin: Option<Range>| {
+            origin.is_some_and(|origin| {
+                defaulted.iter().any(|(defaulted_name, range)| {
+                    defaulted_name == name
+                        && crate::analysis::range_utils::range_contains_range(origin, *range)
+                })
+            })
+        };
+        let diagnose = |kind: DiagnoseKind| diagnose_kinds.contains(&kind);
+        let env_names = references
+            .iter()
+            .filter(|_| diagnose(DiagnoseKind::DirectReference))
+            .map(|reference| {
+                let use_ = (reference.name.clone(), reference.name_range, None);
+                (use_, reference.has_default)
+            })
+            .chain(
+                env_var_symbols
+                    .into_iter()
+                    .filter(|_| diagnose(DiagnoseKind::BindingDeclaration))
+                    .chain(
+                        property_accesses
+                            .into_iter()
+                            .filter(|_| diagnose(DiagnoseKind::PropertyAccess)),
+                    )
+                    .map(|use_| {
+                        let has_default = has_default(&use_.0, use_.2);
+                        (use_, has_default)
+                    }),
+            )
+            .collect::<Vec<_>>();
+
+        let env_names: Vec<_> = {
+            let config = state.config.get_config();
+            let config = config.read().await;
+            env_names
+                .into_iter()
+                .filter(|((name, _, _), _)| config.tracks_env_var(name))
+                .collect()
+        };
 
-            if resolved.is_none() {
+        // A binding like `const a = process.env.SECRET` repeats a direct
+        // reference that already gets its own prefix warning
+        let repeats_reference = |name: &CompactString, origin: Option<Range>| {
+            diagnose(DiagnoseKind::DirectReference)
+                && origin.is_some_and(|origin| {
+                    references.iter().any(|reference| {
+                        reference.name == *name
+                            && crate::analysis::range_utils::range_contains_range(
+                                origin,
+                                reference.full_range,
+                            )
+                    })
+                })
+        };
+
+        for ((env_name, range, origin), has_default) in env_names {
+            if !required_prefixes.is_empty()
+                && !repeats_reference(&env_name, origin)
+                && !required_prefixes
+                    .iter()
+                    .any(|prefix| env_name.starts_with(prefix.as_str()))
+            {
                 diagnostics.push(Diagnostic {
                     range,
                     severity: Some(DiagnosticSeverity::WARNING),
-                    code: Some(NumberOrString::String("undefined-env-var".to_string())),
+                    code: Some(NumberOrString::String("EDF040".to_string())),
                     source: Some("ecolog".to_string()),
-                    message: format!("Environment variable '{}' is not defined.", env_name),
+                    message: format!(
+                        "Environment variable '{}' is not exposed here; expected prefix {}.",
+                        env_name,
+                        required_prefixes
+                            .iter()
+                            .map(|p| format!("'{}'", p))
+                            .collect::<Vec<_>>()
+                            .join(" or ")
+                    ),
                     ..Default::default()
                 });
             }
-        }
 
-        for (env_name, range) in property_accesses {
-            let resolved =
-                crate::server::util::safe_get_for_file(&state.core, &env_name, &file_path).await;
+            let is_defined = match defined.get(&env_name) {
+                Some(&is_defined) => is_defined,
+                // After one timeout the rest would likely time out too, so
+                // don't wait on each of them
+                None if timed_out => Err(ResolutionTimedOut),
+                None => {
+                    let is_defined =
+                        is_env_var_defined(state, &env_name, &file_path, file_sources_only)
+                            .await;
+                    timed_out |= is_defined.is_err();
+                    defined.insert(env_name.clone(), is_defined);
+                    is_defined
+                }
+            };
 
-            if resolved.is_none() {
+            let Ok(is_defined) = is_defined else {
                 diagnostics.push(Diagnostic {
                     range,
-                    severity: Some(DiagnosticSeverity::WARNING),
+                    severity: Some(DiagnosticSeverity::INFORMATION),
+                    code: Some(NumberOrString::String("resolution-timeout".to_string())),
+                    source: Some("ecolog".to_string()),
+                    message: format!(
+                        "Couldn't check whether '{}' is defined; resolution timed out.",
+                        env_name
+                    ),
+                    ..Default::default()
+                });
+                continue;
+            };
+
+            if !is_defined {
+                let related_information = origin.map(|origin| {
+                    vec![DiagnosticRelatedInformation {
+                        location: Location::new(uri.clone(), origin),
+                        message: format!("'{}' is read from the environment here", env_name),
+                    }]
+                });
+                if !has_default {
+                    if examples.is_none() {
+                        examples =
+                            Some(example_declarations(state, &file_path, &workspace_root).await);
+                    }
+                    if let Some(example) = examples.as_ref().and_then(|e| e.get(&env_name)) {
+                        diagnostics.push(Diagnostic {
+                            range,
+                            severity: Some(DiagnosticSeverity::WARNING),
+                            code: Some(NumberOrString::String("EDF050".to_string())),
+                            source: Some("ecolog".to_string()),
+                            message: format!(
+                                "Environment variable '{}' is declared in {} but not set.",
+                                env_name, example
+                            ),
+                            related_information,
+                            ..Default::default()
+                        });
+                        continue;
+                    }
+                }
+                let (severity, message) = if has_default {
+                    (
+                        DiagnosticSeverity::HINT,
+                        format!(
+                            "Environment variable '{}' is not defined; the default in code is used.",
+                            env_name
+                        ),
+                    )
+                } else {
+                    (
+                        DiagnosticSeverity::WARNING,
+                        format!("Environment variable '{}' is not defined.", env_name),
+                    )
+                };
+                diagnostics.push(Diagnostic {
+                    range,
+                    severity: Some(severity),
                     code: Some(NumberOrString::String("undefined-env-var".to_string())),
                     source: Some("ecolog".to_string()),
-                    message: format!("Environment variable '{}' is not defined.", env_name),
+                    message,
+                    related_information,
                     ..Default::default()
                 });
             }
         }
     }
 
+    if !disabled_codes.is_empty() {
+        diagnostics.retain(|d| !disabled_codes.contains(&code_key(d)));
+    }
+
+    // Editors list problems in the order they're published; keep it stable
+    // across runs.
+    diagnostics.sort_by(|a, b| {
+        (a.range.start.line, a.range.start.character, code_key(a))
+            .cmp(&(b.range.start.line, b.range.start.character, code_key(b)))
+    });
+
     tracing::debug!(
         "[COMPUTE_DIAGNOSTICS_EXIT] count={} elapsed_ms={}",
         diagnostics.len(),
@@ -222,3 +642,64 @@ pub async fn compute_diagnostics(uri: &Url, state: &ServerState) -> Vec<Diagnost
     );
     diagnostics
 }
+
+fn code_key(diagnostic: &Diagnostic) -> String {
+    match &diagnostic.code {
+        Some(NumberOrString::String(code)) => code.clone(),
+        Some(NumberOrString::Number(code)) => code.to_string(),
+        None => String::new(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn codes(content: &str, entries: &[korni::Entry]) -> Vec<String> {
+        lint_env_file(content, entries)
+            .into_iter()
+            .map(|d| match d.code {
+                Some(NumberOrString::String(code)) => code,
+                other => panic!("unexpected code {:?}", other),
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_lint_leaves_whitespace_around_equals_to_korni() {
+        let content = "KEY = value\nOK=1\n";
+        let entries = korni::parse_with_options(content, ParseOptions::full());
+        assert!(codes(content, &entries).is_empty());
+    }
+
+    #[test]
+    fn test_lint_trailing_whitespace_after_unquoted_value() {
+        let content = "A=value  \nB=\"quoted\"  \nC=v # comment\n";
+        let entries = korni::parse_with_options(content, ParseOptions::full());
+        let diags = lint_env_file(content, &entries);
+        assert_eq!(diags.len(), 1, "got {:?}", diags);
+        assert_eq!(diags[0].code, Some(NumberOrString::String("EDF031".into())));
+        assert_eq!(diags[0].range.start, Position::new(0, 7));
+        assert_eq!(diags[0].range.end, Position::new(0, 9));
+    }
+
+    #[test]
+    fn test_lint_quote_swallowing_assignments() {
+        let content = "A=\"value\nB=ok\nC=\"x\"\n";
+        let entries = korni::parse_with_options(content, ParseOptions::full());
+        let diags = lint_env_file(content, &entries);
+        assert_eq!(
+            diags.iter().filter_map(|d| d.code.clone()).collect::<Vec<_>>(),
+            vec![NumberOrString::String("EDF032".into())]
+        );
+        assert_eq!(diags[0].range.start, Position::new(0, 2));
+        assert_eq!(diags[0].range.end, Position::new(0, 8));
+    }
+
+    #[test]
+    fn test_lint_accepts_multiline_quoted_value() {
+        let content = "CERT=\"line one\nline two\"\nNEXT=1\n";
+        let entries = korni::parse_with_options(content, ParseOptions::full());
+        assert!(codes(content, &entries).is_empty());
+    }
+}
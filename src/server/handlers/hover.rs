@@ -1,6 +1,8 @@
 use crate::analysis::{CrossModuleResolution, CrossModuleResolver};
+use crate::server::config::MaskTarget;
 use crate::server::handlers::util::{
-    format_hover_markdown, get_identifier_at_position, resolve_env_var_value,
+    format_hover_markdown, get_identifier_at_position, mask_value, masking_for_file,
+    overridden_definitions, push_overrides_markdown, resolve_env_var_value, ResolvedEnvVarValue,
 };
 use crate::server::state::ServerState;
 use crate::types::ImportContext;
@@ -9,6 +11,24 @@ use tower_lsp::lsp_types::{
     Hover, HoverContents, HoverParams, MarkupContent, MarkupKind, Position, Range, Url,
 };
 
+/// Resolves a value for display in a hover, masked per the masking config.
+async fn resolve_for_hover(
+    env_var_name: &str,
+    file_path: &std::path::Path,
+    state: &ServerState,
+) -> Option<ResolvedEnvVarValue> {
+    let mut resolved = resolve_env_var_value(env_var_name, file_path, state).await?;
+    let masking = masking_for_file(state, file_path).await;
+    resolved.value = mask_value(env_var_name, &resolved.value, &masking, MaskTarget::Hover);
+    Some(resolved)
+}
+
+async fn show_overrides(state: &ServerState) -> bool {
+    let config = state.config.get_config();
+    let config = config.read().await;
+    config.features.hover_show_overrides
+}
+
 /// Context for hover operations on imported env object properties
 struct ImportedEnvPropertyHoverContext<'a> {
     uri: &'a Url,
@@ -71,15 +91,36 @@ pub async fn handle_hover(params: HoverParams, state: &ServerState) -> Option<Ho
             return handle_hover_cross_module(params, state).await;
         };
 
+    let is_object = matches!(binding_kind, Some(crate::types::BindingKind::Object));
+    if !is_object && !crate::server::util::is_env_var_tracked(state, &env_var_name).await {
+        tracing::debug!(
+            "[HANDLE_HOVER_EXIT] untracked elapsed_ms={}",
+            start.elapsed().as_millis()
+        );
+        return None;
+    }
+
     let file_path = uri.to_file_path().ok()?;
 
-    if let Some(resolved) = resolve_env_var_value(&env_var_name, &file_path, state).await {
-        let markdown = if is_binding {
+    if let Some(resolved) = resolve_for_hover(&env_var_name, &file_path, state).await {
+        let mut markdown = if is_binding {
             let b_name = binding_name.as_deref().unwrap_or(env_var_name.as_str());
             format_hover_markdown(&env_var_name, Some(b_name), &resolved)
         } else {
             format_hover_markdown(&env_var_name, None, &resolved)
         };
+        if show_overrides(state).await {
+            let masking = masking_for_file(state, &file_path).await;
+            let overrides: Vec<_> =
+                overridden_definitions(&env_var_name, &file_path, &resolved.source, state)
+                    .await
+                    .into_iter()
+                    .map(|(source, value)| {
+                        (source, mask_value(&env_var_name, &value, &masking, MaskTarget::Hover))
+                    })
+                    .collect();
+            push_overrides_markdown(&mut markdown, &overrides);
+        }
 
         tracing::debug!(
             "[HANDLE_HOVER_EXIT] found elapsed_ms={}",
@@ -172,7 +213,7 @@ async fn handle_hover_cross_module(params: HoverParams, state: &ServerState) ->
             name: env_var_name, ..
         } => {
             let file_path = uri.to_file_path().ok()?;
-            let resolved = resolve_env_var_value(&env_var_name, &file_path, state).await?;
+            let resolved = resolve_for_hover(&env_var_name, &file_path, state).await?;
             let markdown =
                 format_hover_markdown(&env_var_name, Some(identifier_name.as_str()), &resolved);
 
@@ -240,7 +281,7 @@ async fn handle_hover_on_imported_env_object_property(
             let file_path = ctx.uri.to_file_path().ok()?;
 
             let markdown = if let Some(resolved) =
-                resolve_env_var_value(env_var_name, &file_path, state).await
+                resolve_for_hover(env_var_name, &file_path, state).await
             {
                 format_hover_markdown(env_var_name, None, &resolved)
             } else {
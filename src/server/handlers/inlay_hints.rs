@@ -1,15 +1,29 @@
 use crate::analysis::graph::EnvVarLocationKind;
 use crate::analysis::BindingResolver;
-use crate::server::config::InlayHintConfig;
-use crate::server::handlers::util::resolve_env_var_value;
+use crate::server::config::{InlayHintConfig, MaskTarget, MultilineHintPosition};
+use crate::server::handlers::util::{mask_value, masking_for_file, resolve_env_var_value};
+use crate::server::util::{is_env_var_tracked, safe_all_for_file};
 use crate::server::state::ServerState;
 use compact_str::CompactString;
-use rustc_hash::FxHashMap;
+use rustc_hash::{FxHashMap, FxHashSet};
+use serde::{Deserialize, Serialize};
+use std::sync::atomic::Ordering;
 use std::time::Instant;
 use tower_lsp::lsp_types::{
-    InlayHint, InlayHintKind, InlayHintLabel, InlayHintParams, InlayHintTooltip, Range,
+    InlayHint, InlayHintKind, InlayHintLabel, InlayHintParams, InlayHintTooltip, Position, Range,
+    Url,
 };
 
+/// Label shown until a deferred hint is resolved.
+const PLACEHOLDER_LABEL: &str = ": …";
+
+/// What `inlayHint/resolve` needs to compute a deferred hint's value.
+#[derive(Serialize, Deserialize)]
+struct HintData {
+    uri: Url,
+    name: String,
+}
+
 pub async fn handle_inlay_hints(
     params: InlayHintParams,
     state: &ServerState,
@@ -33,18 +47,19 @@ pub async fn handle_inlay_hints(
     }
 
     // 2. Get inlay hint config
-    let config = {
-        let config_arc = state.config.get_config();
-        let config = config_arc.read().await;
-        config.inlay_hints.clone()
-    };
+    let config = state.config.get_config().read().await.inlay_hints.clone();
 
     // 3. Get binding graph
     let graph = state.document_manager.get_binding_graph(uri)?;
     let resolver = BindingResolver::new(&graph);
 
     // 4. Get all env vars
-    let env_vars = resolver.all_env_vars();
+    let mut env_vars = Vec::new();
+    for var in resolver.all_env_vars() {
+        if is_env_var_tracked(state, &var).await {
+            env_vars.push(var);
+        }
+    }
     if env_vars.is_empty() {
         tracing::debug!(
             "[HANDLE_INLAY_HINTS_EXIT] no env vars, elapsed_ms={}",
@@ -53,13 +68,36 @@ pub async fn handle_inlay_hints(
         return Some(vec![]);
     }
 
-    // 5. Resolve values (batch)
+    // 5. Resolve values (batch). Variables that don't resolve get no hint;
+    // when the client resolves hints, the display value is left to
+    // `inlayHint/resolve` and `None` marks a placeholder.
     let file_path = uri.to_file_path().ok()?;
-    let mut resolved: FxHashMap<CompactString, (String, String)> = FxHashMap::default();
-    for var in &env_vars {
-        if let Some(r) = resolve_env_var_value(var, &file_path, state).await {
-            let display = format_value(&r.value, &config);
-            resolved.insert(var.clone(), (display, r.source));
+    let deferred = state.defer_inlay_hint_values.load(Ordering::Relaxed);
+    let mut resolved: FxHashMap<CompactString, Option<(String, String)>> = FxHashMap::default();
+    if deferred {
+        // One batch lookup tells which variables have a value; only the
+        // ones it misses go through the fallback sources.
+        let timeout = state.config.resolution_timeout();
+        let known: FxHashSet<CompactString> = safe_all_for_file(&state.core, &file_path, timeout)
+            .await
+            .iter()
+            .map(|var| var.key.clone())
+            .collect();
+        for var in &env_vars {
+            if known.contains(var)
+                || resolve_env_var_value(var, &file_path, state).await.is_some()
+            {
+                resolved.insert(var.clone(), None);
+            }
+        }
+    } else {
+        let masking = masking_for_file(state, &file_path).await;
+        for var in &env_vars {
+            let Some(r) = resolve_env_var_value(var, &file_path, state).await else {
+                continue;
+            };
+            let value = mask_value(var, &r.value, &masking, MaskTarget::InlayHint);
+            resolved.insert(var.clone(), Some((format_value(&value, &config), r.source)));
         }
     }
 
@@ -68,7 +106,7 @@ pub async fn handle_inlay_hints(
     let mut per_line: FxHashMap<u32, usize> = FxHashMap::default();
 
     for var in &env_vars {
-        let Some((display, source)) = resolved.get(var) else {
+        let Some(resolved_value) = resolved.get(var) else {
             continue;
         };
         let Some(locations) = graph.get_env_var_locations(var) else {
@@ -83,7 +121,13 @@ pub async fn handle_inlay_hints(
                 continue;
             }
 
-            let line = loc.range.end.line;
+            let full_range = graph
+                .direct_references()
+                .iter()
+                .find(|reference| reference.name_range == loc.range)
+                .map(|reference| &reference.full_range);
+            let position = hint_position(loc.range, full_range, &config);
+            let line = position.line;
             if config.max_hints_per_line > 0 {
                 let count = per_line.entry(line).or_insert(0);
                 if *count >= config.max_hints_per_line {
@@ -92,16 +136,27 @@ pub async fn handle_inlay_hints(
                 *count += 1;
             }
 
-            hints.push(InlayHint {
-                position: loc.range.end,
-                label: InlayHintLabel::String(format!(": \"{}\"", display)),
+            let mut hint = InlayHint {
+                position,
+                label: InlayHintLabel::String(PLACEHOLDER_LABEL.to_string()),
                 kind: Some(InlayHintKind::TYPE),
                 text_edits: None,
-                tooltip: Some(InlayHintTooltip::String(format!("Source: {}", source))),
+                tooltip: None,
                 padding_left: Some(false),
                 padding_right: Some(true),
                 data: None,
-            });
+            };
+            match resolved_value {
+                Some((display, source)) => fill_value(&mut hint, display, source),
+                None => {
+                    hint.data = serde_json::to_value(HintData {
+                        uri: uri.clone(),
+                        name: var.to_string(),
+                    })
+                    .ok();
+                }
+            }
+            hints.push(hint);
         }
     }
 
@@ -113,6 +168,40 @@ pub async fn handle_inlay_hints(
     Some(hints)
 }
 
+/// Resolves the value of a hint returned without one.
+///
+/// Hints that can't be resolved, e.g. because the variable is no longer
+/// defined, are returned unchanged, `data` included, so the client can ask
+/// again once the variable is defined.
+pub async fn handle_inlay_hint_resolve(mut hint: InlayHint, state: &ServerState) -> InlayHint {
+    let Some(data) = hint
+        .data
+        .clone()
+        .and_then(|data| serde_json::from_value::<HintData>(data).ok())
+    else {
+        return hint;
+    };
+    tracing::debug!("[HANDLE_INLAY_HINT_RESOLVE] name={}", data.name);
+
+    let Ok(file_path) = data.uri.to_file_path() else {
+        return hint;
+    };
+    let config = state.config.get_config().read().await.inlay_hints.clone();
+    let masking = masking_for_file(state, &file_path).await;
+
+    if let Some(r) = resolve_env_var_value(&data.name, &file_path, state).await {
+        let value = mask_value(&data.name, &r.value, &masking, MaskTarget::InlayHint);
+        fill_value(&mut hint, &format_value(&value, &config), &r.source);
+        hint.data = None;
+    }
+    hint
+}
+
+fn fill_value(hint: &mut InlayHint, display: &str, source: &str) {
+    hint.label = InlayHintLabel::String(format!(": \"{}\"", display));
+    hint.tooltip = Some(InlayHintTooltip::String(format!("Source: {}", source)));
+}
+
 fn format_value(value: &str, config: &InlayHintConfig) -> String {
     if value.is_empty() {
         return "(empty)".to_string();
@@ -131,6 +220,17 @@ fn format_value(value: &str, config: &InlayHintConfig) -> String {
     }
 }
 
+/// Places the hint after the name, or after the whole reference when it spans
+/// several lines and the config asks for that.
+fn hint_position(name_range: Range, full_range: Option<&Range>, config: &InlayHintConfig) -> Position {
+    match (config.multiline_position, full_range) {
+        (MultilineHintPosition::ReferenceEnd, Some(full)) if full.start.line != full.end.line => {
+            full.end
+        }
+        _ => name_range.end,
+    }
+}
+
 fn should_show(kind: EnvVarLocationKind, config: &InlayHintConfig) -> bool {
     match kind {
         EnvVarLocationKind::DirectReference => config.direct_references,
@@ -190,6 +290,30 @@ mod tests {
         assert_eq!(result, "(empty)");
     }
 
+    #[test]
+    fn test_hint_position_multiline() {
+        let name = make_range(1, 2, 1, 11);
+        let full = make_range(0, 10, 2, 1);
+
+        let config = InlayHintConfig::default();
+        assert_eq!(hint_position(name, Some(&full), &config), Position::new(2, 1));
+        assert_eq!(hint_position(name, None, &config), Position::new(1, 11));
+
+        let config = InlayHintConfig {
+            multiline_position: MultilineHintPosition::NameEnd,
+            ..Default::default()
+        };
+        assert_eq!(hint_position(name, Some(&full), &config), Position::new(1, 11));
+    }
+
+    #[test]
+    fn test_hint_position_single_line_uses_name_end() {
+        let name = make_range(0, 23, 0, 30);
+        let full = make_range(0, 11, 0, 32);
+        let config = InlayHintConfig::default();
+        assert_eq!(hint_position(name, Some(&full), &config), Position::new(0, 30));
+    }
+
     #[test]
     fn test_should_show_direct_reference() {
         let config = InlayHintConfig {
@@ -1,3 +1,5 @@
+mod code_action;
+mod code_lens;
 mod commands;
 mod completion;
 mod definition;
@@ -6,13 +8,24 @@ mod hover;
 mod inlay_hints;
 mod references;
 mod rename;
+mod selection_range;
+mod semantic_tokens;
 pub(crate) mod util;
 
+pub use code_action::handle_code_action;
+pub use code_lens::{handle_code_lens, handle_code_lens_resolve};
 pub use commands::handle_execute_command;
-pub use completion::handle_completion;
+pub use completion::{completion_response, handle_completion};
 pub use definition::handle_definition;
 pub use diagnostics::compute_diagnostics;
 pub use hover::handle_hover;
-pub use inlay_hints::handle_inlay_hints;
-pub use references::{handle_references, handle_workspace_symbol};
-pub use rename::{handle_prepare_rename, handle_rename};
+pub use inlay_hints::{handle_inlay_hint_resolve, handle_inlay_hints};
+pub use references::{
+    handle_document_symbol, handle_references, handle_references_with_cancellation,
+    handle_workspace_symbol, handle_workspace_symbol_with_cancellation,
+};
+pub use rename::{handle_prepare_rename, handle_rename, handle_rename_with_cancellation};
+pub use selection_range::handle_selection_range;
+pub use semantic_tokens::{
+    handle_semantic_tokens_full, semantic_tokens_legend, ENV_VARIABLE_TOKEN_TYPE,
+};
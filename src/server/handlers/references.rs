@@ -1,19 +1,34 @@
+use crate::analysis::indexer::{read_env_file, read_to_string_lossy};
+use crate::analysis::line_index::LineIndex;
 use crate::analysis::{
     AnalysisPipeline, BindingGraph, BindingResolver, CrossModuleResolution, CrossModuleResolver,
 };
-use crate::server::handlers::util::{get_identifier_at_position, korni_span_to_range, KorniEntryExt};
+use crate::server::cancellation::CancellationToken;
+use crate::server::handlers::util::{get_identifier_at_position, KorniEntryExt};
 use crate::server::state::ServerState;
 use crate::types::ImportContext;
 use korni::ParseOptions;
+use std::path::{Path, PathBuf};
 use std::time::Instant;
 use tower_lsp::lsp_types::{
-    Location, Position, Range, ReferenceParams, SymbolInformation, SymbolKind as LspSymbolKind,
-    Url, WorkspaceSymbolParams,
+    DocumentSymbol, DocumentSymbolParams, DocumentSymbolResponse, Location, Position, Range,
+    ReferenceParams, SymbolInformation, SymbolKind as LspSymbolKind, Url, WorkspaceSymbolParams,
 };
 
 pub async fn handle_references(
     params: ReferenceParams,
     state: &ServerState,
+) -> Option<Vec<Location>> {
+    handle_references_with_cancellation(params, state, &CancellationToken::new()).await
+}
+
+/// Finds references, returning `None` once `cancel` is triggered. The token
+/// is checked between files, so a cancelled request stops after the file
+/// it's on.
+pub async fn handle_references_with_cancellation(
+    params: ReferenceParams,
+    state: &ServerState,
+    cancel: &CancellationToken,
 ) -> Option<Vec<Location>> {
     let uri = &params.text_document_position.text_document.uri;
     let position = params.text_document_position.position;
@@ -27,7 +42,18 @@ pub async fn handle_references(
     );
     let start = Instant::now();
 
-    let env_var_name = match get_env_var_at_position(state, uri, position).await {
+    // On a key in an env file, the key itself is the declaration and the
+    // references are its consumers in code.
+    let env_file_key = if crate::server::util::is_env_file_uri(state, uri).await {
+        get_env_var_in_env_file(state, uri, position).await
+    } else {
+        None
+    };
+    let env_var_name = match env_file_key.as_ref() {
+        Some((name, _)) => Some(name.clone()),
+        None => get_env_var_at_position(state, uri, position).await,
+    };
+    let env_var_name = match env_var_name {
         Some(name) => name,
         None => {
             tracing::debug!(
@@ -37,18 +63,40 @@ pub async fn handle_references(
             return None;
         }
     };
+    if !crate::server::util::is_env_var_tracked(state, &env_var_name).await {
+        tracing::debug!(
+            "[HANDLE_REFERENCES_EXIT] untracked elapsed_ms={}",
+            start.elapsed().as_millis()
+        );
+        return None;
+    }
 
+    index_lazily(state, &env_var_name, cancel).await;
     let files = state.workspace_index.files_for_env_var(&env_var_name);
+    let include_binding_usages = {
+        let config = state.config.get_config();
+        let config = config.read().await;
+        config.references.include_binding_usages
+    };
 
     let mut locations = Vec::new();
 
     for file_uri in &files {
+        if cancel.is_cancelled() {
+            tracing::debug!(
+                "[HANDLE_REFERENCES_EXIT] cancelled elapsed_ms={}",
+                start.elapsed().as_millis()
+            );
+            return None;
+        }
         let usages = get_env_var_usages_in_file(state, file_uri, &env_var_name).await;
         for usage in usages {
-            if matches!(
-                usage.kind,
-                crate::analysis::resolver::UsageKind::BindingUsage
-            ) {
+            if !include_binding_usages
+                && matches!(
+                    usage.kind,
+                    crate::analysis::resolver::UsageKind::BindingUsage
+                )
+            {
                 continue;
             }
             locations.push(Location {
@@ -59,7 +107,14 @@ pub async fn handle_references(
     }
 
     if include_declaration {
-        if let Some(def_location) = find_env_definition(state, &env_var_name).await {
+        let declaration = match env_file_key {
+            Some((_, range)) => Some(Location {
+                uri: uri.clone(),
+                range,
+            }),
+            None => find_env_definition(state, &env_var_name, Some(uri)).await,
+        };
+        if let Some(def_location) = declaration {
             if !locations.iter().any(|loc| loc == &def_location) {
                 locations.push(def_location);
             }
@@ -112,6 +167,67 @@ pub(crate) async fn get_env_var_at_position(
     None
 }
 
+/// Finds the key under `position` in an env file, with its range.
+pub(crate) async fn get_env_var_in_env_file(
+    state: &ServerState,
+    uri: &Url,
+    position: Position,
+) -> Option<(String, Range)> {
+    let content = {
+        let doc_content = state
+            .document_manager
+            .get(uri)
+            .map(|doc| doc.content.clone());
+        match doc_content {
+            Some(c) => c,
+            None => {
+                if let Ok(path) = uri.to_file_path() {
+                    std::sync::Arc::new(read_to_string_lossy(&path).await.ok()?)
+                } else {
+                    return None;
+                }
+            }
+        }
+    };
+
+    let entries = korni::parse_with_options(&content, ParseOptions::full());
+    let line_index = LineIndex::new(&content);
+
+    for kv in entries.into_iter().filter_map(|e| e.into_valid_pair()) {
+        if let Some(key_span) = kv.key_span {
+            let range = line_index.range(key_span.start.offset, key_span.end.offset);
+
+            if position.line >= range.start.line
+                && position.line <= range.end.line
+                && position.character >= range.start.character
+                && position.character <= range.end.character
+            {
+                return Some((kv.key.as_ref().to_string(), range));
+            }
+        }
+    }
+
+    None
+}
+
+/// Under `workspace.lazy_index` only opened files are indexed, so the
+/// unopened files that mention `name` are indexed before the index is asked
+/// which files use it.
+pub(crate) async fn index_lazily(state: &ServerState, name: &str, cancel: &CancellationToken) {
+    let env_files = {
+        let config = state.config.get_config();
+        let config = config.read().await;
+        if !config.workspace.lazy_index {
+            return;
+        }
+        config.workspace.env_files.clone()
+    };
+    state
+        .indexer
+        .index_files_mentioning(name, &env_files, cancel)
+        .await;
+}
+
 async fn get_env_var_from_cross_module(
     state: &ServerState,
     uri: &Url,
@@ -136,7 +252,7 @@ async fn get_env_var_from_cross_module(
         state.languages.clone(),
     );
 
-    let is_default = original_name == identifier_name;
+    let is_default = original_name == module_path;
 
     match cross_resolver.resolve_import(uri, &module_path, &original_name, is_default) {
         CrossModuleResolution::EnvVar { name, .. } => Some(name.to_string()),
@@ -164,7 +280,10 @@ pub(crate) async fn get_env_var_usages_in_file(
 
 async fn parse_file_for_binding_graph(state: &ServerState, uri: &Url) -> Option<BindingGraph> {
     let path = uri.to_file_path().ok()?;
-    let content = tokio::fs::read_to_string(&path).await.ok()?;
+    let content = read_to_string_lossy(&path).await.ok()?;
+    if let Some(graph) = state.document_manager.analyze_with_detector(uri, &content) {
+        return Some(graph);
+    }
     let lang = state.languages.get_for_uri(uri)?;
 
     let query_engine = state.document_manager.query_engine();
@@ -182,46 +301,159 @@ async fn parse_file_for_binding_graph(state: &ServerState, uri: &Url) -> Option<
     Some(graph)
 }
 
-pub(crate) async fn find_env_definition(state: &ServerState, env_var_name: &str) -> Option<Location> {
+/// Directories that may hold a definition for `from`, nearest first.
+///
+/// Walks from the consuming file's directory up to the workspace root, so a
+/// service's own `.env` wins over the root one. Without a file, or for files
+/// outside the workspace, only the root is searched.
+fn env_search_dirs(workspace_root: &Path, from: Option<&Url>) -> Vec<PathBuf> {
+    let start_dir = from
+        .and_then(|uri| uri.to_file_path().ok())
+        .and_then(|path| path.parent().map(Path::to_path_buf))
+        .filter(|dir| dir.starts_with(workspace_root));
+
+    let Some(start_dir) = start_dir else {
+        return vec![workspace_root.to_path_buf()];
+    };
+
+    start_dir
+        .ancestors()
+        .take_while(|dir| dir.starts_with(workspace_root))
+        .map(Path::to_path_buf)
+        .collect()
+}
+
+pub(crate) async fn find_env_definition(
+    state: &ServerState,
+    env_var_name: &str,
+    from: Option<&Url>,
+) -> Option<Location> {
     let workspace_root = crate::server::util::get_workspace_root(&state.core.workspace).await;
 
-    let env_patterns: Vec<String> = {
+    let (env_patterns, max_bytes, follow_includes): (Vec<String>, u64, bool) = {
         let config = state.config.get_config();
         let config = config.read().await;
-        config
-            .workspace
-            .env_files
-            .iter()
-            .map(|s| s.to_string())
-            .collect()
+        (
+            config
+                .workspace
+                .env_files
+                .iter()
+                .filter(|pattern| !pattern.starts_with('!'))
+                .map(|s| s.to_string())
+                .collect(),
+            config.workspace.max_env_file_bytes,
+            config.workspace.follow_env_includes,
+        )
     };
 
-    for pattern in env_patterns {
-        let env_path = workspace_root.join(&pattern);
-        if env_path.exists() {
-            if let Ok(content) = tokio::fs::read_to_string(&env_path).await {
-                let entries = korni::parse_with_options(&content, ParseOptions::full());
-
-                for kv in entries.into_iter().filter_map(|e| e.into_valid_pair()) {
-                    if kv.key.as_ref() == env_var_name {
-                        if let Some(key_span) = kv.key_span {
-                            let range = korni_span_to_range(&content, key_span);
-                            let uri = Url::from_file_path(&env_path).ok()?;
-                            return Some(Location { uri, range });
-                        }
-                    }
+    let mut searched = Vec::new();
+    for dir in env_search_dirs(&workspace_root, from) {
+        for pattern in &env_patterns {
+            let env_path = dir.join(pattern);
+            if !env_path.exists() {
+                continue;
+            }
+            if let Some(content) = read_env_file(&env_path, max_bytes).await {
+                if let Some(location) = key_location(&content, &env_path, env_var_name) {
+                    return Some(location);
                 }
             }
+            searched.push(env_path);
         }
     }
 
+    if follow_includes {
+        let included =
+            crate::server::handlers::util::included_env_files(state, &searched, max_bytes).await;
+        return included
+            .iter()
+            .find_map(|(path, content)| key_location(content, path, env_var_name));
+    }
+
     None
 }
 
+/// Location of `env_var_name`'s key in an env file's content.
+fn key_location(content: &str, env_path: &Path, env_var_name: &str) -> Option<Location> {
+    let line_index = LineIndex::new(content);
+    korni::parse_with_options(content, ParseOptions::full())
+        .into_iter()
+        .filter_map(|e| e.into_valid_pair())
+        .find(|kv| kv.key.as_ref() == env_var_name)
+        .and_then(|kv| {
+            let key_span = kv.key_span?;
+            let range = line_index.range(key_span.start.offset, key_span.end.offset);
+            let uri = Url::from_file_path(env_path).ok()?;
+            Some(Location { uri, range })
+        })
+}
+
+/// Lists the variables an env file defines. Each symbol's range spans the
+/// whole entry, including every line of a multiline quoted value; the
+/// selection range is just the key. Values are never included.
 #[allow(deprecated)]
+pub async fn handle_document_symbol(
+    params: DocumentSymbolParams,
+    state: &ServerState,
+) -> Option<DocumentSymbolResponse> {
+    let uri = &params.text_document.uri;
+    if !crate::server::util::is_env_file_uri(state, uri).await {
+        return None;
+    }
+
+    let open_content = state.document_manager.get(uri).map(|doc| doc.content.to_string());
+    let content = match open_content {
+        Some(content) => content,
+        None => read_to_string_lossy(&uri.to_file_path().ok()?).await.ok()?,
+    };
+    let kind: LspSymbolKind = {
+        let config = state.config.get_config();
+        let config = config.read().await;
+        config.features.symbol_kind.into()
+    };
+
+    let line_index = LineIndex::new(&content);
+    let symbols = korni::parse_with_options(&content, ParseOptions::full())
+        .into_iter()
+        .filter_map(|entry| entry.into_valid_pair())
+        .filter_map(|kv| {
+            let key_span = kv.key_span?;
+            let end = match (kv.close_quote_pos, kv.value_span) {
+                (Some(close), _) => close.offset + 1,
+                (None, Some(value)) => value.end.offset,
+                (None, None) => key_span.end.offset,
+            };
+            let start = kv.export_span.map_or(key_span.start.offset, |s| s.start.offset);
+            Some(DocumentSymbol {
+                name: kv.key.to_string(),
+                detail: None,
+                kind,
+                tags: None,
+                deprecated: None,
+                range: line_index.range(start, end),
+                selection_range: line_index.range(key_span.start.offset, key_span.end.offset),
+                children: None,
+            })
+        })
+        .collect();
+
+    Some(DocumentSymbolResponse::Nested(symbols))
+}
+
 pub async fn handle_workspace_symbol(
     params: WorkspaceSymbolParams,
     state: &ServerState,
+) -> Option<Vec<SymbolInformation>> {
+    handle_workspace_symbol_with_cancellation(params, state, &CancellationToken::new()).await
+}
+
+/// Lists workspace symbols, returning `None` once `cancel` is triggered.
+/// The token is checked before each variable's definition lookup.
+#[allow(deprecated)]
+pub async fn handle_workspace_symbol_with_cancellation(
+    params: WorkspaceSymbolParams,
+    state: &ServerState,
+    cancel: &CancellationToken,
 ) -> Option<Vec<SymbolInformation>> {
     let query = params.query.to_lowercase();
     tracing::debug!("[HANDLE_WORKSPACE_SYMBOL_ENTER] query={}", query);
@@ -237,14 +469,27 @@ pub async fn handle_workspace_symbol(
         return None;
     }
 
+    let kind: LspSymbolKind = {
+        let config = state.config.get_config();
+        let config = config.read().await;
+        config.features.symbol_kind.into()
+    };
+
     let mut symbols = Vec::new();
 
     for var_name in all_vars {
         if !query.is_empty() && !var_name.to_lowercase().contains(&query) {
             continue;
         }
+        if cancel.is_cancelled() {
+            tracing::debug!(
+                "[HANDLE_WORKSPACE_SYMBOL_EXIT] cancelled elapsed_ms={}",
+                start.elapsed().as_millis()
+            );
+            return None;
+        }
 
-        let location = if let Some(def_location) = find_env_definition(state, &var_name).await {
+        let location = if let Some(def_location) = find_env_definition(state, &var_name, None).await {
             def_location
         } else {
             let files = state.workspace_index.files_for_env_var(&var_name);
@@ -268,7 +513,7 @@ pub async fn handle_workspace_symbol(
 
         symbols.push(SymbolInformation {
             name: var_name.to_string(),
-            kind: LspSymbolKind::CONSTANT,
+            kind,
             location,
             tags: None,
             deprecated: None,
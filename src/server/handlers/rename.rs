@@ -1,9 +1,12 @@
+use crate::server::cancellation::CancellationToken;
+use crate::server::config::RenameConcurrency;
 use crate::server::handlers::references::{
-    find_env_definition, get_env_var_at_position, get_env_var_usages_in_file,
+    find_env_definition, get_env_var_at_position, get_env_var_in_env_file,
+    get_env_var_usages_in_file, index_lazily,
 };
-use crate::server::handlers::util::{is_valid_env_var_name, korni_span_to_range, KorniEntryExt};
+use crate::server::handlers::util::is_valid_env_var_name;
 use crate::server::state::ServerState;
-use korni::ParseOptions;
+use crate::server::util::is_env_file_uri;
 use std::collections::HashMap;
 use std::time::Instant;
 use tower_lsp::lsp_types::{
@@ -26,6 +29,15 @@ pub async fn handle_prepare_rename(
         return Some(PrepareRenameResponse::Range(range));
     }
 
+    if let Some(ranges) = state.document_manager.get_local_binding_ranges(uri, position) {
+        if is_exported_binding(state, uri, position) {
+            return None;
+        }
+        let contains = |r: &Range| r.start <= position && position <= r.end;
+        let range = ranges.iter().find(|r| contains(r)).or(ranges.first());
+        return range.copied().map(PrepareRenameResponse::Range);
+    }
+
     let (env_var_name, range) = get_env_var_with_range(state, uri, position)?;
 
     if !is_valid_env_var_name(&env_var_name) {
@@ -36,6 +48,17 @@ pub async fn handle_prepare_rename(
 }
 
 pub async fn handle_rename(params: RenameParams, state: &ServerState) -> Option<WorkspaceEdit> {
+    handle_rename_with_cancellation(params, state, &CancellationToken::new()).await
+}
+
+/// Renames across the workspace, returning `None` once `cancel` is
+/// triggered. The token is checked between files, and a cancelled rename
+/// makes no edits at all.
+pub async fn handle_rename_with_cancellation(
+    params: RenameParams,
+    state: &ServerState,
+    cancel: &CancellationToken,
+) -> Option<WorkspaceEdit> {
     let uri = &params.text_document_position.text_document.uri;
     let position = params.text_document_position.position;
     let new_name = &params.new_name;
@@ -43,6 +66,41 @@ pub async fn handle_rename(params: RenameParams, state: &ServerState) -> Option<
     tracing::debug!("[HANDLE_RENAME_ENTER] uri={} new_name={}", uri, new_name);
     let start = Instant::now();
 
+    // Renaming the local name of a binding only touches this file, and the
+    // new name follows the language's identifier rules. Importers refer to
+    // an exported binding by name, so it's not renamed at all.
+    if let Some(ranges) = state.document_manager.get_local_binding_ranges(uri, position) {
+        if is_exported_binding(state, uri, position) {
+            tracing::debug!(
+                "[HANDLE_RENAME_EXIT] exported_binding elapsed_ms={}",
+                start.elapsed().as_millis()
+            );
+            return None;
+        }
+        if !state.document_manager.is_valid_identifier(uri, new_name) {
+            tracing::debug!(
+                "[HANDLE_RENAME_EXIT] invalid_identifier elapsed_ms={}",
+                start.elapsed().as_millis()
+            );
+            return None;
+        }
+        let edits = ranges
+            .into_iter()
+            .map(|range| TextEdit {
+                range,
+                new_text: new_name.to_string(),
+            })
+            .collect();
+        tracing::debug!(
+            "[HANDLE_RENAME_EXIT] local_binding elapsed_ms={}",
+            start.elapsed().as_millis()
+        );
+        return Some(WorkspaceEdit {
+            changes: Some(HashMap::from([(uri.clone(), edits)])),
+            ..Default::default()
+        });
+    }
+
     if !is_valid_env_var_name(new_name) {
         tracing::debug!(
             "[HANDLE_RENAME_EXIT] invalid_name elapsed_ms={}",
@@ -51,6 +109,25 @@ pub async fn handle_rename(params: RenameParams, state: &ServerState) -> Option<
         return None;
     }
 
+    let (concurrency, across_packages) = {
+        let config = state.config.get_config();
+        let config = config.read().await;
+        (config.rename.concurrency, config.rename.across_packages)
+    };
+    let _rename_guard = match concurrency {
+        RenameConcurrency::Queue => state.rename_lock.lock().await,
+        RenameConcurrency::Reject => match state.rename_lock.try_lock() {
+            Ok(guard) => guard,
+            Err(_) => {
+                tracing::debug!(
+                    "[HANDLE_RENAME_EXIT] rejected_concurrent elapsed_ms={}",
+                    start.elapsed().as_millis()
+                );
+                return None;
+            }
+        },
+    };
+
     let is_source_env_file = is_env_file_uri(state, uri).await;
 
     let (old_name, source_range) = if is_source_env_file {
@@ -69,15 +146,55 @@ pub async fn handle_rename(params: RenameParams, state: &ServerState) -> Option<
         });
     }
 
+    // A var with the same name in another package is a different var.
+    let package_root = if across_packages {
+        None
+    } else {
+        state.get_env_context(uri).map(|ctx| ctx.package_root)
+    };
+    let in_package = |file_uri: &Url| match &package_root {
+        Some(root) => state
+            .get_env_context(file_uri)
+            .is_some_and(|ctx| &ctx.package_root == root),
+        None => true,
+    };
+
+    // The env file that defines the var is renamed even when it sits above
+    // the package, like a root `.env` shared by every package.
+    let definition = find_env_definition(state, &old_name, Some(uri)).await;
+    let defining_file = definition.as_ref().map(|def| &def.uri);
+    // A definition outside the package is shared, so every package that
+    // resolves the var to it gets renamed too.
+    let shared_definition = defining_file.filter(|def_uri| !in_package(def_uri));
+
+    index_lazily(state, &old_name, cancel).await;
     let files = state.workspace_index.files_for_env_var(&old_name);
-    for file_uri in &files {
+    for file_uri in files.iter() {
+        if cancel.is_cancelled() {
+            tracing::debug!(
+                "[HANDLE_RENAME_EXIT] cancelled elapsed_ms={}",
+                start.elapsed().as_millis()
+            );
+            return None;
+        }
+        let included = in_package(file_uri)
+            || defining_file == Some(file_uri)
+            || match shared_definition {
+                Some(def_uri) => find_env_definition(state, &old_name, Some(file_uri))
+                    .await
+                    .is_some_and(|def| &def.uri == def_uri),
+                None => false,
+            };
+        if !included {
+            continue;
+        }
         let edits = collect_rename_edits(state, file_uri, &old_name, new_name).await;
         if !edits.is_empty() {
             changes.insert(file_uri.clone(), edits);
         }
     }
 
-    if let Some(def_location) = find_env_definition(state, &old_name).await {
+    if let Some(def_location) = definition {
         if !changes.contains_key(&def_location.uri) {
             changes
                 .entry(def_location.uri.clone())
@@ -108,66 +225,29 @@ pub async fn handle_rename(params: RenameParams, state: &ServerState) -> Option<
     }
 }
 
-async fn is_env_file_uri(state: &ServerState, uri: &Url) -> bool {
-    let file_name = match uri
-        .to_file_path()
-        .ok()
-        .and_then(|p| p.file_name().map(|s| s.to_string_lossy().to_string()))
-    {
-        Some(name) => name,
-        None => return false,
-    };
-
-    let config = state.config.get_config();
-    let config = config.read().await;
-    config.workspace.env_files.iter().any(|pattern| {
-        glob::Pattern::new(pattern)
-            .map(|p| p.matches(&file_name))
-            .unwrap_or(false)
+/// Whether the binding at `position` is exported from `uri`.
+fn is_exported_binding(state: &ServerState, uri: &Url, position: Position) -> bool {
+    let name = state
+        .document_manager
+        .get_env_binding_cloned(uri, position)
+        .map(|binding| binding.binding_name)
+        .or_else(|| {
+            state
+                .document_manager
+                .get_binding_usage_cloned(uri, position)
+                .map(|usage| usage.name)
+        });
+    name.is_some_and(|name| {
+        state.workspace_index.get_exports_ref(uri).is_some_and(|exports| {
+            exports
+                .named_exports
+                .values()
+                .chain(exports.default_export.as_ref())
+                .any(|export| *export.local_name_or_exported() == name)
+        })
     })
 }
 
-async fn get_env_var_in_env_file(
-    state: &ServerState,
-    uri: &Url,
-    position: Position,
-) -> Option<(String, Range)> {
-    let content = {
-        let doc_content = state
-            .document_manager
-            .get(uri)
-            .map(|doc| doc.content.clone());
-        match doc_content {
-            Some(c) => c,
-            None => {
-                if let Ok(path) = uri.to_file_path() {
-                    std::sync::Arc::new(tokio::fs::read_to_string(&path).await.ok()?)
-                } else {
-                    return None;
-                }
-            }
-        }
-    };
-
-    let entries = korni::parse_with_options(&content, ParseOptions::full());
-
-    for kv in entries.into_iter().filter_map(|e| e.into_valid_pair()) {
-        if let Some(key_span) = kv.key_span {
-            let range = korni_span_to_range(&content, key_span);
-
-            if position.line >= range.start.line
-                && position.line <= range.end.line
-                && position.character >= range.start.character
-                && position.character <= range.end.character
-            {
-                return Some((kv.key.as_ref().to_string(), range));
-            }
-        }
-    }
-
-    None
-}
-
 fn get_env_var_with_range(
     state: &ServerState,
     uri: &Url,
@@ -0,0 +1,77 @@
+use crate::analysis::pipeline::ts_to_lsp_range;
+use crate::server::state::ServerState;
+use tower_lsp::lsp_types::{Position, Range, SelectionRange, SelectionRangeParams};
+use tree_sitter::Point;
+
+/// Expands from an env var name to its full access expression, then outward
+/// through the enclosing syntax nodes up to (but excluding) the whole file.
+pub async fn handle_selection_range(
+    params: SelectionRangeParams,
+    state: &ServerState,
+) -> Option<Vec<SelectionRange>> {
+    let uri = &params.text_document.uri;
+    let tree = state.document_manager.get(uri)?.tree.clone();
+
+    let mut found = false;
+    let ranges = params
+        .positions
+        .iter()
+        .map(|&position| {
+            let Some(reference) = state
+                .document_manager
+                .get_env_reference_cloned(uri, position)
+            else {
+                return SelectionRange {
+                    range: Range::new(position, position),
+                    parent: None,
+                };
+            };
+            found = true;
+
+            let mut chain = vec![reference.name_range];
+            if reference.full_range != reference.name_range {
+                chain.push(reference.full_range);
+            }
+            if let Some(tree) = &tree {
+                let root = tree.root_node();
+                let start = to_point(reference.full_range.start);
+                let end = to_point(reference.full_range.end);
+                let mut node = root.descendant_for_point_range(start, end);
+                while let Some(current) = node {
+                    if current.id() == root.id() {
+                        break;
+                    }
+                    let range = ts_to_lsp_range(current.range());
+                    if chain
+                        .last()
+                        .is_some_and(|last| contains(range, *last) && range != *last)
+                    {
+                        chain.push(range);
+                    }
+                    node = current.parent();
+                }
+            }
+
+            chain
+                .into_iter()
+                .rev()
+                .fold(None, |parent, range| {
+                    Some(SelectionRange {
+                        range,
+                        parent: parent.map(Box::new),
+                    })
+                })
+                .expect("chain holds at least the name range")
+        })
+        .collect();
+
+    found.then_some(ranges)
+}
+
+fn to_point(position: Position) -> Point {
+    Point::new(position.line as usize, position.character as usize)
+}
+
+fn contains(outer: Range, inner: Range) -> bool {
+    outer.start <= inner.start && inner.end <= outer.end
+}
@@ -0,0 +1,91 @@
+use crate::analysis::BindingResolver;
+use crate::server::state::ServerState;
+use crate::server::util::is_env_var_tracked;
+use std::time::Instant;
+use tower_lsp::lsp_types::{
+    Range, SemanticToken, SemanticTokenType, SemanticTokens, SemanticTokensLegend,
+    SemanticTokensParams, SemanticTokensResult,
+};
+
+/// The only token type in the legend, so its index is always 0.
+pub const ENV_VARIABLE_TOKEN_TYPE: &str = "envVariable";
+
+/// Legend advertised in the server capabilities.
+pub fn semantic_tokens_legend() -> SemanticTokensLegend {
+    SemanticTokensLegend {
+        token_types: vec![SemanticTokenType::new(ENV_VARIABLE_TOKEN_TYPE)],
+        token_modifiers: Vec::new(),
+    }
+}
+
+/// Tags every env var location in the document (references, bindings and
+/// their usages) with the `envVariable` token type.
+pub async fn handle_semantic_tokens_full(
+    params: SemanticTokensParams,
+    state: &ServerState,
+) -> Option<SemanticTokensResult> {
+    let uri = &params.text_document.uri;
+    let start = Instant::now();
+    tracing::debug!("[HANDLE_SEMANTIC_TOKENS_ENTER] uri={}", uri);
+
+    if !state.config.is_semantic_tokens_enabled() {
+        tracing::debug!("[HANDLE_SEMANTIC_TOKENS_EXIT] feature disabled");
+        return None;
+    }
+
+    let graph = state.document_manager.get_binding_graph(uri)?;
+    let resolver = BindingResolver::new(&graph);
+
+    let mut ranges = Vec::new();
+    for var in resolver.all_env_vars() {
+        if !is_env_var_tracked(state, &var).await {
+            continue;
+        }
+        if let Some(locations) = graph.get_env_var_locations(&var) {
+            ranges.extend(locations.iter().map(|loc| loc.range));
+        }
+    }
+
+    let data = encode_tokens(ranges);
+    tracing::debug!(
+        "[HANDLE_SEMANTIC_TOKENS_EXIT] count={} elapsed_ms={}",
+        data.len(),
+        start.elapsed().as_millis()
+    );
+    Some(SemanticTokensResult::Tokens(SemanticTokens {
+        result_id: None,
+        data,
+    }))
+}
+
+/// Delta-encodes ranges as the spec requires: each token's line is relative
+/// to the previous token, and its start character too when on the same line.
+/// Tokens can't span lines or overlap, so those ranges are dropped.
+fn encode_tokens(mut ranges: Vec<Range>) -> Vec<SemanticToken> {
+    ranges.retain(|range| range.start.line == range.end.line && range.end > range.start);
+    ranges.sort_by_key(|range| (range.start.line, range.start.character));
+
+    let mut tokens = Vec::with_capacity(ranges.len());
+    let mut previous: Option<Range> = None;
+    for range in ranges {
+        let (delta_line, delta_start) = match previous {
+            Some(prev) if prev.start.line == range.start.line => {
+                if range.start.character < prev.end.character {
+                    continue;
+                }
+                (0, range.start.character - prev.start.character)
+            }
+            Some(prev) => (range.start.line - prev.start.line, range.start.character),
+            None => (range.start.line, range.start.character),
+        };
+        tokens.push(SemanticToken {
+            delta_line,
+            delta_start,
+            length: range.end.character - range.start.character,
+            token_type: 0,
+            token_modifiers_bitset: 0,
+        });
+        previous = Some(range);
+    }
+    tokens
+}
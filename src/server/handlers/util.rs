@@ -1,6 +1,7 @@
+use crate::server::config::{MaskMode, MaskTarget, MaskingConfig};
 use crate::server::state::ServerState;
 use abundantis::source::VariableSource;
-use std::path::Path;
+use std::path::{Path, PathBuf};
 use std::time::Instant;
 use tower_lsp::lsp_types::{Position, Range, Url};
 
@@ -30,6 +31,60 @@ pub(crate) struct ResolvedEnvVarValue {
     pub value: String,
     pub source: String,
     pub description: Option<compact_str::CompactString>,
+    /// Inferred from the raw value, so it survives masking.
+    pub value_type: ValueType,
+}
+
+/// What a value looks like, shown as a badge in hovers. Display only.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum ValueType {
+    String,
+    Boolean,
+    Number,
+    Url,
+    Json,
+}
+
+impl ValueType {
+    fn badge(self) -> Option<&'static str> {
+        match self {
+            ValueType::String => None,
+            ValueType::Boolean => Some("boolean"),
+            ValueType::Number => Some("number"),
+            ValueType::Url => Some("url"),
+            ValueType::Json => Some("json"),
+        }
+    }
+}
+
+/// Infers a value's type from its text. Anything unrecognized is a string.
+pub(crate) fn infer_value_type(value: &str) -> ValueType {
+    let value = value.trim();
+    if value.eq_ignore_ascii_case("true") || value.eq_ignore_ascii_case("false") {
+        return ValueType::Boolean;
+    }
+    // f64 also parses words like `inf` and `NaN`, which read as strings
+    let numeric_chars = value
+        .bytes()
+        .all(|b| b.is_ascii_digit() || matches!(b, b'.' | b'-' | b'+' | b'e' | b'E'));
+    if numeric_chars && value.bytes().any(|b| b.is_ascii_digit()) && value.parse::<f64>().is_ok() {
+        return ValueType::Number;
+    }
+    if let Some((scheme, rest)) = value.split_once("://") {
+        let valid_scheme = scheme.starts_with(|c: char| c.is_ascii_alphabetic())
+            && scheme
+                .chars()
+                .all(|c| c.is_ascii_alphanumeric() || matches!(c, '+' | '-' | '.'));
+        if valid_scheme && !rest.is_empty() && !value.contains(char::is_whitespace) {
+            return ValueType::Url;
+        }
+    }
+    let bracketed = (value.starts_with('{') && value.ends_with('}'))
+        || (value.starts_with('[') && value.ends_with(']'));
+    if bracketed && serde_json::from_str::<serde_json::Value>(value).is_ok() {
+        return ValueType::Json;
+    }
+    ValueType::String
 }
 
 pub(crate) async fn resolve_env_var_value(
@@ -38,8 +93,24 @@ pub(crate) async fn resolve_env_var_value(
     state: &ServerState,
 ) -> Option<ResolvedEnvVarValue> {
     let start = Instant::now();
-    let resolved =
-        crate::server::util::safe_get_for_file(&state.core, env_var_name, file_path).await?;
+    let timeout = state.config.resolution_timeout();
+    let Some(resolved) =
+        crate::server::util::safe_get_for_file(&state.core, env_var_name, file_path, timeout)
+            .await
+    else {
+        let included = resolve_from_included_files(env_var_name, file_path, state).await;
+        if let Some((path, value)) = included {
+            let workspace_root =
+                crate::server::util::get_workspace_root(&state.core.workspace).await;
+            return Some(ResolvedEnvVarValue {
+                value_type: infer_value_type(&value),
+                value,
+                source: path.strip_prefix(&workspace_root).unwrap_or(&path).display().to_string(),
+                description: None,
+            });
+        }
+        return resolve_from_fallback_sources(env_var_name, file_path, state).await;
+    };
     let elapsed = start.elapsed();
     if elapsed.as_millis() > 100 {
         tracing::warn!(
@@ -58,9 +129,222 @@ pub(crate) async fn resolve_env_var_value(
         value: resolved.resolved_value.to_string(),
         source: source_str,
         description: resolved.description.clone(),
+        value_type: infer_value_type(&resolved.resolved_value),
     })
 }
 
+/// The sources defining `env_var_name` for `file_path` other than the one
+/// it resolves from, as `(source, raw value)` pairs in precedence order.
+pub(crate) async fn overridden_definitions(
+    env_var_name: &str,
+    file_path: &Path,
+    winning_source: &str,
+    state: &ServerState,
+) -> Vec<(String, String)> {
+    use abundantis::config::SourcePrecedence;
+    use abundantis::source::SourceType;
+
+    let precedence = {
+        let config = state.config.get_config();
+        let config = config.read().await;
+        config.resolution.precedence.clone()
+    };
+    let workspace_root = crate::server::util::get_workspace_root(&state.core.workspace).await;
+    let registry = &state.core.registry;
+
+    // Only one env file per directory is active, so the files it masks are
+    // found among the registered ones in the document's directory chain.
+    let mut env_files = state.core.active_env_files(file_path);
+    let mut masked: Vec<_> = registry
+        .registered_file_paths()
+        .into_iter()
+        .filter(|path| {
+            let absolute = workspace_root.join(path);
+            absolute
+                .parent()
+                .is_some_and(|dir| file_path.starts_with(dir))
+                && !env_files.iter().any(|active| active.ends_with(path))
+        })
+        .collect();
+    masked.sort();
+    env_files.extend(masked);
+
+    let sources = precedence.iter().flat_map(|source| match source {
+        SourcePrecedence::Shell => registry.sources_of_type(SourceType::Shell),
+        SourcePrecedence::Remote => registry.sources_of_type(SourceType::Remote),
+        SourcePrecedence::File => registry.sources_for_paths(&env_files),
+    });
+
+    let mut definitions = Vec::new();
+    for source in sources {
+        let Ok(snapshot) = source.load() else {
+            continue;
+        };
+        let Some(variable) = snapshot
+            .variables
+            .iter()
+            .find(|v| v.key == env_var_name && !v.is_commented)
+        else {
+            continue;
+        };
+        let label = format_source(&variable.source, &workspace_root);
+        if label != winning_source {
+            definitions.push((label, variable.raw_value.to_string()));
+        }
+    }
+    definitions
+}
+
+/// Formats a defining file's URI relative to the workspace root.
+pub(crate) fn format_uri_source(uri: &Url, root: &Path) -> String {
+    match uri.to_file_path() {
+        Ok(path) => path.strip_prefix(root).unwrap_or(&path).display().to_string(),
+        Err(_) => uri.to_string(),
+    }
+}
+
+/// Looks a variable up in the files that `file_path`'s active env files pull
+/// in with `# include:` comments. Returns `None` unless
+/// `workspace.follow_env_includes` is on.
+pub(crate) async fn resolve_from_included_files(
+    env_var_name: &str,
+    file_path: &Path,
+    state: &ServerState,
+) -> Option<(PathBuf, String)> {
+    let max_bytes = {
+        let config = state.config.get_config();
+        let config = config.read().await;
+        if !config.workspace.follow_env_includes {
+            return None;
+        }
+        config.workspace.max_env_file_bytes
+    };
+    let active_files = state.core.active_env_files(file_path);
+    let included = included_env_files(state, &active_files, max_bytes).await;
+    crate::analysis::env_include::find_included_key(&included, env_var_name)
+}
+
+/// The files `env_files` pull in with `# include:` comments, nearest first.
+/// Chains of indexed env files come from the workspace index; others are
+/// read from disk.
+pub(crate) async fn included_env_files(
+    state: &ServerState,
+    env_files: &[PathBuf],
+    max_bytes: u64,
+) -> Vec<(PathBuf, String)> {
+    let workspace_root = crate::server::util::get_workspace_root(&state.core.workspace).await;
+    let mut files: Vec<(PathBuf, String)> = Vec::new();
+    for env_file in env_files {
+        let cached = Url::from_file_path(env_file)
+            .ok()
+            .filter(|uri| state.workspace_index.is_file_indexed(uri))
+            .map(|uri| state.workspace_index.env_includes(&uri));
+        let chain = match cached {
+            Some(chain) => chain.map(|chain| chain.to_vec()).unwrap_or_default(),
+            None => {
+                let roots = std::slice::from_ref(env_file);
+                crate::analysis::env_include::included_files(roots, &workspace_root, max_bytes)
+                    .await
+            }
+        };
+        for (path, content) in chain {
+            if !env_files.contains(&path) && !files.iter().any(|(seen, _)| *seen == path) {
+                files.push((path, content));
+            }
+        }
+    }
+    files
+}
+
+/// Resolves variables abundantis doesn't know about: external definitions
+/// (deployment configs, source plugins) first, then the embedder's env
+/// source provider. Dynamic definitions have no value to resolve to.
+async fn resolve_from_fallback_sources(
+    env_var_name: &str,
+    file_path: &Path,
+    state: &ServerState,
+) -> Option<ResolvedEnvVarValue> {
+    let from = Url::from_file_path(file_path).ok();
+    let external = state
+        .workspace_index
+        .external_definition(env_var_name, from.as_ref())
+        .filter(|(_, definition)| !definition.dynamic);
+    if let Some((uri, definition)) = external {
+        let workspace_root = crate::server::util::get_workspace_root(&state.core.workspace).await;
+        return Some(ResolvedEnvVarValue {
+            value: definition.value.to_string(),
+            source: format_uri_source(&uri, &workspace_root),
+            description: None,
+            value_type: infer_value_type(&definition.value),
+        });
+    }
+
+    let provider = state.env_source_provider.as_ref()?;
+    let value = provider.resolve(env_var_name)?;
+    Some(ResolvedEnvVarValue {
+        value_type: infer_value_type(&value),
+        value,
+        source: provider.name().to_string(),
+        description: None,
+    })
+}
+
+/// Characters left visible at each end of a value in partial mode.
+const PARTIAL_VISIBLE: usize = 3;
+/// Values shorter than this are fully masked even in partial mode.
+const PARTIAL_MIN_LEN: usize = 2 * PARTIAL_VISIBLE + 3;
+
+/// Returns the masking config for values displayed in `file_path`, with
+/// masking turned off when the file matches `masking.mask_exclude_globs`.
+pub(crate) async fn masking_for_file(state: &ServerState, file_path: &Path) -> MaskingConfig {
+    let mut masking = state.config.get_config().read().await.masking.clone();
+    if masking.enabled && !masking.mask_exclude_globs.is_empty() {
+        let root = crate::server::util::get_workspace_root(&state.core.workspace).await;
+        masking.enabled = !crate::server::util::matches_workspace_glob(
+            &masking.mask_exclude_globs,
+            file_path,
+            &root,
+        );
+    }
+    masking
+}
+
+/// Masks `key`'s value for display on `target`, or returns it unchanged when
+/// masking doesn't apply there. Every display path goes through this so
+/// the same secret looks the same everywhere.
+pub(crate) fn mask_value(
+    key: &str,
+    value: &str,
+    masking: &MaskingConfig,
+    target: MaskTarget,
+) -> String {
+    if !masking.masks_key(key, target) {
+        return value.to_string();
+    }
+    value
+        .split('\n')
+        .map(|line| mask_line(line, masking.mode))
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+fn mask_line(line: &str, mode: MaskMode) -> String {
+    let len = line.chars().count();
+    if mode == MaskMode::Full || len < PARTIAL_MIN_LEN {
+        return "*".repeat(len);
+    }
+    line.chars()
+        .enumerate()
+        .map(|(i, c)| {
+            if i < PARTIAL_VISIBLE || i >= len - PARTIAL_VISIBLE {
+                c
+            } else {
+                '*'
+            }
+        })
+        .collect()
+}
+
 pub(crate) fn format_hover_markdown(
     env_var_name: &str,
     identifier_name: Option<&str>,
@@ -71,18 +355,15 @@ pub(crate) fn format_hover_markdown(
         _ => format!("**`{}`**", env_var_name),
     };
 
-    let value_formatted = if resolved.value.is_empty() {
-        "*(empty)*".to_string()
-    } else if resolved.value.contains('\n') {
-        format!("`{}`", resolved.value.replace('\n', "`\n`"))
-    } else {
-        format!("`{}`", resolved.value)
-    };
-
     let mut markdown = format!(
-        "{}\n\n**Value**: {}\n\n**Source**: `{}`",
-        header, value_formatted, resolved.source
+        "{}\n\n**Value**: {}",
+        header,
+        format_value_markdown(&resolved.value)
     );
+    if let Some(badge) = resolved.value_type.badge() {
+        markdown.push_str(&format!("\n\n*({})*", badge));
+    }
+    markdown.push_str(&format!("\n\n**Source**: `{}`", resolved.source));
 
     if let Some(desc) = &resolved.description {
         if !desc.is_empty() {
@@ -93,6 +374,32 @@ pub(crate) fn format_hover_markdown(
     markdown
 }
 
+/// Formats a value for the `**Value**:` line of hovers and completion docs.
+pub(crate) fn format_value_markdown(value: &str) -> String {
+    if value.is_empty() {
+        "*(empty)*".to_string()
+    } else if value.contains('\n') {
+        // Inline code can't hold line breaks, so show the value as a block
+        // fenced by more backticks than it contains in a row.
+        let longest_run = value.split(|c| c != '`').map(str::len).max().unwrap_or(0);
+        let fence = "`".repeat(longest_run.max(2) + 1);
+        format!("\n\n{}\n{}\n{}", fence, value, fence)
+    } else {
+        format!("`{}`", value)
+    }
+}
+
+/// Appends the "Also defined in" list of overridden sources to a hover.
+pub(crate) fn push_overrides_markdown(markdown: &mut String, overrides: &[(String, String)]) {
+    if overrides.is_empty() {
+        return;
+    }
+    markdown.push_str("\n\n---\n\n**Also defined in**:");
+    for (source, value) in overrides {
+        markdown.push_str(&format!("\n- `{}`: `{}`", source, value.replace('\n', " ")));
+    }
+}
+
 pub(crate) fn get_line_col(content: &str, offset: usize) -> (u32, u32) {
     if offset >= content.len() {
         return (0, 0);
@@ -106,60 +413,6 @@ pub(crate) fn get_line_col(content: &str, offset: usize) -> (u32, u32) {
     (line_idx as u32, col_char as u32)
 }
 
-pub(crate) fn korni_span_to_range(content: &str, span: korni::Span) -> Range {
-    let (start_line, start_col) = offset_to_line_col(content, span.start.offset);
-    let (end_line, end_col) = offset_to_line_col(content, span.end.offset);
-
-    Range {
-        start: Position {
-            line: start_line,
-            character: start_col,
-        },
-        end: Position {
-            line: end_line,
-            character: end_col,
-        },
-    }
-}
-
-pub(crate) fn offset_to_line_col(content: &str, offset: usize) -> (u32, u32) {
-    let mut line = 0u32;
-    let mut col = 0u32;
-    for (i, ch) in content.char_indices() {
-        if i >= offset {
-            break;
-        }
-        if ch == '\n' {
-            line += 1;
-            col = 0;
-        } else {
-            col += 1;
-        }
-    }
-    (line, col)
-}
-
-pub(crate) fn is_valid_env_var_name(name: &str) -> bool {
-    if name.is_empty() {
-        return false;
-    }
-
-    let mut chars = name.chars();
-    if let Some(first) = chars.next() {
-        if !first.is_ascii_alphabetic() && first != '_' {
-            return false;
-        }
-    }
-
-    for ch in chars {
-        if !ch.is_ascii_alphanumeric() && ch != '_' {
-            return false;
-        }
-    }
-
-    true
-}
-
 pub(crate) async fn get_identifier_at_position(
     state: &ServerState,
     uri: &Url,
@@ -199,8 +452,36 @@ pub(crate) async fn get_identifier_at_position(
     None
 }
 
+/// Leaves plain values bare and double quotes the rest, escaping what a
+/// dotenv parser would otherwise read as a line break, quote or
+/// interpolation.
+pub(crate) fn dotenv_value(value: &str) -> String {
+    let plain = value
+        .chars()
+        .all(|c| c.is_ascii_alphanumeric() || "_-./:@,+%=*".contains(c));
+    if plain {
+        return value.to_string();
+    }
+    let mut quoted = String::with_capacity(value.len() + 2);
+    quoted.push('"');
+    for c in value.chars() {
+        match c {
+            '\\' => quoted.push_str("\\\\"),
+            '"' => quoted.push_str("\\\""),
+            '$' => quoted.push_str("\\$"),
+            '\n' => quoted.push_str("\\n"),
+            '\r' => quoted.push_str("\\r"),
+            '\t' => quoted.push_str("\\t"),
+            c => quoted.push(c),
+        }
+    }
+    quoted.push('"');
+    quoted
+}
+
 // Re-export KorniEntryExt from types for backwards compatibility
 pub use crate::types::KorniEntryExt;
+pub(crate) use crate::types::is_valid_env_var_name;
 
 #[cfg(test)]
 mod tests {
@@ -272,6 +553,74 @@ mod tests {
         assert_eq!(result, "Remote (doppler)");
     }
 
+    // =========================================================================
+    // mask_value tests
+    // =========================================================================
+
+    fn masking(mode: MaskMode) -> MaskingConfig {
+        MaskingConfig {
+            enabled: true,
+            mode,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_mask_value_disabled() {
+        let config = MaskingConfig::default();
+        assert_eq!(mask_value("KEY", "secret", &config, MaskTarget::Hover), "secret");
+    }
+
+    #[test]
+    fn test_mask_value_full() {
+        let config = masking(MaskMode::Full);
+        assert_eq!(mask_value("KEY", "secret", &config, MaskTarget::Command), "******");
+        assert_eq!(mask_value("KEY", "", &config, MaskTarget::Command), "");
+    }
+
+    #[test]
+    fn test_mask_value_partial() {
+        let config = masking(MaskMode::Partial);
+        assert_eq!(
+            mask_value("KEY", "sk_live_abcdef", &config, MaskTarget::Hover),
+            "sk_********def"
+        );
+        // Too short to reveal anything
+        assert_eq!(mask_value("KEY", "12345678", &config, MaskTarget::Hover), "********");
+    }
+
+    #[test]
+    fn test_mask_value_multiline_keeps_line_breaks() {
+        let config = masking(MaskMode::Full);
+        assert_eq!(mask_value("KEY", "ab\ncde", &config, MaskTarget::Hover), "**\n***");
+    }
+
+    #[test]
+    fn test_mask_value_per_target_toggle() {
+        let config = MaskingConfig {
+            mask_in_completion: false,
+            ..masking(MaskMode::Full)
+        };
+        assert_eq!(mask_value("KEY", "abc", &config, MaskTarget::Completion), "abc");
+        assert_eq!(mask_value("KEY", "abc", &config, MaskTarget::InlayHint), "***");
+    }
+
+    #[test]
+    fn test_mask_value_key_patterns() {
+        let config = MaskingConfig {
+            force_mask_key_patterns: vec!["*_SECRET".into(), "*_KEY".into()],
+            never_mask_key_patterns: vec!["PUBLIC_*".into()],
+            ..MaskingConfig::default()
+        };
+        assert_eq!(mask_value("APP_SECRET", "abc", &config, MaskTarget::Hover), "***");
+        assert_eq!(mask_value("PUBLIC_KEY", "abc", &config, MaskTarget::Hover), "abc");
+        assert_eq!(mask_value("PORT", "8080", &config, MaskTarget::Hover), "8080");
+
+        let enabled = MaskingConfig { enabled: true, ..config };
+        assert_eq!(mask_value("PUBLIC_URL", "abc", &enabled, MaskTarget::Hover), "abc");
+        assert_eq!(mask_value("PORT", "8080", &enabled, MaskTarget::Hover), "****");
+    }
+
     // =========================================================================
     // format_hover_markdown tests
     // =========================================================================
@@ -282,6 +631,7 @@ mod tests {
             value: "postgres://localhost".to_string(),
             source: ".env".to_string(),
             description: None,
+            value_type: ValueType::String,
         };
         let result = format_hover_markdown("DATABASE_URL", None, &resolved);
         assert!(result.contains("**`DATABASE_URL`**"));
@@ -295,6 +645,7 @@ mod tests {
             value: "secret".to_string(),
             source: ".env.local".to_string(),
             description: None,
+            value_type: ValueType::String,
         };
         let result = format_hover_markdown("API_KEY", Some("apiKey"), &resolved);
         assert!(result.contains("**`apiKey`** → **`API_KEY`**"));
@@ -306,6 +657,7 @@ mod tests {
             value: "8080".to_string(),
             source: ".env".to_string(),
             description: None,
+            value_type: ValueType::String,
         };
         // When binding name is same as env var name, no arrow
         let result = format_hover_markdown("PORT", Some("PORT"), &resolved);
@@ -319,6 +671,7 @@ mod tests {
             value: "true".to_string(),
             source: ".env".to_string(),
             description: Some(compact_str::CompactString::from("Enable debug mode")),
+            value_type: ValueType::Boolean,
         };
         let result = format_hover_markdown("DEBUG", None, &resolved);
         assert!(result.contains("*Enable debug mode*"));
@@ -330,10 +683,57 @@ mod tests {
             value: "line1\nline2".to_string(),
             source: ".env".to_string(),
             description: None,
+            value_type: ValueType::String,
+        };
+        let result = format_hover_markdown("MULTILINE", None, &resolved);
+        assert!(result.contains("**Value**: \n\n```\nline1\nline2\n```"), "{}", result);
+
+        let resolved = ResolvedEnvVarValue {
+            value: "a ``` b\nc".to_string(),
+            ..resolved
         };
         let result = format_hover_markdown("MULTILINE", None, &resolved);
-        // Newlines should be formatted specially
-        assert!(result.contains("`line1`\n`line2`"));
+        assert!(result.contains("\n````\na ``` b\nc\n````"), "{}", result);
+    }
+
+    #[test]
+    fn test_format_hover_markdown_type_badge() {
+        let resolved = ResolvedEnvVarValue {
+            value: "****".to_string(),
+            source: ".env".to_string(),
+            description: None,
+            value_type: ValueType::Number,
+        };
+        let result = format_hover_markdown("PORT", None, &resolved);
+        assert!(result.contains("**Value**: `****`\n\n*(number)*\n\n**Source**"), "{}", result);
+
+        let resolved = ResolvedEnvVarValue {
+            value_type: ValueType::String,
+            ..resolved
+        };
+        assert!(!format_hover_markdown("PORT", None, &resolved).contains("*("));
+    }
+
+    #[test]
+    fn test_infer_value_type() {
+        assert_eq!(infer_value_type("true"), ValueType::Boolean);
+        assert_eq!(infer_value_type("FALSE"), ValueType::Boolean);
+        assert_eq!(infer_value_type("8080"), ValueType::Number);
+        assert_eq!(infer_value_type("-1.5e3"), ValueType::Number);
+        assert_eq!(infer_value_type("postgres://user@localhost:5432/db"), ValueType::Url);
+        assert_eq!(infer_value_type("https://example.com"), ValueType::Url);
+        assert_eq!(infer_value_type(r#"{"a": [1, 2]}"#), ValueType::Json);
+        assert_eq!(infer_value_type("[1, 2]"), ValueType::Json);
+    }
+
+    #[test]
+    fn test_infer_value_type_plain_string() {
+        assert_eq!(infer_value_type("secret_key"), ValueType::String);
+        assert_eq!(infer_value_type(""), ValueType::String);
+        assert_eq!(infer_value_type("inf"), ValueType::String);
+        assert_eq!(infer_value_type("1.2.3"), ValueType::String);
+        assert_eq!(infer_value_type("://nothing"), ValueType::String);
+        assert_eq!(infer_value_type("{not json}"), ValueType::String);
     }
 
     #[test]
@@ -342,6 +742,7 @@ mod tests {
             value: "".to_string(),
             source: ".env".to_string(),
             description: None,
+            value_type: ValueType::String,
         };
         let result = format_hover_markdown("EMPTY_VAR", None, &resolved);
         assert!(result.contains("**`EMPTY_VAR`**"));
@@ -374,93 +775,4 @@ mod tests {
         assert_eq!(get_line_col(content, 100), (0, 0));
     }
 
-    // =========================================================================
-    // offset_to_line_col tests
-    // =========================================================================
-
-    #[test]
-    fn test_offset_to_line_col_single_line() {
-        let content = "hello world";
-        assert_eq!(offset_to_line_col(content, 0), (0, 0));
-        assert_eq!(offset_to_line_col(content, 6), (0, 6));
-    }
-
-    #[test]
-    fn test_offset_to_line_col_after_newline() {
-        let content = "abc\ndefg";
-        assert_eq!(offset_to_line_col(content, 4), (1, 0)); // 'd' after newline
-        assert_eq!(offset_to_line_col(content, 6), (1, 2)); // 'f'
-    }
-
-    #[test]
-    fn test_offset_to_line_col_multiple_newlines() {
-        let content = "a\nb\nc";
-        assert_eq!(offset_to_line_col(content, 0), (0, 0)); // 'a'
-        assert_eq!(offset_to_line_col(content, 2), (1, 0)); // 'b'
-        assert_eq!(offset_to_line_col(content, 4), (2, 0)); // 'c'
-    }
-
-    // =========================================================================
-    // is_valid_env_var_name tests
-    // =========================================================================
-
-    #[test]
-    fn test_is_valid_env_var_name_valid() {
-        assert!(is_valid_env_var_name("DATABASE_URL"));
-        assert!(is_valid_env_var_name("API_KEY"));
-        assert!(is_valid_env_var_name("_PRIVATE"));
-        assert!(is_valid_env_var_name("VAR1"));
-        assert!(is_valid_env_var_name("A"));
-        assert!(is_valid_env_var_name("_"));
-        assert!(is_valid_env_var_name("__name__"));
-    }
-
-    #[test]
-    fn test_is_valid_env_var_name_invalid() {
-        assert!(!is_valid_env_var_name("")); // empty
-        assert!(!is_valid_env_var_name("1VAR")); // starts with number
-        assert!(!is_valid_env_var_name("VAR-NAME")); // contains hyphen
-        assert!(!is_valid_env_var_name("VAR.NAME")); // contains dot
-        assert!(!is_valid_env_var_name("VAR NAME")); // contains space
-        assert!(!is_valid_env_var_name("VAR@NAME")); // contains special char
-    }
-
-    #[test]
-    fn test_is_valid_env_var_name_unicode() {
-        // Unicode letters are not valid in env var names (ASCII only)
-        assert!(!is_valid_env_var_name("日本語"));
-        assert!(!is_valid_env_var_name("VARäble"));
-    }
-
-    // =========================================================================
-    // korni_span_to_range tests
-    // =========================================================================
-
-    #[test]
-    fn test_korni_span_to_range_single_line() {
-        let content = "KEY=value";
-        let span = korni::Span {
-            start: korni::Position { offset: 0 },
-            end: korni::Position { offset: 3 },
-        };
-        let range = korni_span_to_range(content, span);
-        assert_eq!(range.start.line, 0);
-        assert_eq!(range.start.character, 0);
-        assert_eq!(range.end.line, 0);
-        assert_eq!(range.end.character, 3);
-    }
-
-    #[test]
-    fn test_korni_span_to_range_multiline() {
-        let content = "KEY1=value1\nKEY2=value2";
-        let span = korni::Span {
-            start: korni::Position { offset: 12 }, // start of KEY2
-            end: korni::Position { offset: 16 },   // end of KEY2
-        };
-        let range = korni_span_to_range(content, span);
-        assert_eq!(range.start.line, 1);
-        assert_eq!(range.start.character, 0);
-        assert_eq!(range.end.line, 1);
-        assert_eq!(range.end.character, 4);
-    }
 }
@@ -0,0 +1,144 @@
+//! Env file source that tolerates invalid UTF-8.
+//!
+//! abundantis' `FileSource` reads with `read_to_string`, so a single stray
+//! Latin-1 byte makes the whole file fail to load and every variable in it
+//! drops out of resolution. [`LossyFileSource`] decodes the file the way the
+//! indexer does, replacing invalid sequences with U+FFFD, and is registered
+//! in place of any file source that fails to load for that reason.
+
+use abundantis::error::SourceError;
+use abundantis::source::{
+    EnvSource, ParsedVariable, Priority, SourceCapabilities, SourceId, SourceSnapshot,
+    SourceType, VariableSource,
+};
+use abundantis::Abundantis;
+use compact_str::CompactString;
+use parking_lot::{Mutex, RwLock};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::SystemTime;
+
+pub struct LossyFileSource {
+    path: PathBuf,
+    id: SourceId,
+    last_modified: Mutex<Option<SystemTime>>,
+    cached_vars: RwLock<Option<Arc<[ParsedVariable]>>>,
+    next_version: Mutex<u64>,
+}
+
+impl LossyFileSource {
+    /// Wraps `path` under `id`, the id of the file source it replaces.
+    pub fn new(path: impl AsRef<Path>, id: SourceId) -> Self {
+        Self {
+            path: path.as_ref().to_path_buf(),
+            id,
+            last_modified: Mutex::new(None),
+            cached_vars: RwLock::new(None),
+            next_version: Mutex::new(1),
+        }
+    }
+
+    fn modified(&self) -> Option<SystemTime> {
+        self.path.metadata().and_then(|m| m.modified()).ok()
+    }
+
+    fn parse_file(&self) -> Result<Vec<ParsedVariable>, SourceError> {
+        let bytes = std::fs::read(&self.path).map_err(|e| SourceError::SourceRead {
+            source_name: self.path.display().to_string(),
+            reason: e.to_string(),
+        })?;
+        *self.last_modified.lock() = self.modified();
+
+        let content = String::from_utf8_lossy(&bytes);
+        let options = korni::ParseOptions {
+            track_positions: true,
+            include_comments: false,
+        };
+        let variables = korni::parse_with_options(&content, options)
+            .into_iter()
+            .filter_map(|entry| match entry {
+                korni::Entry::Pair(kv) => Some(ParsedVariable {
+                    key: CompactString::new(&kv.key),
+                    raw_value: CompactString::new(&kv.value),
+                    source: VariableSource::File {
+                        path: self.path.clone(),
+                        offset: kv.key_span.map(|s| s.start.offset).unwrap_or(0),
+                    },
+                    description: None,
+                    is_commented: kv.is_comment,
+                }),
+                _ => None,
+            })
+            .collect();
+        Ok(variables)
+    }
+}
+
+impl EnvSource for LossyFileSource {
+    fn id(&self) -> &SourceId {
+        &self.id
+    }
+
+    fn source_type(&self) -> SourceType {
+        SourceType::File
+    }
+
+    fn priority(&self) -> Priority {
+        Priority::FILE
+    }
+
+    fn capabilities(&self) -> SourceCapabilities {
+        SourceCapabilities::READ | SourceCapabilities::CACHEABLE
+    }
+
+    fn load(&self) -> Result<SourceSnapshot, SourceError> {
+        let cached = if self.has_changed() { None } else { self.cached_vars.read().clone() };
+        let variables = match cached {
+            Some(variables) => variables,
+            None => {
+                let variables: Arc<[ParsedVariable]> = self.parse_file()?.into();
+                *self.cached_vars.write() = Some(Arc::clone(&variables));
+                *self.next_version.lock() += 1;
+                variables
+            }
+        };
+        Ok(SourceSnapshot {
+            source_id: self.id.clone(),
+            variables,
+            timestamp: std::time::Instant::now(),
+            version: Some(*self.next_version.lock()),
+        })
+    }
+
+    fn has_changed(&self) -> bool {
+        let last = *self.last_modified.lock();
+        last.is_none() || last != self.modified()
+    }
+
+    fn invalidate(&self) {
+        *self.cached_vars.write() = None;
+        *self.last_modified.lock() = None;
+    }
+}
+
+/// Replaces each registered file source that fails to load because its file
+/// isn't valid UTF-8 with a [`LossyFileSource`] under the same id.
+///
+/// Run after anything that may register new file sources, i.e. building the
+/// core and refreshing it. Sources that load fine are left alone.
+pub fn register_lossy_file_sources(core: &Abundantis) {
+    for source in core.registry.sources_of_type(SourceType::File) {
+        if source.load().is_ok() {
+            continue;
+        }
+        let Some(path) = source.id().as_str().strip_prefix("file:").map(PathBuf::from) else {
+            continue;
+        };
+        let invalid_utf8 = std::fs::read(&path).is_ok_and(|b| std::str::from_utf8(&b).is_err());
+        if invalid_utf8 {
+            tracing::info!("Resolving {:?} lossily, it isn't valid UTF-8", path);
+            let lossy = LossyFileSource::new(&path, source.id().clone());
+            core.registry.register_sync(Arc::new(lossy));
+        }
+    }
+}
@@ -3,17 +3,20 @@ pub mod config;
 pub mod env_resolution;
 pub mod error;
 pub mod handlers;
+pub mod lossy_source;
 pub mod services;
 pub mod state;
+pub mod status;
+pub mod transport;
 pub mod util;
 
 pub use error::LspError;
 
 use crate::analysis::{DocumentManager, QueryEngine};
 use crate::languages::LanguageRegistry;
-use crate::server::cancellation::CancellationToken;
+use crate::server::cancellation::{CancelOnDrop, CancellationToken};
+use crate::server::config::{HeartbeatLevel, LoggingConfig};
 use crate::server::state::ServerState;
-use dashmap::DashMap;
 use futures::future::join_all;
 use parking_lot::Mutex;
 use std::sync::Arc;
@@ -26,8 +29,6 @@ use tracing::info;
 pub struct LspServer {
     pub client: Client,
     pub state: ServerState,
-    /// Per-URI pending analysis tasks for debouncing did_change
-    pending_analysis: DashMap<Url, tokio::task::JoinHandle<()>>,
     /// Token for cancelling background tasks on shutdown
     cancellation_token: CancellationToken,
     /// Handle to the heartbeat task for cleanup
@@ -66,6 +67,7 @@ impl LspServer {
         registry.register(Arc::new(crate::languages::csharp::CSharp));
         registry.register(Arc::new(crate::languages::elixir::Elixir));
         registry.register(Arc::new(crate::languages::zig::Zig));
+        registry.register(Arc::new(crate::languages::dart::Dart));
 
         let languages = Arc::new(registry);
 
@@ -89,12 +91,76 @@ impl LspServer {
         Self {
             client,
             state,
-            pending_analysis: DashMap::new(),
             cancellation_token: CancellationToken::new(),
             heartbeat_handle: Mutex::new(None),
         }
     }
 
+    /// Spawns the periodic heartbeat log unless `heartbeat_secs` is 0, in
+    /// which case no task is spawned and no handle is stored.
+    fn start_heartbeat(&self, logging: &LoggingConfig) {
+        let Some(period) = logging.heartbeat_interval() else {
+            tracing::debug!("Heartbeat disabled");
+            return;
+        };
+        let level = logging.heartbeat_level;
+
+        let document_manager = Arc::clone(&self.state.document_manager);
+        let workspace_index = Arc::clone(&self.state.workspace_index);
+        let cancellation_token = self.cancellation_token.clone();
+        let heartbeat_handle = tokio::spawn(async move {
+            let mut interval = tokio::time::interval(period);
+            let mut heartbeat_count = 0u64;
+            loop {
+                tokio::select! {
+                    biased;
+                    _ = cancellation_token.cancelled() => {
+                        tracing::debug!("Heartbeat loop cancelled");
+                        break;
+                    }
+                    _ = interval.tick() => {
+                        heartbeat_count += 1;
+
+                        // Collect memory metrics
+                        let doc_count = document_manager.document_count();
+                        let index_stats = workspace_index.stats();
+                        let module_cache_len = workspace_index.module_cache_len();
+
+                        let message = format!(
+                            "LSP heartbeat #{} - docs={} indexed_files={} env_vars={} module_cache={}",
+                            heartbeat_count,
+                            doc_count,
+                            index_stats.total_files,
+                            index_stats.total_env_vars,
+                            module_cache_len
+                        );
+                        match level {
+                            HeartbeatLevel::Trace => tracing::trace!("{}", message),
+                            HeartbeatLevel::Debug => tracing::debug!("{}", message),
+                            HeartbeatLevel::Info => tracing::info!("{}", message),
+                        }
+                    }
+                }
+            }
+        });
+        *self.heartbeat_handle.lock() = Some(heartbeat_handle);
+    }
+
+    /// Adds a fallback env source consulted after abundantis' sources.
+    pub fn with_env_source_provider(
+        mut self,
+        provider: Arc<dyn env_resolution::EnvSourceProvider>,
+    ) -> Self {
+        self.state = self.state.with_env_source_provider(provider);
+        self
+    }
+
+    /// Registers a parser for a custom env file format with the indexer.
+    pub fn with_env_source_plugin(self, plugin: Arc<dyn crate::analysis::EnvSourcePlugin>) -> Self {
+        self.state.indexer.register_plugin(plugin);
+        self
+    }
+
     pub async fn register_watched_files(&self) {
         let mut watchers = vec![FileSystemWatcher {
             glob_pattern: GlobPattern::String("**/ecolog.toml".to_string()),
@@ -104,7 +170,9 @@ impl LspServer {
         {
             let config = self.state.config.get_config();
             let config = config.read().await;
-            for pattern in &config.workspace.env_files {
+            // Watcher globs can't exclude; excluded files are filtered when
+            // their change arrives
+            for pattern in config.workspace.env_files.iter().filter(|p| !p.starts_with('!')) {
                 watchers.push(FileSystemWatcher {
                     glob_pattern: GlobPattern::String(format!("**/{}", pattern)),
                     kind: None,
@@ -143,33 +211,52 @@ impl LspServer {
         Self::update_workspace_index_for_document_impl(&self.state, uri).await;
     }
 
+    /// Re-indexes a changed document and publishes its diagnostics, unless
+    /// it was closed while the index was updating or no longer has `stamp`.
+    async fn analyze_changed_document(
+        state: &ServerState,
+        client: &Client,
+        uri: Url,
+        stamp: Option<(u64, i32)>,
+    ) {
+        Self::update_workspace_index_for_document_impl(state, &uri).await;
+
+        // did_close may have raced the index update; undo it so the
+        // closed document doesn't linger in the index. did_close drops the
+        // document before its index entry, so one of the two removals runs
+        // after the update.
+        if state.document_manager.version_stamp(&uri).is_none() {
+            state.workspace_index.remove_file(&uri);
+            return;
+        }
+
+        let diagnostics = handlers::compute_diagnostics(&uri, state).await;
+        if state.document_manager.version_stamp(&uri) != stamp {
+            tracing::debug!("[ANALYSIS] dropping stale diagnostics for uri={}", uri);
+            return;
+        }
+        client.publish_diagnostics(uri, diagnostics, None).await;
+    }
+
     /// Static implementation for workspace index updates, callable from spawned tasks
     async fn update_workspace_index_for_document_impl(state: &ServerState, uri: &Url) {
-        use crate::analysis::{workspace_index::FileIndexEntry, BindingResolver};
+        use crate::analysis::{
+            build_script, deploy_config, properties, workspace_index::FileIndexEntry, yaml_config,
+            BindingResolver,
+        };
         use crate::server::handlers::util::KorniEntryExt;
         use compact_str::CompactString;
         use korni::ParseOptions;
         use rustc_hash::FxHashSet;
         use std::time::SystemTime;
 
-        let path = uri
-            .to_file_path()
-            .unwrap_or_else(|_| std::path::PathBuf::from(uri.path()));
+        let path = util::uri_to_path(uri);
 
         let mtime = SystemTime::now();
 
-        let file_name = path.file_name().and_then(|n| n.to_str()).unwrap_or("");
-        let is_env_file = {
-            let config = state.config.get_config();
-            let config = config.read().await;
-            config.workspace.env_files.iter().any(|pattern| {
-                glob::Pattern::new(pattern.as_str())
-                    .map(|p| p.matches(file_name))
-                    .unwrap_or(false)
-            })
-        };
+        let is_env_file = util::is_env_file_uri(state, uri).await;
 
-        let env_vars: FxHashSet<CompactString> = if is_env_file {
+        let mut env_vars: FxHashSet<CompactString> = if is_env_file {
             let vars = if let Some(doc) = state.document_manager.get(uri) {
                 let content = &doc.content;
                 let entries = korni::parse_with_options(content, ParseOptions::full());
@@ -182,16 +269,68 @@ impl LspServer {
                 FxHashSet::default()
             };
 
-            util::safe_refresh(&state.core, abundantis::RefreshOptions::preserve_all()).await;
+            util::safe_refresh(&state.core, &state.config, abundantis::RefreshOptions::preserve_all())
+                .await;
 
             vars
         } else if let Some(graph_ref) = state.document_manager.get_binding_graph(uri) {
+            state
+                .workspace_index
+                .set_dynamic_env_access(uri, graph_ref.has_dynamic_env_access());
             let resolver = BindingResolver::new(&graph_ref);
             resolver.all_env_vars().into_iter().collect()
         } else {
             FxHashSet::default()
         };
 
+        if deploy_config::is_deploy_config(&path) {
+            let definitions = state
+                .document_manager
+                .get(uri)
+                .map(|doc| deploy_config::extract_definitions(&path, &doc.content))
+                .unwrap_or_default();
+            env_vars.extend(definitions.iter().map(|def| def.name.clone()));
+            state.workspace_index.update_external_definitions(uri, definitions);
+        }
+
+        if properties::is_properties_file(&path) {
+            let definitions = state
+                .document_manager
+                .get(uri)
+                .map(|doc| properties::extract_definitions(&doc.content))
+                .unwrap_or_default();
+            env_vars.extend(definitions.iter().map(|def| def.name.clone()));
+            state.workspace_index.update_external_definitions(uri, definitions);
+        }
+
+        if yaml_config::is_compose_file(&path) {
+            let content = state.document_manager.get(uri).map(|doc| doc.content.clone());
+            let definitions = match content {
+                Some(content) => {
+                    let max_bytes = {
+                        let config = state.config.get_config();
+                        let config = config.read().await;
+                        config.workspace.max_env_file_bytes
+                    };
+                    let root = util::get_workspace_root(&state.core.workspace).await;
+                    yaml_config::extract_definitions(&path, &content, &root, max_bytes).await
+                }
+                None => Vec::new(),
+            };
+            env_vars.extend(definitions.iter().map(|def| def.name.clone()));
+            state.workspace_index.update_local_definitions(uri, definitions);
+        }
+
+        if build_script::is_build_script(&path) {
+            let definitions = state
+                .document_manager
+                .get(uri)
+                .map(|doc| build_script::extract_definitions(&doc.content))
+                .unwrap_or_default();
+            env_vars.extend(definitions.iter().map(|def| def.name.clone()));
+            state.workspace_index.update_crate_definitions(uri, definitions);
+        }
+
         state.workspace_index.update_file(
             uri,
             FileIndexEntry {
@@ -203,6 +342,12 @@ impl LspServer {
         );
     }
 
+    /// Passive documents have no language support and aren't env files, so
+    /// there is nothing to index or diagnose.
+    async fn is_passive_document(state: &ServerState, uri: &Url) -> bool {
+        state.document_manager.is_passive(uri) && !util::is_env_file_uri(state, uri).await
+    }
+
     /// Request the client to refresh all inlay hints
     async fn refresh_inlay_hints(&self) {
         // workspace/inlayHint/refresh is a server-to-client request
@@ -216,6 +361,20 @@ impl LspServer {
         }
     }
 
+    /// Runs a workspace-wide request on its own task. If tower-lsp drops the
+    /// request on `$/cancelRequest`, the guard cancels the token so the task
+    /// stops at its next check instead of finishing unobserved.
+    async fn run_cancellable<T, F, Fut>(&self, handler: F) -> Option<T>
+    where
+        F: FnOnce(ServerState, CancellationToken) -> Fut,
+        Fut: std::future::Future<Output = Option<T>> + Send + 'static,
+        T: Send + 'static,
+    {
+        let cancel = CancellationToken::new();
+        let _guard = CancelOnDrop::new(cancel.clone());
+        tokio::spawn(handler(self.state.clone(), cancel)).await.ok().flatten()
+    }
+
     /// Refresh diagnostics for all open documents in parallel
     async fn refresh_all_diagnostics(&self) {
         let uris: Vec<_> = self.state.document_manager.all_uris();
@@ -238,6 +397,27 @@ impl LspServer {
         }
     }
 
+    /// Hands the `workspace` settings the indexer and workspace index keep
+    /// their own copy of to them, at startup and whenever `ecolog.toml` is
+    /// reloaded.
+    fn apply_indexer_settings(&self, cfg: &config::EcologConfig) {
+        self.state
+            .indexer
+            .set_max_depth(cfg.workspace.max_index_depth);
+        self.state
+            .indexer
+            .set_max_env_file_bytes(cfg.workspace.max_env_file_bytes);
+        self.state
+            .indexer
+            .set_follow_env_includes(cfg.workspace.follow_env_includes);
+        self.state
+            .indexer
+            .set_case_insensitive_env_files(cfg.workspace.case_insensitive_env_files);
+        self.state
+            .workspace_index
+            .set_module_cache_max(cfg.workspace.module_cache_max);
+    }
+
     /// Spawn external providers based on configuration
     async fn spawn_configured_providers(&self, providers_config: &config::ProvidersConfig) {
         use std::path::PathBuf;
@@ -331,6 +511,17 @@ impl LspServer {
 #[tower_lsp::async_trait]
 impl LanguageServer for LspServer {
     async fn initialize(&self, params: InitializeParams) -> Result<InitializeResult> {
+        let resolves_labels = params
+            .capabilities
+            .text_document
+            .as_ref()
+            .and_then(|td| td.inlay_hint.as_ref())
+            .and_then(|ih| ih.resolve_support.as_ref())
+            .is_some_and(|rs| rs.properties.iter().any(|p| p == "label"));
+        self.state
+            .defer_inlay_hint_values
+            .store(resolves_labels, std::sync::atomic::Ordering::Relaxed);
+
         self.state
             .config
             .set_init_settings(params.initialization_options)
@@ -365,18 +556,34 @@ impl LanguageServer for LspServer {
                         work_done_progress: None,
                     },
                 })),
-                text_document_sync: Some(TextDocumentSyncCapability::Kind(
-                    TextDocumentSyncKind::FULL,
+                text_document_sync: Some(TextDocumentSyncCapability::Options(
+                    TextDocumentSyncOptions {
+                        open_close: Some(true),
+                        change: Some(TextDocumentSyncKind::FULL),
+                        save: Some(TextDocumentSyncSaveOptions::SaveOptions(SaveOptions {
+                            include_text: Some(true),
+                        })),
+                        ..Default::default()
+                    },
                 )),
                 execute_command_provider: Some(ExecuteCommandOptions {
                     commands: vec![
                         "ecolog.file.setActive".to_string(),
+                        "ecolog.file.setProfile".to_string(),
+                        "ecolog.profile.diff".to_string(),
+                        "ecolog.export".to_string(),
                         "ecolog.file.list".to_string(),
+                        "ecolog.file.resolutionOrder".to_string(),
+                        "ecolog.file.envVarLocations".to_string(),
                         "ecolog.listEnvVariables".to_string(),
                         "ecolog.generateEnvExample".to_string(),
                         "ecolog.variable.get".to_string(),
+                        "ecolog.variable.getForFile".to_string(),
+                        "ecolog.variable.reveal".to_string(),
                         "ecolog.workspace.list".to_string(),
                         "ecolog.workspace.setRoot".to_string(),
+                        "ecolog.workspace.findUnused".to_string(),
+                        "ecolog.workspace.findUndefined".to_string(),
                         "ecolog.interpolation.set".to_string(),
                         "ecolog.interpolation.get".to_string(),
                         "ecolog.source.list".to_string(),
@@ -398,13 +605,55 @@ impl LanguageServer for LspServer {
                         "ecolog.provider.select".to_string(),
                         "ecolog.provider.refresh".to_string(),
                         "ecolog.provider.shutdown".to_string(),
+                        // Diagnostics
+                        "ecolog.debug.stats".to_string(),
+                        "ecolog.ping".to_string(),
                     ],
                     work_done_progress_options: WorkDoneProgressOptions {
                         work_done_progress: None,
                     },
                 }),
                 workspace_symbol_provider: Some(OneOf::Left(true)),
-                inlay_hint_provider: Some(OneOf::Left(true)),
+                document_symbol_provider: Some(OneOf::Left(true)),
+                selection_range_provider: Some(SelectionRangeProviderCapability::Simple(true)),
+                workspace: Some(WorkspaceServerCapabilities {
+                    workspace_folders: None,
+                    file_operations: Some(WorkspaceFileOperationsServerCapabilities {
+                        did_rename: Some(FileOperationRegistrationOptions {
+                            filters: vec![FileOperationFilter {
+                                scheme: Some("file".to_string()),
+                                pattern: FileOperationPattern {
+                                    glob: "**/*".to_string(),
+                                    matches: Some(FileOperationPatternKind::File),
+                                    options: None,
+                                },
+                            }],
+                        }),
+                        ..Default::default()
+                    }),
+                }),
+                code_lens_provider: Some(CodeLensOptions {
+                    resolve_provider: Some(true),
+                }),
+                semantic_tokens_provider: Some(
+                    SemanticTokensServerCapabilities::SemanticTokensOptions(SemanticTokensOptions {
+                        legend: handlers::semantic_tokens_legend(),
+                        full: Some(SemanticTokensFullOptions::Bool(true)),
+                        ..Default::default()
+                    }),
+                ),
+                code_action_provider: Some(CodeActionProviderCapability::Options(
+                    CodeActionOptions {
+                        code_action_kinds: Some(vec![CodeActionKind::REFACTOR_EXTRACT]),
+                        ..Default::default()
+                    },
+                )),
+                inlay_hint_provider: Some(OneOf::Right(InlayHintServerCapabilities::Options(
+                    InlayHintOptions {
+                        resolve_provider: Some(true),
+                        work_done_progress_options: Default::default(),
+                    },
+                ))),
                 ..Default::default()
             },
             ..Default::default()
@@ -439,6 +688,24 @@ impl LanguageServer for LspServer {
 
             // Spawn enabled external providers
             self.spawn_configured_providers(&cfg.providers).await;
+
+            self.apply_indexer_settings(cfg);
+            self.state.set_detectors(cfg.detectors.clone());
+            self.state
+                .set_scan_all_config_files(cfg.workspace.scan_all_config_files);
+            self.state
+                .set_env_accessors(cfg.custom_env_accessors.clone());
+            self.state
+                .languages
+                .set_extension_overrides(cfg.extension_overrides.clone());
+        }
+
+        if let Ok(value) = std::env::var(util::ACTIVE_FILES_ENV_VAR) {
+            let patterns = util::parse_active_files(&value);
+            if !patterns.is_empty() {
+                info!("Active env files from {}: {:?}", util::ACTIVE_FILES_ENV_VAR, patterns);
+                self.state.env.set_active_files(&patterns);
+            }
         }
 
         self.client
@@ -450,8 +717,14 @@ impl LanguageServer for LspServer {
 
         self.register_watched_files().await;
 
+        self.client
+            .send_notification::<status::StatusNotification>(status::StatusParams {
+                status: status::ServerStatus::Indexing,
+            })
+            .await;
+
         let languages = Arc::clone(&self.state.languages);
-        tokio::spawn(async move {
+        let prewarm = tokio::spawn(async move {
             tokio::task::spawn_blocking(move || {
                 for lang in languages.all_languages() {
                     let _ = lang.reference_query();
@@ -474,72 +747,63 @@ impl LanguageServer for LspServer {
         let indexer = Arc::clone(&self.state.indexer);
         let config = self.state.config.get_config();
         let client = self.client.clone();
+        let cancel = self.cancellation_token.clone();
+        let index_complete = Arc::clone(&self.state.initial_index_complete);
 
         tokio::spawn(async move {
-            let env_files = {
+            let (env_files, lazy_index) = {
                 let config = config.read().await;
-                config.workspace.env_files.clone()
+                (config.workspace.env_files.clone(), config.workspace.lazy_index)
             };
-            info!("Starting background workspace indexing...");
-            client
-                .log_message(MessageType::INFO, "Starting workspace indexing...")
-                .await;
-
-            if let Err(e) = indexer.index_workspace(&env_files).await {
+            let result = if lazy_index {
+                info!("Lazy indexing enabled, indexing env files only");
                 client
-                    .log_message(
-                        MessageType::WARNING,
-                        format!("Workspace indexing failed: {}", e),
-                    )
+                    .log_message(MessageType::INFO, "Lazy indexing enabled, indexing env files...")
                     .await;
+                indexer.index_env_files_with_cancellation(&env_files, &cancel).await
             } else {
-                let stats = indexer.index().stats();
+                info!("Starting background workspace indexing...");
                 client
-                    .log_message(
-                        MessageType::INFO,
-                        format!(
-                            "Workspace indexing complete: {} files, {} env vars",
-                            stats.total_files, stats.total_env_vars
-                        ),
-                    )
+                    .log_message(MessageType::INFO, "Starting workspace indexing...")
                     .await;
-            }
-        });
-
-        let document_manager = Arc::clone(&self.state.document_manager);
-        let workspace_index = Arc::clone(&self.state.workspace_index);
-        let cancellation_token = self.cancellation_token.clone();
-        let heartbeat_handle = tokio::spawn(async move {
-            let mut interval = tokio::time::interval(std::time::Duration::from_secs(30));
-            let mut heartbeat_count = 0u64;
-            loop {
-                tokio::select! {
-                    biased;
-                    _ = cancellation_token.cancelled() => {
-                        tracing::debug!("Heartbeat loop cancelled");
-                        break;
-                    }
-                    _ = interval.tick() => {
-                        heartbeat_count += 1;
-
-                        // Collect memory metrics
-                        let doc_count = document_manager.document_count();
-                        let index_stats = workspace_index.stats();
-                        let module_cache_len = workspace_index.module_cache_len();
+                indexer.index_workspace_with_cancellation(&env_files, &cancel).await
+            };
 
-                        tracing::info!(
-                            "LSP heartbeat #{} - docs={} indexed_files={} env_vars={} module_cache={}",
-                            heartbeat_count,
-                            doc_count,
-                            index_stats.total_files,
-                            index_stats.total_env_vars,
-                            module_cache_len
-                        );
-                    }
+            match result {
+                Err(e) => {
+                    client
+                        .log_message(
+                            MessageType::WARNING,
+                            format!("Workspace indexing failed: {}", e),
+                        )
+                        .await;
                 }
+                Ok(stats) => {
+                    client
+                        .log_message(
+                            MessageType::INFO,
+                            format!(
+                                "Workspace indexing complete: {} files, {} env vars, {} failed",
+                                stats.total_files, stats.total_env_vars, stats.failed_files
+                            ),
+                        )
+                        .await;
+                }
+            }
+
+            prewarm.await.ok();
+            index_complete.store(true, std::sync::atomic::Ordering::Release);
+            if !cancel.is_cancelled() {
+                client
+                    .send_notification::<status::StatusNotification>(status::StatusParams {
+                        status: status::ServerStatus::Ready,
+                    })
+                    .await;
             }
         });
-        *self.heartbeat_handle.lock() = Some(heartbeat_handle);
+
+        let logging = self.state.config.get_config().read().await.logging.clone();
+        self.start_heartbeat(&logging);
     }
 
     async fn shutdown(&self) -> Result<()> {
@@ -554,10 +818,10 @@ impl LanguageServer for LspServer {
         }
 
         // Abort all pending analysis tasks
-        for entry in self.pending_analysis.iter() {
+        for entry in self.state.pending_analysis.iter() {
             entry.value().abort();
         }
-        self.pending_analysis.clear();
+        self.state.pending_analysis.clear();
 
         tracing::info!("LSP shutdown complete");
         Ok(())
@@ -578,6 +842,14 @@ impl LanguageServer for LspServer {
             )
             .await;
 
+        if Self::is_passive_document(&self.state, uri).await {
+            tracing::debug!(
+                "[HANDLER_EXIT] did_open passive document elapsed_ms={}",
+                start.elapsed().as_millis()
+            );
+            return;
+        }
+
         self.update_workspace_index_for_document(&params.text_document.uri)
             .await;
 
@@ -605,12 +877,42 @@ impl LanguageServer for LspServer {
             .change(&uri, params.content_changes, version)
             .await;
 
+        if Self::is_passive_document(&self.state, &uri).await {
+            tracing::debug!(
+                "[HANDLER_EXIT] did_change passive document elapsed_ms={}",
+                start.elapsed().as_millis()
+            );
+            return;
+        }
+
         // 2. Cancel previous pending analysis for this URI
-        if let Some((_, handle)) = self.pending_analysis.remove(&uri) {
+        if let Some((_, handle)) = self.state.pending_analysis.remove(&uri) {
             handle.abort();
         }
 
-        // 3. Spawn debounced task for expensive operations
+        let stamp = self.state.document_manager.version_stamp(&uri);
+
+        // 3. Small documents are analyzed right away; debouncing them only
+        // adds lag
+        let inline_max_lines = {
+            let config = self.state.config.get_config();
+            let config = config.read().await;
+            config.workspace.inline_analysis_max_lines
+        };
+        let is_small = inline_max_lines > 0
+            && self.state.document_manager.get(&uri).is_some_and(|doc| {
+                doc.content.lines().take(inline_max_lines + 1).count() <= inline_max_lines
+            });
+        if is_small {
+            Self::analyze_changed_document(&self.state, &self.client, uri, stamp).await;
+            tracing::debug!(
+                "[HANDLER_EXIT] did_change inline elapsed_ms={}",
+                start.elapsed().as_millis()
+            );
+            return;
+        }
+
+        // 4. Spawn debounced task for expensive operations
         let state = self.state.clone();
         let client = self.client.clone();
         let uri_clone = uri.clone();
@@ -619,33 +921,24 @@ impl LanguageServer for LspServer {
             // Wait 300ms before performing expensive analysis
             tokio::time::sleep(Duration::from_millis(300)).await;
 
-            // Check if document version still matches (hasn't changed during debounce)
-            let current_version = state
-                .document_manager
-                .get(&uri_clone)
-                .map(|doc| doc.version);
+            // Check the document hasn't changed, or been closed and
+            // reopened, during the debounce
+            let current_stamp = state.document_manager.version_stamp(&uri_clone);
 
-            if current_version != Some(version) {
+            if current_stamp != stamp {
                 tracing::debug!(
-                    "[DEBOUNCE] skipping analysis for uri={} (version mismatch: expected {}, got {:?})",
+                    "[DEBOUNCE] skipping analysis for uri={} (stale: expected {:?}, got {:?})",
                     uri_clone,
-                    version,
-                    current_version
+                    stamp,
+                    current_stamp
                 );
                 return;
             }
 
-            // Update workspace index
-            Self::update_workspace_index_for_document_impl(&state, &uri_clone).await;
-
-            // Compute and publish diagnostics
-            let diagnostics = handlers::compute_diagnostics(&uri_clone, &state).await;
-            client
-                .publish_diagnostics(uri_clone, diagnostics, None)
-                .await;
+            Self::analyze_changed_document(&state, &client, uri_clone, stamp).await;
         });
 
-        self.pending_analysis.insert(uri, handle);
+        self.state.pending_analysis.insert(uri, handle);
 
         tracing::debug!(
             "[HANDLER_EXIT] did_change elapsed_ms={}",
@@ -653,13 +946,68 @@ impl LanguageServer for LspServer {
         );
     }
 
+    async fn did_save(&self, params: DidSaveTextDocumentParams) {
+        let uri = params.text_document.uri;
+        tracing::debug!("[HANDLER_ENTER] did_save uri={}", uri);
+        let start = std::time::Instant::now();
+
+        // Save supersedes any debounced analysis
+        if let Some((_, handle)) = self.state.pending_analysis.remove(&uri) {
+            handle.abort();
+        }
+
+        let Some((content, version)) = self
+            .state
+            .document_manager
+            .get(&uri)
+            .map(|doc| (doc.content.to_string(), doc.version))
+        else {
+            tracing::debug!(
+                "[HANDLER_EXIT] did_save document not open elapsed_ms={}",
+                start.elapsed().as_millis()
+            );
+            return;
+        };
+
+        if Self::is_passive_document(&self.state, &uri).await {
+            tracing::debug!(
+                "[HANDLER_EXIT] did_save passive document elapsed_ms={}",
+                start.elapsed().as_millis()
+            );
+            return;
+        }
+
+        // Re-apply the saved text as a full replacement so the document is
+        // reparsed from scratch rather than incrementally
+        let change = TextDocumentContentChangeEvent {
+            range: None,
+            range_length: None,
+            text: params.text.unwrap_or(content),
+        };
+        self.state
+            .document_manager
+            .change(&uri, vec![change], version)
+            .await;
+
+        // Also refreshes abundantis for env files, so resolution sees on-disk state
+        self.update_workspace_index_for_document(&uri).await;
+
+        let diagnostics = handlers::compute_diagnostics(&uri, &self.state).await;
+        self.client.publish_diagnostics(uri, diagnostics, None).await;
+
+        tracing::debug!(
+            "[HANDLER_EXIT] did_save elapsed_ms={}",
+            start.elapsed().as_millis()
+        );
+    }
+
     async fn did_close(&self, params: DidCloseTextDocumentParams) {
         let uri = params.text_document.uri;
         tracing::debug!("[HANDLER_ENTER] did_close uri={}", uri);
         let start = std::time::Instant::now();
 
         // Cancel any pending analysis for this document
-        if let Some((_, handle)) = self.pending_analysis.remove(&uri) {
+        if let Some((_, handle)) = self.state.pending_analysis.remove(&uri) {
             handle.abort();
         }
 
@@ -692,8 +1040,47 @@ impl LanguageServer for LspServer {
             let config = config_arc.read().await;
             config.clone()
         };
+        let workspace_root = util::get_workspace_root(&self.state.core.workspace).await;
 
-        for change in params.changes {
+        // A rename arrives as a delete plus a create. Move code files' index
+        // entries instead, so importers of a renamed module keep resolving.
+        // Env files take the normal path since abundantis must reload them.
+        let is_code_file = |uri: &Url| {
+            uri.to_file_path().is_ok_and(|path| {
+                !util::is_env_file_path(
+                    &config.workspace.env_files,
+                    &path,
+                    &workspace_root,
+                    config.workspace.case_insensitive_env_files,
+                )
+            })
+        };
+        let uris_of = |typ: FileChangeType| -> Vec<Url> {
+            params
+                .changes
+                .iter()
+                .filter(|change| change.typ == typ && is_code_file(&change.uri))
+                .map(|change| change.uri.clone())
+                .collect()
+        };
+        let renames = self
+            .state
+            .indexer
+            .pair_renames(&uris_of(FileChangeType::DELETED), &uris_of(FileChangeType::CREATED))
+            .await;
+        for (old, new) in &renames {
+            self.state
+                .indexer
+                .on_file_renamed(old, new, &config.workspace.env_files)
+                .await;
+        }
+        let changes = params.changes.into_iter().filter(|change| {
+            !renames
+                .iter()
+                .any(|(old, new)| &change.uri == old || &change.uri == new)
+        });
+
+        for change in changes {
             let path = match change.uri.to_file_path() {
                 Ok(p) => p,
                 Err(_) => continue,
@@ -703,17 +1090,25 @@ impl LanguageServer for LspServer {
                 self.client
                     .log_message(MessageType::INFO, "Reloading configuration...")
                     .await;
-                let workspace_root = util::get_workspace_root(&self.state.core.workspace).await;
-                let _ = self.state.config.load_from_workspace(&workspace_root).await;
+                if let Ok(cfg) = self.state.config.load_from_workspace(&workspace_root).await {
+                    self.apply_indexer_settings(&cfg);
+                    self.state
+                        .set_scan_all_config_files(cfg.workspace.scan_all_config_files);
+                    self.state.set_detectors(cfg.detectors);
+                    self.state.set_env_accessors(cfg.custom_env_accessors);
+                    self.state
+                        .languages
+                        .set_extension_overrides(cfg.extension_overrides);
+                }
                 continue;
             }
 
-            let file_name = path.file_name().and_then(|n| n.to_str()).unwrap_or("");
-            let is_env_file = config.workspace.env_files.iter().any(|pattern| {
-                glob::Pattern::new(pattern.as_str())
-                    .map(|p| p.matches(file_name))
-                    .unwrap_or(false)
-            });
+            let is_env_file = util::is_env_file_path(
+                &config.workspace.env_files,
+                &path,
+                &workspace_root,
+                config.workspace.case_insensitive_env_files,
+            );
 
             match change.typ {
                 FileChangeType::CREATED | FileChangeType::CHANGED => {
@@ -725,6 +1120,7 @@ impl LanguageServer for LspServer {
                     if is_env_file {
                         util::safe_refresh(
                             &self.state.core,
+                            &self.state.config,
                             abundantis::RefreshOptions::preserve_all(),
                         )
                         .await;
@@ -740,6 +1136,7 @@ impl LanguageServer for LspServer {
                     if is_env_file {
                         util::safe_refresh(
                             &self.state.core,
+                            &self.state.config,
                             abundantis::RefreshOptions::preserve_all(),
                         )
                         .await;
@@ -754,11 +1151,55 @@ impl LanguageServer for LspServer {
         }
     }
 
+    async fn did_rename_files(&self, params: RenameFilesParams) {
+        let config = {
+            let config_arc = self.state.config.get_config();
+            let config = config_arc.read().await;
+            config.clone()
+        };
+        let workspace_root = util::get_workspace_root(&self.state.core.workspace).await;
+
+        let mut env_file_renamed = false;
+        for file in params.files {
+            let (Ok(old), Ok(new)) = (Url::parse(&file.old_uri), Url::parse(&file.new_uri)) else {
+                continue;
+            };
+            env_file_renamed |= [&old, &new].iter().any(|uri| {
+                uri.to_file_path().is_ok_and(|path| {
+                    util::is_env_file_path(
+                        &config.workspace.env_files,
+                        &path,
+                        &workspace_root,
+                        config.workspace.case_insensitive_env_files,
+                    )
+                })
+            });
+            self.state
+                .indexer
+                .on_file_renamed(&old, &new, &config.workspace.env_files)
+                .await;
+        }
+
+        if env_file_renamed {
+            util::safe_refresh(
+                &self.state.core,
+                &self.state.config,
+                abundantis::RefreshOptions::preserve_all(),
+            )
+            .await;
+            self.refresh_all_diagnostics().await;
+            self.refresh_inlay_hints().await;
+        }
+    }
+
     async fn completion(&self, params: CompletionParams) -> Result<Option<CompletionResponse>> {
         let uri = &params.text_document_position.text_document.uri;
         tracing::debug!("[HANDLER_ENTER] completion uri={}", uri);
         let start = std::time::Instant::now();
-        let result = handlers::handle_completion(params, &self.state).await.map(CompletionResponse::Array);
+        let max_items = self.state.config.get_config().read().await.features.max_completion_items;
+        let result = handlers::handle_completion(params, &self.state)
+            .await
+            .map(|items| handlers::completion_response(items, max_items));
         tracing::debug!(
             "[HANDLER_EXIT] completion result={} elapsed_ms={}",
             if result.is_some() { "some" } else { "none" },
@@ -798,6 +1239,7 @@ impl LanguageServer for LspServer {
             "ecolog.source.setPrecedence",
             "ecolog.interpolation.set",
             "ecolog.file.setActive",
+            "ecolog.file.setProfile",
             "ecolog.workspace.setRoot",
         ];
         if refresh_commands.contains(&command.as_str()) {
@@ -820,7 +1262,11 @@ impl LanguageServer for LspServer {
         let uri = &params.text_document_position.text_document.uri;
         tracing::debug!("[HANDLER_ENTER] references uri={}", uri);
         let start = std::time::Instant::now();
-        let result = handlers::handle_references(params, &self.state).await;
+        let result = self
+            .run_cancellable(|state, cancel| async move {
+                handlers::handle_references_with_cancellation(params, &state, &cancel).await
+            })
+            .await;
         tracing::debug!(
             "[HANDLER_EXIT] references result={} elapsed_ms={}",
             if result.is_some() { "some" } else { "none" },
@@ -829,6 +1275,22 @@ impl LanguageServer for LspServer {
         Ok(result)
     }
 
+    async fn selection_range(
+        &self,
+        params: SelectionRangeParams,
+    ) -> Result<Option<Vec<SelectionRange>>> {
+        let uri = &params.text_document.uri;
+        tracing::debug!("[HANDLER_ENTER] selection_range uri={}", uri);
+        let start = std::time::Instant::now();
+        let result = handlers::handle_selection_range(params, &self.state).await;
+        tracing::debug!(
+            "[HANDLER_EXIT] selection_range result={} elapsed_ms={}",
+            if result.is_some() { "some" } else { "none" },
+            start.elapsed().as_millis()
+        );
+        Ok(result)
+    }
+
     async fn prepare_rename(
         &self,
         params: TextDocumentPositionParams,
@@ -849,7 +1311,11 @@ impl LanguageServer for LspServer {
         let uri = &params.text_document_position.text_document.uri;
         tracing::debug!("[HANDLER_ENTER] rename uri={}", uri);
         let start = std::time::Instant::now();
-        let result = handlers::handle_rename(params, &self.state).await;
+        let result = self
+            .run_cancellable(|state, cancel| async move {
+                handlers::handle_rename_with_cancellation(params, &state, &cancel).await
+            })
+            .await;
         tracing::debug!(
             "[HANDLER_EXIT] rename result={} elapsed_ms={}",
             if result.is_some() { "some" } else { "none" },
@@ -858,13 +1324,33 @@ impl LanguageServer for LspServer {
         Ok(result)
     }
 
+    async fn document_symbol(
+        &self,
+        params: DocumentSymbolParams,
+    ) -> Result<Option<DocumentSymbolResponse>> {
+        let uri = &params.text_document.uri;
+        tracing::debug!("[HANDLER_ENTER] document_symbol uri={}", uri);
+        let start = std::time::Instant::now();
+        let result = handlers::handle_document_symbol(params, &self.state).await;
+        tracing::debug!(
+            "[HANDLER_EXIT] document_symbol result={} elapsed_ms={}",
+            if result.is_some() { "some" } else { "none" },
+            start.elapsed().as_millis()
+        );
+        Ok(result)
+    }
+
     async fn symbol(
         &self,
         params: WorkspaceSymbolParams,
     ) -> Result<Option<Vec<SymbolInformation>>> {
         tracing::debug!("[HANDLER_ENTER] workspace_symbol query={}", params.query);
         let start = std::time::Instant::now();
-        let result = handlers::handle_workspace_symbol(params, &self.state).await;
+        let result = self
+            .run_cancellable(|state, cancel| async move {
+                handlers::handle_workspace_symbol_with_cancellation(params, &state, &cancel).await
+            })
+            .await;
         tracing::debug!(
             "[HANDLER_EXIT] workspace_symbol result={} elapsed_ms={}",
             if result.is_some() { "some" } else { "none" },
@@ -885,4 +1371,121 @@ impl LanguageServer for LspServer {
         );
         Ok(result)
     }
+
+    async fn inlay_hint_resolve(&self, params: InlayHint) -> Result<InlayHint> {
+        tracing::debug!("[HANDLER_ENTER] inlay_hint_resolve");
+        let start = std::time::Instant::now();
+        let result = handlers::handle_inlay_hint_resolve(params, &self.state).await;
+        tracing::debug!(
+            "[HANDLER_EXIT] inlay_hint_resolve elapsed_ms={}",
+            start.elapsed().as_millis()
+        );
+        Ok(result)
+    }
+
+    async fn code_action(&self, params: CodeActionParams) -> Result<Option<CodeActionResponse>> {
+        let uri = &params.text_document.uri;
+        tracing::debug!("[HANDLER_ENTER] code_action uri={}", uri);
+        let start = std::time::Instant::now();
+        let result = handlers::handle_code_action(params, &self.state).await;
+        tracing::debug!(
+            "[HANDLER_EXIT] code_action result={} elapsed_ms={}",
+            if result.is_some() { "some" } else { "none" },
+            start.elapsed().as_millis()
+        );
+        Ok(result)
+    }
+
+    async fn code_lens(&self, params: CodeLensParams) -> Result<Option<Vec<CodeLens>>> {
+        let uri = &params.text_document.uri;
+        tracing::debug!("[HANDLER_ENTER] code_lens uri={}", uri);
+        let start = std::time::Instant::now();
+        let result = handlers::handle_code_lens(params, &self.state).await;
+        tracing::debug!(
+            "[HANDLER_EXIT] code_lens result={} elapsed_ms={}",
+            if result.is_some() { "some" } else { "none" },
+            start.elapsed().as_millis()
+        );
+        Ok(result)
+    }
+
+    async fn semantic_tokens_full(
+        &self,
+        params: SemanticTokensParams,
+    ) -> Result<Option<SemanticTokensResult>> {
+        let uri = &params.text_document.uri;
+        tracing::debug!("[HANDLER_ENTER] semantic_tokens_full uri={}", uri);
+        let start = std::time::Instant::now();
+        let result = handlers::handle_semantic_tokens_full(params, &self.state).await;
+        tracing::debug!(
+            "[HANDLER_EXIT] semantic_tokens_full result={} elapsed_ms={}",
+            if result.is_some() { "some" } else { "none" },
+            start.elapsed().as_millis()
+        );
+        Ok(result)
+    }
+
+    async fn code_lens_resolve(&self, params: CodeLens) -> Result<CodeLens> {
+        tracing::debug!("[HANDLER_ENTER] code_lens_resolve");
+        let start = std::time::Instant::now();
+        let result = handlers::handle_code_lens_resolve(params, &self.state).await;
+        tracing::debug!(
+            "[HANDLER_EXIT] code_lens_resolve elapsed_ms={}",
+            start.elapsed().as_millis()
+        );
+        Ok(result)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    async fn with_server(test: impl FnOnce(&LspServer)) {
+        let temp_dir = TempDir::new().unwrap();
+        let core = abundantis::Abundantis::builder()
+            .root(temp_dir.path())
+            .build()
+            .await
+            .unwrap();
+        let (service, _socket) = tower_lsp::LspService::new(|client| LspServer::new(client, core));
+        test(service.inner());
+    }
+
+    #[tokio::test]
+    async fn test_heartbeat_disabled_stores_no_handle() {
+        with_server(|server| {
+            let logging = LoggingConfig {
+                heartbeat_secs: 0,
+                ..Default::default()
+            };
+            server.start_heartbeat(&logging);
+            assert!(server.heartbeat_handle.lock().is_none());
+        })
+        .await;
+    }
+
+    #[tokio::test]
+    async fn test_heartbeat_enabled_stores_handle() {
+        with_server(|server| {
+            server.start_heartbeat(&LoggingConfig::default());
+            let handle = server.heartbeat_handle.lock().take();
+            assert!(handle.is_some());
+            handle.unwrap().abort();
+        })
+        .await;
+    }
+
+    #[tokio::test]
+    async fn test_apply_indexer_settings_resizes_module_cache() {
+        with_server(|server| {
+            let mut cfg = config::EcologConfig::default();
+            cfg.workspace.module_cache_max = 64;
+            server.apply_indexer_settings(&cfg);
+            let stats = server.state.workspace_index.module_cache_stats();
+            assert_eq!(stats.capacity, 64);
+        })
+        .await;
+    }
 }
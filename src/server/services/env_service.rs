@@ -3,6 +3,8 @@
 //! This service wraps `abundantis::Abundantis` and provides a focused interface
 //! for environment variable resolution with ~90% cohesion.
 
+use crate::server::config::ConfigManager;
+use crate::server::util::prepare_file_sources;
 use abundantis::{Abundantis, ResolvedVariable};
 use parking_lot::RwLock;
 use std::path::{Path, PathBuf};
@@ -24,12 +26,21 @@ const REFRESH_TIMEOUT: Duration = Duration::from_secs(10);
 /// - Managing active files and workspace root
 pub struct EnvService {
     core: Arc<Abundantis>,
+    /// Read when a refresh re-applies the `env_files` patterns.
+    config: Arc<ConfigManager>,
+    /// Global active file patterns as last set through this service;
+    /// abundantis doesn't expose them.
+    active_patterns: Arc<RwLock<Option<Vec<String>>>>,
 }
 
 impl EnvService {
     /// Creates a new EnvService wrapping the given Abundantis core.
-    pub fn new(core: Arc<Abundantis>) -> Self {
-        Self { core }
+    pub fn new(core: Arc<Abundantis>, config: Arc<ConfigManager>) -> Self {
+        Self {
+            core,
+            config,
+            active_patterns: Arc::new(RwLock::new(None)),
+        }
     }
 
     /// Returns a reference to the underlying Abundantis core.
@@ -100,7 +111,7 @@ impl EnvService {
     /// Refreshes env var sources with timeout protection.
     pub async fn refresh(&self, options: abundantis::RefreshOptions) {
         match tokio::time::timeout(REFRESH_TIMEOUT, self.core.refresh(options)).await {
-            Ok(Ok(())) => {}
+            Ok(Ok(())) => prepare_file_sources(&self.core, &self.config),
             Ok(Err(e)) => {
                 tracing::warn!("refresh error: {}", e);
             }
@@ -113,9 +124,10 @@ impl EnvService {
     /// Spawns a background refresh task.
     pub fn spawn_background_refresh(&self, options: abundantis::RefreshOptions) {
         let core = Arc::clone(&self.core);
+        let config = Arc::clone(&self.config);
         tokio::spawn(async move {
             match tokio::time::timeout(REFRESH_TIMEOUT, core.refresh(options)).await {
-                Ok(Ok(())) => {}
+                Ok(Ok(())) => prepare_file_sources(&core, &config),
                 Ok(Err(e)) => {
                     tracing::warn!("background refresh error: {}", e);
                 }
@@ -128,12 +140,29 @@ impl EnvService {
 
     /// Sets the active env files filter.
     pub fn set_active_files(&self, patterns: &[String]) {
+        let mut active = self.active_patterns.write();
         self.core.set_active_files(patterns);
+        *active = Some(patterns.to_vec());
     }
 
     /// Clears the active env files filter.
     pub fn clear_active_files(&self) {
+        let mut active = self.active_patterns.write();
         self.core.clear_active_files();
+        *active = None;
+    }
+
+    /// Returns the active env files filter, `None` when cleared.
+    pub fn active_file_patterns(&self) -> Option<Vec<String>> {
+        self.active_patterns.read().clone()
+    }
+
+    /// Restores a filter returned by [`active_file_patterns`](Self::active_file_patterns).
+    pub fn restore_active_files(&self, patterns: Option<Vec<String>>) {
+        match patterns {
+            Some(patterns) => self.set_active_files(&patterns),
+            None => self.clear_active_files(),
+        }
     }
 
     /// Gets the active env files for a path.
@@ -157,6 +186,8 @@ impl Clone for EnvService {
     fn clone(&self) -> Self {
         Self {
             core: Arc::clone(&self.core),
+            config: Arc::clone(&self.config),
+            active_patterns: Arc::clone(&self.active_patterns),
         }
     }
 }
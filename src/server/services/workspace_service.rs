@@ -208,7 +208,7 @@ impl WorkspaceService {
     // =========================================================================
 
     /// Starts indexing the workspace.
-    pub async fn index_workspace(&self, config: &EcologConfig) -> anyhow::Result<()> {
+    pub async fn index_workspace(&self, config: &EcologConfig) -> anyhow::Result<IndexStats> {
         self.indexer.index_workspace(&config.workspace.env_files).await
     }
 
@@ -217,6 +217,13 @@ impl WorkspaceService {
         self.indexer.on_file_changed(uri, &config.workspace.env_files).await;
     }
 
+    /// Handles a file rename, keeping the file's index entries.
+    pub async fn on_file_renamed(&self, old: &Url, new: &Url, config: &EcologConfig) {
+        self.indexer
+            .on_file_renamed(old, new, &config.workspace.env_files)
+            .await;
+    }
+
     /// Handles a file deletion event.
     pub fn on_file_deleted(&self, uri: &Url) {
         self.indexer.on_file_deleted(uri);
@@ -8,11 +8,17 @@ use crate::analysis::{
 };
 use crate::languages::LanguageRegistry;
 use crate::server::config::ConfigManager;
+use crate::server::env_resolution::EnvSourceProvider;
+use crate::server::util::prepare_file_sources;
 use crate::server::services::{DocumentService, EnvService, WorkspaceService};
 use abundantis::source::remote::ProviderManager;
 use abundantis::Abundantis;
+use dashmap::DashMap;
 use std::path::PathBuf;
+use std::sync::atomic::AtomicBool;
 use std::sync::Arc;
+use std::time::Instant;
+use tower_lsp::lsp_types::Url;
 
 /// Main server state that coordinates between focused services.
 ///
@@ -50,10 +56,27 @@ pub struct ServerState {
     pub module_resolver: Arc<ModuleResolver>,
     /// External provider manager for out-of-process providers.
     pub provider_manager: Arc<ProviderManager>,
+
+    // Runtime bookkeeping
+    /// Per-URI pending analysis tasks for debouncing did_change.
+    pub pending_analysis: Arc<DashMap<Url, tokio::task::JoinHandle<()>>>,
+    /// When the server state was created, used to report uptime.
+    pub started_at: Instant,
+    /// Optional fallback env source consulted after abundantis.
+    pub env_source_provider: Option<Arc<dyn EnvSourceProvider>>,
+    /// Serializes rename requests so each computes edits against one index snapshot.
+    pub rename_lock: Arc<tokio::sync::Mutex<()>>,
+    /// Set when the client resolves inlay hint labels, so values are computed
+    /// in `inlayHint/resolve` instead of up front.
+    pub defer_inlay_hint_values: Arc<AtomicBool>,
+    /// Set once the initial workspace indexing in `initialized` finishes;
+    /// reported as `ready` by `ecolog.ping`.
+    pub initial_index_complete: Arc<AtomicBool>,
 }
 
 impl ServerState {
     /// Creates a new ServerState with all components.
+    #[allow(clippy::too_many_arguments)]
     pub fn new(
         document_manager: Arc<DocumentManager>,
         languages: Arc<LanguageRegistry>,
@@ -64,9 +87,11 @@ impl ServerState {
         module_resolver: Arc<ModuleResolver>,
         provider_manager: Arc<ProviderManager>,
     ) -> Self {
+        prepare_file_sources(&core, &config);
+
         // Create services wrapping the underlying components
         let documents = DocumentService::new(Arc::clone(&document_manager));
-        let env = EnvService::new(Arc::clone(&core));
+        let env = EnvService::new(Arc::clone(&core), Arc::clone(&config));
         let workspace = WorkspaceService::new(
             Arc::clone(&workspace_index),
             Arc::clone(&indexer),
@@ -87,9 +112,50 @@ impl ServerState {
             indexer,
             module_resolver,
             provider_manager,
+            // Runtime bookkeeping
+            pending_analysis: Arc::new(DashMap::new()),
+            started_at: Instant::now(),
+            rename_lock: Arc::new(tokio::sync::Mutex::new(())),
+            defer_inlay_hint_values: Arc::new(AtomicBool::new(false)),
+            initial_index_complete: Arc::new(AtomicBool::new(false)),
+            env_source_provider: None,
         }
     }
 
+    /// Sets the fallback env source consulted when abundantis has no value.
+    pub fn with_env_source_provider(mut self, provider: Arc<dyn EnvSourceProvider>) -> Self {
+        self.env_source_provider = Some(provider);
+        self
+    }
+
+    /// Installs the configured custom detectors for both open documents and
+    /// workspace indexing.
+    pub fn set_detectors(&self, detectors: Vec<crate::analysis::CustomDetector>) {
+        self.document_manager.set_detectors(detectors.clone());
+        self.indexer.set_detectors(detectors);
+    }
+
+    /// Whether every YAML and JSON file is scanned for interpolation, not
+    /// just Compose files, for open documents and workspace indexing.
+    pub fn set_scan_all_config_files(&self, scan_all: bool) {
+        self.document_manager.set_scan_all_config_files(scan_all);
+        self.indexer.set_scan_all_config_files(scan_all);
+    }
+
+    /// Installs the configured env accessor calls. The query engine is
+    /// shared, so this covers open documents and workspace indexing.
+    pub fn set_env_accessors(&self, accessors: Vec<crate::analysis::EnvAccessor>) {
+        self.document_manager.query_engine().set_env_accessors(accessors);
+    }
+
+    /// Number of debounced analysis tasks that haven't finished yet.
+    pub fn pending_analysis_count(&self) -> usize {
+        self.pending_analysis
+            .iter()
+            .filter(|entry| !entry.value().is_finished())
+            .count()
+    }
+
     /// Gets the workspace context for a file URI.
     pub fn get_env_context(
         &self,
@@ -0,0 +1,43 @@
+//! `$/ecolog/status` notifications.
+//!
+//! Query pre-warming and workspace indexing run in the background after
+//! `initialized`, so hover and completion may be incomplete for a moment.
+//! The server reports `indexing` when that work starts and `ready` once it
+//! has finished, letting editor extensions show a status indicator.
+
+use serde::{Deserialize, Serialize};
+use tower_lsp::lsp_types::notification::Notification;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ServerStatus {
+    Indexing,
+    Ready,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct StatusParams {
+    pub status: ServerStatus,
+}
+
+/// The `$/ecolog/status` notification.
+pub enum StatusNotification {}
+
+impl Notification for StatusNotification {
+    type Params = StatusParams;
+    const METHOD: &'static str = "$/ecolog/status";
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_status_params_serialize_lowercase() {
+        let params = StatusParams {
+            status: ServerStatus::Ready,
+        };
+        assert_eq!(serde_json::to_value(params).unwrap(), json!({"status": "ready"}));
+    }
+}
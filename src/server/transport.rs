@@ -0,0 +1,113 @@
+//! Transport selection for the server binary.
+//!
+//! Stdio is the default. `--socket <port>` listens on `127.0.0.1:<port>`
+//! instead and serves the first client that connects, which suits remote
+//! and dev-container setups where the editor can't spawn the server itself.
+
+use tower_lsp::{ClientSocket, LanguageServer, LspService, Server};
+
+pub const USAGE: &str = "\
+Usage: ecolog-lsp [OPTIONS]
+
+Options:
+      --stdio          Communicate over stdin/stdout (default)
+      --socket <PORT>  Listen on 127.0.0.1:<PORT> and serve the first client
+  -h, --help           Print help
+  -V, --version        Print version
+";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Transport {
+    Stdio,
+    Socket(u16),
+}
+
+/// What the command line asks the binary to do.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CliCommand {
+    Serve(Transport),
+    Help,
+    Version,
+}
+
+/// Parses the arguments after the program name.
+pub fn parse_args(args: impl IntoIterator<Item = String>) -> Result<CliCommand, String> {
+    let mut transport = Transport::Stdio;
+    let mut args = args.into_iter();
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "-h" | "--help" => return Ok(CliCommand::Help),
+            "-V" | "--version" => return Ok(CliCommand::Version),
+            "--stdio" => transport = Transport::Stdio,
+            "--socket" => {
+                let port = args.next().ok_or("--socket requires a port")?;
+                transport = Transport::Socket(parse_port(&port)?);
+            }
+            _ => match arg.strip_prefix("--socket=") {
+                Some(port) => transport = Transport::Socket(parse_port(port)?),
+                None => return Err(format!("Unknown argument: {}", arg)),
+            },
+        }
+    }
+    Ok(CliCommand::Serve(transport))
+}
+
+fn parse_port(port: &str) -> Result<u16, String> {
+    port.parse()
+        .map_err(|_| format!("Invalid port: {}", port))
+}
+
+/// Serves `service` over `transport` until the client sends `exit` or
+/// disconnects.
+pub async fn serve<S: LanguageServer>(
+    transport: Transport,
+    service: LspService<S>,
+    socket: ClientSocket,
+) -> std::io::Result<()> {
+    match transport {
+        Transport::Stdio => {
+            Server::new(tokio::io::stdin(), tokio::io::stdout(), socket)
+                .serve(service)
+                .await;
+        }
+        Transport::Socket(port) => {
+            let listener = tokio::net::TcpListener::bind(("127.0.0.1", port)).await?;
+            tracing::info!("Listening on {}", listener.local_addr()?);
+            let (stream, peer) = listener.accept().await?;
+            tracing::info!("Client connected from {}", peer);
+            let (read, write) = stream.into_split();
+            Server::new(read, write, socket).serve(service).await;
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(args: &[&str]) -> Result<CliCommand, String> {
+        parse_args(args.iter().map(|s| s.to_string()))
+    }
+
+    #[test]
+    fn test_parse_args_defaults_to_stdio() {
+        assert_eq!(parse(&[]), Ok(CliCommand::Serve(Transport::Stdio)));
+        assert_eq!(parse(&["--stdio"]), Ok(CliCommand::Serve(Transport::Stdio)));
+    }
+
+    #[test]
+    fn test_parse_args_socket() {
+        let expected = Ok(CliCommand::Serve(Transport::Socket(9257)));
+        assert_eq!(parse(&["--socket", "9257"]), expected);
+        assert_eq!(parse(&["--socket=9257"]), expected);
+    }
+
+    #[test]
+    fn test_parse_args_errors() {
+        assert!(parse(&["--socket"]).is_err());
+        assert!(parse(&["--socket", "http"]).is_err());
+        assert!(parse(&["--tcp"]).is_err());
+        assert_eq!(parse(&["--socket", "1", "--help"]), Ok(CliCommand::Help));
+    }
+}
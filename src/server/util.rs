@@ -1,12 +1,27 @@
+use crate::server::config::ConfigManager;
+use crate::server::lossy_source::register_lossy_file_sources;
+use crate::server::state::ServerState;
 use parking_lot::RwLock;
 use ropey::Rope;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 use std::sync::Arc;
 use std::time::Duration;
+use tower_lsp::lsp_types::Url;
+
+pub use crate::analysis::workspace_glob::{is_env_file_path, matches_workspace_glob};
 
 const REFRESH_TIMEOUT: Duration = Duration::from_secs(10);
 
-const RESOLUTION_TIMEOUT: Duration = Duration::from_secs(5);
+/// Comma-separated env file patterns made active at startup, for CI and
+/// headless use where no client issues `ecolog.file.setActive`.
+pub const ACTIVE_FILES_ENV_VAR: &str = "ECOLOG_ACTIVE_FILES";
+
+/// Default for `workspace.resolution_timeout_ms`.
+pub const DEFAULT_RESOLUTION_TIMEOUT_MS: u64 = 5000;
+
+/// A lookup that ran past its timeout, as opposed to one that found nothing.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ResolutionTimedOut;
 
 pub async fn get_workspace_root(
     workspace: &Arc<RwLock<abundantis::workspace::WorkspaceManager>>,
@@ -15,9 +30,89 @@ pub async fn get_workspace_root(
     guard.root().to_path_buf()
 }
 
-pub async fn safe_refresh(core: &Arc<abundantis::Abundantis>, options: abundantis::RefreshOptions) {
+/// Converts a document URI to a filesystem path.
+///
+/// `file://` URIs go through [`Url::to_file_path`]. URIs it rejects, like a
+/// `file://` URI with a remote host or an `untitled:` buffer, fall back to
+/// their percent-decoded path, so `%20` still comes back as a space.
+pub fn uri_to_path(uri: &Url) -> PathBuf {
+    uri.to_file_path().unwrap_or_else(|_| {
+        PathBuf::from(
+            percent_encoding::percent_decode_str(uri.path())
+                .decode_utf8_lossy()
+                .into_owned(),
+        )
+    })
+}
+
+/// Checks whether `uri` names an env file according to `workspace.env_files`.
+pub async fn is_env_file_uri(state: &ServerState, uri: &Url) -> bool {
+    let path = uri_to_path(uri);
+    let root = get_workspace_root(&state.core.workspace).await;
+
+    let config = state.config.get_config();
+    let config = config.read().await;
+    is_env_file_path(
+        &config.workspace.env_files,
+        &path,
+        &root,
+        config.workspace.case_insensitive_env_files,
+    )
+}
+
+/// Whether handlers should analyze `name`, per the env var allow and deny
+/// lists.
+pub async fn is_env_var_tracked(state: &ServerState, name: &str) -> bool {
+    let config = state.config.get_config();
+    let config = config.read().await;
+    config.tracks_env_var(name)
+}
+
+/// Fixes up the file sources abundantis discovered: drops files excluded by a
+/// `!` pattern in `workspace.env_files`, which abundantis' globbing can't
+/// express, and swaps in lossy sources for files that aren't valid UTF-8.
+///
+/// Run after building the core and after every refresh, since refreshing
+/// rediscovers file sources.
+pub fn prepare_file_sources(core: &abundantis::Abundantis, config: &ConfigManager) {
+    unregister_excluded_file_sources(core, config.case_insensitive_env_files());
+    register_lossy_file_sources(core);
+}
+
+fn unregister_excluded_file_sources(core: &abundantis::Abundantis, case_insensitive: bool) {
+    let patterns = &core.config.workspace.env_files;
+    if !patterns.iter().any(|pattern| pattern.starts_with('!')) {
+        return;
+    }
+    let root = core.workspace.read().root().to_path_buf();
+    for source in core.registry.sources_of_type(abundantis::source::SourceType::File) {
+        let Some(path) = source.id().as_str().strip_prefix("file:").map(PathBuf::from) else {
+            continue;
+        };
+        if !is_env_file_path(patterns, &path, &root, case_insensitive) {
+            tracing::debug!("Dropping excluded env file source {:?}", path);
+            core.registry.unregister_sync(source.id());
+        }
+    }
+}
+
+/// Splits an `ECOLOG_ACTIVE_FILES` value into patterns, dropping empty ones.
+pub fn parse_active_files(value: &str) -> Vec<String> {
+    value
+        .split(',')
+        .map(str::trim)
+        .filter(|pattern| !pattern.is_empty())
+        .map(String::from)
+        .collect()
+}
+
+pub async fn safe_refresh(
+    core: &Arc<abundantis::Abundantis>,
+    config: &ConfigManager,
+    options: abundantis::RefreshOptions,
+) {
     match tokio::time::timeout(REFRESH_TIMEOUT, core.refresh(options)).await {
-        Ok(Ok(())) => {}
+        Ok(Ok(())) => prepare_file_sources(core, config),
         Ok(Err(e)) => {
             tracing::warn!("safe_refresh error: {}", e);
         }
@@ -29,47 +124,101 @@ pub async fn safe_refresh(core: &Arc<abundantis::Abundantis>, options: abundanti
 
 pub fn spawn_background_refresh(
     core: Arc<abundantis::Abundantis>,
+    config: Arc<ConfigManager>,
     options: abundantis::RefreshOptions,
 ) {
     tokio::spawn(async move {
-        safe_refresh(&core, options).await;
+        safe_refresh(&core, &config, options).await;
     });
 }
 
-pub async fn safe_get_for_file(
+/// Resolves `key` for a file, telling a timeout apart from a miss.
+///
+/// Errors are logged and treated as a miss.
+pub async fn try_get_for_file(
     core: &Arc<abundantis::Abundantis>,
     key: &str,
     file_path: &std::path::Path,
-) -> Option<std::sync::Arc<abundantis::ResolvedVariable>> {
-    match tokio::time::timeout(RESOLUTION_TIMEOUT, core.get_for_file(key, file_path)).await {
-        Ok(Ok(result)) => result,
+    timeout: Duration,
+) -> Result<Option<std::sync::Arc<abundantis::ResolvedVariable>>, ResolutionTimedOut> {
+    match tokio::time::timeout(timeout, core.get_for_file(key, file_path)).await {
+        Ok(Ok(result)) => Ok(result),
         Ok(Err(e)) => {
             tracing::warn!("get_for_file error for key '{}': {}", key, e);
-            None
+            Ok(None)
         }
         Err(_) => {
-            tracing::error!(
-                "get_for_file timeout after {:?} for key '{}'",
-                RESOLUTION_TIMEOUT,
-                key
-            );
-            None
+            tracing::error!("get_for_file timeout after {:?} for key '{}'", timeout, key);
+            Err(ResolutionTimedOut)
         }
     }
 }
 
+pub async fn safe_get_for_file(
+    core: &Arc<abundantis::Abundantis>,
+    key: &str,
+    file_path: &std::path::Path,
+    timeout: Duration,
+) -> Option<std::sync::Arc<abundantis::ResolvedVariable>> {
+    try_get_for_file(core, key, file_path, timeout)
+        .await
+        .ok()
+        .flatten()
+}
+
 pub async fn safe_all_for_file(
     core: &Arc<abundantis::Abundantis>,
     file_path: &std::path::Path,
+    timeout: Duration,
 ) -> Vec<std::sync::Arc<abundantis::ResolvedVariable>> {
-    match tokio::time::timeout(RESOLUTION_TIMEOUT, core.all_for_file(file_path)).await {
+    match tokio::time::timeout(timeout, core.all_for_file(file_path)).await {
         Ok(Ok(result)) => result,
         Ok(Err(e)) => {
             tracing::warn!("all_for_file error: {}", e);
             Vec::new()
         }
         Err(_) => {
-            tracing::error!("all_for_file timeout after {:?}", RESOLUTION_TIMEOUT);
+            tracing::error!("all_for_file timeout after {:?}", timeout);
+            Vec::new()
+        }
+    }
+}
+
+/// Like [`safe_all_for_file`] for `root`, with the files matching
+/// `patterns` as the active env files in place of the shared filter, which
+/// is left untouched. Patterns resolve against `root` as
+/// `ecolog.file.setActive` patterns do.
+pub async fn safe_all_for_patterns(
+    core: &Arc<abundantis::Abundantis>,
+    root: &Path,
+    patterns: &[String],
+    timeout: Duration,
+) -> Vec<std::sync::Arc<abundantis::ResolvedVariable>> {
+    use abundantis::path_cache::PathCache;
+    use abundantis::selection::ActiveFileSelector;
+    use abundantis::source::SourceId;
+
+    let Some(context) = core.workspace.read().context_for_file(root) else {
+        return Vec::new();
+    };
+    let path_cache = Arc::new(PathCache::new());
+    // File sources are keyed by their canonical path
+    let source_ids = ActiveFileSelector::new(root, Arc::clone(&path_cache))
+        .resolve_patterns(root, patterns)
+        .iter()
+        .map(|path| SourceId::from(format!("file:{}", path_cache.canonicalize(path).display())))
+        .collect();
+    let resolve = core
+        .resolution
+        .all_variables_with_filter(&context, &core.registry, Some(&source_ids));
+    match tokio::time::timeout(timeout, resolve).await {
+        Ok(Ok(result)) => result,
+        Ok(Err(e)) => {
+            tracing::warn!("all_variables_with_filter error: {}", e);
+            Vec::new()
+        }
+        Err(_) => {
+            tracing::error!("all_variables_with_filter timeout after {:?}", timeout);
             Vec::new()
         }
     }
@@ -92,6 +241,24 @@ pub fn offset_to_linecol(content: &str, offset: usize) -> (u32, u32) {
 mod tests {
     use super::*;
 
+    #[test]
+    fn test_parse_active_files() {
+        assert_eq!(
+            parse_active_files(" .env , config/*.env,,"),
+            vec![".env", "config/*.env"]
+        );
+        assert!(parse_active_files(" , ").is_empty());
+    }
+
+    #[test]
+    fn test_uri_to_path_decodes_percent_escapes() {
+        let uri = Url::parse("file:///my%20project/caf%C3%A9/app.js").unwrap();
+        assert_eq!(uri_to_path(&uri), PathBuf::from("/my project/café/app.js"));
+
+        let remote = Url::parse("file://remote-host/my%20project/.env").unwrap();
+        assert_eq!(uri_to_path(&remote), PathBuf::from("/my project/.env"));
+    }
+
     #[test]
     fn test_offset_to_linecol_single_line() {
         let content = "hello world";
@@ -28,6 +28,25 @@ pub struct EnvReference {
     pub default_value: Option<CompactString>,
 }
 
+/// A variable defined outside abundantis' env files, e.g. by a deployment
+/// config or an [`EnvSourcePlugin`](crate::analysis::EnvSourcePlugin).
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct EnvDefinition {
+   
+    pub name: CompactString,
+
+   
+    pub value: CompactString,
+
+   
+    pub name_range: Range,
+
+    /// The value is only known when the definition runs, like a `build.rs`
+    /// emission built from format arguments. `value` is empty then, and
+    /// the variable counts as defined without a value to show.
+    pub dynamic: bool,
+}
+
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum AccessType {
@@ -706,6 +725,49 @@ impl<'a> KorniEntryExt<'a> for korni::Entry<'a> {
     }
 }
 
+/// Whether `name` is a portable env var name: ASCII letters, digits and
+/// `_`, not starting with a digit.
+pub fn is_valid_env_var_name(name: &str) -> bool {
+    if name.is_empty() {
+        return false;
+    }
+
+    let mut chars = name.chars();
+    if let Some(first) = chars.next() {
+        if !first.is_ascii_alphabetic() && first != '_' {
+            return false;
+        }
+    }
+
+    for ch in chars {
+        if !ch.is_ascii_alphanumeric() && ch != '_' {
+            return false;
+        }
+    }
+
+    true
+}
+
+/// Every code [`compute_diagnostics`](crate::server::handlers::compute_diagnostics)
+/// can report, used to validate `diagnostics.disabled_diagnostics`.
+pub const DIAGNOSTIC_CODES: &[&str] = &[
+    "EDF001",
+    "EDF002",
+    "EDF003",
+    "EDF004",
+    "EDF005",
+    "EDF006",
+    "EDF031",
+    "EDF032",
+    "EDF040",
+    "EDF050",
+    "EDF999",
+    "syntax-error",
+    "parse-error",
+    "resolution-timeout",
+    "undefined-env-var",
+];
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -1169,7 +1231,7 @@ mod tests {
         ctx.aliases
             .insert("env".into(), ("os".into(), "environ".into()));
         assert!(ctx.is_env_related("env", &["os", "process"]));
-        assert!(!ctx.is_env_related("unknown".into(), &["os", "process"]));
+        assert!(!ctx.is_env_related("unknown", &["os", "process"]));
     }
 
    
@@ -1194,4 +1256,36 @@ mod tests {
        
         assert!(!state.has_intelligence());
     }
+
+    // =========================================================================
+    // is_valid_env_var_name tests
+    // =========================================================================
+
+    #[test]
+    fn test_is_valid_env_var_name_valid() {
+        assert!(is_valid_env_var_name("DATABASE_URL"));
+        assert!(is_valid_env_var_name("API_KEY"));
+        assert!(is_valid_env_var_name("_PRIVATE"));
+        assert!(is_valid_env_var_name("VAR1"));
+        assert!(is_valid_env_var_name("A"));
+        assert!(is_valid_env_var_name("_"));
+        assert!(is_valid_env_var_name("__name__"));
+    }
+
+    #[test]
+    fn test_is_valid_env_var_name_invalid() {
+        assert!(!is_valid_env_var_name("")); // empty
+        assert!(!is_valid_env_var_name("1VAR")); // starts with number
+        assert!(!is_valid_env_var_name("VAR-NAME")); // contains hyphen
+        assert!(!is_valid_env_var_name("VAR.NAME")); // contains dot
+        assert!(!is_valid_env_var_name("VAR NAME")); // contains space
+        assert!(!is_valid_env_var_name("VAR@NAME")); // contains special char
+    }
+
+    #[test]
+    fn test_is_valid_env_var_name_unicode() {
+        // Unicode letters are not valid in env var names (ASCII only)
+        assert!(!is_valid_env_var_name("日本語"));
+        assert!(!is_valid_env_var_name("VARäble"));
+    }
 }
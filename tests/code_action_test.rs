@@ -0,0 +1,130 @@
+//! Tests for server/handlers/code_action.rs - Extract to env var
+
+mod common;
+
+use common::TestFixture;
+use ecolog_lsp::server::handlers::handle_code_action;
+use tower_lsp::lsp_types::{
+    CodeActionContext, CodeActionOrCommand, CodeActionParams, Position, Range,
+    TextDocumentIdentifier, Url,
+};
+
+async fn code_actions(
+    fixture: &TestFixture,
+    uri: Url,
+    position: Position,
+) -> Vec<CodeActionOrCommand> {
+    handle_code_action(
+        CodeActionParams {
+            text_document: TextDocumentIdentifier { uri },
+            range: Range::new(position, position),
+            context: CodeActionContext::default(),
+            work_done_progress_params: Default::default(),
+            partial_result_params: Default::default(),
+        },
+        &fixture.state,
+    )
+    .await
+    .unwrap_or_default()
+}
+
+async fn open_js(fixture: &TestFixture, content: &str) -> Url {
+    let uri = fixture.create_file("test.js", content);
+    fixture
+        .state
+        .document_manager
+        .open(uri.clone(), "javascript".to_string(), content.to_string(), 0)
+        .await;
+    uri
+}
+
+#[tokio::test]
+async fn test_extract_string_literal_to_env_var() {
+    let fixture = TestFixture::new().await;
+    let content = "const redisUrl = \"redis://localhost:6379\";\n";
+    let uri = open_js(&fixture, content).await;
+
+    let actions = code_actions(&fixture, uri.clone(), Position::new(0, 22)).await;
+    assert_eq!(actions.len(), 1);
+    let CodeActionOrCommand::CodeAction(action) = &actions[0] else {
+        panic!("Expected a code action");
+    };
+    assert_eq!(action.title, "Extract to environment variable REDIS_URL");
+
+    let changes = action.edit.clone().unwrap().changes.unwrap();
+    assert_eq!(changes.len(), 2);
+
+    let code_edits = &changes[&uri];
+    assert_eq!(code_edits.len(), 1);
+    assert_eq!(
+        code_edits[0].range,
+        Range::new(Position::new(0, 17), Position::new(0, 41))
+    );
+    assert_eq!(code_edits[0].new_text, "process.env.REDIS_URL");
+
+    let env_uri = Url::from_file_path(fixture.temp_dir.join(".env")).unwrap();
+    let env_edits = &changes[&env_uri];
+    assert_eq!(env_edits.len(), 1);
+    assert!(env_edits[0].new_text.ends_with("REDIS_URL=redis://localhost:6379\n"));
+}
+
+#[tokio::test]
+async fn test_no_extract_for_plain_strings() {
+    let fixture = TestFixture::new().await;
+    let content = "const greeting = \"hello world\";\nconst url = process.env.DB_URL;\n";
+    let uri = open_js(&fixture, content).await;
+
+    assert!(code_actions(&fixture, uri.clone(), Position::new(0, 20)).await.is_empty());
+    assert!(code_actions(&fixture, uri, Position::new(1, 26)).await.is_empty());
+}
+
+#[tokio::test]
+async fn test_extract_suffixes_name_already_in_env_file() {
+    let fixture = TestFixture::new().await;
+    fixture.create_file(".env", "DB_URL=postgres://localhost\nNEW_ENV_VAR=taken");
+    let content = "connect(\"redis://localhost:6379\");\n";
+    let uri = open_js(&fixture, content).await;
+
+    let actions = code_actions(&fixture, uri.clone(), Position::new(0, 12)).await;
+    let CodeActionOrCommand::CodeAction(action) = &actions[0] else {
+        panic!("Expected a code action");
+    };
+    assert_eq!(action.title, "Extract to environment variable NEW_ENV_VAR_2");
+
+    let changes = action.edit.clone().unwrap().changes.unwrap();
+    assert_eq!(changes[&uri][0].new_text, "process.env.NEW_ENV_VAR_2");
+    let env_uri = Url::from_file_path(fixture.temp_dir.join(".env")).unwrap();
+    assert_eq!(changes[&env_uri][0].new_text, "\nNEW_ENV_VAR_2=redis://localhost:6379\n");
+}
+
+#[tokio::test]
+async fn test_extract_quotes_value_with_dollar_sign() {
+    let fixture = TestFixture::new().await;
+    let content = "const dbUrl = \"postgres://u:pa$word@h/db\";\n";
+    let uri = open_js(&fixture, content).await;
+
+    let actions = code_actions(&fixture, uri, Position::new(0, 20)).await;
+    let CodeActionOrCommand::CodeAction(action) = &actions[0] else {
+        panic!("Expected a code action");
+    };
+    let changes = action.edit.clone().unwrap().changes.unwrap();
+    let env_uri = Url::from_file_path(fixture.temp_dir.join(".env")).unwrap();
+    assert!(changes[&env_uri][0]
+        .new_text
+        .ends_with("=\"postgres://u:pa\\$word@h/db\"\n"));
+}
+
+#[tokio::test]
+async fn test_no_extract_when_env_file_is_unreadable() {
+    let fixture = TestFixture::new().await;
+    {
+        let config_arc = fixture.state.config.get_config();
+        let mut config = config_arc.write().await.clone();
+        config.workspace.max_env_file_bytes = 4;
+        fixture.state.config.update(config).await;
+    }
+    let content = "const redisUrl = \"redis://localhost:6379\";\n";
+    let uri = open_js(&fixture, content).await;
+
+    assert!(code_actions(&fixture, uri, Position::new(0, 22)).await.is_empty());
+}
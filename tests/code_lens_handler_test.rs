@@ -0,0 +1,116 @@
+//! Tests for server/handlers/code_lens.rs - Code lens handler
+
+mod common;
+
+use common::TestFixture;
+use ecolog_lsp::server::handlers::{
+    handle_code_lens, handle_code_lens_resolve, handle_execute_command,
+};
+use serde_json::json;
+use tower_lsp::lsp_types::{
+    CodeLensParams, ExecuteCommandParams, Position, TextDocumentIdentifier, Url,
+};
+
+fn make_params(uri: Url) -> CodeLensParams {
+    CodeLensParams {
+        text_document: TextDocumentIdentifier { uri },
+        work_done_progress_params: Default::default(),
+        partial_result_params: Default::default(),
+    }
+}
+
+async fn enable_code_lens(fixture: &TestFixture, masking: bool) {
+    let config_arc = fixture.state.config.get_config();
+    let mut config = config_arc.write().await.clone();
+    config.features.code_lens = true;
+    config.masking.enabled = masking;
+    fixture.state.config.update(config).await;
+}
+
+async fn open_js(fixture: &TestFixture, content: &str) -> Url {
+    let uri = fixture.create_file("test.js", content);
+    fixture
+        .state
+        .document_manager
+        .open(uri.clone(), "javascript".into(), content.into(), 1)
+        .await;
+    uri
+}
+
+#[tokio::test]
+async fn test_code_lens_disabled_by_default() {
+    let fixture = TestFixture::new().await;
+    let uri = open_js(&fixture, "const db = process.env.DB_URL;").await;
+
+    assert!(handle_code_lens(make_params(uri), &fixture.state).await.is_none());
+}
+
+#[tokio::test]
+async fn test_code_lens_one_unresolved_lens_per_reference() {
+    let fixture = TestFixture::new().await;
+    enable_code_lens(&fixture, false).await;
+    let uri = open_js(
+        &fixture,
+        "const db = process.env.DB_URL;\nconst port = process.env.PORT;\nconst x = db;",
+    )
+    .await;
+
+    let lenses = handle_code_lens(make_params(uri), &fixture.state)
+        .await
+        .expect("lenses");
+    assert_eq!(lenses.len(), 2, "{:?}", lenses);
+    assert_eq!(lenses[0].range.start, Position::new(0, 23));
+    assert_eq!(lenses[1].range.start.line, 1);
+    assert!(lenses.iter().all(|lens| lens.command.is_none() && lens.data.is_some()));
+}
+
+#[tokio::test]
+async fn test_code_lens_resolve_shows_value_and_source() {
+    let fixture = TestFixture::new().await;
+    enable_code_lens(&fixture, false).await;
+    let uri = open_js(&fixture, "const db = process.env.DB_URL;\nprocess.env.NOPE;").await;
+
+    let lenses = handle_code_lens(make_params(uri.clone()), &fixture.state)
+        .await
+        .expect("lenses");
+    let resolved = handle_code_lens_resolve(lenses[0].clone(), &fixture.state).await;
+    let command = resolved.command.expect("resolved command");
+    assert_eq!(command.title, "= postgres://localhost (.env)");
+    assert_eq!(command.command, "ecolog.variable.reveal");
+    assert_eq!(
+        command.arguments,
+        Some(vec![json!("DB_URL"), json!(uri)])
+    );
+
+    let missing = handle_code_lens_resolve(lenses[1].clone(), &fixture.state).await;
+    assert_eq!(missing.command.unwrap().title, "NOPE is not defined");
+}
+
+#[tokio::test]
+async fn test_code_lens_resolve_masks_and_reveal_does_not() {
+    let fixture = TestFixture::new().await;
+    enable_code_lens(&fixture, true).await;
+    let uri = open_js(&fixture, "const key = process.env.API_KEY;").await;
+
+    let lenses = handle_code_lens(make_params(uri), &fixture.state)
+        .await
+        .expect("lenses");
+    let command = handle_code_lens_resolve(lenses[0].clone(), &fixture.state)
+        .await
+        .command
+        .expect("resolved command");
+    assert_eq!(command.title, "= ********** (.env)");
+
+    let revealed = handle_execute_command(
+        ExecuteCommandParams {
+            command: command.command,
+            arguments: command.arguments.unwrap(),
+            work_done_progress_params: Default::default(),
+        },
+        &fixture.state,
+    )
+    .await
+    .unwrap();
+    assert_eq!(revealed["value"], "secret_key");
+    assert_eq!(revealed["source"], ".env");
+}
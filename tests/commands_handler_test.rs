@@ -3,8 +3,11 @@
 mod common;
 
 use common::TestFixture;
+use ecolog_lsp::server::config::ProfileConfig;
 use ecolog_lsp::server::handlers::handle_execute_command;
+use ecolog_lsp::server::state::ServerState;
 use serde_json::json;
+use std::sync::Arc;
 use tower_lsp::lsp_types::ExecuteCommandParams;
 
 fn make_cmd(command: &str, arguments: Vec<serde_json::Value>) -> ExecuteCommandParams {
@@ -234,6 +237,111 @@ async fn test_file_set_active_with_patterns() {
     assert!(value.get("patterns").is_some(), "Should return patterns");
 }
 
+#[tokio::test]
+async fn test_file_set_profile_switches_active_files() {
+    let fixture = TestFixture::with_files(&[
+        (".env.development", "PORT=3000\n"),
+        (".env.production", "PORT=80\n"),
+    ])
+    .await;
+    let config_arc = fixture.state.config.get_config();
+    let mut config = config_arc.write().await.clone();
+    for (name, file) in [("dev", ".env.development"), ("prod", ".env.production")] {
+        config.profiles.insert(
+            name.to_string(),
+            ProfileConfig {
+                files: vec![file.to_string()],
+            },
+        );
+    }
+    fixture.state.config.update(config).await;
+
+    let result = handle_execute_command(
+        make_cmd("ecolog.file.setProfile", vec![json!("dev")]),
+        &fixture.state,
+    )
+    .await
+    .unwrap();
+    assert_eq!(result["files"], json!([".env.development"]));
+
+    let result = handle_execute_command(
+        make_cmd("ecolog.file.setProfile", vec![json!("prod")]),
+        &fixture.state,
+    )
+    .await
+    .unwrap();
+    assert_eq!(result["success"], true);
+    assert_eq!(result["profile"], "prod");
+    assert_eq!(result["files"], json!([".env.production"]));
+
+    let result = handle_execute_command(make_cmd("ecolog.file.list", vec![]), &fixture.state)
+        .await
+        .unwrap();
+    assert_eq!(result["files"], json!([".env.production"]));
+}
+
+#[tokio::test]
+async fn test_profile_diff() {
+    let fixture = TestFixture::with_files(&[
+        (".env.development", "DEBUG=true\nDEV_ONLY=1\nPORT=3000\n"),
+        (".env.production", "DEBUG=false\nPROD_ONLY=1\nPORT=3000\n"),
+    ])
+    .await;
+    let config_arc = fixture.state.config.get_config();
+    let mut config = config_arc.write().await.clone();
+    for (name, file) in [("dev", ".env.development"), ("prod", ".env.production")] {
+        config.profiles.insert(
+            name.to_string(),
+            ProfileConfig {
+                files: vec![file.to_string()],
+            },
+        );
+    }
+    fixture.state.config.update(config.clone()).await;
+    handle_execute_command(
+        make_cmd("ecolog.file.setActive", vec![json!(".env")]),
+        &fixture.state,
+    )
+    .await
+    .unwrap();
+
+    let diff = || make_cmd("ecolog.profile.diff", vec![json!("dev"), json!("prod")]);
+    let result = handle_execute_command(diff(), &fixture.state).await.unwrap();
+    assert_eq!(result["onlyInA"], json!([{ "name": "DEV_ONLY", "value": "1" }]));
+    assert_eq!(result["onlyInB"], json!([{ "name": "PROD_ONLY", "value": "1" }]));
+    assert_eq!(
+        result["differs"],
+        json!([{ "name": "DEBUG", "a": "true", "b": "false" }])
+    );
+
+    // The previous active files are restored
+    let list = handle_execute_command(make_cmd("ecolog.file.list", vec![]), &fixture.state)
+        .await
+        .unwrap();
+    assert_eq!(list["files"], json!([".env"]));
+
+    config.masking.enabled = true;
+    fixture.state.config.update(config).await;
+    let result = handle_execute_command(diff(), &fixture.state).await.unwrap();
+    assert_eq!(
+        result["differs"],
+        json!([{ "name": "DEBUG", "a": "****", "b": "*****" }])
+    );
+}
+
+#[tokio::test]
+async fn test_file_set_profile_unknown() {
+    let fixture = TestFixture::new().await;
+
+    let result = handle_execute_command(
+        make_cmd("ecolog.file.setProfile", vec![json!("staging")]),
+        &fixture.state,
+    )
+    .await
+    .unwrap();
+    assert!(result["error"].as_str().unwrap().contains("staging"));
+}
+
 #[tokio::test]
 async fn test_workspace_set_root_no_arg() {
     let fixture = TestFixture::new().await;
@@ -283,3 +391,424 @@ async fn test_unknown_command_returns_none() {
 
     assert!(result.is_none(), "Unknown command should return None");
 }
+
+#[tokio::test]
+async fn test_debug_stats() {
+    let fixture = TestFixture::new().await;
+    let uri = fixture.create_file("app.js", "const url = process.env.DB_URL;");
+    fixture.create_file("main.py", "import os\nkey = os.environ['API_KEY']");
+    fixture.index_workspace().await;
+
+    fixture
+        .state
+        .document_manager
+        .open(
+            uri,
+            "javascript".to_string(),
+            "const url = process.env.DB_URL;".to_string(),
+            1,
+        )
+        .await;
+
+    let params = make_cmd("ecolog.debug.stats", vec![]);
+    let result = handle_execute_command(params, &fixture.state).await;
+
+    assert!(result.is_some());
+    let value = result.unwrap();
+    assert_eq!(value.get("documentCount").unwrap(), 1);
+    assert_eq!(value.get("pendingAnalysisTasks").unwrap(), 0);
+    assert!(value.get("moduleCacheLen").is_none());
+    assert!(value["moduleCache"]["len"].is_u64());
+    assert_eq!(value["moduleCache"]["capacity"], 512);
+    assert!(value["moduleCache"]["hitRate"].is_f64());
+    assert!(value.get("uptimeSecs").unwrap().is_u64());
+
+    let index = value.get("index").expect("Should have index stats");
+    assert_eq!(index.get("totalFiles").unwrap(), 3);
+    assert_eq!(index.get("envFiles").unwrap(), 1);
+    assert!(index.get("totalEnvVars").unwrap().is_u64());
+    assert_eq!(index.get("failedFiles").unwrap(), 0);
+
+    let languages = value.get("languages").expect("Should have per-language counts");
+    assert_eq!(languages.get("javascript").unwrap(), 1);
+    assert_eq!(languages.get("python").unwrap(), 1);
+}
+
+#[tokio::test]
+async fn test_find_unused() {
+    let fixture = TestFixture::new().await;
+    fixture.create_file(
+        ".env",
+        "DB_URL=postgres://localhost\nUNUSED_SECRET=shh\n",
+    );
+    fixture.create_file("app.js", "const url = process.env.DB_URL;");
+    fixture.index_workspace().await;
+
+    let params = make_cmd("ecolog.workspace.findUnused", vec![]);
+    let result = handle_execute_command(params, &fixture.state).await.unwrap();
+
+    let entries = result.as_array().expect("Should return an array");
+    let names: Vec<_> = entries
+        .iter()
+        .map(|e| e.get("name").unwrap().as_str().unwrap())
+        .collect();
+    assert_eq!(names, vec!["UNUSED_SECRET"]);
+
+    let defined_in = entries[0].get("definedIn").unwrap().as_array().unwrap();
+    assert!(defined_in[0].as_str().unwrap().ends_with(".env"));
+}
+
+#[tokio::test]
+async fn test_find_unused_suppressed_by_dynamic_env_access() {
+    let fixture = TestFixture::new().await;
+    fixture.create_file(
+        ".env",
+        "DB_URL=postgres://localhost\nUNUSED_SECRET=shh\n",
+    );
+    fixture.create_file("main.ts", "const env = Deno.env.toObject();");
+    fixture.index_workspace().await;
+
+    let params = make_cmd("ecolog.workspace.findUnused", vec![]);
+    let result = handle_execute_command(params, &fixture.state).await.unwrap();
+
+    assert_eq!(result, json!([]));
+}
+
+#[tokio::test]
+async fn test_find_undefined() {
+    let fixture = TestFixture::new().await;
+    let uri = fixture.create_file(
+        "app.js",
+        "const url = process.env.DB_URL;\nconst x = process.env.NOT_DEFINED_ANYWHERE;",
+    );
+    fixture.index_workspace().await;
+
+    let params = make_cmd("ecolog.workspace.findUndefined", vec![]);
+    let result = handle_execute_command(params, &fixture.state).await.unwrap();
+
+    assert_eq!(
+        result,
+        json!([{ "name": "NOT_DEFINED_ANYWHERE", "files": [uri.to_string()] }])
+    );
+}
+
+#[tokio::test]
+async fn test_file_resolution_order_cascade() {
+    let fixture = TestFixture::new().await;
+    fixture.create_file(".env.local", "PORT=7070\n");
+    fixture
+        .state
+        .core
+        .refresh(abundantis::RefreshOptions::reset_all())
+        .await
+        .expect("Refresh failed");
+    let uri = fixture.create_file("src/server.js", "process.env.PORT");
+
+    let params = make_cmd(
+        "ecolog.file.setActive",
+        vec![json!(".env.local"), json!(".env")],
+    );
+    handle_execute_command(params, &fixture.state).await.unwrap();
+
+    let params = make_cmd(
+        "ecolog.file.resolutionOrder",
+        vec![json!(uri.to_string()), json!("PORT")],
+    );
+    let result = handle_execute_command(params, &fixture.state).await.unwrap();
+
+    assert_eq!(result["file"], "src/server.js");
+    assert_eq!(
+        result["sources"],
+        json!([
+            { "type": "Shell", "wins": false },
+            { "type": "File", "path": ".env", "wins": false },
+            { "type": "File", "path": ".env.local", "wins": true },
+        ])
+    );
+    assert_eq!(result["winner"], ".env.local");
+}
+
+#[tokio::test]
+async fn test_file_resolution_order_requires_file() {
+    let fixture = TestFixture::new().await;
+
+    let params = make_cmd("ecolog.file.resolutionOrder", vec![]);
+    let result = handle_execute_command(params, &fixture.state).await.unwrap();
+
+    assert!(result.get("error").is_some());
+}
+
+#[tokio::test]
+async fn test_file_env_var_locations() {
+    let fixture = TestFixture::new().await;
+    let content = "const { DB_URL } = process.env;\nconsole.log(DB_URL);\n\
+                   const env = process.env;\nenv.DB_URL;\nprocess.env.DB_URL;\n";
+    let uri = fixture.create_file("locations.js", content);
+    fixture
+        .state
+        .document_manager
+        .open(uri.clone(), "javascript".to_string(), content.to_string(), 0)
+        .await;
+
+    let params = make_cmd(
+        "ecolog.file.envVarLocations",
+        vec![json!(uri.to_string()), json!("DB_URL")],
+    );
+    let result = handle_execute_command(params, &fixture.state).await.unwrap();
+    let locations = result["locations"].as_array().unwrap();
+    let summary: Vec<_> = locations
+        .iter()
+        .map(|loc| {
+            (
+                loc["range"]["start"]["line"].as_u64().unwrap(),
+                loc["kind"].as_str().unwrap(),
+                loc["bindingName"].as_str(),
+            )
+        })
+        .collect();
+
+    assert_eq!(
+        summary,
+        vec![
+            (0, "bindingDeclaration", Some("DB_URL")),
+            (1, "bindingUsage", Some("DB_URL")),
+            (3, "propertyAccess", Some("env")),
+            (4, "directReference", None),
+        ]
+    );
+}
+
+#[tokio::test]
+async fn test_file_env_var_locations_requires_open_document() {
+    let fixture = TestFixture::new().await;
+    let uri = fixture.create_file("closed.js", "process.env.DB_URL;");
+
+    let params = make_cmd(
+        "ecolog.file.envVarLocations",
+        vec![json!(uri.to_string()), json!("DB_URL")],
+    );
+    let result = handle_execute_command(params, &fixture.state).await.unwrap();
+
+    assert!(result.get("error").is_some());
+}
+
+#[tokio::test]
+async fn test_generate_env_example_grouped_with_descriptions() {
+    let fixture = TestFixture::with_files(&[
+        (
+            ".env",
+            "# Primary database\n# Use a local instance in dev\nDB_URL=postgres://localhost\n\n# OLD_KEY=unused\nAPI_KEY=secret_key\n",
+        ),
+        (".env.local", "# Local port override\nPORT=8080\n"),
+        ("app.js", "const x = process.env.ONLY_IN_CODE;"),
+    ])
+    .await;
+    fixture.index_workspace().await;
+
+    let params = make_cmd(
+        "ecolog.generateEnvExample",
+        vec![json!({ "grouped": true, "withValues": true })],
+    );
+    let value = handle_execute_command(params, &fixture.state).await.unwrap();
+    let content = value["content"].as_str().unwrap();
+
+    assert!(
+        content.starts_with(
+            "# .env\nAPI_KEY= # e.g. **********\n# Primary database\n# Use a local instance in dev\nDB_URL= # e.g. "
+        ),
+        "{}",
+        content
+    );
+    assert!(!content.contains("OLD_KEY"), "{}", content);
+    assert!(!content.contains("secret_key"), "{}", content);
+    assert!(
+        content.contains("# .env.local\n# Local port override\nPORT= # e.g. ****\n"),
+        "{}",
+        content
+    );
+    assert!(
+        content.ends_with("# Referenced in code\nONLY_IN_CODE=\n"),
+        "{}",
+        content
+    );
+}
+
+#[tokio::test]
+async fn test_generate_env_example_ungrouped_keeps_descriptions() {
+    let fixture =
+        TestFixture::with_files(&[(".env", "# Service port\nPORT=8080\nDEBUG=true\n")]).await;
+
+    let params = make_cmd("ecolog.generateEnvExample", vec![]);
+    let value = handle_execute_command(params, &fixture.state).await.unwrap();
+    assert_eq!(value["content"], "DEBUG=\n# Service port\nPORT=\n");
+}
+
+#[tokio::test]
+async fn test_generate_env_example_skips_unreferenced_shell_vars() {
+    assert!(std::env::var_os("PATH").is_some() && std::env::var_os("HOME").is_some());
+    let fixture = TestFixture::with_files(&[
+        (".env", "PORT=8080\n"),
+        ("app.js", "const home = process.env.HOME;"),
+    ])
+    .await;
+    fixture.index_workspace().await;
+
+    let params = make_cmd(
+        "ecolog.generateEnvExample",
+        vec![json!({ "grouped": true, "withValues": true })],
+    );
+    let value = handle_execute_command(params, &fixture.state).await.unwrap();
+    let content = value["content"].as_str().unwrap();
+
+    assert!(!content.contains("PATH="), "{}", content);
+    assert!(
+        content.ends_with("# Referenced in code\nHOME=\n"),
+        "{}",
+        content
+    );
+}
+
+/// A fixture resolving only env files, with values that need escaping.
+async fn export_fixture() -> TestFixture {
+    let fixture = TestFixture::new().await;
+    fixture.create_file(
+        ".env",
+        "QUOTED=\"say \\\"hi\\\" it's $HOME\"\nMULTI=\"line1\\nline2\"\nPLAIN=abc\n",
+    );
+    fixture
+        .state
+        .core
+        .refresh(abundantis::RefreshOptions::reset_all())
+        .await
+        .expect("Refresh failed");
+    let params = make_cmd("ecolog.source.setPrecedence", vec![json!("File")]);
+    handle_execute_command(params, &fixture.state).await.unwrap();
+    fixture
+}
+
+async fn export(fixture: &TestFixture, format: &str) -> String {
+    let params = make_cmd("ecolog.export", vec![json!(format), json!(null), json!(false)]);
+    let result = handle_execute_command(params, &fixture.state).await.unwrap();
+    assert_eq!(result["count"], 3, "{}", result);
+    result["content"].as_str().unwrap().to_string()
+}
+
+const QUOTED: &str = "say \"hi\" it's $HOME";
+
+#[tokio::test]
+async fn test_export_json() {
+    let fixture = export_fixture().await;
+    let content = export(&fixture, "json").await;
+    let parsed: serde_json::Value = serde_json::from_str(&content).unwrap();
+    assert_eq!(
+        parsed,
+        json!({ "MULTI": "line1\nline2", "PLAIN": "abc", "QUOTED": QUOTED })
+    );
+}
+
+#[tokio::test]
+async fn test_export_dotenv_round_trips() {
+    let fixture = export_fixture().await;
+    let content = export(&fixture, "dotenv").await;
+    assert!(content.contains("PLAIN=abc\n"), "{}", content);
+    assert!(content.contains("MULTI=\"line1\\nline2\"\n"), "{}", content);
+
+    let parsed: std::collections::HashMap<String, String> =
+        korni::parse_with_options(&content, korni::ParseOptions::full())
+            .into_iter()
+            .filter_map(|entry| match entry {
+                korni::Entry::Pair(kv) => Some((kv.key.to_string(), kv.value.to_string())),
+                _ => None,
+            })
+            .collect();
+    assert_eq!(parsed["QUOTED"], QUOTED);
+    assert_eq!(parsed["MULTI"], "line1\nline2");
+    assert_eq!(parsed["PLAIN"], "abc");
+}
+
+#[tokio::test]
+async fn test_export_shell_quotes_for_sh() {
+    let fixture = export_fixture().await;
+    let content = export(&fixture, "shell").await;
+    assert!(content.contains("export QUOTED='say \"hi\" it'\\''s $HOME'\n"), "{}", content);
+
+    let script = format!("{}printf '%s|%s' \"$QUOTED\" \"$MULTI\"", content);
+    let output = std::process::Command::new("sh")
+        .arg("-c")
+        .arg(script)
+        .output()
+        .expect("sh runs");
+    assert_eq!(
+        String::from_utf8(output.stdout).unwrap(),
+        format!("{}|line1\nline2", QUOTED)
+    );
+}
+
+#[tokio::test]
+async fn test_export_masks_by_default_and_rejects_unknown_format() {
+    let fixture = export_fixture().await;
+    let config_arc = fixture.state.config.get_config();
+    let mut config = config_arc.write().await.clone();
+    config.masking.enabled = true;
+    fixture.state.config.update(config).await;
+
+    let params = make_cmd("ecolog.export", vec![json!("dotenv")]);
+    let result = handle_execute_command(params, &fixture.state).await.unwrap();
+    let content = result["content"].as_str().unwrap();
+    assert!(content.contains("PLAIN=***\n"), "{}", content);
+
+    let params = make_cmd("ecolog.export", vec![json!("yaml")]);
+    let result = handle_execute_command(params, &fixture.state).await.unwrap();
+    assert!(result.get("error").is_some());
+}
+
+#[tokio::test]
+async fn test_variable_get_for_file_uses_file_scope() {
+    let fixture = TestFixture::new().await;
+    fixture.create_file("packages/api/.env.local", "PORT=3000\n");
+    fixture.create_file("packages/api/index.js", "process.env.PORT");
+    fixture.create_file("packages/web/index.js", "process.env.PORT");
+
+    // A cascading monorepo: each package reads the root .env and its own files
+    let core = Arc::new(
+        abundantis::Abundantis::builder()
+            .root(&fixture.temp_dir)
+            .provider(abundantis::config::MonorepoProviderType::Custom)
+            .roots(vec![".", "packages/*"])
+            .cascading(true)
+            .build()
+            .await
+            .expect("Failed to build Abundantis"),
+    );
+    let state = ServerState::new(
+        fixture.state.document_manager.clone(),
+        fixture.state.languages.clone(),
+        core,
+        fixture.state.config.clone(),
+        fixture.state.workspace_index.clone(),
+        fixture.state.indexer.clone(),
+        fixture.state.module_resolver.clone(),
+        fixture.state.provider_manager.clone(),
+    );
+
+    let get_for_file = |file: &str| {
+        make_cmd("ecolog.variable.getForFile", vec![json!("PORT"), json!(file)])
+    };
+
+    let result = handle_execute_command(get_for_file("packages/web/index.js"), &state)
+        .await
+        .unwrap();
+    assert_eq!(result["value"], "8080", "{}", result);
+    assert_eq!(result["source"], ".env");
+
+    let result = handle_execute_command(get_for_file("packages/api/index.js"), &state)
+        .await
+        .unwrap();
+    assert_eq!(result["value"], "3000", "{}", result);
+    assert_eq!(result["source"], "packages/api/.env.local");
+    assert_eq!(result["file"], "packages/api/index.js");
+
+    let missing_path = make_cmd("ecolog.variable.getForFile", vec![json!("PORT")]);
+    let result = handle_execute_command(missing_path, &state).await.unwrap();
+    assert!(result.get("error").is_some());
+}
@@ -23,6 +23,12 @@ pub struct TestFixture {
 
 impl TestFixture {
     pub async fn new() -> Self {
+        Self::with_files(&[]).await
+    }
+
+    /// Like [`new`](Self::new), but writes `files` before the workspace is
+    /// set up, so monorepo layouts are detected.
+    pub async fn with_files(files: &[(&str, &str)]) -> Self {
         
         let timestamp = SystemTime::now()
             .duration_since(UNIX_EPOCH)
@@ -43,6 +49,14 @@ impl TestFixture {
         writeln!(env_file, "DEBUG=true").unwrap();
         writeln!(env_file, "PORT=8080").unwrap();
 
+        for (name, content) in files {
+            let path = temp_dir.join(name);
+            if let Some(parent) = path.parent() {
+                fs::create_dir_all(parent).unwrap();
+            }
+            fs::write(&path, content).unwrap();
+        }
+
         
         let mut registry = LanguageRegistry::new();
         registry.register(Arc::new(ecolog_lsp::languages::javascript::JavaScript));
@@ -61,6 +75,7 @@ impl TestFixture {
         registry.register(Arc::new(ecolog_lsp::languages::csharp::CSharp));
         registry.register(Arc::new(ecolog_lsp::languages::elixir::Elixir));
         registry.register(Arc::new(ecolog_lsp::languages::zig::Zig));
+        registry.register(Arc::new(ecolog_lsp::languages::dart::Dart));
         registry.register(Arc::new(ecolog_lsp::languages::bash::Bash));
         let languages = Arc::new(registry);
 
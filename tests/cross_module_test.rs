@@ -9,14 +9,15 @@ use ecolog_lsp::analysis::{
 };
 use ecolog_lsp::languages::LanguageRegistry;
 use ecolog_lsp::server::config::{ConfigManager, EcologConfig};
-use ecolog_lsp::server::handlers::handle_hover;
+use ecolog_lsp::server::handlers::{handle_definition, handle_hover, handle_references};
 use ecolog_lsp::server::state::ServerState;
 use std::fs::{self, File};
 use std::io::Write;
 use std::sync::Arc;
 use std::time::{SystemTime, UNIX_EPOCH};
 use tower_lsp::lsp_types::{
-    HoverParams, Position, TextDocumentIdentifier, TextDocumentPositionParams, Url,
+    GotoDefinitionParams, GotoDefinitionResponse, HoverParams, Position, ReferenceContext,
+    ReferenceParams, TextDocumentIdentifier, TextDocumentPositionParams, Url,
 };
 
 fn default_config() -> EcologConfig {
@@ -1114,3 +1115,150 @@ async fn test_wildcard_reexport_cycle_detection() {
 
     let _ = fs::remove_dir_all(&temp_dir);
 }
+
+#[tokio::test]
+async fn test_hover_after_imported_module_renamed() {
+    let timestamp = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap()
+        .as_nanos();
+    let temp_dir = std::env::temp_dir().join(format!("ecolog_cross_module_rename_{}", timestamp));
+    fs::create_dir_all(&temp_dir).unwrap();
+
+    fs::write(temp_dir.join(".env"), "ANOTHER=test_value\n").unwrap();
+    let old_path = temp_dir.join("config.ts");
+    fs::write(&old_path, "export const a = process.env.ANOTHER;").unwrap();
+    let test_path = temp_dir.join("test.ts");
+    let test_content = "import { a } from './config';\na;";
+    fs::write(&test_path, test_content).unwrap();
+
+    let state = setup_test_state(&temp_dir).await;
+    let config = default_config();
+    state.indexer.index_workspace(&config.workspace.env_files).await.unwrap();
+
+    let test_uri = Url::from_file_path(&test_path).unwrap();
+    state
+        .document_manager
+        .open(test_uri.clone(), "typescript".to_string(), test_content.to_string(), 0)
+        .await;
+    let hover = || {
+        handle_hover(
+            HoverParams {
+                text_document_position_params: TextDocumentPositionParams {
+                    text_document: TextDocumentIdentifier { uri: test_uri.clone() },
+                    position: Position::new(1, 0),
+                },
+                work_done_progress_params: Default::default(),
+            },
+            &state,
+        )
+    };
+    assert!(format!("{:?}", hover().await).contains("test_value"));
+
+    // The watcher reports the rename as a delete plus a create.
+    let new_path = temp_dir.join("config.mts");
+    fs::rename(&old_path, &new_path).unwrap();
+    let old_uri = Url::from_file_path(&old_path).unwrap();
+    let new_uri = Url::from_file_path(&new_path).unwrap();
+    let renames = state
+        .indexer
+        .pair_renames(std::slice::from_ref(&old_uri), std::slice::from_ref(&new_uri))
+        .await;
+    assert_eq!(renames, vec![(old_uri.clone(), new_uri.clone())]);
+
+    state
+        .indexer
+        .on_file_renamed(&old_uri, &new_uri, &config.workspace.env_files)
+        .await;
+
+    assert!(state.workspace_index.get_exports(&new_uri).is_some());
+    assert!(state.workspace_index.get_exports(&old_uri).is_none());
+    assert!(format!("{:?}", hover().await).contains("test_value"));
+
+    let _ = fs::remove_dir_all(&temp_dir);
+}
+
+#[tokio::test]
+async fn test_named_reexport_barrel() {
+    let timestamp = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap()
+        .as_nanos();
+    let temp_dir = std::env::temp_dir().join(format!("ecolog_barrel_{}", timestamp));
+    fs::create_dir_all(&temp_dir).unwrap();
+
+    fs::write(temp_dir.join(".env"), "DB_URL=barrel_value\n").unwrap();
+    let config_path = temp_dir.join("config.ts");
+    fs::write(&config_path, "export const dbUrl = process.env.DB_URL;").unwrap();
+    fs::write(
+        temp_dir.join("index.ts"),
+        "export { dbUrl } from './config';\nexport { dbUrl as url } from './config';",
+    )
+    .unwrap();
+    let app_path = temp_dir.join("app.ts");
+    let app_content = "import { dbUrl, url } from './index';\ndbUrl;\nurl;";
+    fs::write(&app_path, app_content).unwrap();
+
+    let state = setup_test_state(&temp_dir).await;
+    let config = default_config();
+    state.indexer.index_workspace(&config.workspace.env_files).await.unwrap();
+
+    let app_uri = Url::from_file_path(&app_path).unwrap();
+    state
+        .document_manager
+        .open(app_uri.clone(), "typescript".to_string(), app_content.to_string(), 0)
+        .await;
+    let position_params = |line| TextDocumentPositionParams {
+        text_document: TextDocumentIdentifier { uri: app_uri.clone() },
+        position: Position::new(line, 1),
+    };
+
+    for line in [1, 2] {
+        let hover = handle_hover(
+            HoverParams {
+                text_document_position_params: position_params(line),
+                work_done_progress_params: Default::default(),
+            },
+            &state,
+        )
+        .await;
+        let hover = format!("{:?}", hover);
+        assert!(hover.contains("barrel_value"), "line {}: {}", line, hover);
+    }
+
+    let definition = handle_definition(
+        GotoDefinitionParams {
+            text_document_position_params: position_params(1),
+            work_done_progress_params: Default::default(),
+            partial_result_params: Default::default(),
+        },
+        &state,
+    )
+    .await;
+    let Some(GotoDefinitionResponse::Scalar(location)) = definition else {
+        panic!("Expected a definition through the barrel, got {:?}", definition);
+    };
+    assert!(location.uri.path().ends_with(".env"));
+
+    let references = handle_references(
+        ReferenceParams {
+            text_document_position: position_params(1),
+            context: ReferenceContext {
+                include_declaration: false,
+            },
+            work_done_progress_params: Default::default(),
+            partial_result_params: Default::default(),
+        },
+        &state,
+    )
+    .await
+    .expect("Expected references through the barrel");
+    let config_uri = Url::from_file_path(&config_path).unwrap();
+    assert!(
+        references.iter().any(|l| l.uri == config_uri),
+        "got {:?}",
+        references
+    );
+
+    let _ = fs::remove_dir_all(&temp_dir);
+}
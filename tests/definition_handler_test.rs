@@ -3,6 +3,7 @@
 mod common;
 
 use common::TestFixture;
+use ecolog_lsp::server::config::DefinitionTarget;
 use ecolog_lsp::server::handlers::handle_definition;
 use tower_lsp::lsp_types::{
     GotoDefinitionParams, GotoDefinitionResponse, Position, TextDocumentIdentifier,
@@ -281,3 +282,86 @@ async fn test_definition_bracket_notation() {
 
     assert!(result.is_some(), "Should find definition via bracket notation");
 }
+
+async fn open_importer(fixture: &TestFixture) -> tower_lsp::lsp_types::Url {
+    fixture.create_file("config.js", "export const dbUrl = process.env.DB_URL;");
+    fixture.index_workspace().await;
+
+    let content = "import { dbUrl } from './config';\nconsole.log(dbUrl);";
+    let uri = fixture.create_file("app.js", content);
+    fixture
+        .state
+        .document_manager
+        .open(uri.clone(), "javascript".into(), content.into(), 1)
+        .await;
+    uri
+}
+
+async fn set_definition_target(fixture: &TestFixture, target: DefinitionTarget) {
+    let config_arc = fixture.state.config.get_config();
+    let mut config = config_arc.write().await.clone();
+    config.definition_target = target;
+    fixture.state.config.update(config).await;
+}
+
+#[tokio::test]
+async fn test_definition_imported_env_var_targets_env_file_by_default() {
+    let fixture = TestFixture::new().await;
+    let uri = open_importer(&fixture).await;
+
+    let result = handle_definition(make_params(uri, 1, 13), &fixture.state).await;
+
+    let Some(GotoDefinitionResponse::Scalar(location)) = result else {
+        panic!("Expected a definition, got {:?}", result);
+    };
+    assert!(location.uri.path().ends_with(".env"));
+    assert_eq!(location.range.start, Position::new(0, 0));
+}
+
+#[tokio::test]
+async fn test_definition_imported_env_var_targets_export_declaration() {
+    let fixture = TestFixture::new().await;
+    set_definition_target(&fixture, DefinitionTarget::Declaration).await;
+    let uri = open_importer(&fixture).await;
+
+    let result = handle_definition(make_params(uri, 1, 13), &fixture.state).await;
+
+    let Some(GotoDefinitionResponse::Scalar(location)) = result else {
+        panic!("Expected a definition, got {:?}", result);
+    };
+    assert!(location.uri.path().ends_with("config.js"));
+    assert_eq!(location.range.start.line, 0);
+}
+
+#[tokio::test]
+async fn test_definition_usage_targets_binding_declaration() {
+    let fixture = TestFixture::new().await;
+    set_definition_target(&fixture, DefinitionTarget::Declaration).await;
+    let content = "const { DEBUG } = process.env;\nconsole.log(DEBUG);";
+    let uri = fixture.create_file("test.js", content);
+    fixture
+        .state
+        .document_manager
+        .open(uri.clone(), "javascript".into(), content.into(), 1)
+        .await;
+
+    let result = handle_definition(make_params(uri.clone(), 1, 12), &fixture.state).await;
+    let Some(GotoDefinitionResponse::Scalar(location)) = result else {
+        panic!("Expected a definition, got {:?}", result);
+    };
+    assert_eq!(location.uri, uri);
+    assert_eq!(location.range.start.line, 0);
+
+    // Direct references declare nothing, so they still go to the env file
+    let direct = fixture.create_file("direct.js", "const db = process.env.DB_URL;");
+    fixture
+        .state
+        .document_manager
+        .open(direct.clone(), "javascript".into(), "const db = process.env.DB_URL;".into(), 1)
+        .await;
+    let result = handle_definition(make_params(direct, 0, 23), &fixture.state).await;
+    let Some(GotoDefinitionResponse::Scalar(location)) = result else {
+        panic!("Expected a definition, got {:?}", result);
+    };
+    assert!(location.uri.path().ends_with(".env"));
+}
@@ -3,8 +3,10 @@
 mod common;
 
 use common::TestFixture;
+use ecolog_lsp::analysis::indexer::read_env_file;
 use ecolog_lsp::server::handlers::compute_diagnostics;
-use tower_lsp::lsp_types::Url;
+use ecolog_lsp::server::util::safe_refresh;
+use tower_lsp::lsp_types::{DiagnosticSeverity, NumberOrString, Position, Url};
 use std::fs;
 
 #[tokio::test]
@@ -29,6 +31,30 @@ async fn test_diagnostics_undefined_env_var() {
     assert!(diagnostics.iter().any(|d| d.message.contains("UNDEFINED_VAR")));
 }
 
+#[tokio::test]
+async fn test_diagnostics_local_env_object_not_flagged() {
+    let fixture = TestFixture::new().await;
+    let content = "import { env } from '$env/dynamic/private';\n\
+                   const a = env.MISSING_OUTER;\n\
+                   function f() {\n  const env = { NOT_REAL: 1 };\n  return env.NOT_REAL;\n}\n";
+    let uri = fixture.create_file("test.ts", content);
+
+    fixture
+        .state
+        .document_manager
+        .open(uri.clone(), "typescript".into(), content.into(), 1)
+        .await;
+
+    let diagnostics = compute_diagnostics(&uri, &fixture.state).await;
+
+    assert!(diagnostics.iter().any(|d| d.message.contains("MISSING_OUTER")));
+    assert!(
+        !diagnostics.iter().any(|d| d.message.contains("NOT_REAL")),
+        "got {:?}",
+        diagnostics
+    );
+}
+
 #[tokio::test]
 async fn test_diagnostics_defined_env_var_no_warning() {
     let fixture = TestFixture::new().await;
@@ -125,6 +151,74 @@ async fn test_diagnostics_destructuring_undefined() {
     assert!(!diagnostics.is_empty(), "Should have diagnostic for destructured undefined var");
 }
 
+#[tokio::test]
+async fn test_diagnostics_destructuring_related_information() {
+    let fixture = TestFixture::new().await;
+    let content = "const { UNDEFINED_VAR } = process.env;";
+    let uri = fixture.create_file("test.js", content);
+
+    fixture
+        .state
+        .document_manager
+        .open(uri.clone(), "javascript".into(), content.into(), 1)
+        .await;
+
+    let diagnostics = compute_diagnostics(&uri, &fixture.state).await;
+    let diagnostic = diagnostics
+        .iter()
+        .find(|d| d.message.contains("UNDEFINED_VAR"))
+        .expect("Should have diagnostic for destructured undefined var");
+
+    let related = diagnostic
+        .related_information
+        .as_ref()
+        .expect("Destructured diagnostic should link to its env source");
+    assert_eq!(related.len(), 1);
+    assert_eq!(related[0].location.uri, uri);
+    assert_eq!(related[0].location.range.start, Position::new(0, 6));
+    assert_eq!(related[0].location.range.end, Position::new(0, 37));
+}
+
+#[tokio::test]
+async fn test_diagnostics_alias_property_related_information() {
+    let fixture = TestFixture::new().await;
+    let content = "const env = process.env;\nconst x = env.UNDEFINED_PROP;";
+    let uri = fixture.create_file("test.js", content);
+
+    fixture
+        .state
+        .document_manager
+        .open(uri.clone(), "javascript".into(), content.into(), 1)
+        .await;
+
+    let diagnostics = compute_diagnostics(&uri, &fixture.state).await;
+    let diagnostic = diagnostics
+        .iter()
+        .find(|d| d.message.contains("UNDEFINED_PROP"))
+        .expect("Should detect undefined property on env alias");
+
+    let related = diagnostic.related_information.as_ref().unwrap();
+    assert_eq!(related[0].location.range.start, Position::new(0, 6));
+}
+
+#[tokio::test]
+async fn test_diagnostics_direct_reference_has_no_related_information() {
+    let fixture = TestFixture::new().await;
+    let content = "console.log(process.env.UNDEFINED_VAR);";
+    let uri = fixture.create_file("test.js", content);
+
+    fixture
+        .state
+        .document_manager
+        .open(uri.clone(), "javascript".into(), content.into(), 1)
+        .await;
+
+    let diagnostics = compute_diagnostics(&uri, &fixture.state).await;
+
+    assert_eq!(diagnostics.len(), 1);
+    assert!(diagnostics[0].related_information.is_none());
+}
+
 #[tokio::test]
 async fn test_diagnostics_env_file_double_equals() {
     let fixture = TestFixture::new().await;
@@ -167,6 +261,143 @@ async fn test_diagnostics_env_file_valid() {
     assert!(diagnostics.is_empty(), "Valid .env should have no diagnostics");
 }
 
+#[tokio::test]
+async fn test_diagnostics_env_file_lint_warnings() {
+    let fixture = TestFixture::new().await;
+
+    let content = "PADDED=value  \nBROKEN=\"open\nNEXT=1\nLAST=\"x\"\n";
+    let env_path = fixture.temp_dir.join(".env.local");
+    fs::write(&env_path, content).unwrap();
+    let uri = Url::from_file_path(&env_path).unwrap();
+
+    fixture
+        .state
+        .document_manager
+        .open(uri.clone(), "env".into(), content.into(), 1)
+        .await;
+
+    let diagnostics = compute_diagnostics(&uri, &fixture.state).await;
+    let codes: Vec<_> = diagnostics
+        .iter()
+        .map(|d| match &d.code {
+            Some(NumberOrString::String(code)) => code.as_str(),
+            _ => "",
+        })
+        .collect();
+
+    assert_eq!(codes, vec!["EDF031", "EDF032"], "got {:?}", diagnostics);
+    assert!(diagnostics
+        .iter()
+        .all(|d| d.severity == Some(DiagnosticSeverity::WARNING)));
+}
+
+#[tokio::test]
+async fn test_diagnostics_env_file_whitespace_reported_once() {
+    let fixture = TestFixture::new().await;
+
+    let content = "KEY = value\n";
+    let env_path = fixture.temp_dir.join(".env.local");
+    fs::write(&env_path, content).unwrap();
+    let uri = Url::from_file_path(&env_path).unwrap();
+
+    fixture
+        .state
+        .document_manager
+        .open(uri.clone(), "env".into(), content.into(), 1)
+        .await;
+
+    let diagnostics = compute_diagnostics(&uri, &fixture.state).await;
+
+    // korni's forbidden-whitespace error already covers the line
+    assert_eq!(diagnostics.len(), 1, "got {:?}", diagnostics);
+    assert_eq!(
+        diagnostics[0].code,
+        Some(NumberOrString::String("EDF001".to_string()))
+    );
+}
+
+#[tokio::test]
+async fn test_diagnostics_disabled_code_not_reported() {
+    let fixture = TestFixture::new().await;
+    let config_arc = fixture.state.config.get_config();
+    let mut config = config_arc.write().await.clone();
+    config.diagnostics.disabled_diagnostics = vec!["EDF001".to_string()];
+    fixture.state.config.update(config).await;
+
+    let content = "KEY = value\nOTHER=1\n";
+    let env_path = fixture.temp_dir.join(".env.local");
+    fs::write(&env_path, content).unwrap();
+    let uri = Url::from_file_path(&env_path).unwrap();
+    fixture
+        .state
+        .document_manager
+        .open(uri.clone(), "env".into(), content.into(), 1)
+        .await;
+
+    let diagnostics = compute_diagnostics(&uri, &fixture.state).await;
+    assert!(diagnostics.is_empty(), "got {:?}", diagnostics);
+}
+
+#[tokio::test]
+async fn test_diagnostics_env_file_invalid_utf8() {
+    let fixture = TestFixture::new().await;
+
+    // A Latin-1 "é" on the middle line
+    let env_path = fixture.temp_dir.join(".env.local");
+    fs::write(&env_path, b"BEFORE=1\nCAFE=caf\xe9\nAFTER=2\n").unwrap();
+    fixture.index_workspace().await;
+    for key in ["BEFORE", "CAFE", "AFTER"] {
+        assert!(
+            !fixture.state.workspace_index.files_for_env_var(key).is_empty(),
+            "{} not indexed",
+            key
+        );
+    }
+
+    let content = read_env_file(&env_path, u64::MAX).await.unwrap();
+    let uri = Url::from_file_path(&env_path).unwrap();
+    fixture
+        .state
+        .document_manager
+        .open(uri.clone(), "env".into(), content, 1)
+        .await;
+
+    let diagnostics = compute_diagnostics(&uri, &fixture.state).await;
+    assert_eq!(diagnostics.len(), 1, "got {:?}", diagnostics);
+    assert_eq!(
+        diagnostics[0].code,
+        Some(NumberOrString::String("EDF006".to_string()))
+    );
+    assert_eq!(diagnostics[0].range.start, Position::new(1, 8));
+
+    // The other keys in the file still resolve
+    safe_refresh(&fixture.state.core, &fixture.state.config, abundantis::RefreshOptions::preserve_all()).await;
+    let code = "const a = process.env.AFTER;";
+    let js_uri = fixture.create_file("test.js", code);
+    fixture
+        .state
+        .document_manager
+        .open(js_uri.clone(), "javascript".into(), code.into(), 1)
+        .await;
+    let diagnostics = compute_diagnostics(&js_uri, &fixture.state).await;
+    assert!(diagnostics.is_empty(), "AFTER should resolve, got {:?}", diagnostics);
+}
+
+#[tokio::test]
+async fn test_diagnostics_env_file_genuine_replacement_character() {
+    let fixture = TestFixture::new().await;
+    let content = "NAME=caf\u{FFFD}\n";
+    let uri = fixture.create_file(".env.local", content);
+    fixture
+        .state
+        .document_manager
+        .open(uri.clone(), "env".into(), content.into(), 1)
+        .await;
+
+    let diagnostics = compute_diagnostics(&uri, &fixture.state).await;
+    assert!(diagnostics.is_empty(), "got {:?}", diagnostics);
+}
+
 #[tokio::test]
 async fn test_diagnostics_document_not_found() {
     let fixture = TestFixture::new().await;
@@ -235,3 +466,433 @@ async fn test_diagnostics_mixed_defined_undefined() {
         "Should warn for UNDEFINED_VAR"
     );
 }
+
+#[tokio::test]
+async fn test_diagnostics_env_vars_in_comments_ignored() {
+    let cases = [
+        ("test.js", "javascript", "// TODO use process.env.COMMENTED_VAR\nconst x = 1;"),
+        ("block.js", "javascript", "/* process.env.COMMENTED_VAR */\nconst x = 1;"),
+        ("test.py", "python", "import os\n# os.environ['COMMENTED_VAR']\nx = 1"),
+        ("test.rb", "ruby", "# ENV['COMMENTED_VAR']\nx = 1"),
+    ];
+
+    let fixture = TestFixture::new().await;
+    for (file, language, content) in cases {
+        let uri = fixture.create_file(file, content);
+        fixture
+            .state
+            .document_manager
+            .open(uri.clone(), language.into(), content.into(), 1)
+            .await;
+
+        let graph = fixture
+            .state
+            .document_manager
+            .get_binding_graph(&uri)
+            .expect("graph for open document");
+        assert!(
+            graph.direct_references().is_empty(),
+            "{} comment should yield no reference",
+            language
+        );
+
+        let diagnostics = compute_diagnostics(&uri, &fixture.state).await;
+        assert!(
+            diagnostics.is_empty(),
+            "{} comment should yield no diagnostic, got {:?}",
+            language,
+            diagnostics
+        );
+    }
+}
+
+/// Fallback source that counts lookups, so tests can observe resolution calls.
+struct CountingProvider(std::sync::Arc<std::sync::atomic::AtomicUsize>);
+
+impl ecolog_lsp::server::env_resolution::EnvSourceProvider for CountingProvider {
+    fn name(&self) -> &str {
+        "counting"
+    }
+
+    fn resolve(&self, _key: &str) -> Option<String> {
+        self.0.fetch_add(1, std::sync::atomic::Ordering::SeqCst);
+        None
+    }
+}
+
+#[tokio::test]
+async fn test_diagnostics_resolves_each_name_once() {
+    let lookups = std::sync::Arc::new(std::sync::atomic::AtomicUsize::new(0));
+    let mut fixture = TestFixture::new().await;
+    fixture.state = fixture
+        .state
+        .clone()
+        .with_env_source_provider(std::sync::Arc::new(CountingProvider(lookups.clone())));
+
+    let content = "console.log(process.env.REPEATED_UNDEFINED);\n".repeat(10);
+    let uri = fixture.create_file("test.js", &content);
+    fixture
+        .state
+        .document_manager
+        .open(uri.clone(), "javascript".into(), content.clone(), 1)
+        .await;
+
+    let diagnostics = compute_diagnostics(&uri, &fixture.state).await;
+
+    assert_eq!(diagnostics.len(), 10, "Every reference still gets a diagnostic");
+    assert_eq!(
+        lookups.load(std::sync::atomic::Ordering::SeqCst),
+        1,
+        "REPEATED_UNDEFINED should be resolved once per diagnostics pass"
+    );
+}
+
+#[tokio::test]
+async fn test_diagnostics_parse_error_info() {
+    let fixture = TestFixture::new().await;
+    let content = "const a = process.env.DB_URL;\nfunction (( {\nconst b = 1;\n";
+    let uri = fixture.create_file("broken.js", content);
+    fixture
+        .state
+        .document_manager
+        .open(uri.clone(), "javascript".into(), content.into(), 1)
+        .await;
+
+    let parse_errors = |diagnostics: &[tower_lsp::lsp_types::Diagnostic]| {
+        diagnostics
+            .iter()
+            .filter(|d| d.code == Some(NumberOrString::String("parse-error".into())))
+            .cloned()
+            .collect::<Vec<_>>()
+    };
+    let diagnostics = compute_diagnostics(&uri, &fixture.state).await;
+    assert!(parse_errors(&diagnostics).is_empty(), "off by default");
+
+    let config_arc = fixture.state.config.get_config();
+    let mut config = config_arc.write().await.clone();
+    config.diagnostics.warn_on_parse_error = true;
+    fixture.state.config.update(config).await;
+
+    // Its own diagnostic, next to an unchanged syntax error at the same range
+    let diagnostics = compute_diagnostics(&uri, &fixture.state).await;
+    let infos = parse_errors(&diagnostics);
+    assert_eq!(infos.len(), 1, "{:?}", diagnostics);
+    assert_eq!(infos[0].severity, Some(DiagnosticSeverity::INFORMATION));
+    assert_eq!(infos[0].range.start.line, 1);
+    assert!(infos[0].message.contains("may be incomplete"));
+    let syntax_errors: Vec<_> = diagnostics
+        .iter()
+        .filter(|d| d.code == Some(NumberOrString::String("syntax-error".into())))
+        .collect();
+    assert!(
+        syntax_errors.iter().any(|d| d.range == infos[0].range),
+        "{:?}",
+        syntax_errors
+    );
+    assert!(
+        syntax_errors.iter().all(|d| !d.message.contains("may be incomplete")),
+        "{:?}",
+        syntax_errors
+    );
+
+    // Still reported when syntax errors are disabled
+    let mut config = config_arc.write().await.clone();
+    config.diagnostics.disabled_diagnostics = vec!["syntax-error".to_string()];
+    fixture.state.config.update(config).await;
+
+    let diagnostics = compute_diagnostics(&uri, &fixture.state).await;
+    assert_eq!(parse_errors(&diagnostics).len(), 1, "{:?}", diagnostics);
+}
+
+#[tokio::test]
+async fn test_diagnostics_required_prefix_by_glob() {
+    let fixture = TestFixture::new().await;
+    let config_arc = fixture.state.config.get_config();
+    let mut config = config_arc.write().await.clone();
+    config
+        .diagnostics
+        .required_prefix_by_glob
+        .insert("src/client/**".into(), "VITE_".into());
+    fixture.state.config.update(config).await;
+
+    let content = "const a = process.env.SECRET;\nconst b = process.env.VITE_OK;\n";
+    let client = fixture.create_file("src/client/app.js", content);
+    let server = fixture.create_file("src/server/app.js", content);
+    for uri in [&client, &server] {
+        fixture
+            .state
+            .document_manager
+            .open(uri.clone(), "javascript".into(), content.into(), 1)
+            .await;
+    }
+
+    let prefix_warnings = |diagnostics: Vec<tower_lsp::lsp_types::Diagnostic>| {
+        diagnostics
+            .into_iter()
+            .filter(|d| d.code == Some(NumberOrString::String("EDF040".into())))
+            .collect::<Vec<_>>()
+    };
+
+    let warnings = prefix_warnings(compute_diagnostics(&client, &fixture.state).await);
+    assert_eq!(warnings.len(), 1, "{:?}", warnings);
+    assert_eq!(warnings[0].severity, Some(DiagnosticSeverity::WARNING));
+    assert_eq!(warnings[0].range.start, Position::new(0, 22));
+    assert!(warnings[0].message.contains("SECRET"));
+    assert!(warnings[0].message.contains("VITE_"));
+
+    let warnings = prefix_warnings(compute_diagnostics(&server, &fixture.state).await);
+    assert!(warnings.is_empty(), "{:?}", warnings);
+}
+
+/// An env source that takes longer to load than the resolution timeout.
+struct SlowSource(abundantis::source::SourceId);
+
+#[async_trait::async_trait]
+impl abundantis::source::AsyncEnvSource for SlowSource {
+    fn id(&self) -> &abundantis::source::SourceId {
+        &self.0
+    }
+
+    fn source_type(&self) -> abundantis::source::SourceType {
+        abundantis::source::SourceType::Remote
+    }
+
+    fn priority(&self) -> abundantis::source::Priority {
+        abundantis::source::Priority::REMOTE
+    }
+
+    fn capabilities(&self) -> abundantis::source::SourceCapabilities {
+        abundantis::source::SourceCapabilities::READ
+    }
+
+    async fn load(
+        &self,
+    ) -> Result<abundantis::source::SourceSnapshot, abundantis::error::SourceError> {
+        tokio::time::sleep(std::time::Duration::from_secs(5)).await;
+        Ok(abundantis::source::SourceSnapshot {
+            source_id: self.0.clone(),
+            variables: Vec::new().into(),
+            timestamp: std::time::Instant::now(),
+            version: None,
+        })
+    }
+
+    async fn refresh(&self) -> Result<bool, abundantis::error::SourceError> {
+        Ok(false)
+    }
+}
+
+#[tokio::test]
+async fn test_diagnostics_resolution_timeout_is_not_undefined() {
+    let fixture = TestFixture::new().await;
+    let config_arc = fixture.state.config.get_config();
+    let mut config = config_arc.write().await.clone();
+    config.workspace.resolution_timeout_ms = 50;
+    fixture.state.config.update(config).await;
+    fixture
+        .state
+        .core
+        .registry
+        .register_async(std::sync::Arc::new(SlowSource("slow".into())));
+
+    let content = "const a = process.env.UNDEFINED_VAR;\nconst b = process.env.OTHER_UNDEFINED;";
+    let uri = fixture.create_file("test.js", content);
+    fixture
+        .state
+        .document_manager
+        .open(uri.clone(), "javascript".into(), content.to_string(), 1)
+        .await;
+
+    let started = std::time::Instant::now();
+    let diagnostics = compute_diagnostics(&uri, &fixture.state).await;
+    assert!(started.elapsed() < std::time::Duration::from_secs(2));
+
+    // Both references and the bindings declared from them get a note, and
+    // none of them is reported as undefined
+    assert_eq!(diagnostics.len(), 4, "{:?}", diagnostics);
+    assert!(
+        diagnostics.iter().all(|d| {
+            d.code == Some(NumberOrString::String("resolution-timeout".into()))
+                && d.severity == Some(DiagnosticSeverity::INFORMATION)
+        }),
+        "{:?}",
+        diagnostics
+    );
+}
+
+#[tokio::test]
+async fn test_diagnostics_diagnose_kinds_direct_only() {
+    use ecolog_lsp::server::config::DiagnoseKind;
+
+    let fixture = TestFixture::new().await;
+    let content =
+        "const { UNDEFINED_DESTRUCTURED } = process.env;\nconst x = process.env.UNDEFINED_DIRECT;";
+    let uri = fixture.create_file("test.js", content);
+    fixture
+        .state
+        .document_manager
+        .open(uri.clone(), "javascript".into(), content.to_string(), 1)
+        .await;
+
+    let all_kinds = compute_diagnostics(&uri, &fixture.state).await;
+    assert!(all_kinds
+        .iter()
+        .any(|d| d.message.contains("UNDEFINED_DESTRUCTURED")));
+
+    let config_arc = fixture.state.config.get_config();
+    let mut config = config_arc.write().await.clone();
+    config.diagnostics.diagnose_kinds = vec![DiagnoseKind::DirectReference];
+    fixture.state.config.update(config).await;
+
+    let diagnostics = compute_diagnostics(&uri, &fixture.state).await;
+    assert!(
+        !diagnostics
+            .iter()
+            .any(|d| d.message.contains("UNDEFINED_DESTRUCTURED")),
+        "{:?}",
+        diagnostics
+    );
+    assert!(diagnostics
+        .iter()
+        .any(|d| d.message.contains("UNDEFINED_DIRECT")));
+}
+
+#[tokio::test]
+async fn test_diagnostics_skip_denylisted_env_var() {
+    let fixture = TestFixture::new().await;
+    let content = "const path = process.env.PATH;\nconst x = process.env.UNDEFINED_VAR;";
+    let uri = fixture.create_file("test.js", content);
+    fixture
+        .state
+        .document_manager
+        .open(uri.clone(), "javascript".into(), content.to_string(), 1)
+        .await;
+
+    let config_arc = fixture.state.config.get_config();
+    let mut config = config_arc.write().await.clone();
+    config.diagnostics.file_sources_only = true;
+    fixture.state.config.update(config.clone()).await;
+
+    let before = compute_diagnostics(&uri, &fixture.state).await;
+    assert!(before.iter().any(|d| d.message.contains("PATH")), "{:?}", before);
+
+    config.env_var_denylist = vec!["PATH".to_string()];
+    fixture.state.config.update(config).await;
+
+    let diagnostics = compute_diagnostics(&uri, &fixture.state).await;
+    assert!(
+        !diagnostics.iter().any(|d| d.message.contains("PATH")),
+        "{:?}",
+        diagnostics
+    );
+    assert!(diagnostics
+        .iter()
+        .any(|d| d.message.contains("UNDEFINED_VAR")));
+}
+
+#[tokio::test]
+async fn test_diagnostics_var_only_in_env_example() {
+    let fixture = TestFixture::new().await;
+    fixture.create_file(".env.example", "DB_URL=\nSTRIPE_KEY=\n");
+    let content = "process.env.STRIPE_KEY;\nprocess.env.OTHER_MISSING;\nprocess.env.DB_URL;";
+    let uri = fixture.create_file("src/test.js", content);
+    fixture
+        .state
+        .document_manager
+        .open(uri.clone(), "javascript".into(), content.to_string(), 1)
+        .await;
+
+    let diagnostics = compute_diagnostics(&uri, &fixture.state).await;
+    assert_eq!(diagnostics.len(), 2, "{:?}", diagnostics);
+
+    let example = diagnostics
+        .iter()
+        .find(|d| d.message.contains("STRIPE_KEY"))
+        .unwrap();
+    assert_eq!(example.severity, Some(DiagnosticSeverity::WARNING));
+    assert_eq!(example.code, Some(NumberOrString::String("EDF050".into())));
+    assert!(example.message.contains("declared in .env.example but not set"));
+
+    let undefined = diagnostics
+        .iter()
+        .find(|d| d.message.contains("OTHER_MISSING"))
+        .unwrap();
+    assert_eq!(
+        undefined.code,
+        Some(NumberOrString::String("undefined-env-var".into()))
+    );
+}
+
+#[tokio::test]
+async fn test_diagnostics_order_is_deterministic() {
+    let fixture = TestFixture::new().await;
+    let config_arc = fixture.state.config.get_config();
+    let mut config = config_arc.write().await.clone();
+    config
+        .diagnostics
+        .required_prefix_by_glob
+        .insert("**/*.js".into(), "VITE_".into());
+    fixture.state.config.update(config).await;
+
+    let content = concat!(
+        "const env = process.env;\n",
+        "env.ZETA_MISSING;\n",
+        "const { ALPHA_MISSING, BETA_MISSING } = process.env;\n",
+        "process.env.GAMMA_MISSING; process.env.DELTA_MISSING;\n",
+    );
+    let uri = fixture.create_file("app.js", content);
+    fixture
+        .state
+        .document_manager
+        .open(uri.clone(), "javascript".into(), content.into(), 1)
+        .await;
+
+    let first = compute_diagnostics(&uri, &fixture.state).await;
+    let second = compute_diagnostics(&uri, &fixture.state).await;
+    assert!(first.len() > 5, "{:?}", first);
+    assert_eq!(first, second);
+
+    let positions: Vec<_> = first
+        .iter()
+        .map(|d| (d.range.start.line, d.range.start.character))
+        .collect();
+    let mut sorted = positions.clone();
+    sorted.sort();
+    assert_eq!(positions, sorted);
+    // At the same position the prefix warning (EDF040) sorts before
+    // undefined-env-var
+    let at_gamma: Vec<_> = first
+        .iter()
+        .filter(|d| d.range.start == Position::new(3, 12))
+        .filter_map(|d| d.code.clone())
+        .collect();
+    assert_eq!(
+        at_gamma,
+        vec![
+            NumberOrString::String("EDF040".into()),
+            NumberOrString::String("undefined-env-var".into()),
+        ]
+    );
+}
+
+#[tokio::test]
+async fn test_diagnostics_extension_override_beats_client_language_id() {
+    let fixture = TestFixture::new().await;
+    fixture.state.languages.set_extension_overrides(std::collections::HashMap::from([(
+        "mycustom".to_string(),
+        "javascript".to_string(),
+    )]));
+
+    let content = "process.env.UNDEFINED_VAR;\nprocess.env.DB_URL;";
+    let uri = fixture.create_file("script.mycustom", content);
+    // A client guessing the language from the content
+    fixture
+        .state
+        .document_manager
+        .open(uri.clone(), "python".into(), content.into(), 1)
+        .await;
+
+    let diagnostics = compute_diagnostics(&uri, &fixture.state).await;
+    assert_eq!(diagnostics.len(), 1, "{:?}", diagnostics);
+    assert!(diagnostics[0].message.contains("UNDEFINED_VAR"));
+}
@@ -230,12 +230,271 @@ fn test_diagnostics_correct_range() {
 
     assert!(!diagnostics.is_empty(), "Should have diagnostic");
 
-    let diag = &diagnostics[0];
-    let range = diag.get("range").expect("Should have range");
-    let start = range.get("start").expect("Should have start");
+    // Diagnostics are sorted by position, so the binding `x` comes first;
+    // the reference's own diagnostic covers `MISSING`.
+    let starts: Vec<_> = diagnostics
+        .iter()
+        .map(|diag| {
+            let start = &diag["range"]["start"];
+            (start["line"].as_i64().unwrap(), start["character"].as_i64().unwrap())
+        })
+        .collect();
+    assert!(starts.contains(&(0, 22)), "{:?}", starts);
+
+    client.shutdown().expect("Shutdown failed");
+}
+
+#[test]
+fn test_unsupported_language_is_passive() {
+    let workspace = TempWorkspace::new();
+    workspace.create_config(
+        r#"
+[workspace]
+env_files = [".env", "secrets.md"]
+"#,
+    );
+
+    let client = LspTestClient::spawn(workspace.root.clone()).expect("Failed to spawn LSP");
+    client.initialize().expect("Initialize failed");
+
+    let notes_uri = workspace.file_uri("notes.md");
+    workspace.create_file("notes.md", "# Notes\n\nprocess.env.UNDEFINED_VAR");
+
+    client
+        .open_document(&notes_uri, "markdown", "# Notes\n\nprocess.env.UNDEFINED_VAR")
+        .expect("Failed to open document");
+    client
+        .change_document(&notes_uri, 2, "# Notes\n\nprocess.env.OTHER_VAR")
+        .expect("Failed to change document");
+
+    let stats = client
+        .execute_command("ecolog.debug.stats", vec![])
+        .expect("Stats command failed");
+    assert_eq!(
+        stats.get("pendingAnalysisTasks").and_then(|v| v.as_u64()),
+        Some(0),
+        "Passive documents should not schedule analysis"
+    );
+
+    let notification =
+        client.wait_for_notification("textDocument/publishDiagnostics", Duration::from_secs(1));
+    assert!(
+        notification.is_none(),
+        "Passive documents should not publish diagnostics"
+    );
+
+    // A markdown file matching an env_files pattern is still linted
+    let secrets_uri = workspace.file_uri("secrets.md");
+    workspace.create_file("secrets.md", "API_KEY = value\n");
+
+    client
+        .open_document(&secrets_uri, "markdown", "API_KEY = value\n")
+        .expect("Failed to open document");
+
+    let notification = client
+        .wait_for_notification("textDocument/publishDiagnostics", Duration::from_secs(5))
+        .expect("Env-file pattern should still be linted");
+
+    let params = notification.params.expect("Should have params");
+    assert_eq!(
+        params.get("uri").and_then(|u| u.as_str()),
+        Some(secrets_uri.as_str())
+    );
+    let diagnostics = params
+        .get("diagnostics")
+        .expect("Should have diagnostics")
+        .as_array()
+        .expect("Diagnostics should be array");
+    assert!(
+        diagnostics
+            .iter()
+            .any(|d| d.get("source").and_then(|s| s.as_str()) == Some("ecolog-linter")),
+        "Expected env-file lint diagnostics, got {:?}",
+        diagnostics
+    );
+
+    client.shutdown().expect("Shutdown failed");
+}
+
+#[test]
+fn test_active_files_from_environment_at_startup() {
+    let workspace = TempWorkspace::new();
+    workspace.create_file(".env.local", "LOCAL_ONLY=1\n");
+    let client = LspTestClient::spawn_with_env(
+        workspace.root.clone(),
+        &[("ECOLOG_ACTIVE_FILES", ".env.local")],
+    )
+    .expect("Failed to spawn LSP");
+    client.initialize().expect("Initialize failed");
+
+    let uri = workspace.file_uri("test.js");
+    let content = "process.env.LOCAL_ONLY;\nprocess.env.DB_URL;";
+    workspace.create_file("test.js", content);
+
+    client
+        .open_document(&uri, "javascript", content)
+        .expect("Failed to open document");
+
+    let notification = client
+        .wait_for_notification("textDocument/publishDiagnostics", Duration::from_secs(5))
+        .expect("Should receive diagnostics");
+
+    let params = notification.params.expect("Should have params");
+    let messages: Vec<&str> = params
+        .get("diagnostics")
+        .and_then(|d| d.as_array())
+        .expect("Diagnostics should be array")
+        .iter()
+        .filter_map(|d| d.get("message")?.as_str())
+        .collect();
+
+    // DB_URL is only in the inactive .env
+    assert!(
+        messages.iter().any(|m| m.contains("DB_URL")),
+        "Expected DB_URL to be undefined: {:?}",
+        messages
+    );
+    assert!(
+        !messages.iter().any(|m| m.contains("LOCAL_ONLY")),
+        "Expected LOCAL_ONLY to resolve: {:?}",
+        messages
+    );
 
-    assert_eq!(start.get("line").unwrap().as_i64(), Some(0));
-    assert!(start.get("character").unwrap().as_i64().unwrap() >= 22);
+    client.shutdown().expect("Shutdown failed");
+}
+
+#[test]
+fn test_extension_override_analyzes_custom_extension() {
+    let workspace = TempWorkspace::new();
+    workspace.create_file(
+        "ecolog.toml",
+        "[extension_overrides]\nmycustom = \"javascript\"\n",
+    );
+    let client = LspTestClient::spawn(workspace.root.clone()).expect("Failed to spawn LSP");
+    client.initialize().expect("Initialize failed");
+
+    let uri = workspace.file_uri("script.mycustom");
+    let content = "process.env.UNDEFINED_VAR;\nprocess.env.DB_URL;";
+    workspace.create_file("script.mycustom", content);
+
+    client
+        .open_document(&uri, "plaintext", content)
+        .expect("Failed to open document");
+
+    let notification = client
+        .wait_for_notification("textDocument/publishDiagnostics", Duration::from_secs(5))
+        .expect("Should receive diagnostics");
+
+    let params = notification.params.expect("Should have params");
+    let messages: Vec<&str> = params
+        .get("diagnostics")
+        .and_then(|d| d.as_array())
+        .expect("Diagnostics should be array")
+        .iter()
+        .filter_map(|d| d.get("message")?.as_str())
+        .collect();
+
+    assert_eq!(messages.len(), 1, "{:?}", messages);
+    assert!(messages[0].contains("UNDEFINED_VAR"));
 
     client.shutdown().expect("Shutdown failed");
 }
+
+#[test]
+fn test_workspace_path_with_spaces_and_unicode() {
+    let workspace = TempWorkspace::with_dir_suffix(" my project/café");
+    workspace.create_file("src/other.js", "process.env.INDEXED_ONLY_VAR;");
+    let client = LspTestClient::spawn(workspace.root.clone()).expect("Failed to spawn LSP");
+    client.initialize().expect("Initialize failed");
+
+    let encoded_uri = |relative: &str| {
+        tower_lsp::lsp_types::Url::from_file_path(workspace.root.join(relative))
+            .unwrap()
+            .to_string()
+    };
+    let uri = encoded_uri("src/app.js");
+    assert!(uri.contains("%20") && uri.contains("caf%C3%A9"), "{}", uri);
+
+    let content = "process.env.UNDEFINED_VAR;\nprocess.env.DB_URL;";
+    workspace.create_file("src/app.js", content);
+    client
+        .open_document(&uri, "javascript", content)
+        .expect("Failed to open document");
+
+    let notification = client
+        .wait_for_notification("textDocument/publishDiagnostics", Duration::from_secs(5))
+        .expect("Should receive diagnostics");
+    let params = notification.params.expect("Should have params");
+    assert_eq!(params["uri"], uri.as_str());
+    let diagnostics = params["diagnostics"].as_array().expect("Diagnostics should be array");
+    assert_eq!(diagnostics.len(), 1, "{:?}", diagnostics);
+    assert!(diagnostics[0]["message"].as_str().unwrap().contains("UNDEFINED_VAR"));
+
+    let start = std::time::Instant::now();
+    let symbols = loop {
+        let result = client.workspace_symbol("INDEXED_ONLY").expect("Workspace symbol failed");
+        if let Some(symbols) = result.as_array().filter(|s| !s.is_empty()) {
+            break symbols.clone();
+        }
+        assert!(start.elapsed() < Duration::from_secs(10), "File under workspace never indexed");
+        thread::sleep(Duration::from_millis(100));
+    };
+    assert_eq!(symbols[0]["location"]["uri"], encoded_uri("src/other.js").as_str());
+
+    client.shutdown().expect("Shutdown failed");
+}
+
+#[test]
+fn test_properties_file_is_analyzed_on_open_and_change() {
+    let workspace = TempWorkspace::new();
+    let client = LspTestClient::spawn(workspace.root.clone()).expect("Failed to spawn LSP");
+    client.initialize().expect("Initialize failed");
+
+    let uri = workspace.file_uri("application.properties");
+    let content = "DB_URL=jdbc:postgresql://${DB_HOST}/app\n";
+    workspace.create_file("application.properties", content);
+
+    client
+        .open_document(&uri, "properties", content)
+        .expect("Failed to open document");
+
+    let notification = client
+        .wait_for_notification("textDocument/publishDiagnostics", Duration::from_secs(5))
+        .expect("Properties files should publish diagnostics");
+    let messages = diagnostic_messages(&notification.params.expect("Should have params"));
+    assert!(messages.iter().any(|m| m.contains("DB_HOST")), "{:?}", messages);
+
+    client.clear_notifications();
+    let changed = "DB_HOST=localhost\nDB_URL=jdbc:postgresql://${DB_HOST}/app\nCACHE_TTL=60\n";
+    client
+        .change_document(&uri, 2, changed)
+        .expect("Failed to change document");
+
+    let notification = client
+        .wait_for_notification("textDocument/publishDiagnostics", Duration::from_secs(5))
+        .expect("Edits to a properties file should publish diagnostics");
+    let messages = diagnostic_messages(&notification.params.expect("Should have params"));
+    assert!(messages.iter().all(|m| !m.contains("DB_HOST")), "{:?}", messages);
+
+    // The edit re-indexed the file's definitions
+    let symbols = client
+        .workspace_symbol("CACHE_TTL")
+        .expect("Workspace symbol request failed");
+    assert!(
+        symbols.as_array().is_some_and(|symbols| !symbols.is_empty()),
+        "{:?}",
+        symbols
+    );
+
+    client.shutdown().expect("Shutdown failed");
+}
+
+fn diagnostic_messages(params: &serde_json::Value) -> Vec<String> {
+    params
+        .get("diagnostics")
+        .and_then(|d| d.as_array())
+        .expect("Diagnostics should be array")
+        .iter()
+        .filter_map(|d| Some(d.get("message")?.as_str()?.to_string()))
+        .collect()
+}
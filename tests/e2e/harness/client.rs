@@ -20,7 +20,7 @@ static REQUEST_ID: AtomicI64 = AtomicI64::new(1);
 
 pub struct LspTestClient {
     
-    _child: Child,
+    child: Child,
     
     stdin: Arc<Mutex<ChildStdin>>,
     
@@ -74,6 +74,14 @@ pub struct JsonRpcNotification {
 impl LspTestClient {
     
     pub fn spawn(workspace_root: PathBuf) -> Result<Self, Box<dyn std::error::Error>> {
+        Self::spawn_with_env(workspace_root, &[])
+    }
+
+    /// Spawns the server with extra environment variables set.
+    pub fn spawn_with_env(
+        workspace_root: PathBuf,
+        envs: &[(&str, &str)],
+    ) -> Result<Self, Box<dyn std::error::Error>> {
         
         let lsp_binary = std::env::var("ECOLOG_LSP_BINARY")
             .map(PathBuf::from)
@@ -101,6 +109,7 @@ impl LspTestClient {
 
         let mut child = Command::new(&lsp_binary)
             .current_dir(&workspace_root)
+            .envs(envs.iter().copied())
             .stdin(Stdio::piped())
             .stdout(Stdio::piped())
             .stderr(Stdio::inherit())
@@ -123,7 +132,7 @@ impl LspTestClient {
         });
 
         Ok(Self {
-            _child: child,
+            child,
             stdin,
             pending_responses,
             notifications,
@@ -368,6 +377,16 @@ impl LspTestClient {
         uri: &str,
         language_id: &str,
         text: &str,
+    ) -> Result<(), Box<dyn std::error::Error>> {
+        self.open_document_with_version(uri, language_id, text, 1)
+    }
+
+    pub fn open_document_with_version(
+        &self,
+        uri: &str,
+        language_id: &str,
+        text: &str,
+        version: i32,
     ) -> Result<(), Box<dyn std::error::Error>> {
         self.notify(
             "textDocument/didOpen",
@@ -375,7 +394,7 @@ impl LspTestClient {
                 "textDocument": {
                     "uri": uri,
                     "languageId": language_id,
-                    "version": 1,
+                    "version": version,
                     "text": text
                 }
             })),
@@ -402,6 +421,16 @@ impl LspTestClient {
     }
 
     
+    pub fn save_document(&self, uri: &str) -> Result<(), Box<dyn std::error::Error>> {
+        self.notify(
+            "textDocument/didSave",
+            Some(json!({
+                "textDocument": { "uri": uri }
+            })),
+        )
+    }
+
+    
     pub fn close_document(&self, uri: &str) -> Result<(), Box<dyn std::error::Error>> {
         self.notify(
             "textDocument/didClose",
@@ -536,3 +565,12 @@ impl LspTestClient {
         )
     }
 }
+
+impl Drop for LspTestClient {
+    // tower-lsp only notices `exit` when the next message arrives, so the
+    // server would otherwise outlive the test and compete for CPU
+    fn drop(&mut self) {
+        let _ = self.child.kill();
+        let _ = self.child.wait();
+    }
+}
@@ -19,13 +19,19 @@ pub struct TempWorkspace {
 impl TempWorkspace {
     
     pub fn new() -> Self {
+        Self::with_dir_suffix("")
+    }
+
+    /// Like [`new`](Self::new), but appends `suffix` to the root directory's
+    /// name, e.g. to put spaces or non-ASCII characters in the path.
+    pub fn with_dir_suffix(suffix: &str) -> Self {
         let timestamp = SystemTime::now()
             .duration_since(UNIX_EPOCH)
             .unwrap()
             .as_nanos();
         let counter = WORKSPACE_COUNTER.fetch_add(1, Ordering::SeqCst);
 
-        let root = std::env::temp_dir().join(format!("ecolog_e2e_{}_{}", timestamp, counter));
+        let root = std::env::temp_dir().join(format!("ecolog_e2e_{}_{}{}", timestamp, counter, suffix));
 
         fs::create_dir_all(&root).expect("Failed to create temp workspace");
 
@@ -1,6 +1,7 @@
 
 
 use crate::harness::{LspTestClient, TempWorkspace};
+use serde_json::json;
 use std::thread;
 use std::time::Duration;
 
@@ -217,3 +218,66 @@ fn test_hover_typescript() {
 
     client.shutdown().expect("Shutdown failed");
 }
+
+#[test]
+fn test_hover_skips_excluded_env_file() {
+    let workspace = TempWorkspace::new();
+    workspace.create_config("[workspace]\nenv_files = [\".env*\", \"!.env.example\"]\n");
+    workspace.create_file(".env.example", "EXAMPLE_KEY=from_example\n");
+    workspace.create_file(".env.staging", "STAGING_KEY=from_staging\n");
+    let client = LspTestClient::spawn(workspace.root.clone()).expect("Failed to spawn LSP");
+    client.initialize().expect("Initialize failed");
+    client
+        .execute_command("ecolog.file.setActive", vec![json!(".env*")])
+        .expect("Set active command failed");
+
+    let uri = workspace.file_uri("test.js");
+    let content = "const a = process.env.EXAMPLE_KEY;\nconst b = process.env.STAGING_KEY;";
+    workspace.create_file("test.js", content);
+    client.open_document(&uri, "javascript", content).expect("Failed to open document");
+    thread::sleep(Duration::from_millis(300));
+
+    let hover = client.hover(&uri, 1, 26).expect("Hover request failed");
+    assert!(
+        hover.to_string().contains("from_staging"),
+        "Included .env.staging should resolve, got {}",
+        hover
+    );
+    let hover = client.hover(&uri, 0, 26).expect("Hover request failed");
+    assert!(
+        !hover.to_string().contains("from_example"),
+        "Excluded .env.example should not resolve, got {}",
+        hover
+    );
+
+    client.shutdown().expect("Shutdown failed");
+}
+
+#[test]
+fn test_hover_skips_excluded_env_file_ignoring_case() {
+    let workspace = TempWorkspace::new();
+    workspace.create_config(
+        "[workspace]\nenv_files = [\".env*\", \"!.env.example\"]\ncase_insensitive_env_files = true\n",
+    );
+    workspace.create_file(".env.Example", "EXAMPLE_KEY=from_example\n");
+    let client = LspTestClient::spawn(workspace.root.clone()).expect("Failed to spawn LSP");
+    client.initialize().expect("Initialize failed");
+    client
+        .execute_command("ecolog.file.setActive", vec![json!(".env*")])
+        .expect("Set active command failed");
+
+    let uri = workspace.file_uri("test.js");
+    let content = "const a = process.env.EXAMPLE_KEY;";
+    workspace.create_file("test.js", content);
+    client.open_document(&uri, "javascript", content).expect("Failed to open document");
+    thread::sleep(Duration::from_millis(300));
+
+    let hover = client.hover(&uri, 0, 26).expect("Hover request failed");
+    assert!(
+        !hover.to_string().contains("from_example"),
+        ".env.Example should be excluded by !.env.example, got {}",
+        hover
+    );
+
+    client.shutdown().expect("Shutdown failed");
+}
@@ -25,7 +25,17 @@ fn test_initialize_response_capabilities() {
 
     
     let sync = capabilities.get("textDocumentSync").unwrap();
-    assert_eq!(sync.as_i64(), Some(1), "Expected TextDocumentSyncKind::FULL");
+    assert_eq!(
+        sync.get("change").and_then(|c| c.as_i64()),
+        Some(1),
+        "Expected TextDocumentSyncKind::FULL"
+    );
+    assert_eq!(sync.get("openClose"), Some(&json!(true)));
+    assert_eq!(
+        sync.get("save"),
+        Some(&json!({ "includeText": true })),
+        "Expected save notifications with text"
+    );
 
     
     let commands = capabilities
@@ -113,3 +123,114 @@ fn test_double_initialize_fails() {
 
     client.shutdown().expect("Shutdown failed");
 }
+
+#[test]
+fn test_status_notifications_report_ready_after_indexing() {
+    let workspace = TempWorkspace::new();
+    let client = LspTestClient::spawn(workspace.root.clone()).expect("Failed to spawn LSP");
+    client.initialize().expect("Initialize failed");
+
+    let statuses = || -> Vec<String> {
+        client
+            .get_notifications_by_method("$/ecolog/status")
+            .iter()
+            .filter_map(|n| n.params.as_ref()?.get("status")?.as_str().map(String::from))
+            .collect()
+    };
+
+    let start = std::time::Instant::now();
+    while !statuses().iter().any(|s| s == "ready") {
+        assert!(
+            start.elapsed() < std::time::Duration::from_secs(30),
+            "Ready status never arrived: {:?}",
+            statuses()
+        );
+        std::thread::sleep(std::time::Duration::from_millis(50));
+    }
+    assert_eq!(statuses(), vec!["indexing", "ready"]);
+
+    client.shutdown().expect("Shutdown failed");
+}
+
+#[test]
+fn test_lazy_index_indexes_only_env_files_at_startup() {
+    let workspace = TempWorkspace::new();
+    workspace.create_config("[workspace]\nlazy_index = true\n");
+    workspace.create_file("app.js", "const url = process.env.DB_URL;");
+    workspace.create_file("other.js", "const key = process.env.API_KEY;");
+
+    let client = LspTestClient::spawn(workspace.root.clone()).expect("Failed to spawn LSP");
+    client.initialize().expect("Initialize failed");
+
+    let start = std::time::Instant::now();
+    while client.get_notifications_by_method("$/ecolog/status").len() < 2 {
+        assert!(
+            start.elapsed() < std::time::Duration::from_secs(30),
+            "Ready status never arrived"
+        );
+        std::thread::sleep(std::time::Duration::from_millis(50));
+    }
+
+    let index_stat = |name: &str| {
+        client
+            .execute_command("ecolog.debug.stats", vec![])
+            .expect("Stats command failed")
+            .pointer(&format!("/index/{}", name))
+            .and_then(|v| v.as_u64())
+    };
+    assert_eq!(index_stat("envFiles"), Some(1), "The .env is indexed at startup");
+    assert_eq!(index_stat("totalFiles"), Some(1), "Code files aren't indexed at startup");
+
+    let uri = workspace.file_uri("app.js");
+    client
+        .open_document(&uri, "javascript", "const url = process.env.DB_URL;")
+        .expect("Failed to open document");
+    let hover = client.hover(&uri, 0, 26).expect("Hover failed");
+    assert!(
+        hover.to_string().contains("postgres://localhost"),
+        "Expected hover on an opened file, got {}",
+        hover
+    );
+    assert_eq!(index_stat("totalFiles"), Some(2));
+
+    client.shutdown().expect("Shutdown failed");
+}
+
+#[test]
+fn test_ping_reports_readiness() {
+    let workspace = TempWorkspace::new();
+    workspace.create_file("app.js", "const url = process.env.DB_URL;");
+
+    let client = LspTestClient::spawn(workspace.root.clone()).expect("Failed to spawn LSP");
+    client
+        .request(
+            "initialize",
+            Some(json!({
+                "processId": std::process::id(),
+                "rootUri": format!("file://{}", workspace.root.display()),
+                "capabilities": {}
+            })),
+        )
+        .expect("Initialize failed");
+
+    let ping = || client.execute_command("ecolog.ping", vec![]).expect("Ping failed");
+    let before = ping();
+    assert_eq!(before["ok"], json!(true));
+    assert_eq!(before["version"], json!(env!("CARGO_PKG_VERSION")));
+    assert_eq!(before["ready"], json!(false), "Indexing hasn't started yet");
+
+    client
+        .notify("initialized", Some(json!({})))
+        .expect("Initialized failed");
+    let start = std::time::Instant::now();
+    while client.get_notifications_by_method("$/ecolog/status").len() < 2 {
+        assert!(
+            start.elapsed() < std::time::Duration::from_secs(30),
+            "Ready status never arrived"
+        );
+        std::thread::sleep(std::time::Duration::from_millis(50));
+    }
+    assert_eq!(ping()["ready"], json!(true));
+
+    client.shutdown().expect("Shutdown failed");
+}
@@ -206,3 +206,280 @@ fn test_env_file_change() {
 
     client.shutdown().expect("Shutdown failed");
 }
+
+#[test]
+fn test_close_during_debounce_removes_from_index() {
+    let workspace = TempWorkspace::new();
+    workspace.create_config("[workspace]\ninline_analysis_max_lines = 0\n");
+    let client = LspTestClient::spawn(workspace.root.clone()).expect("Failed to spawn LSP");
+    client.initialize().expect("Initialize failed");
+
+    // Not written to disk, so only the open document can put it in the index
+    let uri = workspace.file_uri("scratch.js");
+
+    client
+        .open_document(&uri, "javascript", "process.env.CLOSED_ONLY_VAR")
+        .expect("Failed to open");
+    client
+        .change_document(&uri, 2, "process.env.CLOSED_ONLY_VAR_EDITED")
+        .expect("Failed to change");
+    client.close_document(&uri).expect("Failed to close");
+
+    // Outlive the 300ms debounce window
+    thread::sleep(Duration::from_millis(600));
+
+    let result = client
+        .workspace_symbol("CLOSED_ONLY_VAR")
+        .expect("Workspace symbol request failed");
+    let symbols = result.as_array().cloned().unwrap_or_default();
+    assert!(
+        symbols.is_empty(),
+        "Closed document should not remain in the workspace index, got {:?}",
+        symbols
+    );
+
+    client.shutdown().expect("Shutdown failed");
+}
+
+#[test]
+fn test_close_and_reopen_drops_stale_analysis() {
+    let workspace = TempWorkspace::new();
+    workspace.create_config("[workspace]\ninline_analysis_max_lines = 0\n");
+    let client = LspTestClient::spawn(workspace.root.clone()).expect("Failed to spawn LSP");
+    client.initialize().expect("Initialize failed");
+
+    let uri = workspace.file_uri("test.js");
+    workspace.create_file("test.js", "process.env.DB_URL");
+
+    client
+        .open_document(&uri, "javascript", "process.env.DB_URL")
+        .expect("Failed to open");
+    client
+        .change_document(&uri, 2, "process.env.STALE_EDIT_VAR")
+        .expect("Failed to change");
+    client.close_document(&uri).expect("Failed to close");
+
+    // Reopened at the version the pending analysis was scheduled for
+    client
+        .open_document_with_version(&uri, "javascript", "process.env.FRESH_OPEN_VAR", 2)
+        .expect("Failed to reopen");
+
+    // Outlive the 300ms debounce window
+    thread::sleep(Duration::from_millis(600));
+
+    let published = published_messages(&client);
+    assert!(
+        published.iter().flatten().all(|m| !m.contains("STALE_EDIT_VAR")),
+        "Stale analysis should not publish, got {:?}",
+        published
+    );
+    assert!(
+        published.last().is_some_and(|messages| messages
+            .iter()
+            .any(|m| m.contains("FRESH_OPEN_VAR"))),
+        "{:?}",
+        published
+    );
+
+    client.shutdown().expect("Shutdown failed");
+}
+
+#[test]
+fn test_save_publishes_diagnostics_without_debounce() {
+    let workspace = TempWorkspace::new();
+    workspace.create_config("[workspace]\ninline_analysis_max_lines = 0\n");
+    let client = LspTestClient::spawn(workspace.root.clone()).expect("Failed to spawn LSP");
+    client.initialize().expect("Initialize failed");
+
+    let uri = workspace.file_uri("test.js");
+    workspace.create_file("test.js", "process.env.DB_URL");
+
+    client
+        .open_document(&uri, "javascript", "process.env.DB_URL")
+        .expect("Failed to open");
+    client
+        .wait_for_notification("textDocument/publishDiagnostics", Duration::from_secs(5))
+        .expect("Open should publish diagnostics");
+    client.clear_notifications();
+
+    client
+        .change_document(&uri, 2, "process.env.UNSAVED_UNDEFINED")
+        .expect("Failed to change");
+    workspace.create_file("test.js", "process.env.UNSAVED_UNDEFINED");
+    thread::sleep(Duration::from_millis(50));
+    client.save_document(&uri).expect("Failed to save");
+
+    let notification = client
+        .wait_for_notification("textDocument/publishDiagnostics", Duration::from_secs(5))
+        .expect("Save should publish diagnostics");
+    let params = notification.params.expect("Should have params");
+    let diagnostics = params
+        .get("diagnostics")
+        .expect("Should have diagnostics")
+        .as_array()
+        .expect("Diagnostics should be array");
+    assert!(
+        diagnostics.iter().any(|d| d
+            .get("message")
+            .and_then(|m| m.as_str())
+            .is_some_and(|m| m.contains("UNSAVED_UNDEFINED"))),
+        "Diagnostics should reflect saved content, got {:?}",
+        diagnostics
+    );
+
+    // The debounced analysis was superseded by the save
+    thread::sleep(Duration::from_millis(600));
+    assert_eq!(
+        client
+            .get_notifications_by_method("textDocument/publishDiagnostics")
+            .len(),
+        1,
+        "Save should cancel the pending debounced analysis"
+    );
+
+    client.shutdown().expect("Shutdown failed");
+}
+
+/// Every diagnostic message published so far, across notifications.
+fn published_messages(client: &LspTestClient) -> Vec<Vec<String>> {
+    client
+        .get_notifications_by_method("textDocument/publishDiagnostics")
+        .iter()
+        .map(|n| {
+            n.params.as_ref().unwrap()["diagnostics"]
+                .as_array()
+                .unwrap()
+                .iter()
+                .map(|d| d["message"].as_str().unwrap().to_string())
+                .collect()
+        })
+        .collect()
+}
+
+/// Opens `name` and sends two edits back to back, each adding a different
+/// undefined variable, well inside one debounce window.
+fn open_and_edit_twice(
+    workspace: &TempWorkspace,
+    client: &LspTestClient,
+    name: &str,
+    lines: usize,
+) {
+    let uri = workspace.file_uri(name);
+    let content = "process.env.DB_URL;\n".repeat(lines);
+    workspace.create_file(name, &content);
+    client
+        .open_document(&uri, "javascript", &content)
+        .expect("Failed to open");
+    client
+        .wait_for_notification("textDocument/publishDiagnostics", Duration::from_secs(5))
+        .expect("Open should publish diagnostics");
+    client.clear_notifications();
+
+    for (version, var) in [(2, "FIRST_EDIT_VAR"), (3, "SECOND_EDIT_VAR")] {
+        let changed = format!("{}process.env.{};\n", content, var);
+        client
+            .change_document(&uri, version, &changed)
+            .expect("Failed to change");
+    }
+}
+
+#[test]
+fn test_small_document_change_skips_debounce() {
+    let workspace = TempWorkspace::new();
+    let client = LspTestClient::spawn(workspace.root.clone()).expect("Failed to spawn LSP");
+    client.initialize().expect("Initialize failed");
+
+    open_and_edit_twice(&workspace, &client, "small.js", 10);
+
+    // A debounced document would only be analyzed after the second edit
+    let start = std::time::Instant::now();
+    while published_messages(&client).len() < 2 {
+        assert!(
+            start.elapsed() < Duration::from_secs(5),
+            "Each edit of a small document should publish diagnostics, got {:?}",
+            published_messages(&client)
+        );
+        thread::sleep(Duration::from_millis(50));
+    }
+    let published = published_messages(&client);
+    assert!(published[0].iter().any(|m| m.contains("FIRST_EDIT_VAR")), "{:?}", published);
+    assert!(published[1].iter().any(|m| m.contains("SECOND_EDIT_VAR")), "{:?}", published);
+
+    client.shutdown().expect("Shutdown failed");
+}
+
+#[test]
+fn test_large_document_change_is_debounced() {
+    let workspace = TempWorkspace::new();
+    let client = LspTestClient::spawn(workspace.root.clone()).expect("Failed to spawn LSP");
+    client.initialize().expect("Initialize failed");
+
+    open_and_edit_twice(&workspace, &client, "large.js", 300);
+
+    client
+        .wait_for_notification("textDocument/publishDiagnostics", Duration::from_secs(5))
+        .expect("Large document should get diagnostics after the debounce");
+    // Outlive another debounce window in case a second analysis was queued
+    thread::sleep(Duration::from_millis(600));
+    let published = published_messages(&client);
+    assert_eq!(published.len(), 1, "{:?}", published);
+    assert!(published[0].iter().any(|m| m.contains("SECOND_EDIT_VAR")), "{:?}", published);
+
+    client.shutdown().expect("Shutdown failed");
+}
+
+#[test]
+fn test_watched_rename_drops_import_that_no_longer_resolves() {
+    let workspace = TempWorkspace::new();
+    workspace.create_file("config.ts", "export const dbUrl = process.env.DB_URL;");
+    let content = "import { dbUrl } from './config';\ndbUrl;";
+    workspace.create_file("app.ts", content);
+    let client = LspTestClient::spawn(workspace.root.clone()).expect("Failed to spawn LSP");
+    client.initialize().expect("Initialize failed");
+
+    // The import only resolves once config.ts has been indexed
+    let start = std::time::Instant::now();
+    while client.get_notifications_by_method("$/ecolog/status").len() < 2 {
+        assert!(start.elapsed() < Duration::from_secs(30), "Ready status never arrived");
+        thread::sleep(Duration::from_millis(50));
+    }
+
+    let uri = workspace.file_uri("app.ts");
+    client
+        .open_document(&uri, "typescript", content)
+        .expect("Failed to open");
+    thread::sleep(Duration::from_millis(500));
+
+    let hover = client.hover(&uri, 1, 1).expect("Hover request failed");
+    assert!(
+        hover.to_string().contains("DB_URL"),
+        "Import should resolve before the rename, got {}",
+        hover
+    );
+
+    let old_uri = workspace.file_uri("config.ts");
+    std::fs::rename(workspace.root.join("config.ts"), workspace.root.join("settings.ts"))
+        .expect("Failed to rename");
+    client
+        .notify(
+            "workspace/didChangeWatchedFiles",
+            Some(serde_json::json!({
+                "changes": [
+                    { "uri": old_uri, "type": 3 },
+                    { "uri": workspace.file_uri("settings.ts"), "type": 1 }
+                ]
+            })),
+        )
+        .expect("Failed to notify");
+    thread::sleep(Duration::from_millis(500));
+
+    // './config' names neither file now
+    let hover = client.hover(&uri, 1, 1).expect("Hover request failed");
+    assert!(
+        !hover.to_string().contains("DB_URL"),
+        "Import of the renamed module should not resolve, got {}",
+        hover
+    );
+
+    client.shutdown().expect("Shutdown failed");
+}
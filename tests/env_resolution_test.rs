@@ -262,3 +262,143 @@ async fn test_resolve_with_cross_module_disabled() {
     // Should be None since cross-module is disabled and this isn't a local env reference
     assert!(result.is_none(), "Cross-module should not resolve when disabled");
 }
+
+struct VaultProvider;
+
+impl ecolog_lsp::server::env_resolution::EnvSourceProvider for VaultProvider {
+    fn name(&self) -> &str {
+        "vault"
+    }
+
+    fn resolve(&self, key: &str) -> Option<String> {
+        (key == "VAULT_SECRET").then(|| "decrypted".to_string())
+    }
+
+    fn keys(&self) -> Vec<compact_str::CompactString> {
+        vec!["VAULT_SECRET".into()]
+    }
+}
+
+#[tokio::test]
+async fn test_env_source_provider_fallback() {
+    use ecolog_lsp::server::handlers::{compute_diagnostics, handle_hover};
+    use tower_lsp::lsp_types::{HoverContents, HoverParams, TextDocumentIdentifier, TextDocumentPositionParams};
+
+    let mut fixture = TestFixture::new().await;
+    fixture.state = fixture
+        .state
+        .clone()
+        .with_env_source_provider(std::sync::Arc::new(VaultProvider));
+
+    let content = "const s = process.env.VAULT_SECRET;";
+    let uri = fixture.create_file("test.js", content);
+    fixture
+        .state
+        .document_manager
+        .open(uri.clone(), "javascript".into(), content.into(), 1)
+        .await;
+
+    let hover = handle_hover(
+        HoverParams {
+            text_document_position_params: TextDocumentPositionParams {
+                text_document: TextDocumentIdentifier { uri: uri.clone() },
+                position: Position::new(0, 24),
+            },
+            work_done_progress_params: Default::default(),
+        },
+        &fixture.state,
+    )
+    .await
+    .expect("Provider value should be shown on hover");
+
+    let HoverContents::Markup(markup) = hover.contents else {
+        panic!("Expected markup hover");
+    };
+    assert!(markup.value.contains("`decrypted`"), "got {}", markup.value);
+    assert!(markup.value.contains("`vault`"), "got {}", markup.value);
+
+    // Provider-backed vars aren't reported as undefined
+    let diagnostics = compute_diagnostics(&uri, &fixture.state).await;
+    assert!(
+        diagnostics.is_empty(),
+        "Provider-supplied var should not be undefined: {:?}",
+        diagnostics
+    );
+}
+
+/// Toy format: one `KEY: value` pair per line.
+struct MyEnvPlugin;
+
+impl ecolog_lsp::analysis::EnvSourcePlugin for MyEnvPlugin {
+    fn name(&self) -> &str {
+        "myenv"
+    }
+
+    fn file_patterns(&self) -> &[&str] {
+        &["*.myenv"]
+    }
+
+    fn parse(&self, content: &str) -> Vec<ecolog_lsp::types::EnvDefinition> {
+        content
+            .lines()
+            .enumerate()
+            .filter_map(|(line, text)| {
+                let (key, value) = text.split_once(':')?;
+                Some(ecolog_lsp::types::EnvDefinition {
+                    name: key.trim().into(),
+                    value: value.trim().into(),
+                    name_range: tower_lsp::lsp_types::Range::new(
+                        Position::new(line as u32, 0),
+                        Position::new(line as u32, key.len() as u32),
+                    ),
+                    dynamic: false,
+                })
+            })
+            .collect()
+    }
+}
+
+#[tokio::test]
+async fn test_env_source_plugin_resolves_custom_format() {
+    use ecolog_lsp::server::handlers::{compute_diagnostics, handle_hover};
+    use tower_lsp::lsp_types::{HoverContents, HoverParams, TextDocumentIdentifier, TextDocumentPositionParams};
+
+    let fixture = TestFixture::new().await;
+    fixture
+        .state
+        .indexer
+        .register_plugin(std::sync::Arc::new(MyEnvPlugin));
+
+    fixture.create_file("secrets.myenv", "PLUGIN_TOKEN: from-plugin\n");
+    let content = "const t = process.env.PLUGIN_TOKEN;";
+    let uri = fixture.create_file("test.js", content);
+    fixture.index_workspace().await;
+
+    fixture
+        .state
+        .document_manager
+        .open(uri.clone(), "javascript".into(), content.into(), 1)
+        .await;
+
+    let hover = handle_hover(
+        HoverParams {
+            text_document_position_params: TextDocumentPositionParams {
+                text_document: TextDocumentIdentifier { uri: uri.clone() },
+                position: Position::new(0, 24),
+            },
+            work_done_progress_params: Default::default(),
+        },
+        &fixture.state,
+    )
+    .await
+    .expect("Plugin value should be shown on hover");
+
+    let HoverContents::Markup(markup) = hover.contents else {
+        panic!("Expected markup hover");
+    };
+    assert!(markup.value.contains("`from-plugin`"), "got {}", markup.value);
+    assert!(markup.value.contains("secrets.myenv"), "got {}", markup.value);
+
+    let diagnostics = compute_diagnostics(&uri, &fixture.state).await;
+    assert!(diagnostics.is_empty(), "got {:?}", diagnostics);
+}
@@ -3,7 +3,7 @@
 mod common;
 
 use common::TestFixture;
-use ecolog_lsp::server::handlers::handle_inlay_hints;
+use ecolog_lsp::server::handlers::{handle_inlay_hint_resolve, handle_inlay_hints};
 use tower_lsp::lsp_types::{
     InlayHintParams, Position, Range, TextDocumentIdentifier,
 };
@@ -244,3 +244,149 @@ async fn test_inlay_hints_undefined_var() {
         assert!(!has_undefined, "Should not show hint for undefined var");
     }
 }
+
+#[tokio::test]
+async fn test_inlay_hints_multiline_reference_end() {
+    let fixture = TestFixture::new().await;
+    {
+        let config_arc = fixture.state.config.get_config();
+        let mut config = config_arc.write().await.clone();
+        config.features.inlay_hints = true;
+        config.inlay_hints.binding_declarations = false;
+        fixture.state.config.update(config).await;
+    }
+
+    let content = "foo(process.env[\n  \"DB_URL\"\n]);";
+    let uri = fixture.create_file("test.js", content);
+    fixture
+        .state
+        .document_manager
+        .open(uri.clone(), "javascript".into(), content.into(), 1)
+        .await;
+
+    let params = make_inlay_params(uri, Position::new(0, 0), Position::new(3, 0));
+    let hints = handle_inlay_hints(params, &fixture.state)
+        .await
+        .expect("Inlay hints should be enabled");
+
+    assert_eq!(hints.len(), 1, "got {:?}", hints);
+    // After the closing bracket, not after the name on the middle line
+    assert_eq!(hints[0].position, Position::new(2, 1));
+}
+
+#[tokio::test]
+async fn test_inlay_hints_deferred_until_resolve() {
+    use std::sync::atomic::Ordering;
+    use tower_lsp::lsp_types::{InlayHintLabel, InlayHintTooltip};
+
+    let fixture = TestFixture::new().await;
+    {
+        let config_arc = fixture.state.config.get_config();
+        let mut config = config_arc.write().await.clone();
+        config.features.inlay_hints = true;
+        fixture.state.config.update(config).await;
+    }
+    fixture
+        .state
+        .defer_inlay_hint_values
+        .store(true, Ordering::Relaxed);
+
+    let content = "const db = process.env.DB_URL;";
+    let uri = fixture.create_file("test.js", content);
+    fixture
+        .state
+        .document_manager
+        .open(uri.clone(), "javascript".into(), content.into(), 1)
+        .await;
+
+    let params = make_inlay_params(uri, Position::new(0, 0), Position::new(0, 30));
+    let hints = handle_inlay_hints(params, &fixture.state)
+        .await
+        .expect("Inlay hints should be enabled");
+
+    assert_eq!(hints.len(), 1, "got {:?}", hints);
+    let hint = hints.into_iter().next().unwrap();
+    let data = hint.data.as_ref().expect("Deferred hint should carry data");
+    assert_eq!(data["name"], "DB_URL");
+    assert!(hint.tooltip.is_none());
+    if let InlayHintLabel::String(label) = &hint.label {
+        assert!(!label.contains("postgres"), "Value should be deferred, got {}", label);
+    }
+
+    let resolved = handle_inlay_hint_resolve(hint, &fixture.state).await;
+    match &resolved.label {
+        InlayHintLabel::String(label) => assert_eq!(label, ": \"postgres://localhost\""),
+        other => panic!("Unexpected label {:?}", other),
+    }
+    match &resolved.tooltip {
+        Some(InlayHintTooltip::String(tooltip)) => assert!(tooltip.starts_with("Source:")),
+        other => panic!("Unexpected tooltip {:?}", other),
+    }
+    assert!(resolved.data.is_none());
+}
+
+#[tokio::test]
+async fn test_inlay_hints_deferred_skips_undefined_vars() {
+    use std::sync::atomic::Ordering;
+    use tower_lsp::lsp_types::InlayHintLabel;
+
+    let fixture = TestFixture::new().await;
+    {
+        let config_arc = fixture.state.config.get_config();
+        let mut config = config_arc.write().await.clone();
+        config.features.inlay_hints = true;
+        fixture.state.config.update(config).await;
+    }
+    fixture
+        .state
+        .defer_inlay_hint_values
+        .store(true, Ordering::Relaxed);
+
+    let content = "const db = process.env.DB_URL;\nconst x = process.env.NOT_DEFINED_ANYWHERE;";
+    let uri = fixture.create_file("test.js", content);
+    fixture
+        .state
+        .document_manager
+        .open(uri.clone(), "javascript".into(), content.into(), 1)
+        .await;
+
+    let params = make_inlay_params(uri, Position::new(0, 0), Position::new(2, 0));
+    let hints = handle_inlay_hints(params, &fixture.state)
+        .await
+        .expect("Inlay hints should be enabled");
+
+    assert_eq!(hints.len(), 1, "got {:?}", hints);
+    assert_eq!(hints[0].data.as_ref().unwrap()["name"], "DB_URL");
+    assert!(matches!(&hints[0].label, InlayHintLabel::String(label) if label == ": …"));
+}
+
+#[tokio::test]
+async fn test_inlay_hint_resolve_leaves_unresolvable_hint_unchanged() {
+    use tower_lsp::lsp_types::{InlayHint, InlayHintLabel};
+
+    let fixture = TestFixture::new().await;
+    let uri = fixture.create_file("test.js", "process.env.GONE;");
+    let placeholder = InlayHint {
+        position: Position::new(0, 16),
+        label: InlayHintLabel::String(": …".to_string()),
+        kind: None,
+        text_edits: None,
+        tooltip: None,
+        padding_left: None,
+        padding_right: None,
+        data: Some(serde_json::json!({ "uri": uri, "name": "GONE" })),
+    };
+
+    let resolved = handle_inlay_hint_resolve(placeholder.clone(), &fixture.state).await;
+    assert!(matches!(&resolved.label, InlayHintLabel::String(label) if label == ": …"));
+    assert!(resolved.tooltip.is_none());
+    assert_eq!(resolved.data, placeholder.data);
+
+    let without_data = InlayHint {
+        data: None,
+        ..placeholder
+    };
+    let unchanged = handle_inlay_hint_resolve(without_data, &fixture.state).await;
+    assert!(matches!(&unchanged.label, InlayHintLabel::String(label) if label == ": …"));
+    assert!(unchanged.tooltip.is_none());
+}
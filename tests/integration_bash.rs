@@ -171,3 +171,40 @@ async fn test_bash_multiple_expansions() {
     assert!(hover2.is_some(), "Expected hover for ${{API_KEY}}");
     assert!(format!("{:?}", hover2.unwrap()).contains("secret_key"));
 }
+
+#[tokio::test]
+async fn test_bash_hover_expansion_in_string_and_heredoc() {
+    let fixture = TestFixture::new().await;
+    fixture.create_file(".env", "DATABASE_URL=postgres://db\n");
+    fixture
+        .state
+        .core
+        .refresh(abundantis::RefreshOptions::reset_all())
+        .await
+        .unwrap();
+
+    let content = "psql \"${DATABASE_URL}\"\ncat <<EOF\nurl=${DATABASE_URL}\nEOF\n";
+    let uri = fixture.create_file("script.sh", content);
+    fixture
+        .state
+        .document_manager
+        .open(uri.clone(), "shellscript".to_string(), content.to_string(), 0)
+        .await;
+
+    for position in [Position::new(0, 10), Position::new(2, 8)] {
+        let hover = handle_hover(
+            HoverParams {
+                text_document_position_params: TextDocumentPositionParams {
+                    text_document: TextDocumentIdentifier { uri: uri.clone() },
+                    position,
+                },
+                work_done_progress_params: Default::default(),
+            },
+            &fixture.state,
+        )
+        .await;
+
+        assert!(hover.is_some(), "Expected hover at {:?}", position);
+        assert!(format!("{:?}", hover.unwrap()).contains("postgres://db"));
+    }
+}
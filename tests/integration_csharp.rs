@@ -60,3 +60,65 @@ async fn test_csharp_diagnostics_undefined() {
 }
 
 // Note: Completion tests skipped for now - completion context queries need refinement
+
+async fn hover_text(fixture: &TestFixture, content: &str, position: Position) -> Option<String> {
+    let uri = fixture.create_file("Program.cs", content);
+    fixture
+        .state
+        .document_manager
+        .open(uri.clone(), "csharp".to_string(), content.to_string(), 0)
+        .await;
+
+    handle_hover(
+        HoverParams {
+            text_document_position_params: TextDocumentPositionParams {
+                text_document: TextDocumentIdentifier { uri },
+                position,
+            },
+            work_done_progress_params: Default::default(),
+        },
+        &fixture.state,
+    )
+    .await
+    .map(|hover| format!("{:?}", hover))
+}
+
+#[tokio::test]
+async fn test_csharp_hover_getenv_with_target() {
+    let fixture = TestFixture::new().await;
+    let content =
+        r#"var port = Environment.GetEnvironmentVariable("PORT", EnvironmentVariableTarget.User);"#;
+
+    let hover = hover_text(&fixture, content, Position::new(0, 49)).await;
+    assert!(hover.expect("hover on the first argument").contains("8080"));
+    // The target argument isn't a key
+    assert!(hover_text(&fixture, content, Position::new(0, 75)).await.is_none());
+}
+
+#[tokio::test]
+async fn test_csharp_hover_configuration_indexer() {
+    let fixture = TestFixture::new().await;
+    let content =
+        "var key = Configuration[\"API_KEY\"];\nvar url = builder.Configuration[\"DB_URL\"];";
+
+    let hover = hover_text(&fixture, content, Position::new(0, 27)).await;
+    assert!(hover.expect("hover on Configuration[...]").contains("secret_key"));
+
+    let hover = hover_text(&fixture, content, Position::new(1, 35)).await;
+    assert!(hover.expect("hover on builder.Configuration[...]").contains("postgres://"));
+}
+
+#[tokio::test]
+async fn test_csharp_hover_configuration_get_value() {
+    let fixture = TestFixture::new().await;
+    let content = concat!(
+        "var port = builder.Configuration.GetValue<int>(\"PORT\");\n",
+        "var debug = configuration.GetValue<bool>(\"DEBUG\");",
+    );
+
+    let hover = hover_text(&fixture, content, Position::new(0, 49)).await;
+    assert!(hover.expect("hover on Configuration.GetValue<T>").contains("8080"));
+
+    let hover = hover_text(&fixture, content, Position::new(1, 44)).await;
+    assert!(hover.expect("hover on configuration.GetValue<T>").contains("true"));
+}
@@ -0,0 +1,150 @@
+mod common;
+use common::TestFixture;
+use ecolog_lsp::server::handlers::{compute_diagnostics, handle_hover};
+use tower_lsp::lsp_types::{
+    HoverParams, Position, TextDocumentIdentifier, TextDocumentPositionParams, Url,
+};
+
+async fn hover_at(fixture: &TestFixture, uri: Url, position: Position) -> Option<String> {
+    handle_hover(
+        HoverParams {
+            text_document_position_params: TextDocumentPositionParams {
+                text_document: TextDocumentIdentifier { uri },
+                position,
+            },
+            work_done_progress_params: Default::default(),
+        },
+        &fixture.state,
+    )
+    .await
+    .map(|hover| format!("{:?}", hover))
+}
+
+#[tokio::test]
+async fn test_dart_hover_string_from_environment() {
+    let fixture = TestFixture::new().await;
+    let content = "void main() {\n  print(String.fromEnvironment('DB_URL'));\n}\n";
+    let uri = fixture.create_file("main.dart", content);
+
+    fixture
+        .state
+        .document_manager
+        .open(uri.clone(), "dart".to_string(), content.to_string(), 0)
+        .await;
+
+    let hover = hover_at(&fixture, uri, Position::new(1, 33)).await;
+
+    assert!(hover.is_some(), "Expected hover for String.fromEnvironment");
+    assert!(hover.unwrap().contains("postgres://"));
+}
+
+#[tokio::test]
+async fn test_dart_hover_const_from_environment() {
+    let fixture = TestFixture::new().await;
+    let content = "void main() {\n  var port = const int.fromEnvironment(\"PORT\");\n}\n";
+    let uri = fixture.create_file("main.dart", content);
+
+    fixture
+        .state
+        .document_manager
+        .open(uri.clone(), "dart".to_string(), content.to_string(), 0)
+        .await;
+
+    let hover = hover_at(&fixture, uri, Position::new(1, 41)).await;
+
+    assert!(hover.is_some(), "Expected hover for const int.fromEnvironment");
+    assert!(hover.unwrap().contains("8080"));
+}
+
+#[tokio::test]
+async fn test_dart_hover_dotenv_env() {
+    let fixture = TestFixture::new().await;
+    let content = "void main() {\n  print(dotenv.env['API_KEY']);\n}\n";
+    let uri = fixture.create_file("main.dart", content);
+
+    fixture
+        .state
+        .document_manager
+        .open(uri.clone(), "dart".to_string(), content.to_string(), 0)
+        .await;
+
+    let hover = hover_at(&fixture, uri, Position::new(1, 22)).await;
+
+    assert!(hover.is_some(), "Expected hover for dotenv.env");
+    assert!(hover.unwrap().contains("secret_key"));
+}
+
+#[tokio::test]
+async fn test_dart_diagnostics_undefined() {
+    let fixture = TestFixture::new().await;
+    let content = "void main() {\n  print(dotenv.env['UNDEFINED_VAR']);\n  print(String.fromEnvironment('DB_URL'));\n}\n";
+    let uri = fixture.create_file("main.dart", content);
+
+    fixture
+        .state
+        .document_manager
+        .open(uri.clone(), "dart".to_string(), content.to_string(), 0)
+        .await;
+
+    let diags = compute_diagnostics(&uri, &fixture.state).await;
+
+    assert_eq!(diags.len(), 1);
+    assert!(diags[0].message.contains("UNDEFINED_VAR"));
+}
+
+#[tokio::test]
+async fn test_dart_env_vars_in_comments_ignored() {
+    let fixture = TestFixture::new().await;
+    let content = "void main() {\n  // dotenv.env['UNDEFINED_VAR']\n  /* String.fromEnvironment('OTHER_UNDEFINED') */\n}\n";
+    let uri = fixture.create_file("main.dart", content);
+
+    fixture
+        .state
+        .document_manager
+        .open(uri.clone(), "dart".to_string(), content.to_string(), 0)
+        .await;
+
+    let diags = compute_diagnostics(&uri, &fixture.state).await;
+
+    assert!(diags.is_empty(), "Expected no diagnostics, got {:?}", diags);
+}
+
+#[tokio::test]
+async fn test_dart_binding() {
+    let fixture = TestFixture::new().await;
+    let content = "final dbUrl = String.fromEnvironment('DB_URL');\n";
+    let uri = fixture.create_file("main.dart", content);
+
+    fixture
+        .state
+        .document_manager
+        .open(uri.clone(), "dart".to_string(), content.to_string(), 0)
+        .await;
+
+    // Hover on the binding name "dbUrl"
+    let hover = hover_at(&fixture, uri, Position::new(0, 8)).await;
+
+    assert!(hover.is_some(), "Expected hover on 'dbUrl' binding");
+    assert!(hover.unwrap().contains("postgres://"));
+}
+
+#[tokio::test]
+async fn test_dart_completion_context_dotenv() {
+    let fixture = TestFixture::new().await;
+    let content = "void main() {\n  print(dotenv.env['']);\n}\n";
+    let uri = fixture.create_file("main.dart", content);
+
+    fixture
+        .state
+        .document_manager
+        .open(uri.clone(), "dart".to_string(), content.to_string(), 0)
+        .await;
+
+    assert!(
+        fixture
+            .state
+            .document_manager
+            .check_completion(&uri, Position::new(1, 20))
+            .await
+    );
+}
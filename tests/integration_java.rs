@@ -92,3 +92,40 @@ async fn test_java_diagnostics_undefined() {
 }
 
 // Note: Completion tests skipped for now - completion context queries need refinement
+
+#[tokio::test]
+async fn test_java_hover_resolves_application_properties() {
+    let fixture = TestFixture::with_files(&[(
+        "src/main/resources/application.properties",
+        "# Payments\nSTRIPE_KEY=sk_test_123\nspring.datasource.url=jdbc:postgresql://${DB_HOST}/app\n",
+    )])
+    .await;
+    fixture.index_workspace().await;
+
+    let content = r#"String key = System.getenv("STRIPE_KEY");"#;
+    let uri = fixture.create_file("src/main/java/Main.java", content);
+    fixture
+        .state
+        .document_manager
+        .open(uri.clone(), "java".to_string(), content.to_string(), 0)
+        .await;
+
+    let hover = handle_hover(
+        HoverParams {
+            text_document_position_params: TextDocumentPositionParams {
+                text_document: TextDocumentIdentifier { uri: uri.clone() },
+                position: Position::new(0, 30),
+            },
+            work_done_progress_params: Default::default(),
+        },
+        &fixture.state,
+    )
+    .await;
+
+    let hover = format!("{:?}", hover.expect("Expected hover for properties key"));
+    assert!(hover.contains("sk_test_123"), "{}", hover);
+    assert!(hover.contains("application.properties"), "{}", hover);
+
+    let diags = compute_diagnostics(&uri, &fixture.state).await;
+    assert!(diags.is_empty(), "{:?}", diags);
+}
@@ -1,11 +1,13 @@
 mod common;
 use common::TestFixture;
 use ecolog_lsp::server::handlers::{
-    compute_diagnostics, handle_completion, handle_definition, handle_hover,
+    completion_response, compute_diagnostics, handle_completion, handle_definition, handle_hover,
+    handle_selection_range,
 };
 use tower_lsp::lsp_types::{
-    CompletionContext, CompletionParams, CompletionTriggerKind, GotoDefinitionParams, HoverParams,
-    Position, TextDocumentIdentifier, TextDocumentPositionParams,
+    CompletionContext, CompletionItem, CompletionParams, CompletionResponse, CompletionTriggerKind,
+    GotoDefinitionParams, HoverParams, Position, Range, SelectionRangeParams,
+    TextDocumentIdentifier, TextDocumentPositionParams, Url,
 };
 
 #[tokio::test]
@@ -40,6 +42,36 @@ async fn test_js_hover_direct() {
     assert!(format!("{:?}", hover.unwrap()).contains("postgres://"));
 }
 
+#[tokio::test]
+async fn test_js_hover_in_template_literal() {
+    let fixture = TestFixture::new().await;
+    let content = "const url = `${process.env.DB_URL}/users?key=${process.env['API_KEY']}`;";
+    let uri = fixture.create_file("test.js", content);
+
+    fixture
+        .state
+        .document_manager
+        .open(uri.clone(), "javascript".to_string(), content.to_string(), 0)
+        .await;
+
+    for (character, expected) in [(28, "postgres://"), (62, "secret_key")] {
+        let hover = handle_hover(
+            HoverParams {
+                text_document_position_params: TextDocumentPositionParams {
+                    text_document: TextDocumentIdentifier { uri: uri.clone() },
+                    position: Position::new(0, character),
+                },
+                work_done_progress_params: Default::default(),
+            },
+            &fixture.state,
+        )
+        .await;
+
+        assert!(hover.is_some(), "Expected hover at character {}", character);
+        assert!(format!("{:?}", hover.unwrap()).contains(expected));
+    }
+}
+
 #[tokio::test]
 async fn test_js_hover_bracket() {
     let fixture = TestFixture::new().await;
@@ -143,6 +175,52 @@ async fn test_js_completion_trigger() {
     assert!(items.iter().any(|i| i.label == "PORT"));
 }
 
+#[tokio::test]
+async fn test_js_completion_skips_denylisted_env_var() {
+    let fixture = TestFixture::new().await;
+    let uri = fixture.create_file("test.js", "process.env.");
+    fixture
+        .state
+        .document_manager
+        .open(
+            uri.clone(),
+            "javascript".to_string(),
+            "process.env.".to_string(),
+            0,
+        )
+        .await;
+
+    let complete = || {
+        handle_completion(
+            CompletionParams {
+                text_document_position: TextDocumentPositionParams {
+                    text_document: TextDocumentIdentifier { uri: uri.clone() },
+                    position: Position::new(0, 12),
+                },
+                work_done_progress_params: Default::default(),
+                partial_result_params: Default::default(),
+                context: Some(CompletionContext {
+                    trigger_kind: CompletionTriggerKind::TRIGGER_CHARACTER,
+                    trigger_character: Some(".".to_string()),
+                }),
+            },
+            &fixture.state,
+        )
+    };
+
+    let items = complete().await.unwrap();
+    assert!(items.iter().any(|i| i.label == "PATH"));
+
+    let config_arc = fixture.state.config.get_config();
+    let mut config = config_arc.write().await.clone();
+    config.env_var_denylist = vec!["PATH".to_string()];
+    fixture.state.config.update(config).await;
+
+    let items = complete().await.unwrap();
+    assert!(!items.iter().any(|i| i.label == "PATH"));
+    assert!(items.iter().any(|i| i.label == "DB_URL"));
+}
+
 #[tokio::test]
 async fn test_js_definition_direct() {
     let fixture = TestFixture::new().await;
@@ -232,6 +310,59 @@ async fn test_js_object_alias_hover() {
     assert!(format!("{:?}", hover.unwrap()).contains("8080")); 
 }
 
+async fn hover_text(fixture: &TestFixture, uri: &Url, position: Position) -> Option<String> {
+    let hover = handle_hover(
+        HoverParams {
+            text_document_position_params: TextDocumentPositionParams {
+                text_document: TextDocumentIdentifier { uri: uri.clone() },
+                position,
+            },
+            work_done_progress_params: Default::default(),
+        },
+        &fixture.state,
+    )
+    .await?;
+    Some(format!("{:?}", hover))
+}
+
+#[tokio::test]
+async fn test_js_env_spread_object_hover() {
+    let fixture = TestFixture::new().await;
+    let content = "const cfg = { ...process.env };
+console.log(cfg.PORT);";
+    let uri = fixture.create_file("test.js", content);
+    fixture
+        .state
+        .document_manager
+        .open(uri.clone(), "javascript".to_string(), content.to_string(), 0)
+        .await;
+
+    let hover = hover_text(&fixture, &uri, Position::new(1, 17)).await;
+    assert!(hover.expect("Expected hover on cfg.PORT").contains("8080"));
+}
+
+#[tokio::test]
+async fn test_ts_env_spread_object_literal_keys_shadow_env() {
+    let fixture = TestFixture::new().await;
+    let content = "const cfg = { DEBUG: 'no', ...process.env, PORT: 3000 };
+\
+                   cfg.PORT;\ncfg.DEBUG;\ncfg.API_KEY;";
+    let uri = fixture.create_file("config.ts", content);
+    fixture
+        .state
+        .document_manager
+        .open(uri.clone(), "typescript".to_string(), content.to_string(), 0)
+        .await;
+
+    // PORT is set after the spread, so it never comes from the env
+    assert!(hover_text(&fixture, &uri, Position::new(1, 5)).await.is_none());
+    // DEBUG is set before the spread, which overrides it
+    let debug = hover_text(&fixture, &uri, Position::new(2, 5)).await;
+    assert!(debug.expect("Expected hover on cfg.DEBUG").contains("true"));
+    let api_key = hover_text(&fixture, &uri, Position::new(3, 5)).await;
+    assert!(api_key.expect("Expected hover on cfg.API_KEY").contains("secret_key"));
+}
+
 
 
 #[tokio::test]
@@ -302,10 +433,7 @@ async fn test_ts_completion_on_alias() {
     
     if let Some(items) = completion {
         assert!(items.iter().any(|i| i.label == "PORT"));
-    } else {
-        
-        
-    }
+    } 
 }
 
 
@@ -707,3 +835,472 @@ async fn test_ts_destructuring_with_type_cast_and_rename() {
     assert!(hover.is_some(), "Expected hover on API_KEY property key");
     assert!(format!("{:?}", hover.unwrap()).contains("secret_key"));
 }
+
+#[tokio::test]
+async fn test_ts_sveltekit_static_private_import_hover() {
+    let fixture = TestFixture::new().await;
+    let content = "import { API_KEY } from '$env/static/private';\nconst key = API_KEY;";
+    let uri = fixture.create_file("+page.server.ts", content);
+    fixture
+        .state
+        .document_manager
+        .open(uri.clone(), "typescript".to_string(), content.to_string(), 0)
+        .await;
+
+    for position in [Position::new(0, 11), Position::new(1, 14)] {
+        let hover = handle_hover(
+            HoverParams {
+                text_document_position_params: TextDocumentPositionParams {
+                    text_document: TextDocumentIdentifier { uri: uri.clone() },
+                    position,
+                },
+                work_done_progress_params: Default::default(),
+            },
+            &fixture.state,
+        )
+        .await;
+
+        assert!(hover.is_some(), "Expected hover on API_KEY at {:?}", position);
+        assert!(format!("{:?}", hover.unwrap()).contains("secret_key"));
+    }
+}
+
+#[tokio::test]
+async fn test_ts_sveltekit_static_public_import_rename_undefined() {
+    let fixture = TestFixture::new().await;
+    let content = "import { PUBLIC_MISSING as missing } from '$env/static/public';\nconsole.log(missing);";
+    let uri = fixture.create_file("+page.ts", content);
+    fixture
+        .state
+        .document_manager
+        .open(uri.clone(), "typescript".to_string(), content.to_string(), 0)
+        .await;
+
+    let diags = compute_diagnostics(&uri, &fixture.state).await;
+
+    assert!(diags.iter().any(|d| d.message.contains("PUBLIC_MISSING")));
+}
+
+#[tokio::test]
+async fn test_ts_sveltekit_dynamic_private_env_hover() {
+    let fixture = TestFixture::new().await;
+    let content = "import { env } from '$env/dynamic/private';\nconst url = env.DB_URL;";
+    let uri = fixture.create_file("+page.server.ts", content);
+    fixture
+        .state
+        .document_manager
+        .open(uri.clone(), "typescript".to_string(), content.to_string(), 0)
+        .await;
+
+    let hover = handle_hover(
+        HoverParams {
+            text_document_position_params: TextDocumentPositionParams {
+                text_document: TextDocumentIdentifier { uri },
+                position: Position::new(1, 18),
+            },
+            work_done_progress_params: Default::default(),
+        },
+        &fixture.state,
+    )
+    .await;
+
+    assert!(hover.is_some(), "Expected hover on env.DB_URL");
+    assert!(format!("{:?}", hover.unwrap()).contains("postgres://"));
+}
+
+#[tokio::test]
+async fn test_ts_non_sveltekit_named_import_ignored() {
+    let fixture = TestFixture::new().await;
+    let content = "import { UNDEFINED_VAR } from './config';\nconsole.log(UNDEFINED_VAR);";
+    let uri = fixture.create_file("test.ts", content);
+    fixture
+        .state
+        .document_manager
+        .open(uri.clone(), "typescript".to_string(), content.to_string(), 0)
+        .await;
+
+    let diags = compute_diagnostics(&uri, &fixture.state).await;
+
+    assert!(diags.is_empty(), "Expected no diagnostics, got {:?}", diags);
+}
+
+#[tokio::test]
+async fn test_ts_deno_env_get_hover() {
+    let fixture = TestFixture::new().await;
+    let content = "const url = Deno.env.get(\"DB_URL\");\nconst missing = Deno.env.get('UNDEFINED_VAR');";
+    let uri = fixture.create_file("main.ts", content);
+    fixture
+        .state
+        .document_manager
+        .open(uri.clone(), "typescript".to_string(), content.to_string(), 0)
+        .await;
+
+    let hover = handle_hover(
+        HoverParams {
+            text_document_position_params: TextDocumentPositionParams {
+                text_document: TextDocumentIdentifier { uri: uri.clone() },
+                position: Position::new(0, 28),
+            },
+            work_done_progress_params: Default::default(),
+        },
+        &fixture.state,
+    )
+    .await;
+
+    assert!(hover.is_some(), "Expected hover on Deno.env.get");
+    assert!(format!("{:?}", hover.unwrap()).contains("postgres://"));
+
+    let diags = compute_diagnostics(&uri, &fixture.state).await;
+    assert_eq!(diags.len(), 1, "got {:?}", diags);
+    assert!(diags[0].message.contains("UNDEFINED_VAR"));
+}
+
+#[tokio::test]
+async fn test_ts_deno_env_to_object_is_dynamic_access() {
+    let fixture = TestFixture::new().await;
+    let content = "const all = Deno.env.toObject();";
+    let uri = fixture.create_file("main.ts", content);
+    fixture
+        .state
+        .document_manager
+        .open(uri.clone(), "typescript".to_string(), content.to_string(), 0)
+        .await;
+
+    let graph = fixture.state.document_manager.get_binding_graph(&uri).unwrap();
+    assert!(graph.has_dynamic_env_access());
+    assert!(graph.direct_references().is_empty());
+
+    let content = "// Deno.env.toObject()\nconst url = Deno.env.get('DB_URL');";
+    let uri = fixture.create_file("other.ts", content);
+    fixture
+        .state
+        .document_manager
+        .open(uri.clone(), "typescript".to_string(), content.to_string(), 0)
+        .await;
+
+    let graph = fixture.state.document_manager.get_binding_graph(&uri).unwrap();
+    assert!(!graph.has_dynamic_env_access());
+}
+
+#[tokio::test]
+async fn test_js_import_meta_env_spread_is_dynamic_access() {
+    let fixture = TestFixture::new().await;
+    let content = "const config = { ...import.meta.env };";
+    let uri = fixture.create_file("bun.js", content);
+    fixture
+        .state
+        .document_manager
+        .open(uri.clone(), "javascript".to_string(), content.to_string(), 0)
+        .await;
+
+    let graph = fixture.state.document_manager.get_binding_graph(&uri).unwrap();
+    assert!(graph.has_dynamic_env_access());
+
+    let content = "validate(import.meta.env);";
+    let uri = fixture.create_file("validate.js", content);
+    fixture
+        .state
+        .document_manager
+        .open(uri.clone(), "javascript".to_string(), content.to_string(), 0)
+        .await;
+
+    let graph = fixture.state.document_manager.get_binding_graph(&uri).unwrap();
+    assert!(graph.has_dynamic_env_access());
+}
+
+#[tokio::test]
+async fn test_js_child_process_env_option_references() {
+    let fixture = TestFixture::new().await;
+    let content = r#"const { spawn, exec } = require("child_process");
+spawn("node", ["worker.js"], {
+  env: { DB_URL: process.env.DB_URL, TOKEN: process.env["MISSING_TOKEN"] },
+});
+exec("make", { env: { ...process.env, PORT: process.env.PORT } });
+"#;
+    let uri = fixture.create_file("spawn.js", content);
+
+    fixture
+        .state
+        .document_manager
+        .open(
+            uri.clone(),
+            "javascript".to_string(),
+            content.to_string(),
+            0,
+        )
+        .await;
+
+    let hover_at = |position| {
+        handle_hover(
+            HoverParams {
+                text_document_position_params: TextDocumentPositionParams {
+                    text_document: TextDocumentIdentifier { uri: uri.clone() },
+                    position,
+                },
+                work_done_progress_params: Default::default(),
+            },
+            &fixture.state,
+        )
+    };
+
+    let hover = hover_at(Position::new(2, 34)).await;
+    assert!(hover.is_some(), "Expected hover inside spawn env option");
+    assert!(format!("{:?}", hover.unwrap()).contains("postgres://"));
+
+    let hover = hover_at(Position::new(4, 57)).await;
+    assert!(hover.is_some(), "Expected hover inside exec env option");
+    assert!(format!("{:?}", hover.unwrap()).contains("8080"));
+
+    let diagnostics = compute_diagnostics(&uri, &fixture.state).await;
+    assert_eq!(diagnostics.len(), 1, "{:?}", diagnostics);
+    assert!(diagnostics[0].message.contains("MISSING_TOKEN"));
+}
+
+#[tokio::test]
+async fn test_js_selection_range_expands_to_full_reference() {
+    let fixture = TestFixture::new().await;
+    let content = "const a = process.env.DB_URL;";
+    let uri = fixture.create_file("test.js", content);
+    fixture
+        .state
+        .document_manager
+        .open(uri.clone(), "javascript".to_string(), content.to_string(), 0)
+        .await;
+
+    let ranges = handle_selection_range(
+        SelectionRangeParams {
+            text_document: TextDocumentIdentifier { uri },
+            positions: vec![Position::new(0, 24)],
+            work_done_progress_params: Default::default(),
+            partial_result_params: Default::default(),
+        },
+        &fixture.state,
+    )
+    .await
+    .expect("selection ranges");
+
+    let name = &ranges[0];
+    assert_eq!(name.range, Range::new(Position::new(0, 22), Position::new(0, 28)));
+    let full = name.parent.as_ref().expect("full reference range");
+    assert_eq!(full.range, Range::new(Position::new(0, 10), Position::new(0, 28)));
+    let outer = full.parent.as_ref().expect("enclosing node range");
+    assert!(outer.range.start <= full.range.start && full.range.end <= outer.range.end);
+    assert_ne!(outer.range, full.range);
+}
+
+#[tokio::test]
+async fn test_js_completion_invoked_mid_identifier() {
+    let fixture = TestFixture::new().await;
+    let content = "const a = process.env.DB_U;";
+    let uri = fixture.create_file("test.js", content);
+    fixture
+        .state
+        .document_manager
+        .open(uri.clone(), "javascript".to_string(), content.to_string(), 0)
+        .await;
+
+    let complete = |trigger_kind| CompletionParams {
+        text_document_position: TextDocumentPositionParams {
+            text_document: TextDocumentIdentifier { uri: uri.clone() },
+            position: Position::new(0, 24),
+        },
+        work_done_progress_params: Default::default(),
+        partial_result_params: Default::default(),
+        context: Some(CompletionContext {
+            trigger_kind,
+            trigger_character: None,
+        }),
+    };
+
+    // Typing mid-identifier isn't a trigger
+    let typed = handle_completion(
+        complete(CompletionTriggerKind::TRIGGER_FOR_INCOMPLETE_COMPLETIONS),
+        &fixture.state,
+    )
+    .await;
+    assert!(typed.is_none());
+
+    let invoked = handle_completion(complete(CompletionTriggerKind::INVOKED), &fixture.state)
+        .await
+        .expect("manual invoke completes inside an env access");
+    assert!(invoked.iter().any(|i| i.label == "DB_URL"));
+}
+
+#[tokio::test]
+async fn test_js_custom_env_accessor_from_config() {
+    let fixture = TestFixture::new().await;
+    let config = ecolog_lsp::server::config::EcologConfig::from_toml_str(
+        "[[custom_env_accessors]]\ncallee = \"env\"\n\n[[custom_env_accessors]]\ncallee = \"config.get\"\narg_index = 1\n",
+    )
+    .unwrap();
+    fixture.state.set_env_accessors(config.custom_env_accessors);
+
+    let content = "const a = env(\"DB_URL\");\nconst b = config.get(ctx, 'MISSING_VAR');\nconst c = env(name);\nconst d = other(\"PORT\");";
+    let uri = fixture.create_file("test.js", content);
+    fixture
+        .state
+        .document_manager
+        .open(uri.clone(), "javascript".to_string(), content.to_string(), 0)
+        .await;
+
+    let hover = handle_hover(
+        HoverParams {
+            text_document_position_params: TextDocumentPositionParams {
+                text_document: TextDocumentIdentifier { uri: uri.clone() },
+                position: Position::new(0, 16),
+            },
+            work_done_progress_params: Default::default(),
+        },
+        &fixture.state,
+    )
+    .await;
+    assert!(format!("{:?}", hover.expect("hover on env(\"DB_URL\")")).contains("postgres://"));
+
+    let diags = compute_diagnostics(&uri, &fixture.state).await;
+    assert_eq!(diags.len(), 1, "{:?}", diags);
+    assert!(diags[0].message.contains("MISSING_VAR"));
+    assert_eq!(diags[0].range.start, Position::new(1, 27));
+}
+
+#[tokio::test]
+async fn test_js_completion_preselects_best_prefix_match() {
+    let fixture = TestFixture::new().await;
+    fixture.create_file(".env", "API_KEY=secret\nDATABASE_URL=postgres://db\nDEBUG=true\n");
+    fixture
+        .state
+        .core
+        .refresh(abundantis::RefreshOptions::reset_all())
+        .await
+        .unwrap();
+
+    let content = "const a = process.env.DA";
+    let uri = fixture.create_file("test.js", content);
+    fixture
+        .state
+        .document_manager
+        .open(uri.clone(), "javascript".to_string(), content.to_string(), 0)
+        .await;
+
+    let items = handle_completion(
+        CompletionParams {
+            text_document_position: TextDocumentPositionParams {
+                text_document: TextDocumentIdentifier { uri },
+                position: Position::new(0, 24),
+            },
+            work_done_progress_params: Default::default(),
+            partial_result_params: Default::default(),
+            context: Some(CompletionContext {
+                trigger_kind: CompletionTriggerKind::INVOKED,
+                trigger_character: None,
+            }),
+        },
+        &fixture.state,
+    )
+    .await
+    .expect("completion");
+
+    let preselected: Vec<_> = items
+        .iter()
+        .filter(|i| i.preselect == Some(true))
+        .map(|i| i.label.as_str())
+        .collect();
+    assert_eq!(preselected, vec!["DATABASE_URL"]);
+
+    let sort_text = |label: &str| {
+        items
+            .iter()
+            .find(|i| i.label == label)
+            .and_then(|i| i.sort_text.clone())
+            .unwrap()
+    };
+    assert!(sort_text("DATABASE_URL") < sort_text("API_KEY"));
+}
+
+#[tokio::test]
+async fn test_js_completion_caps_large_lists_as_incomplete() {
+    let fixture = TestFixture::new().await;
+    let env: String = (0..500).map(|i| format!("VAR_{:03}=value\n", i)).collect();
+    fixture.create_file(".env", &env);
+    fixture
+        .state
+        .core
+        .refresh(abundantis::RefreshOptions::reset_all())
+        .await
+        .unwrap();
+
+    let content = "const a = process.env.VAR_49";
+    let uri = fixture.create_file("test.js", content);
+    fixture
+        .state
+        .document_manager
+        .open(uri.clone(), "javascript".to_string(), content.to_string(), 0)
+        .await;
+
+    let items = handle_completion(
+        CompletionParams {
+            text_document_position: TextDocumentPositionParams {
+                text_document: TextDocumentIdentifier { uri },
+                position: Position::new(0, 28),
+            },
+            work_done_progress_params: Default::default(),
+            partial_result_params: Default::default(),
+            context: Some(CompletionContext {
+                trigger_kind: CompletionTriggerKind::INVOKED,
+                trigger_character: None,
+            }),
+        },
+        &fixture.state,
+    )
+    .await
+    .expect("completion");
+    assert!(items.len() >= 500);
+
+    let max_items = fixture
+        .state
+        .config
+        .get_config()
+        .read()
+        .await
+        .features
+        .max_completion_items;
+    assert_eq!(max_items, 200);
+    let CompletionResponse::List(list) = completion_response(items, max_items) else {
+        panic!("Expected an incomplete list");
+    };
+    assert!(list.is_incomplete);
+    assert_eq!(list.items.len(), 200);
+    // The best matches survive the cut
+    let labels: Vec<_> = list.items.iter().map(|i| i.label.as_str()).collect();
+    let expected: Vec<_> = (490..500).map(|i| format!("VAR_{}", i)).collect();
+    assert_eq!(&labels[..10], &expected);
+
+    let few = vec![CompletionItem::new_simple("A".into(), String::new())];
+    assert!(matches!(completion_response(few, max_items), CompletionResponse::Array(_)));
+}
+
+#[tokio::test]
+async fn test_js_hover_env_object_returned_from_function() {
+    let fixture = TestFixture::new().await;
+    let content = "function getEnv() {\n  return process.env;\n}\nconst e = getEnv();\nconst url = e.DB_URL;";
+    let uri = fixture.create_file("test.js", content);
+    fixture
+        .state
+        .document_manager
+        .open(uri.clone(), "javascript".to_string(), content.to_string(), 0)
+        .await;
+
+    let hover = handle_hover(
+        HoverParams {
+            text_document_position_params: TextDocumentPositionParams {
+                text_document: TextDocumentIdentifier { uri },
+                position: Position::new(4, 16),
+            },
+            work_done_progress_params: Default::default(),
+        },
+        &fixture.state,
+    )
+    .await;
+
+    assert!(hover.is_some(), "Expected hover on e.DB_URL");
+    assert!(format!("{:?}", hover.unwrap()).contains("postgres://"));
+}
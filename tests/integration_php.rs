@@ -4,8 +4,8 @@ use ecolog_lsp::server::handlers::compute_diagnostics;
 use ecolog_lsp::server::handlers::handle_completion;
 use ecolog_lsp::server::handlers::handle_hover;
 use tower_lsp::lsp_types::{
-    CompletionContext, CompletionParams, CompletionTriggerKind, HoverParams, Position,
-    TextDocumentIdentifier, TextDocumentPositionParams,
+    CompletionContext, CompletionParams, CompletionTriggerKind, DiagnosticSeverity, HoverParams,
+    Position, TextDocumentIdentifier, TextDocumentPositionParams,
 };
 
 #[tokio::test]
@@ -234,3 +234,74 @@ async fn test_php_hover_binding() {
     assert!(hover.is_some());
     assert!(format!("{:?}", hover.unwrap()).contains("postgres://"));
 }
+
+#[tokio::test]
+async fn test_php_getenv_short_ternary_default() {
+    let fixture = TestFixture::new().await;
+    let content = "<?php\n$port = getenv('PORT') ?: '3000';\n$mode = getenv('MODE') ? 'on' : 'off';";
+    let uri = fixture.create_file("test.php", content);
+
+    fixture
+        .state
+        .document_manager
+        .open(uri.clone(), "php".to_string(), content.to_string(), 0)
+        .await;
+
+    let reference = fixture
+        .state
+        .document_manager
+        .get_env_reference_cloned(&uri, Position::new(1, 17))
+        .expect("reference");
+    assert!(reference.has_default);
+    assert_eq!(reference.default_value.as_deref(), Some("3000"));
+
+    // A full ternary only branches on the value
+    let reference = fixture
+        .state
+        .document_manager
+        .get_env_reference_cloned(&uri, Position::new(2, 17))
+        .expect("reference");
+    assert!(!reference.has_default);
+
+    let graph = fixture.state.document_manager.get_binding_graph(&uri).unwrap();
+    assert_eq!(graph.direct_references().len(), 2);
+}
+
+#[tokio::test]
+async fn test_php_superglobal_null_coalesce_default() {
+    let fixture = TestFixture::new().await;
+    let content = "<?php\n$a = $_ENV['MISSING_A'] ?? 'x';\n$b = $_SERVER['MISSING_B'] ?? null;\n$c = $_ENV['MISSING_C'];";
+    let uri = fixture.create_file("test.php", content);
+
+    fixture
+        .state
+        .document_manager
+        .open(uri.clone(), "php".to_string(), content.to_string(), 0)
+        .await;
+
+    let diags = compute_diagnostics(&uri, &fixture.state).await;
+    for name in ["MISSING_A", "MISSING_B"] {
+        let diag = diags.iter().find(|d| d.message.contains(name)).unwrap();
+        assert_eq!(diag.severity, Some(DiagnosticSeverity::HINT));
+    }
+    assert!(diags
+        .iter()
+        .filter(|d| d.message.contains("MISSING_C"))
+        .all(|d| d.severity == Some(DiagnosticSeverity::WARNING)));
+}
+
+#[tokio::test]
+async fn test_php_superglobal_iteration_has_no_references() {
+    let fixture = TestFixture::new().await;
+    let content = "<?php\nforeach ($_ENV as $key => $value) {}\n$all = $_SERVER;\n$x = $_ENV[$key];\n['A' => $a] = $_ENV;";
+    let uri = fixture.create_file("test.php", content);
+
+    fixture
+        .state
+        .document_manager
+        .open(uri.clone(), "php".to_string(), content.to_string(), 0)
+        .await;
+
+    let diags = compute_diagnostics(&uri, &fixture.state).await;
+    assert!(diags.is_empty(), "{:?}", diags);
+}
@@ -4,8 +4,8 @@ use ecolog_lsp::server::handlers::compute_diagnostics;
 use ecolog_lsp::server::handlers::handle_completion;
 use ecolog_lsp::server::handlers::handle_hover;
 use tower_lsp::lsp_types::{
-    CompletionContext, CompletionParams, CompletionTriggerKind, HoverParams, Position,
-    TextDocumentIdentifier, TextDocumentPositionParams,
+    CompletionContext, CompletionParams, CompletionTriggerKind, DiagnosticSeverity, HoverParams,
+    Position, TextDocumentIdentifier, TextDocumentPositionParams,
 };
 
 #[tokio::test]
@@ -173,8 +173,8 @@ async fn test_py_hover_from_import() {
 
     
     
-    if hover.is_some() {
-        assert!(format!("{:?}", hover.unwrap()).contains("true"));
+    if let Some(hover) = hover {
+        assert!(format!("{:?}", hover).contains("true"));
     }
 }
 
@@ -631,3 +631,65 @@ async fn test_py_hover_config_class_getenv() {
     assert!(hover.is_some(), "Expected hover for getenv in config class");
     assert!(format!("{:?}", hover.unwrap()).contains("8080"));
 }
+
+#[tokio::test]
+async fn test_py_diagnostics_default_downgrades_to_hint() {
+    let fixture = TestFixture::new().await;
+    let content = "import os\na = os.environ.get(\"MISSING_A\", \"fallback\")\nb = os.environ[\"MISSING_B\"]\nc = os.getenv(\"MISSING_C\")\n";
+    let uri = fixture.create_file("test.py", content);
+    fixture
+        .state
+        .document_manager
+        .open(uri.clone(), "python".to_string(), content.to_string(), 0)
+        .await;
+
+    let diags = compute_diagnostics(&uri, &fixture.state).await;
+    let severities = |name: &str| {
+        diags
+            .iter()
+            .filter(|d| d.message.contains(name))
+            .map(|d| d.severity)
+            .collect::<Vec<_>>()
+    };
+
+    let a = severities("MISSING_A");
+    assert!(!a.is_empty());
+    assert!(a.iter().all(|s| *s == Some(DiagnosticSeverity::HINT)), "{:?}", diags);
+    let b = severities("MISSING_B");
+    assert!(!b.is_empty());
+    assert!(b.iter().all(|s| *s == Some(DiagnosticSeverity::WARNING)), "{:?}", diags);
+    let c = severities("MISSING_C");
+    assert!(!c.is_empty());
+    assert!(c.iter().all(|s| *s == Some(DiagnosticSeverity::WARNING)), "{:?}", diags);
+}
+
+#[tokio::test]
+async fn test_py_custom_env_accessor_from_config() {
+    let fixture = TestFixture::new().await;
+    let config = ecolog_lsp::server::config::EcologConfig::from_toml_str(
+        "[[custom_env_accessors]]\ncallee = \"get_env\"\n",
+    )
+    .unwrap();
+    fixture.state.set_env_accessors(config.custom_env_accessors);
+
+    let content = "from settings import get_env\nkey = get_env(\"API_KEY\")\n";
+    let uri = fixture.create_file("test.py", content);
+    fixture
+        .state
+        .document_manager
+        .open(uri.clone(), "python".to_string(), content.to_string(), 0)
+        .await;
+
+    let hover = handle_hover(
+        HoverParams {
+            text_document_position_params: TextDocumentPositionParams {
+                text_document: TextDocumentIdentifier { uri: uri.clone() },
+                position: Position::new(1, 17),
+            },
+            work_done_progress_params: Default::default(),
+        },
+        &fixture.state,
+    )
+    .await;
+    assert!(format!("{:?}", hover.expect("hover on get_env(\"API_KEY\")")).contains("secret_key"));
+}
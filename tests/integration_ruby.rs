@@ -4,8 +4,8 @@ use ecolog_lsp::server::handlers::compute_diagnostics;
 use ecolog_lsp::server::handlers::handle_completion;
 use ecolog_lsp::server::handlers::handle_hover;
 use tower_lsp::lsp_types::{
-    CompletionContext, CompletionParams, CompletionTriggerKind, HoverParams, Position,
-    TextDocumentIdentifier, TextDocumentPositionParams,
+    CompletionContext, CompletionParams, CompletionTriggerKind, DiagnosticSeverity, HoverParams,
+    Position, TextDocumentIdentifier, TextDocumentPositionParams,
 };
 
 #[tokio::test]
@@ -257,3 +257,43 @@ async fn test_ruby_hover_inside_method() {
     assert!(hover.is_some());
     assert!(format!("{:?}", hover.unwrap()).contains("postgres://"));
 }
+
+#[tokio::test]
+async fn test_ruby_fetch_defaults_downgrade_diagnostics() {
+    let fixture = TestFixture::new().await;
+    let content = "a = ENV['MISSING_A']\n\
+                   b = ENV.fetch('MISSING_B')\n\
+                   c = ENV.fetch('MISSING_C', 'fallback')\n\
+                   d = ENV.fetch('MISSING_D') { 'fallback' }\n\
+                   e = ENV.fetch('MISSING_E') do\n  'fallback'\nend\n\
+                   ENV.each { |key, value| puts key }\n";
+    let uri = fixture.create_file("test.rb", content);
+
+    fixture
+        .state
+        .document_manager
+        .open(uri.clone(), "ruby".to_string(), content.to_string(), 0)
+        .await;
+
+    let mut diags = compute_diagnostics(&uri, &fixture.state).await;
+    diags.retain(|d| d.message.contains("not defined"));
+    // The reference and the binding declared from it each get one
+    let mut found: Vec<_> = diags
+        .iter()
+        .map(|d| (d.range.start.line, d.severity.unwrap()))
+        .collect();
+    found.dedup();
+    assert_eq!(
+        found,
+        vec![
+            (0, DiagnosticSeverity::WARNING),
+            (1, DiagnosticSeverity::WARNING),
+            (2, DiagnosticSeverity::HINT),
+            (3, DiagnosticSeverity::HINT),
+            (4, DiagnosticSeverity::HINT),
+        ],
+        "{:?}",
+        diags
+    );
+    assert!(!diags.iter().any(|d| d.message.contains("fallback")));
+}
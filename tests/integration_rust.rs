@@ -360,8 +360,8 @@ async fn test_rust_dotenv_macro_mock() {
     )
     .await;
 
-    if hover.is_some() {
-        assert!(format!("{:?}", hover.unwrap()).contains("true"));
+    if let Some(hover) = hover {
+        assert!(format!("{:?}", hover).contains("true"));
     }
 }
 
@@ -536,3 +536,77 @@ async fn test_rust_hover_static_env_macro() {
     assert!(hover.is_some(), "Expected hover for static env! macro");
     assert!(format!("{:?}", hover.unwrap()).contains("postgres://"));//"));
 }
+
+#[tokio::test]
+async fn test_rust_build_script_rustc_env_defines_var() {
+    let fixture = TestFixture::new().await;
+    fixture.create_file(
+        "build.rs",
+        "fn main() {\n    println!(\"cargo:rustc-env=GIT_HASH={}\", hash());\n}\n",
+    );
+    fixture.index_workspace().await;
+
+    let content = "const HASH: &str = env!(\"GIT_HASH\");\nconst OTHER: &str = env!(\"NOT_SET\");\n";
+    let uri = fixture.create_file("main.rs", content);
+    fixture
+        .state
+        .document_manager
+        .open(uri.clone(), "rust".to_string(), content.to_string(), 0)
+        .await;
+
+    let diags = compute_diagnostics(&uri, &fixture.state).await;
+    assert!(!diags.is_empty());
+    assert!(diags.iter().all(|d| d.message.contains("NOT_SET")), "{:?}", diags);
+
+    // The value comes from `hash()` at build time, not the `{}` placeholder
+    let hover = handle_hover(
+        HoverParams {
+            text_document_position_params: TextDocumentPositionParams {
+                text_document: TextDocumentIdentifier { uri },
+                position: Position::new(0, 26),
+            },
+            work_done_progress_params: Default::default(),
+        },
+        &fixture.state,
+    )
+    .await;
+    assert!(!format!("{:?}", hover).contains("{}"), "{:?}", hover);
+}
+
+#[tokio::test]
+async fn test_rust_build_script_definitions_stay_in_their_crate() {
+    let fixture = TestFixture::new().await;
+    fixture.create_file(
+        "tools/build.rs",
+        "fn main() {\n    println!(\"cargo:rustc-env=TOOLS_VERSION=1.2\");\n}\n",
+    );
+    fixture.index_workspace().await;
+
+    let content = "const VERSION: &str = env!(\"TOOLS_VERSION\");\n";
+    let inside = fixture.create_file("tools/src/main.rs", content);
+    let outside = fixture.create_file("src/main.rs", content);
+    let script = "console.log(process.env.TOOLS_VERSION);\n";
+    let non_rust = fixture.create_file("tools/run.js", script);
+    for (uri, language, text) in [
+        (&inside, "rust", content),
+        (&outside, "rust", content),
+        (&non_rust, "javascript", script),
+    ] {
+        fixture
+            .state
+            .document_manager
+            .open(uri.clone(), language.to_string(), text.to_string(), 0)
+            .await;
+    }
+
+    assert!(compute_diagnostics(&inside, &fixture.state).await.is_empty());
+    for uri in [&outside, &non_rust] {
+        let diags = compute_diagnostics(uri, &fixture.state).await;
+        assert!(
+            diags.iter().any(|d| d.message.contains("TOOLS_VERSION")),
+            "{}: {:?}",
+            uri,
+            diags
+        );
+    }
+}
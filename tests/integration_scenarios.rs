@@ -261,7 +261,9 @@ async fn test_scenario_empty_value() {
 
     let h_str = format!("{:?}", hover.unwrap());
     assert!(
-        h_str.contains("Value**: ``") || h_str.contains("Value**: \"\""),
+        h_str.contains("Value**: ``")
+            || h_str.contains("Value**: \"\"")
+            || h_str.contains("Value**: *(empty)*"),
         "Expected empty value, got: {}",
         h_str
     );
@@ -311,6 +313,158 @@ async fn test_scenario_multiline_value() {
     assert!(format!("{:?}", hover.unwrap()).contains("line1"));
 }
 
+#[tokio::test]
+async fn test_scenario_multiline_value_completion_matches_hover() {
+    let fixture = TestFixture::new().await;
+    let env_path = fixture.temp_dir.join(".env");
+    {
+        let mut f = OpenOptions::new().append(true).open(env_path).unwrap();
+        writeln!(f, "MULTI=\"line1\\nline2\"").unwrap();
+    }
+
+    fixture
+        .state
+        .core
+        .refresh(abundantis::RefreshOptions::reset_all())
+        .await
+        .unwrap();
+
+    let uri = fixture.create_file("test.js", "process.env.");
+    fixture
+        .state
+        .document_manager
+        .open(
+            uri.clone(),
+            "javascript".to_string(),
+            "process.env.".to_string(),
+            0,
+        )
+        .await;
+
+    let completion = handle_completion(
+        CompletionParams {
+            text_document_position: TextDocumentPositionParams {
+                text_document: TextDocumentIdentifier { uri },
+                position: Position::new(0, 12),
+            },
+            work_done_progress_params: Default::default(),
+            partial_result_params: Default::default(),
+            context: None,
+        },
+        &fixture.state,
+    )
+    .await
+    .expect("Expected completions");
+
+    let item = completion.iter().find(|item| item.label == "MULTI").unwrap();
+    let Some(Documentation::MarkupContent(doc)) = &item.documentation else {
+        panic!("Expected markdown documentation");
+    };
+    assert!(
+        doc.value.starts_with("**Value**: \n\n```\nline1\nline2\n```"),
+        "got {}",
+        doc.value
+    );
+}
+
+#[tokio::test]
+async fn test_scenario_multiline_private_key() {
+    let fixture = TestFixture::new().await;
+    let env_path = fixture.temp_dir.join(".env");
+    {
+        let mut f = OpenOptions::new().append(true).open(&env_path).unwrap();
+        writeln!(f, "PRIVATE_KEY=\"-----BEGIN KEY-----").unwrap();
+        writeln!(f, "abc123").unwrap();
+        writeln!(f, "-----END KEY-----\"").unwrap();
+        writeln!(f, "AFTER=1").unwrap();
+    }
+
+    fixture
+        .state
+        .core
+        .refresh(abundantis::RefreshOptions::reset_all())
+        .await
+        .unwrap();
+
+    let content = "std::env::var(\"PRIVATE_KEY\")";
+    let uri = fixture.create_file("test.rs", content);
+    fixture
+        .state
+        .document_manager
+        .open(uri.clone(), "rust".to_string(), content.to_string(), 0)
+        .await;
+    let position = TextDocumentPositionParams {
+        text_document: TextDocumentIdentifier { uri },
+        position: Position::new(0, 18),
+    };
+
+    let definition = handle_definition(
+        GotoDefinitionParams {
+            text_document_position_params: position.clone(),
+            work_done_progress_params: Default::default(),
+            partial_result_params: Default::default(),
+        },
+        &fixture.state,
+    )
+    .await;
+    let Some(GotoDefinitionResponse::Scalar(location)) = definition else {
+        panic!("Expected a single definition, got {:?}", definition);
+    };
+    assert_eq!(
+        location.range,
+        Range::new(Position::new(4, 0), Position::new(4, 11))
+    );
+
+    let hover = handle_hover(
+        HoverParams {
+            text_document_position_params: position,
+            work_done_progress_params: Default::default(),
+        },
+        &fixture.state,
+    )
+    .await
+    .expect("hover");
+    let HoverContents::Markup(markup) = hover.contents else {
+        panic!("Expected markup hover");
+    };
+    assert!(
+        markup
+            .value
+            .contains("```\n-----BEGIN KEY-----\nabc123\n-----END KEY-----\n```"),
+        "{}",
+        markup.value
+    );
+
+    let env_uri = Url::from_file_path(&env_path).unwrap();
+    let symbols = handle_document_symbol(
+        DocumentSymbolParams {
+            text_document: TextDocumentIdentifier { uri: env_uri },
+            work_done_progress_params: Default::default(),
+            partial_result_params: Default::default(),
+        },
+        &fixture.state,
+    )
+    .await;
+    let Some(DocumentSymbolResponse::Nested(symbols)) = symbols else {
+        panic!("Expected document symbols, got {:?}", symbols);
+    };
+    let names: Vec<_> = symbols.iter().map(|s| s.name.as_str()).collect();
+    assert_eq!(
+        names,
+        vec!["DB_URL", "API_KEY", "DEBUG", "PORT", "PRIVATE_KEY", "AFTER"]
+    );
+    let key = &symbols[4];
+    assert_eq!(
+        key.selection_range,
+        Range::new(Position::new(4, 0), Position::new(4, 11))
+    );
+    assert_eq!(
+        key.range,
+        Range::new(Position::new(4, 0), Position::new(6, 18))
+    );
+    assert!(key.detail.is_none());
+}
+
 #[tokio::test]
 async fn test_scenario_forbidden_whitespace() {
     let fixture = TestFixture::new().await;
@@ -500,6 +654,112 @@ async fn test_active_file_selection() {
     assert!(format!("{:?}", hover.unwrap()).contains("PROD"));
 }
 
+#[tokio::test]
+async fn test_env_include_resolves_for_scoped_file() {
+    let fixture = TestFixture::new().await;
+    std::fs::write(fixture.temp_dir.join("a.env"), "# include: b.env\nA_VAR=a\n").unwrap();
+    std::fs::write(fixture.temp_dir.join("b.env"), "SHARED_VAR=from_b\n").unwrap();
+    fixture
+        .state
+        .core
+        .refresh(abundantis::RefreshOptions::reset_all())
+        .await
+        .unwrap();
+    fixture.state.core.set_active_files(&["a.env"]);
+
+    let content = "process.env.SHARED_VAR;";
+    let uri = fixture.create_file("test.js", content);
+    fixture
+        .state
+        .document_manager
+        .open(uri.clone(), "javascript".to_string(), content.to_string(), 0)
+        .await;
+    let hover = || {
+        handle_hover(
+            HoverParams {
+                text_document_position_params: TextDocumentPositionParams {
+                    text_document: TextDocumentIdentifier { uri: uri.clone() },
+                    position: Position::new(0, 15),
+                },
+                work_done_progress_params: Default::default(),
+            },
+            &fixture.state,
+        )
+    };
+    let undefined = |diagnostics: Vec<Diagnostic>| {
+        diagnostics
+            .iter()
+            .any(|d| d.message.contains("'SHARED_VAR' is not defined"))
+    };
+
+    // Includes are opt-in
+    assert!(hover().await.is_none());
+    assert!(undefined(compute_diagnostics(&uri, &fixture.state).await));
+
+    let config_arc = fixture.state.config.get_config();
+    let mut config = config_arc.write().await.clone();
+    config.workspace.follow_env_includes = true;
+    fixture.state.config.update(config).await;
+
+    let hover = format!("{:?}", hover().await.expect("hover"));
+    assert!(hover.contains("from_b"), "{}", hover);
+    assert!(hover.contains("b.env"), "{}", hover);
+    assert!(!undefined(compute_diagnostics(&uri, &fixture.state).await));
+}
+
+#[tokio::test]
+async fn test_env_include_chain_is_reread_only_when_included_file_changes() {
+    let fixture = TestFixture::with_files(&[
+        (".env", "# include: shared.env\nLOCAL_VAR=1\n"),
+        ("shared.env", "SHARED_VAR=first\n"),
+    ])
+    .await;
+    let env_files = {
+        let config_arc = fixture.state.config.get_config();
+        let mut config = config_arc.write().await.clone();
+        config.workspace.follow_env_includes = true;
+        let env_files = config.workspace.env_files.clone();
+        fixture.state.config.update(config).await;
+        env_files
+    };
+    fixture.state.indexer.set_follow_env_includes(true);
+    fixture.index_workspace().await;
+
+    let content = "process.env.SHARED_VAR;";
+    let uri = fixture.create_file("test.js", content);
+    fixture
+        .state
+        .document_manager
+        .open(uri.clone(), "javascript".to_string(), content.to_string(), 0)
+        .await;
+    let hover = || async {
+        let hover = handle_hover(
+            HoverParams {
+                text_document_position_params: TextDocumentPositionParams {
+                    text_document: TextDocumentIdentifier { uri: uri.clone() },
+                    position: Position::new(0, 15),
+                },
+                work_done_progress_params: Default::default(),
+            },
+            &fixture.state,
+        )
+        .await;
+        format!("{:?}", hover.expect("hover"))
+    };
+    assert!(hover().await.contains("first"));
+
+    // The chain indexed with `.env` is reused instead of read again
+    let shared = fixture.temp_dir.join("shared.env");
+    std::fs::write(&shared, "SHARED_VAR=second\n").unwrap();
+    assert!(hover().await.contains("first"));
+
+    // A change to the included file re-indexes `.env` through the dependency graph
+    let shared_uri =
+        tower_lsp::lsp_types::Url::from_file_path(shared.canonicalize().unwrap()).unwrap();
+    fixture.state.indexer.on_file_changed(&shared_uri, &env_files).await;
+    assert!(hover().await.contains("second"));
+}
+
 #[tokio::test]
 async fn test_list_variables_command() {
     let fixture = TestFixture::new().await;
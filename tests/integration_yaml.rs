@@ -0,0 +1,159 @@
+mod common;
+use common::TestFixture;
+use ecolog_lsp::server::handlers::{compute_diagnostics, handle_hover};
+use tower_lsp::lsp_types::{
+    DiagnosticSeverity, HoverParams, Position, TextDocumentIdentifier, TextDocumentPositionParams,
+};
+
+const COMPOSE: &str = "services:
+  db:
+    image: postgres:${PG_VERSION:-16}
+    environment:
+      POSTGRES_PASSWORD: ${POSTGRES_PASSWORD}
+      REPLICA_HOST: ${REPLICA_HOST}
+";
+
+#[tokio::test]
+async fn test_yaml_compose_hover_and_undefined_diagnostics() {
+    let fixture = TestFixture::new().await;
+    std::fs::write(fixture.temp_dir.join(".env"), "POSTGRES_PASSWORD=hunter2\n").unwrap();
+    fixture
+        .state
+        .core
+        .refresh(abundantis::RefreshOptions::reset_all())
+        .await
+        .unwrap();
+    let uri = fixture.create_file("docker-compose.yml", COMPOSE);
+    fixture
+        .state
+        .document_manager
+        .open(uri.clone(), "yaml".to_string(), COMPOSE.to_string(), 0)
+        .await;
+    assert!(!fixture.state.document_manager.is_passive(&uri));
+
+    let hover = handle_hover(
+        HoverParams {
+            text_document_position_params: TextDocumentPositionParams {
+                text_document: TextDocumentIdentifier { uri: uri.clone() },
+                position: Position::new(4, 32),
+            },
+            work_done_progress_params: Default::default(),
+        },
+        &fixture.state,
+    )
+    .await;
+    let hover = format!("{:?}", hover.expect("Expected hover for ${POSTGRES_PASSWORD}"));
+    assert!(hover.contains("hunter2"), "{}", hover);
+
+    // PG_VERSION has a default, so only REPLICA_HOST is reported
+    let diags = compute_diagnostics(&uri, &fixture.state).await;
+    let undefined: Vec<_> = diags
+        .iter()
+        .filter(|d| d.severity == Some(DiagnosticSeverity::WARNING))
+        .collect();
+    assert_eq!(undefined.len(), 1, "{:?}", diags);
+    assert!(undefined[0].message.contains("REPLICA_HOST"));
+    assert_eq!(undefined[0].range.start.line, 5);
+}
+
+#[tokio::test]
+async fn test_yaml_references_are_indexed() {
+    let fixture = TestFixture::with_files(&[("deploy/compose.yaml", COMPOSE)]).await;
+    fixture.index_workspace().await;
+
+    let files = fixture.state.workspace_index.files_for_env_var("REPLICA_HOST");
+    assert_eq!(files.len(), 1);
+    assert!(files[0].path().ends_with("deploy/compose.yaml"));
+}
+
+#[tokio::test]
+async fn test_non_compose_yaml_is_not_scanned() {
+    let workflow = "steps:\n  - run: echo $GITHUB_SHA\n";
+    let fixture = TestFixture::with_files(&[(".github/workflows/ci.yml", workflow)]).await;
+    fixture.index_workspace().await;
+    assert!(fixture.state.workspace_index.files_for_env_var("GITHUB_SHA").is_empty());
+
+    let uri = fixture.create_file("config.yaml", workflow);
+    fixture
+        .state
+        .document_manager
+        .open(uri.clone(), "yaml".to_string(), workflow.to_string(), 0)
+        .await;
+    assert!(compute_diagnostics(&uri, &fixture.state).await.is_empty());
+}
+
+#[tokio::test]
+async fn test_scan_all_config_files_covers_other_yaml_and_json() {
+    let workflow = "steps:\n  - run: echo $GITHUB_SHA\n";
+    let settings = "{ \"$schema\": \"./schema.json\", \"db\": \"${SETTINGS_DB}\" }\n";
+    let fixture = TestFixture::with_files(&[
+        (".github/workflows/ci.yml", workflow),
+        ("appsettings.json", settings),
+    ])
+    .await;
+    fixture.state.set_scan_all_config_files(true);
+    fixture.index_workspace().await;
+    assert_eq!(fixture.state.workspace_index.files_for_env_var("GITHUB_SHA").len(), 1);
+    assert_eq!(fixture.state.workspace_index.files_for_env_var("SETTINGS_DB").len(), 1);
+    assert!(fixture.state.workspace_index.files_for_env_var("schema").is_empty());
+
+    let uri = fixture.create_file("config.yaml", workflow);
+    fixture
+        .state
+        .document_manager
+        .open(uri.clone(), "yaml".to_string(), workflow.to_string(), 0)
+        .await;
+    assert!(!fixture.state.document_manager.is_passive(&uri));
+    let diags = compute_diagnostics(&uri, &fixture.state).await;
+    assert!(diags.iter().any(|d| d.message.contains("GITHUB_SHA")), "{:?}", diags);
+}
+
+#[tokio::test]
+async fn test_compose_env_file_defines_service_variables() {
+    let compose = "services:\n  api:\n    env_file:\n      - ./api.env\n    environment:\n      \
+                   TOKEN: ${API_TOKEN}\n";
+    let fixture = TestFixture::with_files(&[
+        ("api.env", "API_TOKEN=from-env-file\n"),
+        ("docker-compose.yml", compose),
+    ])
+    .await;
+    fixture.index_workspace().await;
+    let uri = fixture.create_file("docker-compose.yml", compose);
+    fixture
+        .state
+        .document_manager
+        .open(uri.clone(), "yaml".to_string(), compose.to_string(), 0)
+        .await;
+
+    let hover = handle_hover(
+        HoverParams {
+            text_document_position_params: TextDocumentPositionParams {
+                text_document: TextDocumentIdentifier { uri: uri.clone() },
+                position: Position::new(5, 17),
+            },
+            work_done_progress_params: Default::default(),
+        },
+        &fixture.state,
+    )
+    .await;
+    let hover = format!("{:?}", hover.expect("Expected hover for ${API_TOKEN}"));
+    assert!(hover.contains("from-env-file"), "{}", hover);
+
+    let diags = compute_diagnostics(&uri, &fixture.state).await;
+    assert!(diags.is_empty(), "API_TOKEN is defined by env_file: {:?}", diags);
+
+    // The keys only reach the Compose file, not the rest of the workspace
+    let code = "process.env.API_TOKEN;";
+    let code_uri = fixture.create_file("app.js", code);
+    fixture
+        .state
+        .document_manager
+        .open(code_uri.clone(), "javascript".to_string(), code.to_string(), 0)
+        .await;
+    let diags = compute_diagnostics(&code_uri, &fixture.state).await;
+    assert!(
+        diags.iter().any(|d| d.message.contains("'API_TOKEN' is not defined")),
+        "{:?}",
+        diags
+    );
+}
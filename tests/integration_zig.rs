@@ -2,7 +2,7 @@ mod common;
 use common::TestFixture;
 use ecolog_lsp::server::handlers::{compute_diagnostics, handle_hover};
 use tower_lsp::lsp_types::{
-    HoverParams, Position, TextDocumentIdentifier, TextDocumentPositionParams,
+    HoverParams, Position, Range, TextDocumentIdentifier, TextDocumentPositionParams,
 };
 
 #[tokio::test]
@@ -121,3 +121,70 @@ async fn test_zig_binding() {
     assert!(hover.is_some(), "Expected hover on 'db' binding");
     assert!(format!("{:?}", hover.unwrap()).contains("postgres://"));
 }
+
+#[tokio::test]
+async fn test_zig_hover_get_env_var_owned() {
+    let fixture = TestFixture::new().await;
+    let content = r#"const url = try std.process.getEnvVarOwned(allocator, "DB_URL");"#;
+    let uri = fixture.create_file("main.zig", content);
+
+    fixture
+        .state
+        .document_manager
+        .open(uri.clone(), "zig".to_string(), content.to_string(), 0)
+        .await;
+
+    let hover = handle_hover(
+        HoverParams {
+            text_document_position_params: TextDocumentPositionParams {
+                text_document: TextDocumentIdentifier { uri },
+                position: Position::new(0, 58),
+            },
+            work_done_progress_params: Default::default(),
+        },
+        &fixture.state,
+    )
+    .await;
+
+    assert!(hover.is_some(), "Expected hover for std.process.getEnvVarOwned");
+    assert!(format!("{:?}", hover.unwrap()).contains("postgres://"));
+}
+
+#[tokio::test]
+async fn test_zig_key_argument_ranges() {
+    let fixture = TestFixture::new().await;
+    let content = "const a = try std.process.getEnvVarOwned(allocator, \"API_KEY\");\n\
+                   const b = std.os.getenv(\"PORT\");\n";
+    let uri = fixture.create_file("main.zig", content);
+
+    fixture
+        .state
+        .document_manager
+        .open(uri.clone(), "zig".to_string(), content.to_string(), 0)
+        .await;
+
+    let graph = fixture.state.document_manager.get_binding_graph(&uri).unwrap();
+    let mut refs: Vec<_> = graph
+        .direct_references()
+        .iter()
+        .map(|r| (r.name.to_string(), r.name_range))
+        .collect();
+    refs.sort_by_key(|(_, range)| range.start.line);
+
+    assert_eq!(
+        refs,
+        vec![
+            (
+                "API_KEY".to_string(),
+                Range::new(Position::new(0, 53), Position::new(0, 60))
+            ),
+            (
+                "PORT".to_string(),
+                Range::new(Position::new(1, 25), Position::new(1, 29))
+            ),
+        ]
+    );
+
+    let diags = compute_diagnostics(&uri, &fixture.state).await;
+    assert!(diags.is_empty(), "{:?}", diags);
+}
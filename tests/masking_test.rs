@@ -0,0 +1,243 @@
+//! Masking is applied the same way on every path that displays a value.
+
+mod common;
+
+use common::TestFixture;
+use ecolog_lsp::server::config::MaskMode;
+use ecolog_lsp::server::handlers::{
+    handle_completion, handle_execute_command, handle_hover, handle_inlay_hints,
+};
+use serde_json::json;
+use tower_lsp::lsp_types::{
+    CompletionContext, CompletionParams, CompletionTriggerKind, Documentation,
+    ExecuteCommandParams, HoverContents, HoverParams, InlayHintLabel, InlayHintParams, Position,
+    Range, TextDocumentIdentifier, TextDocumentPositionParams,
+};
+
+const MASKED: &str = "sec****key";
+
+async fn enable_masking(fixture: &TestFixture, mode: MaskMode) {
+    let config_arc = fixture.state.config.get_config();
+    let mut config = config_arc.write().await.clone();
+    config.masking.enabled = true;
+    config.masking.mode = mode;
+    config.features.inlay_hints = true;
+    fixture.state.config.update(config).await;
+}
+
+#[tokio::test]
+async fn test_all_display_paths_mask_identically() {
+    let fixture = TestFixture::new().await;
+    enable_masking(&fixture, MaskMode::Partial).await;
+
+    let content = "const key = process.env.API_KEY;\nprocess.env.";
+    let uri = fixture.create_file("test.js", content);
+    fixture
+        .state
+        .document_manager
+        .open(uri.clone(), "javascript".to_string(), content.to_string(), 0)
+        .await;
+
+    let hover = handle_hover(
+        HoverParams {
+            text_document_position_params: TextDocumentPositionParams {
+                text_document: TextDocumentIdentifier { uri: uri.clone() },
+                position: Position::new(0, 26),
+            },
+            work_done_progress_params: Default::default(),
+        },
+        &fixture.state,
+    )
+    .await
+    .expect("hover");
+    let HoverContents::Markup(markup) = hover.contents else {
+        panic!("Expected markup hover");
+    };
+    assert!(markup.value.contains(&format!("`{}`", MASKED)), "{}", markup.value);
+
+    let completion = handle_completion(
+        CompletionParams {
+            text_document_position: TextDocumentPositionParams {
+                text_document: TextDocumentIdentifier { uri: uri.clone() },
+                position: Position::new(1, 12),
+            },
+            work_done_progress_params: Default::default(),
+            partial_result_params: Default::default(),
+            context: Some(CompletionContext {
+                trigger_kind: CompletionTriggerKind::TRIGGER_CHARACTER,
+                trigger_character: Some(".".to_string()),
+            }),
+        },
+        &fixture.state,
+    )
+    .await
+    .expect("completion");
+    let item = completion.iter().find(|i| i.label == "API_KEY").unwrap();
+    let Some(Documentation::MarkupContent(doc)) = &item.documentation else {
+        panic!("Expected markup documentation");
+    };
+    assert!(doc.value.contains(&format!("`{}`", MASKED)), "{}", doc.value);
+
+    let hints = handle_inlay_hints(
+        InlayHintParams {
+            text_document: TextDocumentIdentifier { uri: uri.clone() },
+            range: Range::new(Position::new(0, 0), Position::new(2, 0)),
+            work_done_progress_params: Default::default(),
+        },
+        &fixture.state,
+    )
+    .await
+    .expect("inlay hints");
+    assert!(hints.iter().any(|hint| matches!(
+        &hint.label,
+        InlayHintLabel::String(label) if label.contains(MASKED)
+    )));
+
+    let command = |name: &str, arguments| ExecuteCommandParams {
+        command: name.to_string(),
+        arguments,
+        work_done_progress_params: Default::default(),
+    };
+    let get = handle_execute_command(
+        command("ecolog.variable.get", vec![json!("API_KEY")]),
+        &fixture.state,
+    )
+    .await
+    .unwrap();
+    assert_eq!(get["value"], MASKED);
+
+    let list = handle_execute_command(command("ecolog.listEnvVariables", vec![]), &fixture.state)
+        .await
+        .unwrap();
+    let listed = list["variables"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .find(|v| v["name"] == "API_KEY")
+        .unwrap();
+    assert_eq!(listed["value"], MASKED);
+}
+
+#[tokio::test]
+async fn test_masking_disabled_shows_values() {
+    let fixture = TestFixture::new().await;
+
+    let params = ExecuteCommandParams {
+        command: "ecolog.variable.get".to_string(),
+        arguments: vec![json!("API_KEY")],
+        work_done_progress_params: Default::default(),
+    };
+    let result = handle_execute_command(params, &fixture.state).await.unwrap();
+    assert_eq!(result["value"], "secret_key");
+}
+
+#[tokio::test]
+async fn test_hover_type_badge_uses_unmasked_value() {
+    let fixture = TestFixture::new().await;
+    enable_masking(&fixture, MaskMode::Full).await;
+
+    let content = "const port = process.env.PORT;";
+    let uri = fixture.create_file("test.js", content);
+    fixture
+        .state
+        .document_manager
+        .open(uri.clone(), "javascript".to_string(), content.to_string(), 0)
+        .await;
+
+    let hover = handle_hover(
+        HoverParams {
+            text_document_position_params: TextDocumentPositionParams {
+                text_document: TextDocumentIdentifier { uri },
+                position: Position::new(0, 27),
+            },
+            work_done_progress_params: Default::default(),
+        },
+        &fixture.state,
+    )
+    .await
+    .expect("hover");
+    let HoverContents::Markup(markup) = hover.contents else {
+        panic!("Expected markup hover");
+    };
+    assert!(markup.value.contains("`****`"), "{}", markup.value);
+    assert!(markup.value.contains("*(number)*"), "{}", markup.value);
+}
+
+#[tokio::test]
+async fn test_mask_exclude_globs_unmask_matching_files() {
+    let fixture = TestFixture::new().await;
+    let config_arc = fixture.state.config.get_config();
+    let mut config = config_arc.write().await.clone();
+    config.masking.enabled = true;
+    config.masking.mask_exclude_globs = vec!["tests/**".to_string()];
+    fixture.state.config.update(config).await;
+
+    let content = "const key = process.env.API_KEY;";
+    let cases = [("tests/app.test.js", "`secret_key`"), ("src/app.js", "`**********`")];
+    for (file, expected) in cases {
+        let uri = fixture.create_file(file, content);
+        fixture
+            .state
+            .document_manager
+            .open(uri.clone(), "javascript".to_string(), content.to_string(), 0)
+            .await;
+
+        let hover = handle_hover(
+            HoverParams {
+                text_document_position_params: TextDocumentPositionParams {
+                    text_document: TextDocumentIdentifier { uri },
+                    position: Position::new(0, 26),
+                },
+                work_done_progress_params: Default::default(),
+            },
+            &fixture.state,
+        )
+        .await
+        .expect("hover");
+        let HoverContents::Markup(markup) = hover.contents else {
+            panic!("Expected markup hover");
+        };
+        assert!(markup.value.contains(expected), "{}: {}", file, markup.value);
+    }
+}
+
+#[tokio::test]
+async fn test_key_patterns_force_and_skip_masking() {
+    let fixture = TestFixture::new().await;
+    std::fs::write(
+        fixture.temp_dir.join(".env"),
+        "DB_PASSWORD=hunter22\nPUBLIC_URL=https://example.com\nPORT=8080\n",
+    )
+    .unwrap();
+    fixture
+        .state
+        .core
+        .refresh(abundantis::RefreshOptions::reset_all())
+        .await
+        .unwrap();
+
+    let get = |name: &str| ExecuteCommandParams {
+        command: "ecolog.variable.get".to_string(),
+        arguments: vec![json!(name)],
+        work_done_progress_params: Default::default(),
+    };
+    let value = |result: Option<serde_json::Value>| result.unwrap()["value"].clone();
+
+    // Masking is off, but the password still matches a forced pattern
+    let config_arc = fixture.state.config.get_config();
+    let mut config = config_arc.write().await.clone();
+    config.masking.force_mask_key_patterns = vec!["*PASSWORD*".to_string()];
+    config.masking.never_mask_key_patterns = vec!["PUBLIC_*".to_string()];
+    fixture.state.config.update(config).await;
+
+    let password = handle_execute_command(get("DB_PASSWORD"), &fixture.state).await;
+    assert_eq!(value(password), "********");
+    let port = handle_execute_command(get("PORT"), &fixture.state).await;
+    assert_eq!(value(port), "8080");
+
+    enable_masking(&fixture, MaskMode::Full).await;
+    let url = handle_execute_command(get("PUBLIC_URL"), &fixture.state).await;
+    assert_eq!(value(url), "https://example.com");
+    let port = handle_execute_command(get("PORT"), &fixture.state).await;
+    assert_eq!(value(port), "****");
+}
@@ -3,7 +3,10 @@
 mod common;
 
 use common::TestFixture;
-use ecolog_lsp::server::handlers::{handle_references, handle_workspace_symbol};
+use ecolog_lsp::server::cancellation::CancellationToken;
+use ecolog_lsp::server::handlers::{
+    handle_references, handle_references_with_cancellation, handle_workspace_symbol,
+};
 use tower_lsp::lsp_types::{
     Position, ReferenceContext, ReferenceParams, TextDocumentIdentifier,
     TextDocumentPositionParams, WorkspaceSymbolParams, PartialResultParams, WorkDoneProgressParams,
@@ -218,6 +221,38 @@ async fn test_workspace_symbol_no_match() {
     }
 }
 
+#[tokio::test]
+async fn test_workspace_symbol_configured_kind() {
+    use ecolog_lsp::server::config::EnvSymbolKind;
+    use tower_lsp::lsp_types::SymbolKind;
+
+    let fixture = TestFixture::new().await;
+    fixture.create_file("app.js", "const db = process.env.DB_URL;");
+    fixture.index_workspace().await;
+
+    let params = || WorkspaceSymbolParams {
+        query: "DB_URL".to_string(),
+        work_done_progress_params: WorkDoneProgressParams::default(),
+        partial_result_params: PartialResultParams::default(),
+    };
+
+    let symbols = handle_workspace_symbol(params(), &fixture.state)
+        .await
+        .expect("Should find DB_URL");
+    assert!(symbols.iter().all(|s| s.kind == SymbolKind::CONSTANT));
+
+    let config_arc = fixture.state.config.get_config();
+    let mut config = config_arc.write().await.clone();
+    config.features.symbol_kind = EnvSymbolKind::Variable;
+    fixture.state.config.update(config).await;
+
+    let symbols = handle_workspace_symbol(params(), &fixture.state)
+        .await
+        .expect("Should find DB_URL");
+    assert!(!symbols.is_empty());
+    assert!(symbols.iter().all(|s| s.kind == SymbolKind::VARIABLE));
+}
+
 #[tokio::test]
 async fn test_references_usage_tracking() {
     let fixture = TestFixture::new().await;
@@ -245,3 +280,146 @@ async fn test_references_usage_tracking() {
 
     assert!(result.is_some(), "Should find references including usages");
 }
+
+#[tokio::test]
+async fn test_references_custom_detector() {
+    use ecolog_lsp::analysis::CustomDetector;
+
+    let fixture = TestFixture::new().await;
+    fixture.state.set_detectors(vec![CustomDetector {
+        files: vec!["*.build.cfg".into()],
+        object_paths: vec!["ctx.env".into()],
+        calls: vec!["getEnv".into()],
+    }]);
+
+    let cfg_content = "publicPath: ctx.env.API_KEY\ndatabase: getEnv('DB_URL')\n";
+    let cfg_uri = fixture.create_file("app.build.cfg", cfg_content);
+    let js_content = "const key = process.env.API_KEY;";
+    let js_uri = fixture.create_file("test.js", js_content);
+
+    fixture.index_workspace().await;
+
+    fixture
+        .state
+        .document_manager
+        .open(js_uri.clone(), "javascript".into(), js_content.into(), 1)
+        .await;
+
+    let result = handle_references(make_reference_params(js_uri, 0, 25, false), &fixture.state)
+        .await
+        .expect("Should find references for API_KEY");
+    let in_cfg: Vec<_> = result.iter().filter(|loc| loc.uri == cfg_uri).collect();
+    assert_eq!(in_cfg.len(), 1, "Expected the detector reference, got {:?}", result);
+    assert_eq!(in_cfg[0].range.start, Position::new(0, 20));
+
+    fixture
+        .state
+        .document_manager
+        .open(cfg_uri.clone(), "plaintext".into(), cfg_content.into(), 1)
+        .await;
+    assert!(!fixture.state.document_manager.is_passive(&cfg_uri));
+
+    let result = handle_references(make_reference_params(cfg_uri.clone(), 1, 19, false), &fixture.state)
+        .await
+        .expect("Should find references for DB_URL from the build config");
+    assert!(result.iter().any(|loc| loc.uri == cfg_uri && loc.range.start == Position::new(1, 18)));
+}
+
+#[tokio::test]
+async fn test_references_include_binding_usages() {
+    let fixture = TestFixture::new().await;
+    let content = "const { DB_URL: a } = process.env;\nconsole.log(a);";
+    let uri = fixture.create_file("test.js", content);
+    fixture.index_workspace().await;
+    fixture
+        .state
+        .document_manager
+        .open(uri.clone(), "javascript".into(), content.into(), 1)
+        .await;
+
+    let usage = tower_lsp::lsp_types::Range::new(Position::new(1, 12), Position::new(1, 13));
+    let locations = handle_references(make_reference_params(uri.clone(), 0, 10, false), &fixture.state)
+        .await
+        .expect("references");
+    assert!(!locations.iter().any(|l| l.range == usage), "{:?}", locations);
+
+    let config_arc = fixture.state.config.get_config();
+    let mut config = config_arc.write().await.clone();
+    config.references.include_binding_usages = true;
+    fixture.state.config.update(config).await;
+
+    let locations = handle_references(make_reference_params(uri.clone(), 0, 10, false), &fixture.state)
+        .await
+        .expect("references");
+    assert!(
+        locations.iter().any(|l| l.uri == uri && l.range == usage),
+        "{:?}",
+        locations
+    );
+}
+
+#[tokio::test]
+async fn test_references_from_env_file_key() {
+    let fixture = TestFixture::new().await;
+    let js_uri = fixture.create_file("app.js", "const db = process.env.DB_URL;");
+    let py_uri = fixture.create_file("worker.py", "import os\nurl = os.environ['DB_URL']\n");
+    let env_uri =
+        tower_lsp::lsp_types::Url::from_file_path(fixture.temp_dir.join(".env")).unwrap();
+
+    fixture.index_workspace().await;
+
+    let params = make_reference_params(env_uri.clone(), 0, 2, false);
+    let result = handle_references(params, &fixture.state)
+        .await
+        .expect("Should find code references for the env file key");
+    let mut uris: Vec<_> = result.iter().map(|loc| loc.uri.clone()).collect();
+    uris.sort();
+    let mut expected = vec![js_uri.clone(), py_uri];
+    expected.sort();
+    assert_eq!(uris, expected);
+    let js_location = result.iter().find(|loc| loc.uri == js_uri).unwrap();
+    assert_eq!(js_location.range.start, Position::new(0, 23));
+
+    let with_declaration =
+        handle_references(make_reference_params(env_uri.clone(), 0, 2, true), &fixture.state)
+            .await
+            .unwrap();
+    assert_eq!(with_declaration.len(), 3);
+    assert!(with_declaration
+        .iter()
+        .any(|loc| loc.uri == env_uri && loc.range.start == Position::new(0, 0)));
+}
+
+#[tokio::test]
+async fn test_references_cancelled_mid_flight_stops_early() {
+    let fixture = TestFixture::new().await;
+    let content = "const db = process.env.DB_URL;";
+    let uri = fixture.create_file("open.js", content);
+    for i in 0..20 {
+        fixture.create_file(&format!("closed_{}.js", i), content);
+    }
+    fixture.index_workspace().await;
+    fixture
+        .state
+        .document_manager
+        .open(uri.clone(), "javascript".into(), content.into(), 1)
+        .await;
+
+    let all = handle_references(make_reference_params(uri.clone(), 0, 23, false), &fixture.state)
+        .await
+        .unwrap();
+    assert_eq!(all.len(), 21);
+
+    // Closed files are read from disk, so the handler yields on the first
+    // one and the token is cancelled before it reaches the next.
+    let cancel = CancellationToken::new();
+    let params = make_reference_params(uri, 0, 23, false);
+    let (result, _) = tokio::join!(
+        handle_references_with_cancellation(params, &fixture.state, &cancel),
+        async {
+            tokio::task::yield_now().await;
+            cancel.cancel();
+        }
+    );
+    assert!(result.is_none(), "cancelled request returned {:?}", result);
+}
@@ -3,10 +3,11 @@
 mod common;
 
 use common::TestFixture;
+use ecolog_lsp::server::cancellation::CancellationToken;
 use ecolog_lsp::server::handlers::{handle_references, handle_rename, handle_prepare_rename};
 use tower_lsp::lsp_types::{
-    Position, ReferenceContext, ReferenceParams, RenameParams, TextDocumentIdentifier,
-    TextDocumentPositionParams,
+    Position, PrepareRenameResponse, ReferenceContext, ReferenceParams, RenameParams,
+    TextDocumentIdentifier, TextDocumentPositionParams,
 };
 
 
@@ -443,7 +444,7 @@ async fn test_rename_from_env_file_updates_code_files() {
         let js_edits = &changes[&js_uri];
         
         assert!(
-            js_edits.len() >= 1,
+            !js_edits.is_empty(),
             "JS file should have at least 1 edit"
         );
     }
@@ -578,3 +579,616 @@ async fn test_rename_does_not_affect_similar_names() {
         
     }
 }
+
+fn rename_params(uri: tower_lsp::lsp_types::Url, new_name: &str) -> RenameParams {
+    RenameParams {
+        text_document_position: TextDocumentPositionParams {
+            text_document: TextDocumentIdentifier { uri },
+            position: Position {
+                line: 0,
+                character: 24,
+            },
+        },
+        new_name: new_name.to_string(),
+        work_done_progress_params: Default::default(),
+    }
+}
+
+#[tokio::test]
+async fn test_concurrent_renames_produce_consistent_edits() {
+    let fixture = TestFixture::new().await;
+
+    let content = "const url = process.env.DB_URL;\nconst x = process.env.DB_URL;";
+    let uri = fixture.create_file("test.js", content);
+    fixture.create_file("other.js", "const y = process.env.DB_URL;");
+
+    fixture.index_workspace().await;
+
+    fixture
+        .state
+        .document_manager
+        .open(uri.clone(), "javascript".to_string(), content.to_string(), 1)
+        .await;
+
+    let (first, second) = tokio::join!(
+        handle_rename(rename_params(uri.clone(), "DATABASE_URL"), &fixture.state),
+        handle_rename(rename_params(uri.clone(), "DB_CONNECTION"), &fixture.state),
+    );
+
+    let first = first.and_then(|e| e.changes).expect("first rename edits");
+    let second = second.and_then(|e| e.changes).expect("second rename edits");
+
+    // Both renames saw the same snapshot: same files, same ranges
+    assert_eq!(first.len(), second.len());
+    for (file, edits) in &first {
+        let mut ranges: Vec<_> = edits.iter().map(|e| e.range).collect();
+        let mut other: Vec<_> = second[file].iter().map(|e| e.range).collect();
+        ranges.sort_by_key(|r| (r.start.line, r.start.character));
+        other.sort_by_key(|r| (r.start.line, r.start.character));
+        assert_eq!(ranges, other, "edit ranges differ for {}", file);
+
+        // No overlapping edits within a file
+        for pair in ranges.windows(2) {
+            assert!(
+                (pair[0].end.line, pair[0].end.character)
+                    <= (pair[1].start.line, pair[1].start.character),
+                "overlapping edits in {}",
+                file
+            );
+        }
+        assert!(edits.iter().all(|e| e.new_text == "DATABASE_URL"));
+        assert!(second[file].iter().all(|e| e.new_text == "DB_CONNECTION"));
+    }
+}
+
+#[tokio::test]
+async fn test_rename_rejected_while_another_in_flight() {
+    let fixture = TestFixture::new().await;
+    {
+        let config_arc = fixture.state.config.get_config();
+        let mut config = config_arc.write().await.clone();
+        config.rename.concurrency = ecolog_lsp::server::config::RenameConcurrency::Reject;
+        fixture.state.config.update(config).await;
+    }
+
+    let content = "const url = process.env.DB_URL;";
+    let uri = fixture.create_file("test.js", content);
+    fixture.index_workspace().await;
+    fixture
+        .state
+        .document_manager
+        .open(uri.clone(), "javascript".to_string(), content.to_string(), 1)
+        .await;
+
+    let in_flight = fixture.state.rename_lock.lock().await;
+    let rejected = handle_rename(rename_params(uri.clone(), "DATABASE_URL"), &fixture.state).await;
+    assert!(rejected.is_none(), "Overlapping rename should be rejected");
+
+    drop(in_flight);
+    let accepted = handle_rename(rename_params(uri, "DATABASE_URL"), &fixture.state).await;
+    assert!(accepted.is_some(), "Rename should run once the lock is free");
+}
+
+#[tokio::test]
+async fn test_find_references_declaration_prefers_nearest_env_file() {
+    let fixture = TestFixture::new().await;
+
+    let nested_env = fixture.create_file("services/api/.env", "# api service\nPORT=9090\n");
+    let content = "const port = process.env.PORT;";
+    let uri = fixture.create_file("services/api/handler.ts", content);
+    let root_uri = fixture.create_file("root.ts", content);
+
+    fixture.index_workspace().await;
+
+    let declaration_for = |file_uri: tower_lsp::lsp_types::Url| ReferenceParams {
+        text_document_position: TextDocumentPositionParams {
+            text_document: TextDocumentIdentifier { uri: file_uri },
+            position: Position::new(0, 26),
+        },
+        work_done_progress_params: Default::default(),
+        partial_result_params: Default::default(),
+        context: ReferenceContext {
+            include_declaration: true,
+        },
+    };
+
+    fixture
+        .state
+        .document_manager
+        .open(uri.clone(), "typescript".to_string(), content.to_string(), 1)
+        .await;
+    let locations = handle_references(declaration_for(uri), &fixture.state)
+        .await
+        .expect("Expected references for PORT");
+
+    let nested = locations
+        .iter()
+        .find(|loc| loc.uri == nested_env)
+        .expect("Nested .env should be the definition target");
+    assert_eq!(nested.range.start, Position::new(1, 0));
+    assert!(
+        !locations.iter().any(|loc| loc.uri.path().ends_with("/.env") && loc.uri != nested_env),
+        "Root .env should not be used when a nearer one defines PORT"
+    );
+
+    fixture
+        .state
+        .document_manager
+        .open(root_uri.clone(), "typescript".to_string(), content.to_string(), 1)
+        .await;
+    let locations = handle_references(declaration_for(root_uri), &fixture.state)
+        .await
+        .expect("Expected references for PORT");
+
+    assert!(
+        locations.iter().any(|loc| loc.uri.path() == fixture.temp_dir.join(".env").to_str().unwrap()),
+        "Root file should resolve to the root .env"
+    );
+}
+
+#[tokio::test]
+async fn test_rename_stays_within_package() {
+    let content = "const port = process.env.PORT;";
+    let fixture = TestFixture::with_files(&[
+        ("package.json", r#"{ "workspaces": ["packages/*"] }"#),
+        ("packages/a/package.json", r#"{ "name": "a" }"#),
+        ("packages/a/.env", "PORT=3000\n"),
+        ("packages/a/index.js", content),
+        ("packages/b/package.json", r#"{ "name": "b" }"#),
+        ("packages/b/.env", "PORT=4000\n"),
+        ("packages/b/index.js", content),
+    ])
+    .await;
+    fixture.index_workspace().await;
+
+    let uri = |path: &str| {
+        tower_lsp::lsp_types::Url::from_file_path(fixture.temp_dir.join(path)).unwrap()
+    };
+    let uris = [
+        (uri("packages/a/.env"), uri("packages/a/index.js")),
+        (uri("packages/b/.env"), uri("packages/b/index.js")),
+    ];
+    let (a_env, a_code) = &uris[0];
+    let (b_env, b_code) = &uris[1];
+    fixture
+        .state
+        .document_manager
+        .open(a_code.clone(), "javascript".to_string(), content.to_string(), 1)
+        .await;
+    let params = || RenameParams {
+        text_document_position: TextDocumentPositionParams {
+            text_document: TextDocumentIdentifier { uri: a_code.clone() },
+            position: Position::new(0, 26),
+        },
+        new_name: "APP_PORT".to_string(),
+        work_done_progress_params: Default::default(),
+    };
+
+    let changes = handle_rename(params(), &fixture.state)
+        .await
+        .and_then(|edit| edit.changes)
+        .expect("Rename should produce edits");
+    assert!(changes.contains_key(a_code), "{:?}", changes.keys());
+    assert!(changes.contains_key(a_env), "{:?}", changes.keys());
+    assert!(!changes.contains_key(b_code), "{:?}", changes.keys());
+    assert!(!changes.contains_key(b_env), "{:?}", changes.keys());
+
+    {
+        let config_arc = fixture.state.config.get_config();
+        let mut config = config_arc.write().await.clone();
+        config.rename.across_packages = true;
+        fixture.state.config.update(config).await;
+    }
+
+    let changes = handle_rename(params(), &fixture.state)
+        .await
+        .and_then(|edit| edit.changes)
+        .expect("Rename should produce edits");
+    assert!(changes.contains_key(a_code));
+    assert!(changes.contains_key(b_code));
+}
+
+#[tokio::test]
+async fn test_rename_in_package_includes_root_env_file() {
+    let content = "const url = process.env.SHARED_URL;";
+    let fixture = TestFixture::with_files(&[
+        ("package.json", r#"{ "workspaces": ["packages/*"] }"#),
+        (".env", "SHARED_URL=http://localhost\n"),
+        ("packages/a/package.json", r#"{ "name": "a" }"#),
+        ("packages/a/index.js", content),
+    ])
+    .await;
+    fixture.index_workspace().await;
+
+    let root_env =
+        tower_lsp::lsp_types::Url::from_file_path(fixture.temp_dir.join(".env")).unwrap();
+    let code =
+        tower_lsp::lsp_types::Url::from_file_path(fixture.temp_dir.join("packages/a/index.js"))
+            .unwrap();
+    fixture
+        .state
+        .document_manager
+        .open(code.clone(), "javascript".to_string(), content.to_string(), 1)
+        .await;
+
+    let params = RenameParams {
+        text_document_position: TextDocumentPositionParams {
+            text_document: TextDocumentIdentifier { uri: code.clone() },
+            position: Position::new(0, 26),
+        },
+        new_name: "BASE_URL".to_string(),
+        work_done_progress_params: Default::default(),
+    };
+    let changes = handle_rename(params, &fixture.state)
+        .await
+        .and_then(|edit| edit.changes)
+        .expect("Rename should produce edits");
+
+    assert!(changes.contains_key(&code), "{:?}", changes.keys());
+    let env_edits = changes
+        .get(&root_env)
+        .unwrap_or_else(|| panic!("Root .env should be renamed: {:?}", changes.keys()));
+    assert_eq!(env_edits[0].range.start, Position::new(0, 0));
+    assert_eq!(env_edits[0].new_text, "BASE_URL");
+}
+
+#[tokio::test]
+async fn test_rename_shared_root_env_var_renames_every_package_using_it() {
+    let content = "const url = process.env.SHARED_URL;";
+    let fixture = TestFixture::with_files(&[
+        ("package.json", r#"{ "workspaces": ["packages/*"] }"#),
+        (".env", "SHARED_URL=http://localhost\n"),
+        ("packages/a/package.json", r#"{ "name": "a" }"#),
+        ("packages/a/index.js", content),
+        ("packages/b/package.json", r#"{ "name": "b" }"#),
+        ("packages/b/index.js", content),
+        ("packages/c/package.json", r#"{ "name": "c" }"#),
+        ("packages/c/.env", "SHARED_URL=http://c\n"),
+        ("packages/c/index.js", content),
+    ])
+    .await;
+    fixture.index_workspace().await;
+
+    let uri = |path: &str| {
+        tower_lsp::lsp_types::Url::from_file_path(fixture.temp_dir.join(path)).unwrap()
+    };
+    let a_code = uri("packages/a/index.js");
+    fixture
+        .state
+        .document_manager
+        .open(a_code.clone(), "javascript".to_string(), content.to_string(), 1)
+        .await;
+
+    let params = RenameParams {
+        text_document_position: TextDocumentPositionParams {
+            text_document: TextDocumentIdentifier { uri: a_code.clone() },
+            position: Position::new(0, 26),
+        },
+        new_name: "BASE_URL".to_string(),
+        work_done_progress_params: Default::default(),
+    };
+    let changes = handle_rename(params, &fixture.state)
+        .await
+        .and_then(|edit| edit.changes)
+        .expect("Rename should produce edits");
+
+    assert!(changes.contains_key(&a_code), "{:?}", changes.keys());
+    assert!(changes.contains_key(&uri(".env")), "{:?}", changes.keys());
+    assert!(
+        changes.contains_key(&uri("packages/b/index.js")),
+        "Package b also reads the root .env: {:?}",
+        changes.keys()
+    );
+    assert!(
+        !changes.contains_key(&uri("packages/c/index.js")),
+        "Package c defines its own SHARED_URL: {:?}",
+        changes.keys()
+    );
+    assert!(!changes.contains_key(&uri("packages/c/.env")), "{:?}", changes.keys());
+}
+
+#[tokio::test]
+async fn test_rename_in_large_env_file_is_fast() {
+    let fixture = TestFixture::new().await;
+
+    let content: String = (0..5000)
+        .map(|i| format!("KEY_{:04}=value_{}\n", i, i))
+        .collect();
+    let env_uri = fixture.create_file(".env", &content);
+    fixture.index_workspace().await;
+    fixture
+        .state
+        .document_manager
+        .open(env_uri.clone(), "plaintext".to_string(), content, 1)
+        .await;
+
+    let params = RenameParams {
+        text_document_position: TextDocumentPositionParams {
+            text_document: TextDocumentIdentifier { uri: env_uri.clone() },
+            position: Position::new(4999, 2),
+        },
+        new_name: "RENAMED".to_string(),
+        work_done_progress_params: Default::default(),
+    };
+
+    let start = std::time::Instant::now();
+    let result = handle_rename(params, &fixture.state).await;
+    let elapsed = start.elapsed();
+
+    let changes = result.expect("rename edits").changes.unwrap();
+    let edits = &changes[&env_uri];
+    assert_eq!(edits[0].range.start, Position::new(4999, 0));
+    assert_eq!(edits[0].range.end, Position::new(4999, 8));
+    assert!(
+        elapsed < std::time::Duration::from_secs(2),
+        "rename took {:?}",
+        elapsed
+    );
+}
+
+#[tokio::test]
+async fn test_rename_destructured_key_keeps_alias() {
+    let fixture = TestFixture::new().await;
+    let content =
+        "const { DB_URL: dbUrl } = process.env;\nconsole.log(dbUrl, process.env.DB_URL);";
+    let uri = fixture.create_file("test.js", content);
+    fixture.index_workspace().await;
+    fixture
+        .state
+        .document_manager
+        .open(uri.clone(), "javascript".to_string(), content.to_string(), 1)
+        .await;
+
+    let key_range = tower_lsp::lsp_types::Range::new(Position::new(0, 8), Position::new(0, 14));
+
+    let references = handle_references(
+        ReferenceParams {
+            text_document_position: TextDocumentPositionParams {
+                text_document: TextDocumentIdentifier { uri: uri.clone() },
+                position: Position::new(0, 10),
+            },
+            work_done_progress_params: Default::default(),
+            partial_result_params: Default::default(),
+            context: ReferenceContext {
+                include_declaration: false,
+            },
+        },
+        &fixture.state,
+    )
+    .await
+    .expect("references");
+    let code_ranges: Vec<_> = references
+        .iter()
+        .filter(|loc| loc.uri == uri)
+        .map(|loc| loc.range)
+        .collect();
+    assert!(code_ranges.contains(&key_range), "{:?}", code_ranges);
+    // Neither the alias nor its use is a reference to the env var
+    let alias_starts = [Position::new(0, 16), Position::new(1, 12)];
+    assert!(!code_ranges.iter().any(|r| alias_starts.contains(&r.start)));
+
+    let edit = handle_rename(
+        RenameParams {
+            text_document_position: TextDocumentPositionParams {
+                text_document: TextDocumentIdentifier { uri: uri.clone() },
+                position: Position::new(0, 10),
+            },
+            new_name: "DATABASE_URL".to_string(),
+            work_done_progress_params: Default::default(),
+        },
+        &fixture.state,
+    )
+    .await
+    .expect("rename edits");
+    let changes = edit.changes.expect("changes");
+    let mut edits = changes.get(&uri).expect("edits in code file").clone();
+    edits.sort_by_key(|e| (e.range.start.line, e.range.start.character));
+
+    let ranges: Vec<_> = edits.iter().map(|e| e.range).collect();
+    assert_eq!(
+        ranges,
+        vec![
+            key_range,
+            tower_lsp::lsp_types::Range::new(Position::new(1, 31), Position::new(1, 37)),
+        ]
+    );
+    assert!(edits.iter().all(|e| e.new_text == "DATABASE_URL"));
+}
+
+#[tokio::test]
+async fn test_rename_binding_alias_is_local() {
+    let fixture = TestFixture::new().await;
+    let content = "const dbUrl = process.env.DB_URL;\nconsole.log(dbUrl);\nconnect(dbUrl);";
+    let uri = fixture.create_file("test.js", content);
+    let other_content = "const dbUrl = process.env.DB_URL;\nconsole.log(dbUrl);";
+    let other_uri = fixture.create_file("other.js", other_content);
+    fixture.index_workspace().await;
+    for (uri, content) in [(&uri, content), (&other_uri, other_content)] {
+        fixture
+            .state
+            .document_manager
+            .open(uri.clone(), "javascript".to_string(), content.to_string(), 1)
+            .await;
+    }
+
+    let position = TextDocumentPositionParams {
+        text_document: TextDocumentIdentifier { uri: uri.clone() },
+        position: Position::new(0, 8),
+    };
+    let alias_range = tower_lsp::lsp_types::Range::new(Position::new(0, 6), Position::new(0, 11));
+    match handle_prepare_rename(position.clone(), &fixture.state).await {
+        Some(PrepareRenameResponse::Range(range)) => assert_eq!(range, alias_range),
+        other => panic!("Expected the alias range, got {:?}", other),
+    }
+
+    let edit = handle_rename(
+        RenameParams {
+            text_document_position: position,
+            new_name: "databaseUrl".to_string(),
+            work_done_progress_params: Default::default(),
+        },
+        &fixture.state,
+    )
+    .await
+    .expect("rename edits");
+    let changes = edit.changes.expect("changes");
+    assert_eq!(changes.len(), 1, "Only this file changes: {:?}", changes);
+
+    let mut edits = changes.get(&uri).expect("edits in code file").clone();
+    edits.sort_by_key(|e| (e.range.start.line, e.range.start.character));
+    let starts: Vec<_> = edits.iter().map(|e| e.range.start).collect();
+    assert_eq!(
+        starts,
+        vec![Position::new(0, 6), Position::new(1, 12), Position::new(2, 8)]
+    );
+    assert!(edits.iter().all(|e| e.new_text == "databaseUrl"));
+}
+
+#[tokio::test]
+async fn test_rename_binding_alias_from_usage() {
+    let fixture = TestFixture::new().await;
+    let content = "const dbUrl = process.env.DB_URL;\nconsole.log(dbUrl);\nconnect(dbUrl);";
+    let uri = fixture.create_file("test.js", content);
+    fixture.index_workspace().await;
+    fixture
+        .state
+        .document_manager
+        .open(uri.clone(), "javascript".to_string(), content.to_string(), 1)
+        .await;
+
+    let position = TextDocumentPositionParams {
+        text_document: TextDocumentIdentifier { uri: uri.clone() },
+        position: Position::new(1, 14),
+    };
+    let usage_range = tower_lsp::lsp_types::Range::new(Position::new(1, 12), Position::new(1, 17));
+    match handle_prepare_rename(position.clone(), &fixture.state).await {
+        Some(PrepareRenameResponse::Range(range)) => assert_eq!(range, usage_range),
+        other => panic!("Expected the usage range, got {:?}", other),
+    }
+
+    // `$` and trailing digits are valid JavaScript identifiers, though not env var names
+    for new_name in ["$db", "dbUrl2"] {
+        let edit = handle_rename(
+            RenameParams {
+                text_document_position: position.clone(),
+                new_name: new_name.to_string(),
+                work_done_progress_params: Default::default(),
+            },
+            &fixture.state,
+        )
+        .await
+        .unwrap_or_else(|| panic!("rename to {} should be accepted", new_name));
+        let changes = edit.changes.expect("changes");
+        let mut edits = changes.get(&uri).expect("edits in code file").clone();
+        edits.sort_by_key(|e| (e.range.start.line, e.range.start.character));
+        let starts: Vec<_> = edits.iter().map(|e| e.range.start).collect();
+        assert_eq!(
+            starts,
+            vec![Position::new(0, 6), Position::new(1, 12), Position::new(2, 8)]
+        );
+        assert!(edits.iter().all(|e| e.new_text == new_name));
+    }
+
+    let invalid = handle_rename(
+        RenameParams {
+            text_document_position: position,
+            new_name: "db-url".to_string(),
+            work_done_progress_params: Default::default(),
+        },
+        &fixture.state,
+    )
+    .await;
+    assert!(invalid.is_none(), "Not a JavaScript identifier");
+}
+
+#[tokio::test]
+async fn test_rename_exported_binding_alias_is_not_local() {
+    let fixture = TestFixture::new().await;
+    let content = "export const dbUrl = process.env.DB_URL;\nconsole.log(dbUrl);";
+    let uri = fixture.create_file("config.js", content);
+    let importer = "import { dbUrl } from './config';\nconnect(dbUrl);";
+    fixture.create_file("app.js", importer);
+    fixture.index_workspace().await;
+    fixture
+        .state
+        .document_manager
+        .open(uri.clone(), "javascript".to_string(), content.to_string(), 1)
+        .await;
+
+    let position = TextDocumentPositionParams {
+        text_document: TextDocumentIdentifier { uri: uri.clone() },
+        position: Position::new(1, 14),
+    };
+    assert!(handle_prepare_rename(position.clone(), &fixture.state).await.is_none());
+    let edit = handle_rename(
+        RenameParams {
+            text_document_position: position,
+            new_name: "databaseUrl".to_string(),
+            work_done_progress_params: Default::default(),
+        },
+        &fixture.state,
+    )
+    .await;
+    assert!(
+        edit.is_none(),
+        "Renaming an exported alias would break importers: {:?}",
+        edit
+    );
+}
+
+#[tokio::test]
+async fn test_lazy_index_references_find_unopened_files() {
+    let fixture = TestFixture::new().await;
+    let env_files = {
+        let config_arc = fixture.state.config.get_config();
+        let mut config = config_arc.write().await.clone();
+        config.workspace.lazy_index = true;
+        let env_files = config.workspace.env_files.clone();
+        fixture.state.config.update(config).await;
+        env_files
+    };
+    let unopened = fixture.create_file("b.ts", "const apiKey = process.env.API_KEY;");
+    let unrelated = fixture.create_file("c.js", "const url = process.env.DB_URL;");
+    let content = "const key = process.env.API_KEY;";
+    let uri = fixture.create_file("a.js", content);
+    fixture
+        .state
+        .indexer
+        .index_env_files_with_cancellation(&env_files, &CancellationToken::new())
+        .await
+        .unwrap();
+    assert!(!fixture.state.workspace_index.is_file_indexed(&unopened));
+    fixture
+        .state
+        .document_manager
+        .open(uri.clone(), "javascript".to_string(), content.to_string(), 1)
+        .await;
+
+    let params = ReferenceParams {
+        text_document_position: TextDocumentPositionParams {
+            text_document: TextDocumentIdentifier { uri },
+            position: Position::new(0, 26),
+        },
+        work_done_progress_params: Default::default(),
+        partial_result_params: Default::default(),
+        context: ReferenceContext {
+            include_declaration: false,
+        },
+    };
+    let locations = handle_references(params, &fixture.state).await.expect("references");
+    assert!(
+        locations.iter().any(|loc| loc.uri == unopened),
+        "Expected a reference in the never-opened file: {:?}",
+        locations
+    );
+    // Files that don't mention the variable are left unparsed
+    assert!(!fixture.state.workspace_index.is_file_indexed(&unrelated));
+
+    // Later names reuse the file list from the first scan
+    fixture
+        .state
+        .indexer
+        .index_files_mentioning("DB_URL", &env_files, &CancellationToken::new())
+        .await;
+    assert!(fixture.state.workspace_index.is_file_indexed(&unrelated));
+}
@@ -0,0 +1,75 @@
+mod common;
+
+use common::TestFixture;
+use ecolog_lsp::server::handlers::{handle_semantic_tokens_full, semantic_tokens_legend};
+use tower_lsp::lsp_types::{
+    SemanticToken, SemanticTokensParams, SemanticTokensResult, TextDocumentIdentifier, Url,
+};
+
+async fn tokens(fixture: &TestFixture, uri: Url) -> Option<Vec<SemanticToken>> {
+    let result = handle_semantic_tokens_full(
+        SemanticTokensParams {
+            text_document: TextDocumentIdentifier { uri },
+            work_done_progress_params: Default::default(),
+            partial_result_params: Default::default(),
+        },
+        &fixture.state,
+    )
+    .await?;
+    let SemanticTokensResult::Tokens(tokens) = result else {
+        panic!("Expected full tokens");
+    };
+    Some(tokens.data)
+}
+
+fn token(delta_line: u32, delta_start: u32, length: u32) -> SemanticToken {
+    SemanticToken {
+        delta_line,
+        delta_start,
+        length,
+        token_type: 0,
+        token_modifiers_bitset: 0,
+    }
+}
+
+#[tokio::test]
+async fn test_semantic_tokens_delta_encode_references() {
+    let fixture = TestFixture::new().await;
+    let config_arc = fixture.state.config.get_config();
+    let mut config = config_arc.write().await.clone();
+    config.features.semantic_tokens = true;
+    fixture.state.config.update(config).await;
+
+    let content =
+        "const a = process.env.DB_URL + process.env.PORT;\n\nconst b = process.env.API_KEY;";
+    let uri = fixture.create_file("test.js", content);
+    fixture
+        .state
+        .document_manager
+        .open(uri.clone(), "javascript".to_string(), content.to_string(), 0)
+        .await;
+
+    let legend = semantic_tokens_legend();
+    assert_eq!(legend.token_types.len(), 1);
+    assert_eq!(legend.token_types[0].as_str(), "envVariable");
+
+    // DB_URL at 0:22, PORT at 0:43, API_KEY at 2:22
+    assert_eq!(
+        tokens(&fixture, uri).await.unwrap(),
+        vec![token(0, 22, 6), token(0, 21, 4), token(2, 22, 7)]
+    );
+}
+
+#[tokio::test]
+async fn test_semantic_tokens_disabled_by_default() {
+    let fixture = TestFixture::new().await;
+    let content = "const a = process.env.DB_URL;";
+    let uri = fixture.create_file("test.js", content);
+    fixture
+        .state
+        .document_manager
+        .open(uri.clone(), "javascript".to_string(), content.to_string(), 0)
+        .await;
+
+    assert!(tokens(&fixture, uri).await.is_none());
+}
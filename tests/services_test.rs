@@ -13,7 +13,7 @@ async fn test_env_service_get_workspace_root() {
     let fixture = TestFixture::new().await;
 
     // Create EnvService from the core
-    let env_service = ecolog_lsp::server::services::EnvService::new(fixture.state.core.clone());
+    let env_service = ecolog_lsp::server::services::EnvService::new(fixture.state.core.clone(), fixture.state.config.clone());
 
     let root = env_service.get_workspace_root().await;
     assert!(root.exists(), "Workspace root should exist");
@@ -23,7 +23,7 @@ async fn test_env_service_get_workspace_root() {
 async fn test_env_service_get_for_file() {
     let fixture = TestFixture::new().await;
 
-    let env_service = ecolog_lsp::server::services::EnvService::new(fixture.state.core.clone());
+    let env_service = ecolog_lsp::server::services::EnvService::new(fixture.state.core.clone(), fixture.state.config.clone());
 
     // DB_URL is defined in the fixture's .env
     let result = env_service.get_for_file("DB_URL", &fixture.temp_dir).await;
@@ -37,7 +37,7 @@ async fn test_env_service_get_for_file() {
 async fn test_env_service_get_for_file_not_found() {
     let fixture = TestFixture::new().await;
 
-    let env_service = ecolog_lsp::server::services::EnvService::new(fixture.state.core.clone());
+    let env_service = ecolog_lsp::server::services::EnvService::new(fixture.state.core.clone(), fixture.state.config.clone());
 
     let result = env_service.get_for_file("NONEXISTENT_VAR", &fixture.temp_dir).await;
 
@@ -48,7 +48,7 @@ async fn test_env_service_get_for_file_not_found() {
 async fn test_env_service_all_for_file() {
     let fixture = TestFixture::new().await;
 
-    let env_service = ecolog_lsp::server::services::EnvService::new(fixture.state.core.clone());
+    let env_service = ecolog_lsp::server::services::EnvService::new(fixture.state.core.clone(), fixture.state.config.clone());
 
     let vars = env_service.all_for_file(&fixture.temp_dir).await;
 
@@ -62,7 +62,7 @@ async fn test_env_service_all_for_file() {
 async fn test_env_service_set_active_files() {
     let fixture = TestFixture::new().await;
 
-    let env_service = ecolog_lsp::server::services::EnvService::new(fixture.state.core.clone());
+    let env_service = ecolog_lsp::server::services::EnvService::new(fixture.state.core.clone(), fixture.state.config.clone());
 
     // Set active file filter
     env_service.set_active_files(&[".env.local".to_string()]);
@@ -75,7 +75,7 @@ async fn test_env_service_set_active_files() {
 async fn test_env_service_active_env_files() {
     let fixture = TestFixture::new().await;
 
-    let env_service = ecolog_lsp::server::services::EnvService::new(fixture.state.core.clone());
+    let env_service = ecolog_lsp::server::services::EnvService::new(fixture.state.core.clone(), fixture.state.config.clone());
 
     let files = env_service.active_env_files(&fixture.temp_dir);
     // Should find at least the .env file
@@ -86,7 +86,7 @@ async fn test_env_service_active_env_files() {
 async fn test_env_service_refresh() {
     let fixture = TestFixture::new().await;
 
-    let env_service = ecolog_lsp::server::services::EnvService::new(fixture.state.core.clone());
+    let env_service = ecolog_lsp::server::services::EnvService::new(fixture.state.core.clone(), fixture.state.config.clone());
 
     // Refresh should not panic
     env_service.refresh(abundantis::RefreshOptions::default()).await;
@@ -96,7 +96,7 @@ async fn test_env_service_refresh() {
 async fn test_env_service_clone() {
     let fixture = TestFixture::new().await;
 
-    let env_service = ecolog_lsp::server::services::EnvService::new(fixture.state.core.clone());
+    let env_service = ecolog_lsp::server::services::EnvService::new(fixture.state.core.clone(), fixture.state.config.clone());
     let cloned = env_service.clone();
 
     // Both should return the same workspace root
@@ -109,7 +109,7 @@ async fn test_env_service_clone() {
 async fn test_env_service_registered_file_paths() {
     let fixture = TestFixture::new().await;
 
-    let env_service = ecolog_lsp::server::services::EnvService::new(fixture.state.core.clone());
+    let env_service = ecolog_lsp::server::services::EnvService::new(fixture.state.core.clone(), fixture.state.config.clone());
 
     let paths = env_service.registered_file_paths();
     // Should have at least the .env file registered
@@ -120,7 +120,7 @@ async fn test_env_service_registered_file_paths() {
 async fn test_env_service_context_for_file() {
     let fixture = TestFixture::new().await;
 
-    let env_service = ecolog_lsp::server::services::EnvService::new(fixture.state.core.clone());
+    let env_service = ecolog_lsp::server::services::EnvService::new(fixture.state.core.clone(), fixture.state.config.clone());
 
     // Create a test file path
     let test_file = fixture.temp_dir.join("test.js");
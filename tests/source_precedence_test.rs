@@ -459,3 +459,108 @@ async fn test_file_works_when_shell_disabled() {
         "Hover should show file value"
     );
 }
+
+async fn set_file_sources_only(fixture: &TestFixture, enabled: bool) {
+    let config_arc = fixture.state.config.get_config();
+    let mut config = config_arc.write().await.clone();
+    config.diagnostics.file_sources_only = enabled;
+    fixture.state.config.update(config).await;
+}
+
+#[tokio::test]
+async fn test_file_sources_only_flags_shell_only_var() {
+    let fixture = TestFixture::new().await;
+
+    set_shell_var(&fixture, "SHELL_ONLY_DIAG_VAR", "shell_value").await;
+
+    let uri = fixture.create_file("test.js", "process.env.SHELL_ONLY_DIAG_VAR");
+    fixture
+        .state
+        .document_manager
+        .open(
+            uri.clone(),
+            "javascript".to_string(),
+            "process.env.SHELL_ONLY_DIAG_VAR".to_string(),
+            0,
+        )
+        .await;
+
+    let diags_default = compute_diagnostics(&uri, &fixture.state).await;
+    assert!(
+        diags_default.is_empty(),
+        "Shell var should count as defined by default"
+    );
+
+    set_file_sources_only(&fixture, true).await;
+
+    let diags = compute_diagnostics(&uri, &fixture.state).await;
+    assert_eq!(diags.len(), 1, "Shell-only var should be flagged");
+    assert!(diags[0].message.contains("SHELL_ONLY_DIAG_VAR"));
+
+    remove_shell_var(&fixture, "SHELL_ONLY_DIAG_VAR").await;
+}
+
+#[tokio::test]
+async fn test_file_sources_only_accepts_var_in_shell_and_file() {
+    let fixture = TestFixture::new().await;
+
+    // Set in both the shell and .env
+    set_shell_var(&fixture, "API_KEY", "shell_key").await;
+    set_file_sources_only(&fixture, true).await;
+
+    let uri = fixture.create_file("test.js", "process.env.API_KEY");
+    fixture
+        .state
+        .document_manager
+        .open(
+            uri.clone(),
+            "javascript".to_string(),
+            "process.env.API_KEY".to_string(),
+            0,
+        )
+        .await;
+
+    let diags = compute_diagnostics(&uri, &fixture.state).await;
+    assert!(diags.is_empty(), "File-defined var should not be flagged: {:?}", diags);
+
+    remove_shell_var(&fixture, "API_KEY").await;
+}
+
+#[tokio::test]
+async fn test_hover_lists_overridden_sources() {
+    let fixture = TestFixture::new().await;
+    std::fs::write(fixture.temp_dir.join(".env.local"), "PORT=9090\n").unwrap();
+    fixture
+        .state
+        .core
+        .refresh(abundantis::RefreshOptions::reset_all())
+        .await
+        .expect("Refresh failed");
+
+    let content = "const port = process.env.PORT;";
+    let uri = fixture.create_file("test.js", content);
+    fixture
+        .state
+        .document_manager
+        .open(uri.clone(), "javascript".to_string(), content.to_string(), 0)
+        .await;
+
+    let hover_text = |hover: Option<tower_lsp::lsp_types::Hover>| match hover.unwrap().contents {
+        tower_lsp::lsp_types::HoverContents::Markup(markup) => markup.value,
+        other => panic!("Expected markup hover, got {:?}", other),
+    };
+
+    let plain = hover_text(get_hover(&fixture, &uri, 0, 27).await);
+    assert!(!plain.contains("Also defined in"), "{}", plain);
+
+    let config_arc = fixture.state.config.get_config();
+    let mut config = config_arc.write().await.clone();
+    config.features.hover_show_overrides = true;
+    fixture.state.config.update(config).await;
+
+    let markdown = hover_text(get_hover(&fixture, &uri, 0, 27).await);
+    let (winner, overrides) = markdown.split_once("**Also defined in**").expect(&markdown);
+    assert!(winner.contains("`9090`") && winner.contains("`.env.local`"), "{}", markdown);
+    assert!(overrides.contains("- `.env`: `8080`"), "{}", markdown);
+    assert!(!overrides.contains(".env.local"), "{}", markdown);
+}